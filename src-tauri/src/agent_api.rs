@@ -0,0 +1,598 @@
+//! The agent manager, and the project-management reads, for scripts on the
+//! same machine: a Unix-domain socket in the app data directory speaking
+//! newline-delimited JSON-RPC 2.0.
+//!
+//! Opt-in and off by default. The switch is an ordinary application setting,
+//! mirrored into `webview-prefs.json` like the others, and is looked at every
+//! few seconds — switching it off closes the socket and every connection on it
+//! without a restart.
+//!
+//! The socket file is the user's alone (mode 0600), and on top of that a
+//! connection must first call `auth` with the token from `agent-api.token`,
+//! which sits next to it with the same mode. The token is new every time the
+//! socket opens, so a script reads the file rather than remembering it.
+//!
+//! Methods, params in camelCase like the webview's commands:
+//!
+//! | Method | Params | Result |
+//! | --- | --- | --- |
+//! | `auth` | `token` | `{ authenticated: true }` |
+//! | `agents.list` | — | `AgentInfo[]` |
+//! | `agents.spawn` | an `AgentConfig` | `AgentInfo` (possibly `queued`) |
+//! | `agents.output` | `agentId`, `sinceSeq?` | `AgentOutputPage` |
+//! | `agents.input` | `agentId`, `text`, `submit?` | `null` |
+//! | `agents.kill` | `agentId` | `null` |
+//! | `pm.load` | `projectPath` | `PmState` |
+//! | `goals.load` | `projectPath` | `GoalsState` |
+//! | `costs.summary` | `projectPath` | `AgentCostSummary` |
+//!
+//! Tailing an agent is polling `agents.output` with the `nextSeq` of the page
+//! before. A request without an `id` is a notification: it runs, and nothing is
+//! written back.
+
+use std::io::{Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// The `APP_CONFIG_KEYS.agentApiEnabled` entry from `src/lib/config/appConfig.ts`.
+/// Changing it here without changing it there turns the API off silently.
+pub const ENABLED_PREF_KEY: &str = "auric.agent-api.enabled";
+
+/// How often the runner checks the setting.
+const RECHECK_SECS: u64 = 5;
+
+/// A request line longer than this ends the connection. The largest honest
+/// request is a spawn with a long prompt, which is nowhere near it.
+const MAX_REQUEST_BYTES: usize = 4 * 1024 * 1024;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// The method ran and failed; the message is the same one the UI would show.
+pub const CALL_FAILED: i64 = -32000;
+/// No `auth` yet on this connection, or a wrong token.
+pub const UNAUTHORIZED: i64 = -32001;
+
+/// Where the socket lives, given the app data directory.
+pub fn socket_path_in(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("agent-api.sock")
+}
+
+/// Where the token lives, given the app data directory.
+pub fn token_path_in(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("agent-api.token")
+}
+
+// ── protocol ────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// A method's own failure, so `?` works on the `_impl` functions' results.
+impl From<String> for RpcError {
+    fn from(message: String) -> Self {
+        Self::new(CALL_FAILED, message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    /// Absent for a notification, which gets no reply.
+    pub id: Option<Value>,
+    pub method: String,
+    pub params: Value,
+}
+
+/// One request line. A malformed one comes back with the id to answer under —
+/// the request's own when it had one, `null` when it could not be read.
+pub fn parse_request(line: &[u8]) -> Result<Request, (Value, RpcError)> {
+    let value: Value = serde_json::from_slice(line).map_err(|e| {
+        (
+            Value::Null,
+            RpcError::new(PARSE_ERROR, format!("Parse error: {}", e)),
+        )
+    })?;
+    let Value::Object(mut request) = value else {
+        return Err((
+            Value::Null,
+            RpcError::new(INVALID_REQUEST, "A request must be a JSON object"),
+        ));
+    };
+    let id = request.remove("id");
+    let invalid = |message: &str| {
+        (
+            id.clone().unwrap_or(Value::Null),
+            RpcError::new(INVALID_REQUEST, message),
+        )
+    };
+
+    if request.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return Err(invalid("\"jsonrpc\" must be \"2.0\""));
+    }
+    let Some(Value::String(method)) = request.remove("method") else {
+        return Err(invalid("\"method\" must be a string"));
+    };
+    Ok(Request {
+        id,
+        method,
+        params: request.remove("params").unwrap_or(Value::Null),
+    })
+}
+
+/// The reply to write back for `id`, newline included.
+pub fn reply_line(id: Value, result: Result<Value, RpcError>) -> String {
+    let reply = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    };
+    let mut line = reply.to_string();
+    line.push('\n');
+    line
+}
+
+/// Named params as `T`. Omitted params are an empty object, so a method whose
+/// params are all optional can be called without any.
+pub fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params)
+        .map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid params: {}", e)))
+}
+
+fn to_result<T: Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))
+}
+
+// ── auth ────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct AuthParams {
+    token: String,
+}
+
+/// The `auth` call: right token or `UNAUTHORIZED`.
+pub fn authenticate(params: Value, token: &str) -> Result<Value, RpcError> {
+    let AuthParams { token: given } = parse_params(params)?;
+    if token_matches(token, &given) {
+        Ok(json!({ "authenticated": true }))
+    } else {
+        Err(RpcError::new(UNAUTHORIZED, "Invalid token"))
+    }
+}
+
+/// Compares without stopping at the first difference, so the time taken says
+/// nothing about how much of a guess was right.
+fn token_matches(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// 256 random bits, hex-encoded.
+pub fn new_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut random| random.read_exact(&mut bytes))
+        .map_err(|e| format!("Failed to generate the agent API token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Writes the token readable by the user only. The file is created with that
+/// mode — creating it with the default mask and tightening afterwards would
+/// leave the token world-readable for the length of the write.
+pub fn write_token(path: &Path, token: &str) -> Result<(), String> {
+    let temporary = path.with_extension("token.tmp");
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&temporary)
+        .map_err(|e| format!("Failed to write the agent API token: {}", e))?;
+    file.write_all(token.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write the agent API token: {}", e))?;
+    drop(file);
+
+    std::fs::rename(&temporary, path)
+        .map_err(|e| format!("Failed to write the agent API token: {}", e))
+}
+
+/// Clears the way for binding `socket`. A socket file outlives the process
+/// that bound it, so one left by a crash is removed — but one something still
+/// answers on belongs to another running AuricIDE, and is left alone.
+pub fn remove_stale_socket(socket: &Path) -> Result<(), String> {
+    if std::fs::symlink_metadata(socket).is_err() {
+        return Ok(());
+    }
+    if std::os::unix::net::UnixStream::connect(socket).is_ok() {
+        return Err(format!(
+            "{} is already served by another AuricIDE",
+            socket.display()
+        ));
+    }
+    std::fs::remove_file(socket)
+        .map_err(|e| format!("Failed to remove stale socket {}: {}", socket.display(), e))
+}
+
+// ── server ──────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AgentParams {
+    agent_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OutputParams {
+    agent_id: String,
+    since_seq: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InputParams {
+    agent_id: String,
+    text: String,
+    submit: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectParams {
+    project_path: String,
+}
+
+/// Runs one authenticated call.
+async fn call(app: &tauri::AppHandle, method: &str, params: Value) -> Result<Value, RpcError> {
+    use tauri::Manager;
+
+    let state = app.state::<crate::AgentManagerState>();
+    let terminal_state = app.state::<crate::TerminalState>();
+    match method {
+        "agents.list" => to_result(crate::agents::list_agents_impl(&state).await?),
+        "agents.spawn" => {
+            let config: crate::agents::AgentConfig = parse_params(params)?;
+            let provider_state = app.state::<crate::ProviderRegistryState>();
+            let info = crate::spawn_agent_with_session(
+                config,
+                &state,
+                &terminal_state,
+                &provider_state,
+                app.clone(),
+            )
+            .await?;
+            to_result(info)
+        }
+        "agents.output" => {
+            let OutputParams {
+                agent_id,
+                since_seq,
+            } = parse_params(params)?;
+            let page =
+                crate::agents::get_agent_output_impl(&agent_id, since_seq.unwrap_or(0), &state)
+                    .await?;
+            to_result(page)
+        }
+        "agents.input" => {
+            let InputParams {
+                agent_id,
+                text,
+                submit,
+            } = parse_params(params)?;
+            let bytes = crate::agents::agent_input_bytes(&text, submit.unwrap_or(true));
+            crate::write_to_agent(&agent_id, &bytes, &state, &terminal_state).await?;
+            Ok(Value::Null)
+        }
+        "agents.kill" => {
+            let AgentParams { agent_id } = parse_params(params)?;
+            crate::kill_agent_with_session(&agent_id, &state, &terminal_state, app).await?;
+            Ok(Value::Null)
+        }
+        "pm.load" => with_project_db(app, params, crate::database::pm_load_impl).await,
+        "goals.load" => with_project_db(app, params, crate::database::goals_load_impl).await,
+        "costs.summary" => {
+            with_project_db(app, params, crate::database::agent_cost_summary_impl).await
+        }
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method {}", method),
+        )),
+    }
+}
+
+/// Runs a read against the project's database: the connection the UI already
+/// has open, or a fresh one when the project is not open in a window. A path
+/// without a project database is refused rather than given one.
+async fn with_project_db<T: Serialize + Send + 'static>(
+    app: &tauri::AppHandle,
+    params: Value,
+    read: fn(&rusqlite::Connection) -> Result<T, String>,
+) -> Result<Value, RpcError> {
+    use tauri::Manager;
+
+    let ProjectParams { project_path } = parse_params(params)?;
+    let app = app.clone();
+    let value = tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<crate::database::DatabaseState>();
+        if let Some(conn) = state.connections.lock().unwrap().get(&project_path) {
+            return read(conn);
+        }
+        if !Path::new(&project_path).join(".auric/project.db").is_file() {
+            return Err(format!("No AuricIDE project at {}", project_path));
+        }
+        read(&crate::database::init_db(&project_path)?)
+    })
+    .await
+    .map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))??;
+    to_result(value)
+}
+
+/// Answers one line. `None` for a notification.
+async fn answer(
+    app: &tauri::AppHandle,
+    token: &str,
+    authenticated: &mut bool,
+    line: &[u8],
+) -> Option<String> {
+    let request = match parse_request(line) {
+        Ok(request) => request,
+        Err((id, error)) => return Some(reply_line(id, Err(error))),
+    };
+    let result = if request.method == "auth" {
+        let result = authenticate(request.params, token);
+        *authenticated = result.is_ok();
+        result
+    } else if !*authenticated {
+        Err(RpcError::new(
+            UNAUTHORIZED,
+            "Call auth with the token first",
+        ))
+    } else {
+        call(app, &request.method, request.params).await
+    };
+    request.id.map(|id| reply_line(id, result))
+}
+
+async fn handle_connection(
+    app: tauri::AppHandle,
+    stream: tokio::net::UnixStream,
+    token: std::sync::Arc<str>,
+) {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut authenticated = false;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = (&mut reader)
+            .take(MAX_REQUEST_BYTES as u64 + 1)
+            .read_until(b'\n', &mut line)
+            .await;
+        match read {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+        if line.len() > MAX_REQUEST_BYTES {
+            let error = RpcError::new(INVALID_REQUEST, "Request too large");
+            let _ = writer
+                .write_all(reply_line(Value::Null, Err(error)).as_bytes())
+                .await;
+            return;
+        }
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        if let Some(reply) = answer(&app, &token, &mut authenticated, &line).await {
+            if writer.write_all(reply.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Binds the socket and writes a fresh token for it.
+fn bind(app_data_dir: &Path) -> Result<(tokio::net::UnixListener, String), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::create_dir_all(app_data_dir).map_err(|e| e.to_string())?;
+    let socket = socket_path_in(app_data_dir);
+    remove_stale_socket(&socket)?;
+    let listener = tokio::net::UnixListener::bind(&socket)
+        .map_err(|e| format!("Failed to bind {}: {}", socket.display(), e))?;
+    std::fs::set_permissions(&socket, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to restrict {}: {}", socket.display(), e))?;
+
+    let token = new_token()?;
+    write_token(&token_path_in(app_data_dir), &token)?;
+    Ok((listener, token))
+}
+
+/// Accepts connections until aborted. The connections live in a `JoinSet`
+/// owned by this task, so aborting it drops them too.
+async fn serve(app: tauri::AppHandle, listener: tokio::net::UnixListener, token: String) {
+    let token: std::sync::Arc<str> = token.into();
+    let mut connections = tokio::task::JoinSet::new();
+    loop {
+        while connections.try_join_next().is_some() {}
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                connections.spawn(handle_connection(app.clone(), stream, token.clone()));
+            }
+            Err(error) => {
+                eprintln!("Agent API: {error}");
+                return;
+            }
+        }
+    }
+}
+
+/// Opens and closes the socket as the setting is switched, for the lifetime of
+/// the app.
+pub fn spawn_agent_api_runner(app: tauri::AppHandle) {
+    use tauri::Manager;
+
+    tauri::async_runtime::spawn(async move {
+        let Ok(dir) = app.path().app_data_dir() else {
+            return;
+        };
+        let mut server: Option<tokio::task::JoinHandle<()>> = None;
+        // A bind that keeps failing is reported once, not every few seconds.
+        let mut last_error = None;
+        loop {
+            let enabled = app
+                .try_state::<crate::webview_prefs::WebviewPrefsState>()
+                .and_then(|prefs| prefs.get(ENABLED_PREF_KEY))
+                .as_deref()
+                == Some("true");
+            let running = server.as_ref().is_some_and(|task| !task.is_finished());
+
+            if enabled && !running {
+                match bind(&dir) {
+                    Ok((listener, token)) => {
+                        server = Some(tokio::spawn(serve(app.clone(), listener, token)));
+                        last_error = None;
+                    }
+                    Err(error) => {
+                        if last_error.as_ref() != Some(&error) {
+                            eprintln!("Agent API: {error}");
+                        }
+                        last_error = Some(error);
+                    }
+                }
+            } else if !enabled && server.is_some() {
+                if let Some(task) = server.take() {
+                    task.abort();
+                }
+                let _ = std::fs::remove_file(socket_path_in(&dir));
+                let _ = std::fs::remove_file(token_path_in(&dir));
+            }
+
+            tokio::time::sleep(std::time::Duration::from_secs(RECHECK_SECS)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_read_or_answered_with_the_right_error() {
+        let request = parse_request(br#"{"jsonrpc":"2.0","id":7,"method":"agents.list"}"#).unwrap();
+        assert_eq!(request.id, Some(json!(7)));
+        assert_eq!(request.method, "agents.list");
+        assert_eq!(request.params, Value::Null);
+
+        let notification =
+            parse_request(br#"{"jsonrpc":"2.0","method":"agents.kill","params":{"agentId":"a"}}"#)
+                .unwrap();
+        assert_eq!(notification.id, None);
+
+        let (id, error) = parse_request(b"{not json").unwrap_err();
+        assert_eq!((id, error.code), (Value::Null, PARSE_ERROR));
+        let (id, error) = parse_request(br#"[1]"#).unwrap_err();
+        assert_eq!((id, error.code), (Value::Null, INVALID_REQUEST));
+        // Readable enough to have an id: the error goes back under it.
+        let (id, error) = parse_request(br#"{"jsonrpc":"1.0","id":"x","method":"m"}"#).unwrap_err();
+        assert_eq!((id, error.code), (json!("x"), INVALID_REQUEST));
+        let (id, error) = parse_request(br#"{"jsonrpc":"2.0","id":3,"method":4}"#).unwrap_err();
+        assert_eq!((id, error.code), (json!(3), INVALID_REQUEST));
+    }
+
+    #[test]
+    fn replies_carry_the_id_and_either_a_result_or_an_error() {
+        let ok: Value = serde_json::from_str(&reply_line(json!(1), Ok(json!({ "a": 1 })))).unwrap();
+        assert_eq!(
+            ok,
+            json!({ "jsonrpc": "2.0", "id": 1, "result": { "a": 1 } })
+        );
+
+        let line = reply_line(json!("q"), Err("Agent x not found".to_string().into()));
+        assert!(line.ends_with('\n') && !line.trim_end().contains('\n'));
+        let failed: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(
+            failed["error"],
+            json!({ "code": CALL_FAILED, "message": "Agent x not found" })
+        );
+
+        let missing: Result<AuthParams, _> = parse_params(json!({ "tokn": "x" }));
+        assert_eq!(missing.err().map(|e| e.code), Some(INVALID_PARAMS));
+        let omitted: Result<AuthParams, _> = parse_params(Value::Null);
+        assert_eq!(omitted.err().map(|e| e.code), Some(INVALID_PARAMS));
+    }
+
+    #[test]
+    fn only_the_token_authenticates() {
+        let token = new_token().unwrap();
+        assert_eq!(token.len(), 64);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, new_token().unwrap());
+
+        assert!(authenticate(json!({ "token": token }), &token).is_ok());
+        let wrong = format!("{}0", &token[..63]);
+        for params in [json!({ "token": wrong }), json!({ "token": "" }), json!({})] {
+            assert!(authenticate(params, &token).is_err());
+        }
+        assert_eq!(
+            authenticate(json!({ "token": "nope" }), &token).map_err(|e| e.code),
+            Err(UNAUTHORIZED)
+        );
+    }
+
+    #[test]
+    fn the_token_file_is_private_to_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = token_path_in(dir.path());
+        write_token(&path, "first").unwrap();
+        write_token(&path, "second").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn a_dead_socket_is_cleared_but_a_live_one_is_not() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = socket_path_in(dir.path());
+        assert!(remove_stale_socket(&socket).is_ok());
+
+        let live = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        assert!(remove_stale_socket(&socket).is_err());
+        assert!(socket.exists());
+
+        drop(live);
+        remove_stale_socket(&socket).unwrap();
+        assert!(!socket.exists());
+    }
+}
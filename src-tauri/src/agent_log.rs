@@ -15,6 +15,11 @@ use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
+/// The webview preference that turns the history on — the key behind
+/// `agentLogPersist` in `src/lib/config/appConfig.ts`. Rows the backend writes
+/// itself honour the same opt-in as the ones the frontend sends.
+pub const PERSIST_PREF_KEY: &str = "auric.agent-log.persist";
+
 /// Where the log lives inside the app data directory.
 pub fn db_path_in(app_data_dir: &Path) -> PathBuf {
     app_data_dir.join("agent-log.db")
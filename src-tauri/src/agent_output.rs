@@ -0,0 +1,600 @@
+//! Scrollback for agent output, so a view that subscribes late can still show
+//! everything an agent printed.
+//!
+//! Output otherwise exists only as `agent-output` events: a webview reload or a
+//! panel opened after the agent started would see the stream from that moment
+//! on. Every emitted chunk is numbered and kept here as well, and
+//! `get_agent_output` hands back everything from a given number.
+//!
+//! Memory is bounded per agent. What falls off the front is appended to
+//! JSON-lines files in the app data directory when one is configured, so a
+//! long session can still be replayed; without one it is gone, and the page
+//! says so. The spill is bounded too: it rotates through numbered segments and
+//! keeps the newest two. Scrollback does not survive a restart, so neither
+//! does the spill: the directory is cleared at startup.
+//!
+//! The store sits behind the agent manager's lock, so it never touches the
+//! disk itself. Recording hands back a [`SpillWrite`] and replaying an
+//! [`OutputReplay`], and the caller runs both once the lock is released.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+/// In-memory scrollback per agent.
+pub const SCROLLBACK_BYTES: usize = 2 * 1024 * 1024;
+/// Agents whose scrollback is kept at all. Running agents are never evicted;
+/// past this many, the oldest finished one goes first.
+pub const RETAINED_TRANSCRIPTS: usize = 64;
+/// A spill segment is closed once it holds this much, and the segment before
+/// it is deleted once the next one has been written to — an agent keeps
+/// between one and two segments of its oldest output on disk.
+pub const SPILL_SEGMENT_BYTES: u64 = 8 * 1024 * 1024;
+const SPILL_SEGMENTS: usize = 2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentOutputChunk {
+    pub seq: u64,
+    pub stream: String,
+    /// Epoch millis.
+    pub timestamp: u64,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentOutputPage {
+    pub chunks: Vec<AgentOutputChunk>,
+    /// What to pass as `since_seq` next time to get only what is new.
+    pub next_seq: u64,
+    /// Output from the requested point on was dropped before it could be
+    /// read back, so `chunks` does not start where the caller asked.
+    pub truncated: bool,
+}
+
+struct StoredChunk {
+    seq: u64,
+    stream: String,
+    timestamp: u64,
+    bytes: Vec<u8>,
+}
+
+impl StoredChunk {
+    fn to_chunk(&self) -> AgentOutputChunk {
+        AgentOutputChunk {
+            seq: self.seq,
+            stream: self.stream.clone(),
+            timestamp: self.timestamp,
+            data: String::from_utf8_lossy(&self.bytes).into_owned(),
+        }
+    }
+}
+
+/// One spill file. Only its first `lines` lines count: a read that overlaps
+/// an append stops there instead of parsing a half-written line.
+struct SpillSegment {
+    path: PathBuf,
+    first_seq: u64,
+    lines: usize,
+    bytes: u64,
+}
+
+struct Spill {
+    /// Segment `n` is `{stem}.{n}.jsonl`.
+    stem: PathBuf,
+    next_index: u64,
+    segment_bytes: u64,
+    /// Oldest first; the last one is written to.
+    segments: VecDeque<SpillSegment>,
+}
+
+impl Spill {
+    fn segment_path(&self, index: u64) -> PathBuf {
+        let mut name = self.stem.as_os_str().to_owned();
+        name.push(format!(".{}.jsonl", index));
+        PathBuf::from(name)
+    }
+
+    fn paths(&self) -> Vec<PathBuf> {
+        self.segments.iter().map(|s| s.path.clone()).collect()
+    }
+}
+
+/// Evicted chunks to append to a spill segment, taken under the manager lock
+/// and written without it.
+pub struct SpillWrite {
+    path: PathBuf,
+    /// Only the first write to a segment may create it: a later one finding
+    /// the file gone means the scrollback was dropped meanwhile.
+    create: bool,
+    chunks: Vec<AgentOutputChunk>,
+}
+
+impl SpillWrite {
+    /// Appends the chunks as JSON lines and returns the bytes written.
+    pub fn write(&self) -> Result<u64, String> {
+        let mut lines = String::new();
+        for chunk in &self.chunks {
+            lines.push_str(&serde_json::to_string(chunk).map_err(|e| e.to_string())?);
+            lines.push('\n');
+        }
+        (|| -> std::io::Result<()> {
+            if self.create {
+                if let Some(parent) = self.path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+            }
+            let mut file = std::fs::OpenOptions::new()
+                .create(self.create)
+                .append(true)
+                .open(&self.path)?;
+            file.write_all(lines.as_bytes())
+        })()
+        .map_err(|e| e.to_string())?;
+        Ok(lines.len() as u64)
+    }
+}
+
+/// What a replay needs from the disk, taken under the manager lock and read
+/// without it.
+pub struct OutputReplay {
+    since_seq: u64,
+    /// Spill segments that may hold chunks from `since_seq` on, with how many
+    /// of their lines were complete when the replay was taken.
+    segments: Vec<(PathBuf, usize)>,
+    /// Everything newer than the spill, already filtered.
+    tail: Vec<AgentOutputChunk>,
+    next_seq: u64,
+    truncated: bool,
+}
+
+impl OutputReplay {
+    pub fn read(self) -> Result<AgentOutputPage, String> {
+        let mut truncated = self.truncated;
+        let mut chunks = Vec::new();
+        for (path, lines) in &self.segments {
+            let file = match std::fs::File::open(path) {
+                Ok(file) => file,
+                // Rotated out since the replay was taken.
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                    truncated = true;
+                    continue;
+                }
+                Err(error) => return Err(format!("Failed to read agent output spill: {}", error)),
+            };
+            for line in std::io::BufReader::new(file).lines().take(*lines) {
+                let line = line.map_err(|e| format!("Failed to read agent output spill: {}", e))?;
+                let chunk: AgentOutputChunk = serde_json::from_str(&line)
+                    .map_err(|e| format!("Failed to parse agent output spill: {}", e))?;
+                if chunk.seq >= self.since_seq {
+                    chunks.push(chunk);
+                }
+            }
+        }
+        chunks.extend(self.tail);
+        Ok(AgentOutputPage {
+            chunks,
+            next_seq: self.next_seq,
+            truncated,
+        })
+    }
+}
+
+/// One agent's numbered output. The bytes are the batches as emitted — the
+/// PTY stream, already cut on UTF-8 boundaries.
+pub struct OutputScrollback {
+    chunks: VecDeque<StoredChunk>,
+    bytes: usize,
+    capacity: usize,
+    /// Evicted from memory but not on disk yet; still replayed from here.
+    unspilled: VecDeque<StoredChunk>,
+    next_seq: u64,
+    /// The oldest seq that can still be read back, from memory or the spill.
+    first_seq: u64,
+    spill: Option<Spill>,
+}
+
+impl OutputScrollback {
+    /// `spill` is the stem the segment files are named after; without one,
+    /// evicted output is dropped.
+    pub fn new(capacity: usize, spill: Option<PathBuf>) -> Self {
+        Self {
+            chunks: VecDeque::new(),
+            bytes: 0,
+            capacity,
+            unspilled: VecDeque::new(),
+            next_seq: 0,
+            first_seq: 0,
+            spill: spill.map(|stem| Spill {
+                stem,
+                next_index: 0,
+                segment_bytes: SPILL_SEGMENT_BYTES,
+                segments: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Stores a chunk and returns its seq. The newest chunk always stays in
+    /// memory, however large.
+    pub fn push(&mut self, stream: &str, timestamp: u64, data: &[u8]) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.bytes += data.len();
+        self.chunks.push_back(StoredChunk {
+            seq,
+            stream: stream.to_string(),
+            timestamp,
+            bytes: data.to_vec(),
+        });
+
+        while self.bytes > self.capacity && self.chunks.len() > 1 {
+            let Some(evicted) = self.chunks.pop_front() else {
+                break;
+            };
+            self.bytes -= evicted.bytes.len();
+            if self.spill.is_some() {
+                self.unspilled.push_back(evicted);
+            } else {
+                self.first_seq = evicted.seq + 1;
+            }
+        }
+        seq
+    }
+
+    /// The evicted chunks still to be written, addressed to the segment they
+    /// go to — a fresh one once the current one is full.
+    pub fn take_spill(&mut self) -> Option<SpillWrite> {
+        let first = self.unspilled.front()?.seq;
+        let spill = self.spill.as_mut()?;
+        let segment_bytes = spill.segment_bytes;
+        if !matches!(spill.segments.back(), Some(s) if s.bytes < segment_bytes) {
+            let path = spill.segment_path(spill.next_index);
+            spill.next_index += 1;
+            spill.segments.push_back(SpillSegment {
+                path,
+                first_seq: first,
+                lines: 0,
+                bytes: 0,
+            });
+        }
+        let segment = spill.segments.back()?;
+        Some(SpillWrite {
+            path: segment.path.clone(),
+            create: segment.lines == 0,
+            chunks: self.unspilled.iter().map(StoredChunk::to_chunk).collect(),
+        })
+    }
+
+    /// Books a finished [`SpillWrite`] and returns the files that are no
+    /// longer needed, for the caller to delete. A spill that fails once is
+    /// switched off for good: a file with a hole in it would replay as if it
+    /// were complete.
+    pub fn finish_spill(
+        &mut self,
+        write: &SpillWrite,
+        written: Result<u64, String>,
+    ) -> Vec<PathBuf> {
+        let Some(spill) = self.spill.as_mut() else {
+            return vec![write.path.clone()];
+        };
+        let bytes = match written {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                eprintln!("Agent output: could not spill scrollback: {error}");
+                let stale = spill.paths();
+                self.spill = None;
+                self.unspilled.clear();
+                self.first_seq = self.chunks.front().map_or(self.next_seq, |c| c.seq);
+                return stale;
+            }
+        };
+        let Some(segment) = spill.segments.back_mut() else {
+            return Vec::new();
+        };
+        segment.lines += write.chunks.len();
+        segment.bytes += bytes;
+        self.unspilled
+            .drain(..write.chunks.len().min(self.unspilled.len()));
+
+        let mut stale = Vec::new();
+        while spill.segments.len() > SPILL_SEGMENTS {
+            if let Some(dropped) = spill.segments.pop_front() {
+                stale.push(dropped.path);
+            }
+        }
+        if let Some(oldest) = spill.segments.front() {
+            self.first_seq = self.first_seq.max(oldest.first_seq);
+        }
+        stale
+    }
+
+    /// Every chunk numbered `since_seq` or later, oldest first: the spill
+    /// segments to read and everything newer, as of now.
+    pub fn replay(&self, since_seq: u64) -> OutputReplay {
+        let held_from = self
+            .unspilled
+            .front()
+            .or(self.chunks.front())
+            .map_or(self.next_seq, |c| c.seq);
+        let mut segments = Vec::new();
+        if let Some(spill) = self.spill.as_ref().filter(|_| since_seq < held_from) {
+            let ends = spill
+                .segments
+                .iter()
+                .skip(1)
+                .map(|s| s.first_seq)
+                .chain(std::iter::once(held_from));
+            for (segment, end) in spill.segments.iter().zip(ends) {
+                if end > since_seq && segment.lines > 0 {
+                    segments.push((segment.path.clone(), segment.lines));
+                }
+            }
+        }
+        OutputReplay {
+            since_seq,
+            segments,
+            tail: self
+                .unspilled
+                .iter()
+                .chain(self.chunks.iter())
+                .filter(|c| c.seq >= since_seq)
+                .map(StoredChunk::to_chunk)
+                .collect(),
+            next_seq: self.next_seq,
+            truncated: since_seq < self.first_seq,
+        }
+    }
+}
+
+impl Drop for OutputScrollback {
+    fn drop(&mut self) {
+        for path in self.spill.take().map(|s| s.paths()).unwrap_or_default() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Every agent's scrollback, kept past the agent's exit so its transcript can
+/// still be opened afterwards.
+#[derive(Default)]
+pub struct AgentOutputStore {
+    scrollbacks: HashMap<String, OutputScrollback>,
+    /// Oldest first, for eviction.
+    order: VecDeque<String>,
+    spill_dir: Option<PathBuf>,
+}
+
+impl AgentOutputStore {
+    /// Where long sessions spill to. Cleared here: anything in it is from a
+    /// previous run, whose scrollback is gone, and an agent id can come round
+    /// again.
+    pub fn set_spill_dir(&mut self, dir: PathBuf) {
+        let _ = std::fs::remove_dir_all(&dir);
+        self.spill_dir = Some(dir);
+    }
+
+    /// Records a chunk for `agent_id` and returns its seq. `is_running` keeps
+    /// live agents out of eviction when a new scrollback needs room.
+    pub fn record(
+        &mut self,
+        agent_id: &str,
+        stream: &str,
+        timestamp: u64,
+        data: &[u8],
+        is_running: impl Fn(&str) -> bool,
+    ) -> u64 {
+        if !self.scrollbacks.contains_key(agent_id) {
+            while self.order.len() >= RETAINED_TRANSCRIPTS {
+                let Some(position) = self.order.iter().position(|id| !is_running(id)) else {
+                    break;
+                };
+                if let Some(evicted) = self.order.remove(position) {
+                    self.scrollbacks.remove(&evicted);
+                }
+            }
+            let spill = self.spill_dir.as_ref().map(|dir| dir.join(agent_id));
+            self.scrollbacks.insert(
+                agent_id.to_string(),
+                OutputScrollback::new(SCROLLBACK_BYTES, spill),
+            );
+            self.order.push_back(agent_id.to_string());
+        }
+        match self.scrollbacks.get_mut(agent_id) {
+            Some(scrollback) => scrollback.push(stream, timestamp, data),
+            None => 0,
+        }
+    }
+
+    /// What `agent_id` has to write to its spill, if anything. An agent's
+    /// output is recorded by one task, which finishes each write before it
+    /// takes the next.
+    pub fn take_spill(&mut self, agent_id: &str) -> Option<SpillWrite> {
+        self.scrollbacks.get_mut(agent_id)?.take_spill()
+    }
+
+    /// Books a finished write; the returned files are for the caller to
+    /// delete. A scrollback evicted while its write ran leaves only the file
+    /// that write may have created.
+    pub fn finish_spill(
+        &mut self,
+        agent_id: &str,
+        write: &SpillWrite,
+        written: Result<u64, String>,
+    ) -> Vec<PathBuf> {
+        match self.scrollbacks.get_mut(agent_id) {
+            Some(scrollback) => scrollback.finish_spill(write, written),
+            None => vec![write.path.clone()],
+        }
+    }
+
+    pub fn since(&self, agent_id: &str, since_seq: u64) -> Result<OutputReplay, String> {
+        self.scrollbacks
+            .get(agent_id)
+            .map(|scrollback| scrollback.replay(since_seq))
+            .ok_or_else(|| format!("No output recorded for agent: {}", agent_id))
+    }
+
+    pub fn clear(&mut self) {
+        self.scrollbacks.clear();
+        self.order.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(page: &AgentOutputPage) -> Vec<String> {
+        page.chunks.iter().map(|c| c.data.clone()).collect()
+    }
+
+    fn since(scrollback: &OutputScrollback, since_seq: u64) -> AgentOutputPage {
+        scrollback.replay(since_seq).read().unwrap()
+    }
+
+    /// Runs the pending spill write the way `emit_agent_output` does.
+    fn spill(scrollback: &mut OutputScrollback) {
+        if let Some(write) = scrollback.take_spill() {
+            let written = write.write();
+            for path in scrollback.finish_spill(&write, written) {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    #[test]
+    fn replays_from_any_seq_and_reports_what_was_dropped() {
+        let mut scrollback = OutputScrollback::new(8, None);
+        assert_eq!(scrollback.push("stdout", 1, b"abcd"), 0);
+        assert_eq!(scrollback.push("stdout", 2, b"efgh"), 1);
+
+        let page = since(&scrollback, 0);
+        assert_eq!(data(&page), ["abcd", "efgh"]);
+        assert_eq!(page.next_seq, 2);
+        assert!(!page.truncated);
+        assert_eq!(data(&since(&scrollback, 1)), ["efgh"]);
+        assert!(since(&scrollback, 2).chunks.is_empty());
+
+        // Over capacity, the oldest chunk goes — and a replay from it says so.
+        scrollback.push("stderr", 3, b"ij");
+        let page = since(&scrollback, 0);
+        assert_eq!(data(&page), ["efgh", "ij"]);
+        assert_eq!(page.chunks[1].stream, "stderr");
+        assert_eq!(page.chunks[1].timestamp, 3);
+        assert!(page.truncated);
+        assert!(!since(&scrollback, 1).truncated);
+
+        // One chunk bigger than the whole buffer is still kept.
+        scrollback.push("stdout", 4, b"0123456789");
+        assert_eq!(data(&since(&scrollback, 0)), ["0123456789"]);
+    }
+
+    #[test]
+    fn spilled_output_replays_in_full_and_is_removed_with_the_scrollback() {
+        let dir = tempfile::tempdir().unwrap();
+        let stem = dir.path().join("spill").join("agent-1");
+        let mut scrollback = OutputScrollback::new(4, Some(stem));
+        for (i, text) in ["ab", "cd", "ef", "gh\u{e4}"].iter().enumerate() {
+            scrollback.push("stdout", i as u64, text.as_bytes());
+            // Evicted but not written yet: still part of the replay.
+            assert_eq!(data(&since(&scrollback, 0)).len(), i + 1);
+            spill(&mut scrollback);
+        }
+
+        let page = since(&scrollback, 0);
+        assert_eq!(data(&page), ["ab", "cd", "ef", "gh\u{e4}"]);
+        assert!(!page.truncated);
+        assert_eq!(data(&since(&scrollback, 1)), ["cd", "ef", "gh\u{e4}"]);
+        let segment = dir.path().join("spill").join("agent-1.0.jsonl");
+        assert!(segment.is_file());
+
+        drop(scrollback);
+        assert!(!segment.exists());
+    }
+
+    #[test]
+    fn a_replay_reads_only_the_lines_that_were_complete_when_it_was_taken() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut scrollback = OutputScrollback::new(2, Some(dir.path().join("agent-1")));
+        for text in ["ab", "cd", "ef"] {
+            scrollback.push("stdout", 0, text.as_bytes());
+            spill(&mut scrollback);
+        }
+        let replay = scrollback.replay(0);
+        // An append racing the read leaves half a line behind the booked ones.
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(dir.path().join("agent-1.0.jsonl"))
+            .unwrap();
+        write!(file, "{{\"seq\":").unwrap();
+
+        assert_eq!(data(&replay.read().unwrap()), ["ab", "cd", "ef"]);
+    }
+
+    #[test]
+    fn the_spill_rotates_and_keeps_the_newest_two_segments() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut scrollback = OutputScrollback::new(1, Some(dir.path().join("agent-1")));
+        scrollback.spill.as_mut().unwrap().segment_bytes = 1;
+        for text in ["a", "b", "c", "d", "e"] {
+            scrollback.push("stdout", 0, text.as_bytes());
+            spill(&mut scrollback);
+        }
+
+        // One chunk per segment: "a" and "b" rotated out, "c" and "d" on
+        // disk, "e" in memory.
+        let page = since(&scrollback, 0);
+        assert_eq!(data(&page), ["c", "d", "e"]);
+        assert!(page.truncated);
+        assert!(!since(&scrollback, 2).truncated);
+        assert_eq!(data(&since(&scrollback, 3)), ["d", "e"]);
+        let mut files: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["agent-1.2.jsonl", "agent-1.3.jsonl"]);
+    }
+
+    #[test]
+    fn a_failed_spill_drops_what_it_held_and_says_so() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut scrollback = OutputScrollback::new(2, Some(dir.path().join("agent-1")));
+        scrollback.push("stdout", 0, b"ab");
+        scrollback.push("stdout", 0, b"cd");
+        let write = scrollback.take_spill().unwrap();
+        for path in scrollback.finish_spill(&write, Err("disk full".to_string())) {
+            let _ = std::fs::remove_file(path);
+        }
+
+        let page = since(&scrollback, 0);
+        assert_eq!(data(&page), ["cd"]);
+        assert!(page.truncated);
+        // Switched off: later evictions are not queued for the disk.
+        scrollback.push("stdout", 0, b"ef");
+        assert!(scrollback.take_spill().is_none());
+    }
+
+    #[test]
+    fn the_store_keeps_running_agents_and_evicts_the_oldest_finished() {
+        let mut store = AgentOutputStore::default();
+        for n in 0..RETAINED_TRANSCRIPTS {
+            store.record(&format!("agent-{}", n), "stdout", 0, b"x", |_| true);
+        }
+        assert!(store.since("nobody", 0).is_err());
+
+        // Everything is running: nothing can go, so the store grows.
+        store.record("agent-new", "stdout", 0, b"x", |_| true);
+        assert!(store.since("agent-0", 0).is_ok());
+
+        // agent-0 is running, agent-1 is not — agent-1 makes room.
+        store.record("agent-newer", "stdout", 0, b"x", |id| id == "agent-0");
+        assert!(store.since("agent-0", 0).is_ok());
+        assert!(store.since("agent-1", 0).is_err());
+
+        // Existing scrollbacks keep numbering where they were.
+        assert_eq!(store.record("agent-0", "stdout", 0, b"y", |_| true), 1);
+    }
+}
@@ -5,6 +5,7 @@
 //! the frontend offers to resume (re-spawn with a continuation task) or
 //! discard each one.
 
+use crate::agents::AgentLimits;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
@@ -31,6 +32,17 @@ pub struct PersistedAgent {
     pub spawned_by_ticket_id: Option<String>,
     #[serde(default)]
     pub spawned_by_goal_id: Option<String>,
+    /// Carried over so a resumed run is held to the bounds the original had.
+    #[serde(default)]
+    pub limits: Option<AgentLimits>,
+    /// The provider's own id for the conversation, once known; a resume goes
+    /// back into it rather than starting over.
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub images: Option<Vec<String>>,
+    #[serde(default)]
+    pub system_prompt: Option<String>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -105,6 +117,36 @@ impl AgentPersistence {
         renamed
     }
 
+    /// Records the provider session a running agent turned out to be in.
+    /// Returns whether the agent was found.
+    pub fn set_session_id(&mut self, agent_id: &str, session_id: &str) -> bool {
+        let Some(agent) = self.active.iter_mut().find(|a| a.id == agent_id) else {
+            return false;
+        };
+        if agent.session_id.as_deref() != Some(session_id) {
+            agent.session_id = Some(session_id.to_string());
+            self.save();
+        }
+        true
+    }
+
+    /// The session a running agent is known to be in.
+    pub fn session_id(&self, agent_id: &str) -> Option<String> {
+        self.active
+            .iter()
+            .find(|a| a.id == agent_id)
+            .and_then(|a| a.session_id.clone())
+    }
+
+    /// Every session id already tied to an agent, running or interrupted.
+    pub fn session_ids(&self) -> Vec<String> {
+        self.active
+            .iter()
+            .chain(self.interrupted.iter())
+            .filter_map(|a| a.session_id.clone())
+            .collect()
+    }
+
     pub fn interrupted(&self) -> Vec<PersistedAgent> {
         self.interrupted.clone()
     }
@@ -175,6 +217,10 @@ mod tests {
             started_at: 42,
             spawned_by_ticket_id: None,
             spawned_by_goal_id: Some("goal-1".to_string()),
+            limits: None,
+            session_id: None,
+            images: None,
+            system_prompt: None,
         }
     }
 
@@ -273,6 +319,29 @@ mod tests {
         assert!(!p.rename("agent-404", "Nobody"));
     }
 
+    #[test]
+    fn test_session_id_learnt_while_running_survives_a_restart() {
+        let path = temp_path("agents.json");
+        let mut p = AgentPersistence::load(Some(path.clone()));
+        p.record_spawn(sample_agent("agent-1"));
+        assert!(p.set_session_id("agent-1", "0f8fad5b-d9cb-469f-a165-70867728950e"));
+        assert!(!p.set_session_id("agent-404", "abc"));
+        assert_eq!(
+            p.session_id("agent-1").as_deref(),
+            Some("0f8fad5b-d9cb-469f-a165-70867728950e")
+        );
+
+        let restarted = AgentPersistence::load(Some(path));
+        assert_eq!(
+            restarted.interrupted()[0].session_id.as_deref(),
+            Some("0f8fad5b-d9cb-469f-a165-70867728950e")
+        );
+        assert_eq!(
+            restarted.session_ids(),
+            vec!["0f8fad5b-d9cb-469f-a165-70867728950e".to_string()]
+        );
+    }
+
     #[test]
     fn test_take_interrupted_removes_and_returns_the_agent() {
         let path = temp_path("agents.json");
@@ -0,0 +1,325 @@
+//! Waiting room for agent spawns, with a global and a per-repository cap.
+//!
+//! The conductor used to be the only thing holding back a fleet, and only for
+//! the runs it started itself — a schedule, a notification action or a manual
+//! start went straight past it. Every spawn now passes through here: it joins
+//! the queue, and whatever fits under the caps leaves it at once. A spawn that
+//! does not fit waits as `Queued` and starts when a slot frees up.
+//!
+//! Order is priority first (higher starts sooner), then arrival. A spawn held
+//! back only by its repository's cap does not hold up the others: the queue is
+//! walked in order and every entry that fits is taken.
+//!
+//! The caps are the webview's settings, read from the `webview-prefs.json`
+//! mirror when they are needed. Unset, empty or `0` is no cap, which is how
+//! spawning behaved before the queue existed.
+
+use std::collections::HashMap;
+
+pub const MAX_CONCURRENT_PREF_KEY: &str = "auric.agent-queue.max-concurrent";
+pub const MAX_PER_REPO_PREF_KEY: &str = "auric.agent-queue.max-per-repo";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AgentQueueLimits {
+    /// Agents running at once, across every repository.
+    pub max_concurrent: Option<u32>,
+    /// Agents running at once in one repository, worktrees included.
+    pub max_per_repo: Option<u32>,
+}
+
+impl AgentQueueLimits {
+    /// Reads both caps through `get`. Anything that is not a positive number
+    /// is no cap: a mangled setting must not stop every agent from starting.
+    pub fn from_prefs(get: impl Fn(&str) -> Option<String>) -> Self {
+        let cap = |key: &str| {
+            get(key)
+                .and_then(|raw| raw.trim().parse::<u32>().ok())
+                .filter(|n| *n > 0)
+        };
+        Self {
+            max_concurrent: cap(MAX_CONCURRENT_PREF_KEY),
+            max_per_repo: cap(MAX_PER_REPO_PREF_KEY),
+        }
+    }
+}
+
+/// The slots already taken — running agents and those being started — counted
+/// overall and per repository.
+#[derive(Debug, Default)]
+pub struct Occupancy {
+    total: u32,
+    per_repo: HashMap<String, u32>,
+}
+
+impl Occupancy {
+    pub fn new<'a>(repos: impl IntoIterator<Item = Option<&'a str>>) -> Self {
+        let mut occupancy = Self::default();
+        for repo in repos {
+            occupancy.take(repo);
+        }
+        occupancy
+    }
+
+    /// Whether one more agent in `repo` fits. An agent without a repository
+    /// counts only against the global cap.
+    pub fn admits(&self, limits: &AgentQueueLimits, repo: Option<&str>) -> bool {
+        if limits.max_concurrent.is_some_and(|max| self.total >= max) {
+            return false;
+        }
+        match (repo, limits.max_per_repo) {
+            (Some(repo), Some(max)) => self.per_repo.get(repo).copied().unwrap_or(0) < max,
+            _ => true,
+        }
+    }
+
+    fn take(&mut self, repo: Option<&str>) {
+        self.total += 1;
+        if let Some(repo) = repo {
+            *self.per_repo.entry(repo.to_string()).or_default() += 1;
+        }
+    }
+}
+
+pub struct QueuedSpawn<T> {
+    pub id: String,
+    /// The repository the per-repo cap counts it under.
+    pub repo: Option<String>,
+    pub priority: i32,
+    pub payload: T,
+}
+
+/// Spawns waiting for a slot, kept in the order they will start.
+pub struct AgentQueue<T> {
+    entries: Vec<QueuedSpawn<T>>,
+}
+
+impl<T> Default for AgentQueue<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<T> AgentQueue<T> {
+    /// Queues a spawn behind everything of the same or higher priority.
+    pub fn push(&mut self, spawn: QueuedSpawn<T>) {
+        let position = self
+            .entries
+            .iter()
+            .position(|queued| queued.priority < spawn.priority)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(position, spawn);
+    }
+
+    /// Every queued spawn, in the order they will start.
+    pub fn iter(&self) -> impl Iterator<Item = &QueuedSpawn<T>> {
+        self.entries.iter()
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<QueuedSpawn<T>> {
+        let position = self.entries.iter().position(|queued| queued.id == id)?;
+        Some(self.entries.remove(position))
+    }
+
+    /// Takes every spawn that fits under `limits`, in queue order, counting
+    /// each against `occupancy` as it goes.
+    pub fn take_ready(
+        &mut self,
+        occupancy: &mut Occupancy,
+        limits: &AgentQueueLimits,
+    ) -> Vec<QueuedSpawn<T>> {
+        let mut ready = Vec::new();
+        let mut index = 0;
+        while index < self.entries.len() {
+            if limits
+                .max_concurrent
+                .is_some_and(|max| occupancy.total >= max)
+            {
+                break;
+            }
+            let repo = self.entries[index].repo.as_deref();
+            if occupancy.admits(limits, repo) {
+                occupancy.take(repo);
+                ready.push(self.entries.remove(index));
+            } else {
+                index += 1;
+            }
+        }
+        ready
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Starts a batch `take_ready` released, one of which a caller is waiting on.
+///
+/// Every spawn in the batch has already left the queue and holds a slot, and
+/// nothing else will start it — so the others are started whatever becomes of
+/// the caller's own. Returns the caller's result, or `None` when its spawn
+/// was not in the batch and stays queued.
+pub async fn start_released<T, R, OwnFuture, OtherFuture>(
+    ready: Vec<QueuedSpawn<T>>,
+    own_id: &str,
+    start_own: impl FnOnce(QueuedSpawn<T>) -> OwnFuture,
+    mut start_other: impl FnMut(QueuedSpawn<T>) -> OtherFuture,
+) -> Option<R>
+where
+    OwnFuture: std::future::Future<Output = R>,
+    OtherFuture: std::future::Future<Output = ()>,
+{
+    let mut start_own = Some(start_own);
+    let mut own = None;
+    for spawn in ready {
+        let start = if spawn.id == own_id {
+            start_own.take()
+        } else {
+            None
+        };
+        match start {
+            Some(start) => own = Some(start(spawn).await),
+            None => start_other(spawn).await,
+        }
+    }
+    own
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn(id: &str, repo: Option<&str>, priority: i32) -> QueuedSpawn<()> {
+        QueuedSpawn {
+            id: id.to_string(),
+            repo: repo.map(str::to_string),
+            priority,
+            payload: (),
+        }
+    }
+
+    fn ids(spawns: &[QueuedSpawn<()>]) -> Vec<&str> {
+        spawns.iter().map(|s| s.id.as_str()).collect()
+    }
+
+    #[test]
+    fn limits_come_from_prefs_and_nonsense_is_no_limit() {
+        let prefs = |entries: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                entries
+                    .iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert_eq!(
+            AgentQueueLimits::from_prefs(prefs(&[
+                (MAX_CONCURRENT_PREF_KEY, "4"),
+                (MAX_PER_REPO_PREF_KEY, " 2 "),
+            ])),
+            AgentQueueLimits {
+                max_concurrent: Some(4),
+                max_per_repo: Some(2),
+            }
+        );
+        assert_eq!(
+            AgentQueueLimits::from_prefs(prefs(&[
+                (MAX_CONCURRENT_PREF_KEY, "0"),
+                (MAX_PER_REPO_PREF_KEY, "lots"),
+            ])),
+            AgentQueueLimits::default()
+        );
+    }
+
+    #[test]
+    fn higher_priority_starts_first_and_equal_priority_in_arrival_order() {
+        let mut queue = AgentQueue::default();
+        queue.push(spawn("a", None, 0));
+        queue.push(spawn("b", None, 5));
+        queue.push(spawn("c", None, 0));
+        queue.push(spawn("d", None, 5));
+        queue.push(spawn("e", None, -1));
+
+        let limits = AgentQueueLimits {
+            max_concurrent: Some(3),
+            max_per_repo: None,
+        };
+        let mut occupancy = Occupancy::new([None]);
+        assert_eq!(ids(&queue.take_ready(&mut occupancy, &limits)), ["b", "d"]);
+        assert_eq!(
+            queue.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(),
+            ["a", "c", "e"]
+        );
+
+        // No caps: everything goes.
+        let mut occupancy = Occupancy::default();
+        let all = queue.take_ready(&mut occupancy, &AgentQueueLimits::default());
+        assert_eq!(ids(&all), ["a", "c", "e"]);
+    }
+
+    #[test]
+    fn a_full_repository_does_not_hold_up_the_others() {
+        let mut queue = AgentQueue::default();
+        queue.push(spawn("api-1", Some("/api"), 0));
+        queue.push(spawn("api-2", Some("/api"), 0));
+        queue.push(spawn("web-1", Some("/web"), 0));
+        queue.push(spawn("loose", None, 0));
+
+        let limits = AgentQueueLimits {
+            max_concurrent: Some(3),
+            max_per_repo: Some(1),
+        };
+        let mut occupancy = Occupancy::new([Some("/api")]);
+        assert!(!occupancy.admits(&limits, Some("/api")));
+        assert!(occupancy.admits(&limits, Some("/web")));
+
+        let ready = queue.take_ready(&mut occupancy, &limits);
+        assert_eq!(ids(&ready), ["web-1", "loose"]);
+        // The global cap now binds too.
+        assert!(!occupancy.admits(&limits, None));
+
+        assert_eq!(
+            queue.remove("api-1").map(|s| s.id).as_deref(),
+            Some("api-1")
+        );
+        assert!(queue.remove("api-1").is_none());
+    }
+
+    #[tokio::test]
+    async fn a_failed_own_start_still_starts_the_rest_of_its_batch() {
+        let mut queue = AgentQueue::default();
+        queue.push(spawn("waiting", None, 0));
+        queue.push(spawn("mine", None, 0));
+        queue.push(spawn("behind", None, 0));
+        let ready = queue.take_ready(&mut Occupancy::default(), &AgentQueueLimits::default());
+        assert_eq!(ids(&ready), ["waiting", "mine", "behind"]);
+
+        let mut others = Vec::new();
+        let own = start_released(
+            ready,
+            "mine",
+            |_| async { Err::<(), _>("provider not permitted") },
+            |spawn| {
+                others.push(spawn.id);
+                async {}
+            },
+        )
+        .await;
+
+        assert_eq!(own, Some(Err("provider not permitted")));
+        assert_eq!(others, ["waiting", "behind"]);
+    }
+
+    #[tokio::test]
+    async fn a_caller_left_queued_gets_no_result() {
+        let own = start_released(
+            vec![spawn("other", None, 0)],
+            "mine",
+            |_| async {},
+            |_| async {},
+        )
+        .await;
+        assert_eq!(own, None);
+    }
+}
@@ -0,0 +1,407 @@
+//! Whether a project's agents run sandboxed, and the sandbox they run in.
+//!
+//! The twin of `src/lib/config/agentSandbox.ts`, and stored the same way as
+//! the provider policy: a JSON value in the project database that the settings
+//! screen writes and the spawn path reads. Both sides parse against
+//! `src/lib/config/agentSandbox.fixtures.json`.
+//!
+//! The sandbox is bubblewrap. The whole filesystem is mounted read-only, and
+//! only the agent's working directory, the project's `.auric` directory and
+//! any paths the project adds stay writable — except git's hooks and config
+//! inside them, which would run code outside the sandbox. Blocking the
+//! network is a second opt-in on top. There is no silent fallback: a project
+//! that asked for a sandbox and cannot get one gets no agent, and the spawn
+//! error says why.
+
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The namespace and key the settings live under in the project database.
+pub const SANDBOX_NAMESPACE: &str = "agent_sandbox";
+pub const SANDBOX_KEY: &str = "settings";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentSandbox {
+    pub enabled: bool,
+    pub block_network: bool,
+    /// Writable on top of the defaults — typically the CLI's own state
+    /// directory, such as `~/.claude`. A leading `~/` is the user's home.
+    pub writable_paths: Vec<String>,
+}
+
+impl AgentSandbox {
+    /// What a stored value we cannot read turns into. Unlike the provider
+    /// policy this fails closed: a corrupted blob must not quietly take the
+    /// sandbox away from a project that asked for one.
+    fn unreadable() -> Self {
+        Self {
+            enabled: true,
+            block_network: true,
+            writable_paths: Vec::new(),
+        }
+    }
+}
+
+pub fn parse_agent_sandbox(raw: Option<&str>) -> AgentSandbox {
+    let Some(raw) = raw.filter(|r| !r.is_empty()) else {
+        return AgentSandbox::default();
+    };
+    let Ok(serde_json::Value::Object(source)) = serde_json::from_str::<serde_json::Value>(raw)
+    else {
+        return AgentSandbox::unreadable();
+    };
+
+    let flag = |key: &str| source.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+    let mut writable_paths: Vec<String> = Vec::new();
+    if let Some(serde_json::Value::Array(entries)) = source.get("writablePaths") {
+        for entry in entries {
+            let Some(text) = entry.as_str().map(str::trim) else {
+                continue;
+            };
+            if !text.is_empty() && !writable_paths.iter().any(|p| p == text) {
+                writable_paths.push(text.to_string());
+            }
+        }
+    }
+
+    AgentSandbox {
+        enabled: flag("enabled"),
+        block_network: flag("blockNetwork"),
+        writable_paths,
+    }
+}
+
+/// Reads the settings for the project rooted at `project`.
+///
+/// Read-only and without creating anything, for the same reason as
+/// `policy_for_project`: a project never opened in the IDE has no settings,
+/// and launching an agent there must not give it a database. Only a value that
+/// exists and cannot be parsed counts as unreadable.
+pub fn sandbox_for_project(project: &Path) -> AgentSandbox {
+    let db_path = project.join(".auric").join("project.db");
+    if !db_path.is_file() {
+        return AgentSandbox::default();
+    }
+
+    let Ok(conn) = Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY) else {
+        return AgentSandbox::default();
+    };
+
+    let raw: Option<String> = conn
+        .query_row(
+            "SELECT value FROM kv_store WHERE namespace = ?1 AND key = ?2",
+            rusqlite::params![SANDBOX_NAMESPACE, SANDBOX_KEY],
+            |row| row.get(0),
+        )
+        .ok();
+
+    parse_agent_sandbox(raw.as_deref())
+}
+
+/// The first `bwrap` on `path`.
+fn find_bwrap(path: Option<&str>) -> Option<PathBuf> {
+    std::env::split_paths(path?)
+        .map(|dir| dir.join("bwrap"))
+        .find(|candidate| candidate.is_file())
+}
+
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Everything bubblewrap is told before the `--`: read-only root, real
+/// devices and a fresh `/proc` and `/tmp`, then the writable binds, then
+/// `read_only` bound back over them.
+fn bwrap_args(
+    sandbox: &AgentSandbox,
+    cwd: &Path,
+    writable: &[PathBuf],
+    read_only: &[PathBuf],
+) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "--die-with-parent",
+        "--ro-bind",
+        "/",
+        "/",
+        "--dev-bind",
+        "/dev",
+        "/dev",
+        "--proc",
+        "/proc",
+        "--tmpfs",
+        "/tmp",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+
+    for path in writable {
+        let path = path.to_string_lossy().to_string();
+        args.extend(["--bind".to_string(), path.clone(), path]);
+    }
+    // Later mounts win, so these must follow the writable binds they sit in.
+    for path in read_only {
+        let path = path.to_string_lossy().to_string();
+        args.extend(["--ro-bind".to_string(), path.clone(), path]);
+    }
+    if sandbox.block_network {
+        args.push("--unshare-net".to_string());
+    }
+    args.extend(["--chdir".to_string(), cwd.to_string_lossy().to_string()]);
+    args
+}
+
+/// The paths an agent in `cwd` may write: the checkout it works in, the
+/// project's `.auric` (the database and its WAL files sit side by side), the
+/// main repository's `.git` when `cwd` is a linked worktree — a commit there
+/// writes its objects into the main repository — and the project's extras.
+/// Paths that do not exist are left out; bubblewrap refuses to bind them.
+fn writable_paths(sandbox: &AgentSandbox, cwd: &Path, project: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![cwd.to_path_buf(), project.join(".auric")];
+    if project != cwd {
+        paths.push(project.join(".git"));
+    }
+    paths.extend(sandbox.writable_paths.iter().map(|p| expand_home(p)));
+
+    let mut seen = Vec::new();
+    for path in paths {
+        if path.exists() && !seen.contains(&path) {
+            seen.push(path);
+        }
+    }
+    seen
+}
+
+/// The parts of every writable git directory that git runs code from: the
+/// hooks and the config (`core.hooksPath`, `core.fsmonitor`). Left writable,
+/// an agent could plant a command that runs outside the sandbox on the
+/// user's next git action.
+fn git_control_paths(writable: &[PathBuf]) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for path in writable {
+        let git_dir = if path.file_name().is_some_and(|name| name == ".git") {
+            path.clone()
+        } else {
+            path.join(".git")
+        };
+        if !git_dir.is_dir() {
+            continue;
+        }
+        for control in [git_dir.join("hooks"), git_dir.join("config")] {
+            if control.exists() && !paths.contains(&control) {
+                paths.push(control);
+            }
+        }
+    }
+    paths
+}
+
+/// The program and arguments that run `shell -c command` inside the
+/// sandbox. `path` is the PATH the agent will get, so `bwrap` is looked up
+/// where the agent's own tools are.
+///
+/// Fails with a message meant for the user: the sandbox was asked for, so an
+/// agent that cannot have one must not start.
+pub fn sandboxed_command(
+    sandbox: &AgentSandbox,
+    cwd: &Path,
+    project: &Path,
+    path: Option<&str>,
+) -> Result<(PathBuf, Vec<String>), String> {
+    if !cfg!(target_os = "linux") {
+        return Err(
+            "This project runs agents sandboxed, which is only supported on Linux. \
+             Turn the sandbox off in Settings → Providers to start agents here."
+                .to_string(),
+        );
+    }
+    let bwrap = find_bwrap(path).ok_or_else(|| {
+        "This project runs agents sandboxed, but bubblewrap (bwrap) is not installed. \
+         Install it, or turn the sandbox off in Settings → Providers."
+            .to_string()
+    })?;
+
+    let writable = writable_paths(sandbox, cwd, project);
+    let args = bwrap_args(sandbox, cwd, &writable, &git_control_paths(&writable));
+
+    // Unprivileged user namespaces can be switched off system-wide; find out
+    // now, with bwrap's own reason, rather than from an agent that dies
+    // without output.
+    let probe = std::process::Command::new(&bwrap)
+        .args(&args)
+        .args(["--", "true"])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| format!("Could not build the agent sandbox: {}", e))?;
+    if !probe.status.success() {
+        let reason = String::from_utf8_lossy(&probe.stderr).trim().to_string();
+        return Err(format!(
+            "Could not build the agent sandbox: {}",
+            if reason.is_empty() {
+                format!("bwrap exited with {}", probe.status)
+            } else {
+                reason
+            }
+        ));
+    }
+
+    Ok((bwrap, args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = include_str!("../../src/lib/config/agentSandbox.fixtures.json");
+
+    #[derive(Deserialize)]
+    struct ParseCase {
+        name: String,
+        raw: Option<String>,
+        expected: AgentSandbox,
+    }
+
+    #[derive(Deserialize)]
+    struct Fixtures {
+        parse: Vec<ParseCase>,
+    }
+
+    #[test]
+    fn matches_the_shared_parse_cases() {
+        let fixtures: Fixtures = serde_json::from_str(FIXTURES).expect("fixtures must parse");
+        assert!(fixtures.parse.len() >= 6);
+        for case in fixtures.parse {
+            assert_eq!(
+                parse_agent_sandbox(case.raw.as_deref()),
+                case.expected,
+                "parse case: {}",
+                case.name
+            );
+        }
+    }
+
+    #[test]
+    fn writes_are_bound_to_the_checkout_the_project_and_the_extras() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("repo");
+        let worktree = dir.path().join("repo.auric-wt").join("agent");
+        for path in [
+            project.join(".auric"),
+            project.join(".git"),
+            worktree.clone(),
+            dir.path().join("state"),
+        ] {
+            std::fs::create_dir_all(path).unwrap();
+        }
+        let sandbox = AgentSandbox {
+            enabled: true,
+            block_network: true,
+            writable_paths: vec![
+                dir.path().join("state").to_string_lossy().to_string(),
+                dir.path().join("missing").to_string_lossy().to_string(),
+            ],
+        };
+
+        let writable = writable_paths(&sandbox, &worktree, &project);
+        assert_eq!(
+            writable,
+            vec![
+                worktree.clone(),
+                project.join(".auric"),
+                project.join(".git"),
+                dir.path().join("state"),
+            ]
+        );
+        // The main checkout itself is not writable from its worktree.
+        assert!(!writable.contains(&project));
+
+        let args = bwrap_args(&sandbox, &worktree, &writable, &[]);
+        assert_eq!(&args[..4], ["--die-with-parent", "--ro-bind", "/", "/"]);
+        assert!(args.contains(&"--unshare-net".to_string()));
+        assert_eq!(
+            &args[args.len() - 2..],
+            [
+                "--chdir".to_string(),
+                worktree.to_string_lossy().to_string()
+            ]
+        );
+
+        let in_main_checkout = writable_paths(&AgentSandbox::default(), &project, &project);
+        assert_eq!(
+            in_main_checkout,
+            vec![project.clone(), project.join(".auric")]
+        );
+        assert!(
+            !bwrap_args(&AgentSandbox::default(), &project, &in_main_checkout, &[])
+                .contains(&"--unshare-net".to_string())
+        );
+    }
+
+    #[test]
+    fn git_hooks_and_config_stay_read_only_inside_the_writable_binds() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("repo");
+        let worktree = dir.path().join("repo.auric-wt").join("agent");
+        std::fs::create_dir_all(project.join(".git").join("hooks")).unwrap();
+        std::fs::write(project.join(".git").join("config"), "").unwrap();
+        std::fs::create_dir_all(project.join(".auric")).unwrap();
+        std::fs::create_dir_all(&worktree).unwrap();
+        // A linked worktree's `.git` is a file; the hooks live in the main one.
+        std::fs::write(worktree.join(".git"), "gitdir: elsewhere\n").unwrap();
+
+        let sandbox = AgentSandbox::default();
+        let writable = writable_paths(&sandbox, &worktree, &project);
+        let read_only = git_control_paths(&writable);
+        assert_eq!(
+            read_only,
+            vec![
+                project.join(".git").join("hooks"),
+                project.join(".git").join("config")
+            ]
+        );
+
+        let args = bwrap_args(&sandbox, &worktree, &writable, &read_only);
+        let last_bind = args.iter().rposition(|a| a == "--bind").unwrap();
+        for path in &read_only {
+            let path = path.to_string_lossy().to_string();
+            let at = args
+                .windows(3)
+                .position(|w| w[0] == "--ro-bind" && w[1] == path && w[2] == path)
+                .unwrap_or_else(|| panic!("{path} is not bound read-only: {args:?}"));
+            assert!(at > last_bind, "{path} is bound before a writable bind");
+        }
+
+        // The main checkout's own `.git` gets the same treatment.
+        let in_main_checkout = writable_paths(&sandbox, &project, &project);
+        assert_eq!(git_control_paths(&in_main_checkout), read_only);
+    }
+
+    #[test]
+    fn a_missing_bwrap_is_a_clear_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let err = sandboxed_command(
+            &AgentSandbox::unreadable(),
+            dir.path(),
+            dir.path(),
+            Some(&path),
+        )
+        .unwrap_err();
+        if cfg!(target_os = "linux") {
+            assert!(
+                err.contains("bubblewrap (bwrap) is not installed"),
+                "{}",
+                err
+            );
+        } else {
+            assert!(err.contains("only supported on Linux"), "{}", err);
+        }
+    }
+}
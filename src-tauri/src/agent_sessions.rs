@@ -0,0 +1,177 @@
+//! Provider-native session ids, so an interrupted agent can be resumed into
+//! the conversation it was having instead of being told its task again.
+//!
+//! A run's session id is learnt one of three ways, whichever the provider
+//! config allows: AuricIDE names the session itself (`sessionId` argument,
+//! e.g. Claude Code's `--session-id`), the CLI reports it in its event stream,
+//! or it shows up as the name of the transcript file the CLI writes under
+//! `sessionDirectory`. The id is kept with the agent's persisted spawn config;
+//! `resume_interrupted_agent` hands it back through the `resume` argument.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::path::Path;
+use std::time::SystemTime;
+
+/// How much of a session file is searched for the agent's working directory.
+const SESSION_FILE_HEAD_BYTES: usize = 16 * 1024;
+/// Session directories are date-sharded (`2026/10/17/…`); deeper is not ours.
+const SESSION_DIR_MAX_DEPTH: usize = 5;
+
+/// A fresh random (version 4) UUID for a session AuricIDE names itself.
+pub fn new_session_id() -> String {
+    // `RandomState` is seeded from the OS; two hashers give 128 random bits
+    // without a dependency for it.
+    let random = || RandomState::new().build_hasher().finish();
+    let mut bytes = [0u8; 16];
+    bytes[..8].copy_from_slice(&random().to_le_bytes());
+    bytes[8..].copy_from_slice(&random().to_le_bytes());
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// Whether `id` can go into a command line unquoted. A session id comes from
+/// the CLI's output or a file name, and is spliced into a shell command on
+/// resume — anything beyond this alphabet is refused rather than escaped.
+pub fn is_valid_session_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// The UUID a session file is named after: `<uuid>.jsonl`, or Codex's
+/// `rollout-<timestamp>-<uuid>.jsonl`.
+pub fn session_id_from_file_name(name: &str) -> Option<String> {
+    let stem = name.split('.').next()?;
+    let tail = stem.get(stem.len().checked_sub(36)?..)?;
+    let is_uuid = tail.char_indices().all(|(i, c)| match i {
+        8 | 13 | 18 | 23 => c == '-',
+        _ => c.is_ascii_hexdigit(),
+    });
+    is_uuid.then(|| tail.to_ascii_lowercase())
+}
+
+/// The session a CLI started in `cwd` at or after `since`, found by the
+/// transcript file it writes under `dir`.
+///
+/// The newest such file wins, provided its head mentions `cwd` — both CLIs
+/// that write these record the working directory in the first lines, and
+/// that is what tells two agents started side by side apart. `claimed` are
+/// ids other agents already own.
+pub fn find_session_in(
+    dir: &Path,
+    since: SystemTime,
+    cwd: &str,
+    claimed: &[String],
+) -> Option<String> {
+    let mut candidates: Vec<_> = walkdir::WalkDir::new(dir)
+        .max_depth(SESSION_DIR_MAX_DEPTH)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            if modified < since {
+                return None;
+            }
+            let id = session_id_from_file_name(&entry.file_name().to_string_lossy())?;
+            if claimed.contains(&id) {
+                return None;
+            }
+            Some((modified, id, entry.into_path()))
+        })
+        .collect();
+    candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.0));
+
+    candidates.into_iter().find_map(|(_, id, path)| {
+        use std::io::Read;
+        let mut head = Vec::with_capacity(SESSION_FILE_HEAD_BYTES);
+        std::fs::File::open(&path)
+            .ok()?
+            .take(SESSION_FILE_HEAD_BYTES as u64)
+            .read_to_end(&mut head)
+            .ok()?;
+        String::from_utf8_lossy(&head).contains(cwd).then_some(id)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLAUDE: &str = "0f8fad5b-d9cb-469f-a165-70867728950e";
+    const CODEX: &str = "7d444840-9dc0-41a4-8b4e-8a3bc6f0f4b0";
+
+    #[test]
+    fn minted_ids_are_distinct_v4_uuids() {
+        let a = new_session_id();
+        let b = new_session_id();
+        assert_ne!(a, b);
+        for id in [&a, &b] {
+            assert_eq!(session_id_from_file_name(id).as_deref(), Some(id.as_str()));
+            assert_eq!(&id[14..15], "4");
+            assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"), "{}", id);
+            assert!(is_valid_session_id(id));
+        }
+        assert!(!is_valid_session_id("abc; rm -rf ~"));
+        assert!(!is_valid_session_id(""));
+    }
+
+    #[test]
+    fn ids_come_from_either_kind_of_session_file_name() {
+        assert_eq!(
+            session_id_from_file_name(&format!("{CLAUDE}.jsonl")).as_deref(),
+            Some(CLAUDE)
+        );
+        assert_eq!(
+            session_id_from_file_name(&format!("rollout-2026-10-17T09-12-03-{CODEX}.jsonl"))
+                .as_deref(),
+            Some(CODEX)
+        );
+        assert_eq!(session_id_from_file_name("history.jsonl"), None);
+        assert_eq!(session_id_from_file_name("notes-0f8fad5b.jsonl"), None);
+    }
+
+    #[test]
+    fn the_newest_file_for_this_working_directory_is_the_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let day = dir.path().join("2026").join("10").join("17");
+        std::fs::create_dir_all(&day).unwrap();
+        let since = SystemTime::now() - std::time::Duration::from_secs(60);
+
+        std::fs::write(
+            day.join(format!("rollout-1-{CLAUDE}.jsonl")),
+            r#"{"type":"session_meta","payload":{"cwd":"/work/other"}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            day.join(format!("rollout-2-{CODEX}.jsonl")),
+            r#"{"type":"session_meta","payload":{"cwd":"/work/repo"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            find_session_in(dir.path(), since, "/work/repo", &[]).as_deref(),
+            Some(CODEX)
+        );
+        // Taken by another agent, or older than this run: not ours.
+        assert_eq!(
+            find_session_in(dir.path(), since, "/work/repo", &[CODEX.to_string()]),
+            None
+        );
+        let later = SystemTime::now() + std::time::Duration::from_secs(60);
+        assert_eq!(find_session_in(dir.path(), later, "/work/repo", &[]), None);
+    }
+}
@@ -0,0 +1,524 @@
+//! Full terminal transcripts of finished agent runs, kept per project.
+//!
+//! `agent_log` records what an agent did; this keeps what it printed, so a
+//! failed conductor run can still be taken apart after its process is gone.
+//! Each run is one gzip-compressed row in `<project>/.auric/transcripts.db` —
+//! a database of its own rather than a table in `project.db`, whose schema is
+//! shared with the MCP server and has no business carrying megabytes of
+//! terminal output.
+//!
+//! Retention is applied on every write: runs older than `RETENTION_DAYS` go,
+//! and past `MAX_TRANSCRIPTS` the oldest do. Search narrows by agent, ticket
+//! and goal in SQL, then decompresses what is left and matches the text with
+//! the terminal's escape sequences stripped.
+
+use crate::database::apply_migration;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+pub const RETENTION_DAYS: u32 = 30;
+pub const MAX_TRANSCRIPTS: u32 = 500;
+/// Matching lines returned per transcript; `matchCount` still counts them all.
+const MAX_SNIPPETS: usize = 5;
+const SNIPPET_CHARS: usize = 200;
+
+const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+/// Where a project's transcripts live, next to its `project.db`.
+pub fn db_path_for_project(project: &Path) -> PathBuf {
+    project.join(".auric").join("transcripts.db")
+}
+
+/// One finished run, without its text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentTranscriptMeta {
+    /// `<agent id>-<started at>`: agent ids come round again after a restart.
+    pub id: String,
+    pub agent_id: String,
+    pub agent_name: String,
+    pub provider: String,
+    pub model: String,
+    pub cwd: Option<String>,
+    pub ticket_id: Option<String>,
+    pub goal_id: Option<String>,
+    /// running|idle|error|limited as the run ended, or `killed`.
+    pub status: String,
+    pub exit_code: Option<i32>,
+    /// Epoch millis.
+    pub started_at: i64,
+    pub ended_at: i64,
+    /// Uncompressed size of the text.
+    pub size_bytes: i64,
+    /// The start of the run's output was lost before it could be saved.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptQuery {
+    pub agent_id: Option<String>,
+    pub ticket_id: Option<String>,
+    pub goal_id: Option<String>,
+    /// Case-insensitive, matched against the text without escape sequences.
+    pub text: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptHit {
+    #[serde(flatten)]
+    pub meta: AgentTranscriptMeta,
+    /// The first matching lines, trimmed. Empty without a text query.
+    pub snippets: Vec<String>,
+    pub match_count: u32,
+}
+
+pub fn run_migrations(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS _migrations (
+            id   INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );",
+    )
+    .map_err(|e| format!("Failed to create _migrations table: {}", e))?;
+
+    apply_migration(
+        conn,
+        1,
+        "create_agent_transcripts",
+        "CREATE TABLE agent_transcripts (
+            id          TEXT PRIMARY KEY,
+            agent_id    TEXT NOT NULL,
+            agent_name  TEXT NOT NULL,
+            provider    TEXT NOT NULL,
+            model       TEXT NOT NULL,
+            cwd         TEXT,
+            ticket_id   TEXT,
+            goal_id     TEXT,
+            status      TEXT NOT NULL,
+            exit_code   INTEGER,
+            started_at  INTEGER NOT NULL,
+            ended_at    INTEGER NOT NULL,
+            size_bytes  INTEGER NOT NULL,
+            truncated   INTEGER NOT NULL DEFAULT 0,
+            transcript  BLOB NOT NULL
+        );
+        CREATE INDEX agent_transcripts_recent ON agent_transcripts(ended_at DESC);
+        CREATE INDEX agent_transcripts_ticket ON agent_transcripts(ticket_id);
+        CREATE INDEX agent_transcripts_goal ON agent_transcripts(goal_id);",
+    )?;
+
+    Ok(())
+}
+
+/// Opens (creating if needed) the transcript database at `path`.
+pub fn init_db(path: &Path) -> Result<Connection, String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create transcript dir: {}", e))?;
+    }
+
+    let conn =
+        Connection::open(path).map_err(|e| format!("Failed to open transcript db: {}", e))?;
+
+    conn.execute_batch("PRAGMA journal_mode=WAL;")
+        .map_err(|e| format!("Failed to set WAL mode: {}", e))?;
+
+    run_migrations(&conn)?;
+
+    Ok(conn)
+}
+
+fn compress(text: &str) -> Result<Vec<u8>, String> {
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder
+        .write_all(text.as_bytes())
+        .and_then(|_| encoder.finish())
+        .map_err(|e| format!("Failed to compress transcript: {}", e))
+}
+
+fn decompress(bytes: &[u8]) -> Result<String, String> {
+    let mut text = String::new();
+    GzDecoder::new(bytes)
+        .read_to_string(&mut text)
+        .map_err(|e| format!("Failed to decompress transcript: {}", e))?;
+    Ok(text)
+}
+
+/// Stores a finished run and applies the retention policy in the same
+/// transaction.
+pub fn save_impl(
+    conn: &mut Connection,
+    meta: &AgentTranscriptMeta,
+    text: &str,
+    now_ms: i64,
+) -> Result<(), String> {
+    let blob = compress(text)?;
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to begin transcript transaction: {}", e))?;
+
+    tx.execute(
+        "INSERT OR REPLACE INTO agent_transcripts
+            (id, agent_id, agent_name, provider, model, cwd, ticket_id, goal_id,
+             status, exit_code, started_at, ended_at, size_bytes, truncated, transcript)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            meta.id,
+            meta.agent_id,
+            meta.agent_name,
+            meta.provider,
+            meta.model,
+            meta.cwd,
+            meta.ticket_id,
+            meta.goal_id,
+            meta.status,
+            meta.exit_code,
+            meta.started_at,
+            meta.ended_at,
+            text.len() as i64,
+            meta.truncated,
+            blob,
+        ],
+    )
+    .map_err(|e| format!("Failed to insert transcript: {}", e))?;
+
+    let cutoff = now_ms - i64::from(RETENTION_DAYS) * MILLIS_PER_DAY;
+    tx.execute(
+        "DELETE FROM agent_transcripts WHERE ended_at < ?1",
+        params![cutoff],
+    )
+    .map_err(|e| format!("Failed to prune transcripts by age: {}", e))?;
+    tx.execute(
+        "DELETE FROM agent_transcripts WHERE id NOT IN (
+            SELECT id FROM agent_transcripts ORDER BY ended_at DESC LIMIT ?1
+        )",
+        params![MAX_TRANSCRIPTS],
+    )
+    .map_err(|e| format!("Failed to prune transcripts by count: {}", e))?;
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transcript: {}", e))
+}
+
+const META_COLUMNS: &str = "id, agent_id, agent_name, provider, model, cwd, ticket_id, goal_id, \
+     status, exit_code, started_at, ended_at, size_bytes, truncated";
+
+fn row_to_meta(row: &rusqlite::Row) -> rusqlite::Result<AgentTranscriptMeta> {
+    Ok(AgentTranscriptMeta {
+        id: row.get(0)?,
+        agent_id: row.get(1)?,
+        agent_name: row.get(2)?,
+        provider: row.get(3)?,
+        model: row.get(4)?,
+        cwd: row.get(5)?,
+        ticket_id: row.get(6)?,
+        goal_id: row.get(7)?,
+        status: row.get(8)?,
+        exit_code: row.get(9)?,
+        started_at: row.get(10)?,
+        ended_at: row.get(11)?,
+        size_bytes: row.get(12)?,
+        truncated: row.get(13)?,
+    })
+}
+
+/// Newest first. Without a text query this is a listing; with one, only runs
+/// whose text contains it, each with its first matching lines.
+pub fn search_impl(
+    conn: &Connection,
+    query: &TranscriptQuery,
+) -> Result<Vec<TranscriptHit>, String> {
+    let sql = format!(
+        "SELECT {}, transcript FROM agent_transcripts
+         WHERE (?1 IS NULL OR agent_id = ?1)
+           AND (?2 IS NULL OR ticket_id = ?2)
+           AND (?3 IS NULL OR goal_id = ?3)
+         ORDER BY ended_at DESC",
+        META_COLUMNS
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to prepare transcript search: {}", e))?;
+    let rows = stmt
+        .query_map(
+            params![query.agent_id, query.ticket_id, query.goal_id],
+            |row| Ok((row_to_meta(row)?, row.get::<_, Vec<u8>>(14)?)),
+        )
+        .map_err(|e| format!("Failed to search transcripts: {}", e))?;
+
+    let needle = query
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase);
+    let limit = query.limit.unwrap_or(50) as usize;
+
+    let mut hits = Vec::new();
+    for row in rows {
+        if hits.len() >= limit {
+            break;
+        }
+        let (meta, blob) = row.map_err(|e| format!("Failed to read transcript: {}", e))?;
+        let Some(needle) = &needle else {
+            hits.push(TranscriptHit {
+                meta,
+                snippets: Vec::new(),
+                match_count: 0,
+            });
+            continue;
+        };
+
+        let text = strip_ansi(&decompress(&blob)?);
+        let mut snippets = Vec::new();
+        let mut match_count = 0u32;
+        for line in text.lines() {
+            if line.to_lowercase().contains(needle.as_str()) {
+                match_count += 1;
+                if snippets.len() < MAX_SNIPPETS {
+                    snippets.push(line.trim().chars().take(SNIPPET_CHARS).collect());
+                }
+            }
+        }
+        if match_count > 0 {
+            hits.push(TranscriptHit {
+                meta,
+                snippets,
+                match_count,
+            });
+        }
+    }
+    Ok(hits)
+}
+
+/// The full text of one run, escape sequences and all, for replay in a
+/// terminal view.
+pub fn load_impl(conn: &Connection, id: &str) -> Result<String, String> {
+    let blob: Vec<u8> = conn
+        .query_row(
+            "SELECT transcript FROM agent_transcripts WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => format!("Transcript not found: {}", id),
+            e => format!("Failed to load transcript: {}", e),
+        })?;
+    decompress(&blob)
+}
+
+/// `search_impl` for the project rooted at `project`. A project that has
+/// never saved a transcript has nothing to find, and gets no database for
+/// asking.
+pub fn search_project(
+    project: &Path,
+    query: &TranscriptQuery,
+) -> Result<Vec<TranscriptHit>, String> {
+    let db_path = db_path_for_project(project);
+    if !db_path.is_file() {
+        return Ok(Vec::new());
+    }
+    search_impl(&init_db(&db_path)?, query)
+}
+
+/// `load_impl` for the project rooted at `project`.
+pub fn load_project(project: &Path, id: &str) -> Result<String, String> {
+    let db_path = db_path_for_project(project);
+    if !db_path.is_file() {
+        return Err(format!("Transcript not found: {}", id));
+    }
+    load_impl(&init_db(&db_path)?, id)
+}
+
+/// Terminal output as a person reads it: CSI and OSC sequences dropped,
+/// carriage-return redraws reduced to their last state.
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: parameters, then one final byte in @..~.
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC: up to BEL or ST (ESC \).
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\r' => {
+                // A bare CR redraws the line: keep only what comes after it.
+                let line_start = out.rfind('\n').map_or(0, |i| i + 1);
+                out.truncate(line_start);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000_000;
+
+    fn temp_db() -> (tempfile::TempDir, Connection) {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let conn = init_db(&db_path_for_project(dir.path())).expect("init db");
+        (dir, conn)
+    }
+
+    fn meta(id: &str, ticket: Option<&str>, ended_at: i64) -> AgentTranscriptMeta {
+        AgentTranscriptMeta {
+            id: id.to_string(),
+            agent_id: id.split('-').take(2).collect::<Vec<_>>().join("-"),
+            agent_name: "Fix login".to_string(),
+            provider: "claude".to_string(),
+            model: "sonnet".to_string(),
+            cwd: Some("/repo".to_string()),
+            ticket_id: ticket.map(str::to_string),
+            goal_id: Some("goal-1".to_string()),
+            status: "error".to_string(),
+            exit_code: Some(1),
+            started_at: ended_at - 1000,
+            ended_at,
+            size_bytes: 0,
+            truncated: false,
+        }
+    }
+
+    fn ids(hits: &[TranscriptHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.meta.id.as_str()).collect()
+    }
+
+    #[test]
+    fn a_saved_run_round_trips_and_is_found_by_its_text() {
+        let (_dir, mut conn) = temp_db();
+        let text =
+            "\x1b[32m✓\x1b[0m compiled\r\nRunning tests\r\n\x1b[31mError: Login TIMEOUT\x1b[0m\r\n";
+        save_impl(&mut conn, &meta("agent-1-1", Some("t-1"), NOW), text, NOW).unwrap();
+        save_impl(
+            &mut conn,
+            &meta("agent-2-1", Some("t-2"), NOW + 1),
+            "all good\n",
+            NOW,
+        )
+        .unwrap();
+
+        assert_eq!(load_impl(&conn, "agent-1-1").unwrap(), text);
+        assert!(load_impl(&conn, "missing")
+            .unwrap_err()
+            .contains("not found"));
+
+        let hits = search_impl(
+            &conn,
+            &TranscriptQuery {
+                text: Some("login timeout".to_string()),
+                ..TranscriptQuery::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(ids(&hits), ["agent-1-1"]);
+        assert_eq!(hits[0].snippets, ["Error: Login TIMEOUT"]);
+        assert_eq!(hits[0].match_count, 1);
+        assert_eq!(hits[0].meta.size_bytes, text.len() as i64);
+
+        // Without text: a listing, newest first, narrowed by ticket.
+        let all = search_impl(&conn, &TranscriptQuery::default()).unwrap();
+        assert_eq!(ids(&all), ["agent-2-1", "agent-1-1"]);
+        let by_ticket = search_impl(
+            &conn,
+            &TranscriptQuery {
+                ticket_id: Some("t-2".to_string()),
+                ..TranscriptQuery::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(ids(&by_ticket), ["agent-2-1"]);
+        let by_agent_and_goal = search_impl(
+            &conn,
+            &TranscriptQuery {
+                agent_id: Some("agent-1".to_string()),
+                goal_id: Some("goal-1".to_string()),
+                ..TranscriptQuery::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(ids(&by_agent_and_goal), ["agent-1-1"]);
+    }
+
+    #[test]
+    fn retention_drops_old_runs_and_caps_the_count() {
+        let (_dir, mut conn) = temp_db();
+        let day = MILLIS_PER_DAY;
+        save_impl(
+            &mut conn,
+            &meta("agent-1-1", None, NOW - (RETENTION_DAYS as i64 + 1) * day),
+            "old",
+            NOW,
+        )
+        .unwrap();
+        save_impl(
+            &mut conn,
+            &meta("agent-2-1", None, NOW - day),
+            "recent",
+            NOW,
+        )
+        .unwrap();
+        assert_eq!(
+            ids(&search_impl(&conn, &TranscriptQuery::default()).unwrap()),
+            ["agent-2-1"]
+        );
+
+        for n in 0..MAX_TRANSCRIPTS {
+            save_impl(
+                &mut conn,
+                &meta(&format!("agent-3-{}", n), None, NOW + n as i64),
+                "x",
+                NOW,
+            )
+            .unwrap();
+        }
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM agent_transcripts", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, MAX_TRANSCRIPTS as i64);
+        assert!(load_impl(&conn, "agent-2-1").is_err());
+    }
+
+    #[test]
+    fn escape_sequences_and_redraws_are_stripped() {
+        assert_eq!(
+            strip_ansi("\x1b]0;title\x07\x1b[1;31mred\x1b[0m\r\n50%\r100% done\n"),
+            "red\n100% done\n"
+        );
+        assert_eq!(
+            strip_ansi("a\x1b]8;;http://x\x1b\\link\x1b]8;;\x1b\\b"),
+            "alinkb"
+        );
+    }
+}
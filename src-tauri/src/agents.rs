@@ -1,12 +1,20 @@
+use crate::agent_events::{AgentStreamEvent, CostTally, EventStream};
+use crate::agent_output::{AgentOutputPage, AgentOutputStore, SpillWrite};
 use crate::agent_persistence::{AgentPersistenceState, PersistedAgent};
-use crate::providers::ProviderRegistryState;
+use crate::agent_queue::{AgentQueue, AgentQueueLimits, Occupancy, QueuedSpawn};
+use crate::agent_sessions::{is_valid_session_id, new_session_id};
+use crate::providers::{
+    CommandLine, CredentialRef, ProviderRegistryState, SessionRequest, SpawnCommand, SpawnContext,
+    TaskVia,
+};
 use portable_pty::{native_pty_system, Child as PtyChild, CommandBuilder, MasterPty, PtySize};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
-use tokio::sync::{Mutex, OnceCell};
+use tokio::sync::{Mutex, Notify, OnceCell};
 
 // ── Data types ──────────────────────────────────────────────────────
 
@@ -14,6 +22,9 @@ use tokio::sync::{Mutex, OnceCell};
 #[serde(rename_all = "camelCase")]
 pub struct AgentConfig {
     pub name: String,
+    /// Empty leaves the choice to the role's preset, then to
+    /// `fallback_model`, then to the provider's default model.
+    #[serde(default)]
     pub model: String,
     pub task: String,
     pub cwd: Option<String>,
@@ -26,6 +37,88 @@ pub struct AgentConfig {
     pub spawned_by_ticket_id: Option<String>,
     #[serde(default)]
     pub spawned_by_goal_id: Option<String>,
+    #[serde(default)]
+    pub limits: Option<AgentLimits>,
+    /// Place in the spawn queue when the concurrency caps are full: higher
+    /// starts sooner, equal starts in arrival order. Unset is 0.
+    #[serde(default)]
+    pub priority: Option<i32>,
+    /// Initial PTY size. The view that attaches resizes it to fit with
+    /// `resize_agent`; a headless run keeps it.
+    #[serde(default)]
+    pub rows: Option<u16>,
+    #[serde(default)]
+    pub cols: Option<u16>,
+    /// A provider session to continue instead of starting a new one. Used
+    /// only if the provider can resume.
+    #[serde(default)]
+    pub resume_session_id: Option<String>,
+    /// Image files to attach, for a provider whose config has an `images`
+    /// argument.
+    #[serde(default)]
+    pub images: Option<Vec<String>>,
+    /// Extra system prompt, for a provider whose config has a `systemPrompt`
+    /// argument.
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    /// What the agent is for. The project's preset for this role supplies
+    /// the provider and model the spawn leaves open.
+    #[serde(default)]
+    pub role: Option<crate::provider_policy::AgentRole>,
+    /// The model when neither `model` nor the role's preset names one —
+    /// the caller's guess, which a preset is allowed to overrule.
+    #[serde(default)]
+    pub fallback_model: Option<String>,
+}
+
+/// PTY size when the spawn names none. Wide, because a headless run never
+/// gets resized and agent CLIs lay out tables and diffs to the width they
+/// are given — at 80 columns, most of it wraps or is cut.
+const DEFAULT_PTY_ROWS: u16 = 40;
+const DEFAULT_PTY_COLS: u16 = 160;
+
+/// Bounds on one agent run. Every field is optional and unset means
+/// unbounded, which is what an agent got before limits existed.
+///
+/// Runtime, idle, output and memory limits are watched by the output task and
+/// end the run from outside. CPU is a `ulimit` on the agent's shell, so it
+/// applies to everything it starts. Memory and CPU are Linux only; elsewhere
+/// they are ignored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentLimits {
+    /// Wall-clock time from spawn.
+    pub max_runtime_secs: Option<u64>,
+    /// Time without a single byte of output.
+    pub idle_timeout_secs: Option<u64>,
+    /// Total output, counted in raw PTY bytes.
+    pub max_output_bytes: Option<u64>,
+    /// Resident memory (`VmRSS`) of the agent's process group, summed —
+    /// what it actually uses, not the address space a Node CLI reserves.
+    pub max_memory_mb: Option<u64>,
+    /// CPU time (`RLIMIT_CPU`), across user and system.
+    pub max_cpu_secs: Option<u64>,
+    /// Turns the CLI itself may take. Passed on by a provider whose config
+    /// has a `maxTurns` argument, ignored by the others.
+    pub max_turns: Option<u64>,
+}
+
+impl AgentLimits {
+    fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("maxRuntimeSecs", self.max_runtime_secs),
+            ("idleTimeoutSecs", self.idle_timeout_secs),
+            ("maxOutputBytes", self.max_output_bytes),
+            ("maxMemoryMb", self.max_memory_mb),
+            ("maxCpuSecs", self.max_cpu_secs),
+            ("maxTurns", self.max_turns),
+        ] {
+            if value == Some(0) {
+                return Err(format!("Agent limit {} must be greater than 0", name));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -35,6 +128,9 @@ pub enum AgentStatus {
     Idle,
     Queued,
     Error,
+    /// Stopped for going over one of its `AgentLimits`; the status event
+    /// carries which one as its `reason`.
+    Limited,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -51,6 +147,9 @@ pub struct AgentInfo {
     pub repo_path: Option<String>,
     pub spawned_by_ticket_id: Option<String>,
     pub spawned_by_goal_id: Option<String>,
+    /// Stopped with `pause_agent`; still `Running`, and still holding its
+    /// slot, until `resume_agent` or a kill.
+    pub paused: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -61,6 +160,21 @@ pub struct AgentOutputEvent {
     pub line: String,
     pub timestamp: u64,
     pub repo_path: Option<String>,
+    /// Per agent, from 0 — the number `get_agent_output` replays from.
+    pub seq: u64,
+}
+
+/// A typed event parsed from a headless agent's JSON output, emitted as
+/// `agent-event` next to the raw `agent-output` it came from.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentStreamEventPayload {
+    pub agent_id: String,
+    pub repo_path: Option<String>,
+    pub timestamp: u64,
+    /// Per agent, from 0 — orders events that share a timestamp.
+    pub seq: u64,
+    pub event: AgentStreamEvent,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -70,6 +184,9 @@ pub struct AgentStatusEvent {
     pub status: AgentStatus,
     pub exit_code: Option<i32>,
     pub repo_path: Option<String>,
+    /// Why the agent stopped, when that is not in its exit code — set for
+    /// `Limited`.
+    pub reason: Option<String>,
 }
 
 // ── Internal state (not serialized) ─────────────────────────────────
@@ -77,11 +194,32 @@ pub struct AgentStatusEvent {
 pub struct AgentProcess {
     pub info: AgentInfo,
     pub child: Box<dyn PtyChild + Send + Sync>,
+    /// The repository it counts against for the per-repo cap.
+    pub repo: Option<String>,
+    /// `info.paused`, shared with the output task so a stopped agent's
+    /// silence does not run down its idle timeout.
+    pub paused: Arc<AtomicBool>,
+}
+
+/// A spawn waiting in the queue: what `list_agents` shows for it, and the
+/// config it will start with.
+pub struct PendingSpawn {
+    pub info: AgentInfo,
+    pub config: AgentConfig,
 }
 
 pub struct AgentManager {
     pub agents: HashMap<String, AgentProcess>,
     pub counter: u64,
+    /// Scrollback per agent, outliving the process it came from.
+    pub output: AgentOutputStore,
+    /// Spawns waiting for a slot under the concurrency caps.
+    pub queue: AgentQueue<PendingSpawn>,
+    /// Agents that have left the queue but are not in `agents` yet, with
+    /// their repository — they hold a slot while their process starts.
+    pub starting: HashMap<String, Option<String>>,
+    /// Woken whenever a slot may have freed up.
+    pub queue_wakeup: Arc<Notify>,
 }
 
 impl AgentManager {
@@ -89,6 +227,10 @@ impl AgentManager {
         Self {
             agents: HashMap::new(),
             counter: 0,
+            output: AgentOutputStore::default(),
+            queue: AgentQueue::default(),
+            starting: HashMap::new(),
+            queue_wakeup: Arc::new(Notify::new()),
         }
     }
 
@@ -96,6 +238,25 @@ impl AgentManager {
         self.counter += 1;
         format!("agent-{}", self.counter)
     }
+
+    fn occupancy(&self) -> Occupancy {
+        Occupancy::new(
+            self.agents
+                .values()
+                .map(|p| p.repo.as_deref())
+                .chain(self.starting.values().map(|repo| repo.as_deref())),
+        )
+    }
+
+    /// Moves every queued spawn that now fits into `starting`.
+    fn take_ready(&mut self, limits: &AgentQueueLimits) -> Vec<QueuedSpawn<PendingSpawn>> {
+        let mut occupancy = self.occupancy();
+        let ready = self.queue.take_ready(&mut occupancy, limits);
+        for spawn in &ready {
+            self.starting.insert(spawn.id.clone(), spawn.repo.clone());
+        }
+        ready
+    }
 }
 
 pub type AgentManagerState = Arc<Mutex<AgentManager>>;
@@ -240,6 +401,7 @@ fn persisted_from_config(
     id: &str,
     provider_id: &str,
     started_at: u64,
+    session_id: Option<&str>,
 ) -> PersistedAgent {
     PersistedAgent {
         id: id.to_string(),
@@ -255,6 +417,10 @@ fn persisted_from_config(
         started_at,
         spawned_by_ticket_id: config.spawned_by_ticket_id.clone(),
         spawned_by_goal_id: config.spawned_by_goal_id.clone(),
+        limits: config.limits.clone(),
+        session_id: session_id.map(str::to_string),
+        images: config.images.clone(),
+        system_prompt: config.system_prompt.clone(),
     }
 }
 
@@ -274,9 +440,72 @@ fn persistence_record_exit(app: &AppHandle, agent_id: &str) {
     }
 }
 
+fn persistence_record_session(app: &AppHandle, agent_id: &str, session_id: &str) {
+    if !is_valid_session_id(session_id) {
+        return;
+    }
+    if let Some(state) = app.try_state::<AgentPersistenceState>() {
+        if let Ok(mut p) = state.lock() {
+            p.set_session_id(agent_id, session_id);
+        }
+    }
+}
+
+/// When the session directory is looked at after spawn, in seconds since
+/// the previous look. A CLI writes its session file once it has started,
+/// which for a cold `npx` can take a while.
+const SESSION_SCAN_DELAYS_SECS: &[u64] = &[2, 3, 5, 10, 20];
+
+/// Finds a running agent's session by the file its CLI writes under `dir`,
+/// for a provider that neither takes a session id nor prints one. Gives up
+/// once the agent has ended or its session is known some other way.
+fn watch_session_directory(
+    app: AppHandle,
+    state: AgentManagerState,
+    agent_id: String,
+    dir: std::path::PathBuf,
+    cwd: String,
+    since: std::time::SystemTime,
+) {
+    tauri::async_runtime::spawn(async move {
+        for delay in SESSION_SCAN_DELAYS_SECS {
+            tokio::time::sleep(std::time::Duration::from_secs(*delay)).await;
+            if !state.lock().await.agents.contains_key(&agent_id) {
+                return;
+            }
+            let claimed = match app.try_state::<AgentPersistenceState>() {
+                Some(persistence) => match persistence.lock() {
+                    Ok(p) if p.session_id(&agent_id).is_some() => return,
+                    Ok(p) => p.session_ids(),
+                    Err(_) => return,
+                },
+                None => return,
+            };
+            let (dir, cwd) = (dir.clone(), cwd.clone());
+            let found = tokio::task::spawn_blocking(move || {
+                crate::agent_sessions::find_session_in(&dir, since, &cwd, &claimed)
+            })
+            .await
+            .ok()
+            .flatten();
+            if let Some(session_id) = found {
+                persistence_record_session(&app, &agent_id, &session_id);
+                return;
+            }
+        }
+    });
+}
+
+/// The task for an interrupted agent resumed into its own provider session:
+/// the conversation is all there, only the interruption is news to it.
+pub const RESUMED_SESSION_PROMPT: &str = "Your previous run was interrupted by an IDE \
+     restart. Check the repository's current state — a command or edit may not have \
+     finished — then continue the task from where you left off.";
+
 /// Wraps an interrupted agent's original task in a continuation preamble.
-/// Provider-agnostic: the resumed process starts a fresh session, so it must
-/// be told that earlier progress may already exist in the working tree.
+/// For a resume without the provider's session: the process starts a fresh
+/// conversation, so it must be told that earlier progress may already exist
+/// in the working tree.
 pub fn resume_task_prompt(original_task: &str) -> String {
     format!(
         "You are resuming work that was interrupted by an IDE restart. \
@@ -291,10 +520,101 @@ pub fn resume_task_prompt(original_task: &str) -> String {
 
 pub async fn list_agents_impl(state: &AgentManagerState) -> Result<Vec<AgentInfo>, String> {
     let manager = state.lock().await;
-    let infos: Vec<AgentInfo> = manager.agents.values().map(|p| p.info.clone()).collect();
+    let infos: Vec<AgentInfo> = manager
+        .agents
+        .values()
+        .map(|p| p.info.clone())
+        .chain(manager.queue.iter().map(|q| q.payload.info.clone()))
+        .collect();
     Ok(infos)
 }
 
+// ── spawn queue ─────────────────────────────────────────────────────
+
+/// The concurrency caps as the settings screen last wrote them.
+pub fn queue_limits(app: &AppHandle) -> AgentQueueLimits {
+    let prefs = app.try_state::<crate::webview_prefs::WebviewPrefsState>();
+    AgentQueueLimits::from_prefs(|key| prefs.as_ref().and_then(|p| p.get(key)))
+}
+
+/// The repository an agent in `cwd` counts against: the main checkout, so a
+/// fleet spread over worktrees still shares one cap.
+fn repo_key(cwd: Option<&str>) -> Option<String> {
+    let cwd = cwd?;
+    let project = crate::git::primary_project_path(std::path::Path::new(cwd))
+        .unwrap_or_else(|| std::path::PathBuf::from(cwd));
+    Some(project.to_string_lossy().into_owned())
+}
+
+/// Puts a spawn in the queue and takes out whatever now fits.
+///
+/// Returns the new agent as it stands queued, and the spawns the caller must
+/// start now — usually just this one, or nothing when the caps are full.
+pub async fn admit_agent(
+    config: AgentConfig,
+    state: &AgentManagerState,
+    limits: &AgentQueueLimits,
+) -> Result<(AgentInfo, Vec<QueuedSpawn<PendingSpawn>>), String> {
+    // Checked here rather than at start: a config that can never run must
+    // not sit in the queue first.
+    config.limits.clone().unwrap_or_default().validate()?;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let repo = repo_key(config.cwd.as_deref());
+
+    let mut manager = state.lock().await;
+    let id = manager.next_id();
+    let info = AgentInfo {
+        id: id.clone(),
+        name: config.name.clone(),
+        model: config.model.clone(),
+        // The requested id, as the spawn will ask for it; which provider
+        // really runs is settled at start, against the policy of that moment.
+        provider: config
+            .provider
+            .clone()
+            .unwrap_or_else(|| "claude".to_string()),
+        status: AgentStatus::Queued,
+        current_task: Some(config.task.clone()),
+        started_at: now,
+        last_activity_at: None,
+        repo_path: config.cwd.clone(),
+        spawned_by_ticket_id: config.spawned_by_ticket_id.clone(),
+        spawned_by_goal_id: config.spawned_by_goal_id.clone(),
+        paused: false,
+    };
+    manager.queue.push(QueuedSpawn {
+        id,
+        repo,
+        priority: config.priority.unwrap_or(0),
+        payload: PendingSpawn {
+            info: info.clone(),
+            config,
+        },
+    });
+    let ready = manager.take_ready(limits);
+    Ok((info, ready))
+}
+
+/// The queued spawns a freed slot lets start.
+pub async fn take_ready_spawns(
+    state: &AgentManagerState,
+    limits: &AgentQueueLimits,
+) -> Vec<QueuedSpawn<PendingSpawn>> {
+    state.lock().await.take_ready(limits)
+}
+
+/// Gives back the slot of an agent that left the queue but failed to start.
+pub async fn abandon_start(state: &AgentManagerState, agent_id: &str) {
+    let mut manager = state.lock().await;
+    if manager.starting.remove(agent_id).is_some() {
+        manager.queue_wakeup.notify_one();
+    }
+}
+
 // ── spawn_agent ─────────────────────────────────────────────────────
 
 /// Resolves the provider a spawn will really use, then holds it against the
@@ -366,8 +686,11 @@ fn attach_usage_sidecar(
     }
 }
 
+/// Starts an agent that `admit_agent` let through, under the id it was given
+/// there.
 pub async fn spawn_agent_impl(
-    config: AgentConfig,
+    id: String,
+    mut config: AgentConfig,
     state: &AgentManagerState,
     app: &AppHandle,
     providers: &ProviderRegistryState,
@@ -383,58 +706,188 @@ pub async fn spawn_agent_impl(
     ),
     String,
 > {
+    let limits = config.limits.clone().unwrap_or_default();
+    limits.validate()?;
+
     let pty_system = native_pty_system();
     let pair = pty_system
         .openpty(PtySize {
-            rows: 24,
-            cols: 80,
+            rows: config.rows.unwrap_or(DEFAULT_PTY_ROWS),
+            cols: config.cols.unwrap_or(DEFAULT_PTY_COLS),
             pixel_width: 0,
             pixel_height: 0,
         })
         .map_err(|e| e.to_string())?;
 
-    // Non-login shell: the PATH/env a login shell would source is applied
-    // explicitly below via the cached login-shell environment, so we avoid
-    // re-parsing rc files on every single agent spawn.
-    let (shell, args) = if cfg!(target_os = "windows") {
-        ("cmd", vec!["/C".to_string()])
-    } else if cfg!(target_os = "macos") {
-        ("/bin/zsh", vec!["-c".to_string()])
-    } else {
-        ("sh", vec!["-c".to_string()])
-    };
-
     // Before anything is opened or spawned: may this provider run in this
     // project at all? The check lives here rather than in the dialogs because
     // this is the one path every agent takes — the conductor, a retry, a
     // resumed run and a notification action all arrive here too.
-    let policy = match config.cwd.as_deref() {
-        Some(cwd) => {
-            let project = crate::git::primary_project_path(std::path::Path::new(cwd))
-                .unwrap_or_else(|| std::path::PathBuf::from(cwd));
-            crate::provider_policy::policy_for_project(&project)
-        }
-        None => crate::provider_policy::ProviderPolicy::default(),
-    };
+    let project = config.cwd.as_deref().map(|cwd| {
+        crate::git::primary_project_path(std::path::Path::new(cwd))
+            .unwrap_or_else(|| std::path::PathBuf::from(cwd))
+    });
+    let policy = project
+        .as_deref()
+        .map(crate::provider_policy::policy_for_project)
+        .unwrap_or_default();
+    // The role's preset fills in first, so what it picks is held against the
+    // policy like anything else. The resolved model is written back: it is
+    // what gets persisted, shown and resumed with.
+    let (requested, model) = crate::provider_policy::apply_role_preset(
+        config.role,
+        config.provider.as_deref(),
+        &config.model,
+        &policy,
+    );
     let (provider_id, provider) =
-        resolve_permitted_provider(config.provider.as_deref(), providers, &policy)?;
+        resolve_permitted_provider(requested.as_deref(), providers, &policy)?;
     let provider_id = provider_id.as_str();
+    config.model = if !model.trim().is_empty() {
+        model
+    } else {
+        match config
+            .fallback_model
+            .take()
+            .filter(|m| !m.trim().is_empty())
+        {
+            Some(fallback) => fallback,
+            None => provider.info().default_model,
+        }
+    };
+
+    // The project's sandbox is built — and proven to work — before anything
+    // spawns, for the same reason as the policy: a project that asked for one
+    // must not get an unsandboxed agent on any path.
+    let sandbox = match (config.cwd.as_deref(), project.as_deref()) {
+        (Some(cwd), Some(project)) => {
+            let settings = crate::agent_sandbox::sandbox_for_project(project);
+            if settings.enabled {
+                let path = cached_login_shell_env()
+                    .await
+                    .iter()
+                    .find(|(key, _)| key == "PATH")
+                    .map(|(_, value)| value.as_str());
+                Some(crate::agent_sandbox::sandboxed_command(
+                    &settings,
+                    std::path::Path::new(cwd),
+                    project,
+                    path,
+                )?)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
 
-    let spawn_cmd = provider.build_spawn_command(
+    // Back into the run's own conversation when resuming; otherwise, if the
+    // CLI lets us, a new session under an id we already know.
+    let session_support = provider.session_support();
+    let session = match config.resume_session_id.as_deref() {
+        Some(resume) if session_support.resumes && is_valid_session_id(resume) => {
+            Some(SessionRequest::Resume(resume.to_string()))
+        }
+        _ if session_support.names_new_sessions => Some(SessionRequest::New(new_session_id())),
+        _ => None,
+    };
+    let session_id = session.as_ref().map(|session| match session {
+        SessionRequest::New(id) | SessionRequest::Resume(id) => id.clone(),
+    });
+
+    let context = SpawnContext {
+        cwd: config.cwd.clone(),
+        project_dir: project
+            .as_deref()
+            .filter(|project| config.cwd.as_deref().map(std::path::Path::new) != Some(*project))
+            .map(|project| project.display().to_string()),
+        images: config.images.clone().unwrap_or_default(),
+        system_prompt: config.system_prompt.clone(),
+        max_turns: limits.max_turns,
+        mcp_config: project
+            .as_deref()
+            .map(|project| project.join(".mcp.json"))
+            .filter(|path| path.is_file())
+            .map(|path| path.display().to_string()),
+    };
+    let spawn_cmd = provider.build_session_spawn_command(
         &config.model,
         &config.task,
         config.permission_mode.as_deref(),
         config.dangerously_ignore_permissions.unwrap_or(false),
         config.auto_accept_edits.unwrap_or(false),
         config.headless.unwrap_or(false),
+        session.as_ref(),
+        &context,
     );
     let spawn_cmd = attach_usage_sidecar(spawn_cmd, app);
+    // Only a headless run prints the machine-readable stream; an interactive
+    // one is a TUI and stays with the frontend's line matchers.
+    let event_stream = if config.headless.unwrap_or(false) {
+        provider.event_parser().map(EventStream::new)
+    } else {
+        None
+    };
+    let persist_events = event_stream.is_some() && agent_log_enabled(app);
+
+    // A task that does not travel on the command line is written out first,
+    // and so is a system prompt the CLI reads from a file; the reap removes
+    // both again.
+    let mut written_files = Vec::new();
+    let task_file = match spawn_cmd.task_via {
+        TaskVia::Argv => None,
+        TaskVia::Stdin | TaskVia::File => {
+            let path = task_file_path(app, &id);
+            write_task_file(&path, &spawn_cmd.task)?;
+            written_files.push(path.clone());
+            Some(path)
+        }
+    };
+    let task_file_arg = task_file.as_ref().map(|path| path.display().to_string());
+    let spawn_cmd = match &task_file_arg {
+        Some(path) => spawn_cmd.with_task_file(path),
+        None => spawn_cmd,
+    };
+    let spawn_cmd = match spawn_cmd.system_prompt_file.clone() {
+        Some(prompt) => {
+            let path = task_file_path(app, &format!("{}.system", id));
+            if let Err(error) = write_task_file(&path, &prompt) {
+                remove_task_files(&written_files);
+                return Err(error);
+            }
+            written_files.push(path.clone());
+            spawn_cmd.with_system_prompt_file(&path.display().to_string())
+        }
+        None => spawn_cmd,
+    };
+    let prelude = if cfg!(target_os = "linux") {
+        rlimit_prelude(&limits)
+    } else {
+        String::new()
+    };
+    let program = match command_argv(&spawn_cmd, &prelude, task_file_arg.as_deref()) {
+        Ok(program) => program,
+        Err(error) => {
+            remove_task_files(&written_files);
+            return Err(error);
+        }
+    };
 
-    let mut cmd = CommandBuilder::new(shell);
-    for arg in args {
+    let mut cmd = match &sandbox {
+        Some((bwrap, bwrap_args)) => {
+            let mut cmd = CommandBuilder::new(bwrap);
+            for arg in bwrap_args {
+                cmd.arg(arg);
+            }
+            cmd.arg("--");
+            cmd.arg(&program[0]);
+            cmd
+        }
+        None => CommandBuilder::new(&program[0]),
+    };
+    for arg in &program[1..] {
         cmd.arg(arg);
     }
-    cmd.arg(&spawn_cmd.command);
 
     for (key, value) in cached_login_shell_env().await {
         cmd.env(key, value);
@@ -445,6 +898,9 @@ pub async fn spawn_agent_impl(
     for (key, value) in &spawn_cmd.env_vars {
         cmd.env(key, value);
     }
+    for (key, value) in credential_env_vars(app, project.as_deref(), &spawn_cmd.credential_env) {
+        cmd.env(key, value);
+    }
 
     if let Some(ref cwd) = config.cwd {
         if std::path::Path::new(cwd).is_dir() {
@@ -458,10 +914,16 @@ pub async fn spawn_agent_impl(
         cmd.env("AURIC_MCP_DB_PATH", db_path.to_string_lossy().as_ref());
     }
 
-    let child = pair
-        .slave
-        .spawn_command(cmd)
-        .map_err(|e| format!("Failed to spawn agent PTY: {}", e))?;
+    // Session files are matched by modification time; a second of slack
+    // covers filesystems with coarse timestamps.
+    let spawned_at = std::time::SystemTime::now() - std::time::Duration::from_secs(1);
+    let child = match pair.slave.spawn_command(cmd) {
+        Ok(child) => child,
+        Err(e) => {
+            remove_task_files(&written_files);
+            return Err(format!("Failed to spawn agent PTY: {}", e));
+        }
+    };
     drop(pair.slave);
 
     let mut reader = pair.master.try_clone_reader().map_err(|e| e.to_string())?;
@@ -473,11 +935,28 @@ pub async fn spawn_agent_impl(
         .as_millis() as u64;
 
     let mut manager = state.lock().await;
-    let id = manager.next_id();
+    let repo = manager.starting.remove(&id).flatten();
 
     // Persist the spawn config BEFORE its fields move into `info`, so the
     // agent can be restored (as interrupted) after an app restart.
-    persistence_record_spawn(app, persisted_from_config(&config, &id, provider_id, now));
+    persistence_record_spawn(
+        app,
+        persisted_from_config(&config, &id, provider_id, now, session_id.as_deref()),
+    );
+    if let (None, Some(dir), Some(cwd)) = (
+        session_id.as_ref(),
+        session_support.directory.as_deref(),
+        config.cwd.as_ref(),
+    ) {
+        watch_session_directory(
+            app.clone(),
+            state.clone(),
+            id.clone(),
+            crate::agent_sandbox::expand_home(dir),
+            cwd.clone(),
+            spawned_at,
+        );
+    }
 
     let info = AgentInfo {
         id: id.clone(),
@@ -491,11 +970,16 @@ pub async fn spawn_agent_impl(
         repo_path: config.cwd.clone(),
         spawned_by_ticket_id: config.spawned_by_ticket_id.clone(),
         spawned_by_goal_id: config.spawned_by_goal_id.clone(),
+        paused: false,
     };
 
+    let paused = Arc::new(AtomicBool::new(false));
+    let pid = child.process_id();
     let process = AgentProcess {
         info: info.clone(),
         child,
+        repo,
+        paused: paused.clone(),
     };
 
     // Stream PTY output to the frontend (batched at ~30fps to avoid IPC
@@ -522,6 +1006,7 @@ pub async fn spawn_agent_impl(
     let rp_clone = info.repo_path.clone();
     let cli_name = provider_id.to_string();
     let state_clone = state.clone();
+    let transcript_info = info.clone();
 
     // Async task for batching and emitting (prevents trapping data in buffers)
     tauri::async_runtime::spawn(async move {
@@ -529,6 +1014,12 @@ pub async fn spawn_agent_impl(
         // independently corrupts umlauts/box-drawing into U+FFFD and shifts
         // every following terminal cell.
         let mut decoder = crate::utf8_stream::Utf8StreamDecoder::new();
+        let mut event_stream = event_stream;
+        let mut event_seq = 0u64;
+        let mut cost = CostTally::default();
+        let mut limit_watch = LimitWatch::new(limits, std::time::Instant::now());
+        let mut limit_reason: Option<String> = None;
+        let mut last_memory_sample = std::time::Instant::now();
         let mut accum = String::new();
         let mut has_produced_output = false;
         let mut last_emit = std::time::Instant::now();
@@ -541,13 +1032,40 @@ pub async fn spawn_agent_impl(
             tokio::select! {
                 data = rx.recv() => {
                     match data {
+                        // Over a limit and waiting for the kill to land:
+                        // whatever the agent still prints goes nowhere.
+                        Some(_) if limit_reason.is_some() => {}
                         Some(bytes) => {
                             has_produced_output = true;
-                            accum.push_str(&decoder.push(&bytes));
+                            limit_watch.record_output(bytes.len(), std::time::Instant::now());
+                            let text = decoder.push(&bytes);
+                            if let Some(stream) = event_stream.as_mut() {
+                                let events = stream.push(&text);
+                                cost.add(&events);
+                                publish_agent_events(
+                                    &app_clone,
+                                    &state_clone,
+                                    &id_clone,
+                                    &rp_clone,
+                                    events,
+                                    &mut event_seq,
+                                    persist_events,
+                                )
+                                .await;
+                            }
+                            accum.push_str(&text);
 
                             if accum.len() > 16384 || last_emit.elapsed() >= batch_interval {
                                 let data = std::mem::take(&mut accum);
-                                emit_agent_output(&app_clone, &id_clone, &rp_clone, data).await;
+                                emit_agent_output(
+                                    &app_clone,
+                                    &state_clone,
+                                    &id_clone,
+                                    &rp_clone,
+                                    "stdout",
+                                    data,
+                                )
+                                .await;
                                 last_emit = std::time::Instant::now();
                             }
                         }
@@ -557,67 +1075,164 @@ pub async fn spawn_agent_impl(
                 _ = &mut timeout => {
                     if !accum.is_empty() {
                         let data = std::mem::take(&mut accum);
-                        emit_agent_output(&app_clone, &id_clone, &rp_clone, data).await;
+                        emit_agent_output(
+                            &app_clone,
+                            &state_clone,
+                            &id_clone,
+                            &rp_clone,
+                            "stdout",
+                            data,
+                        )
+                        .await;
                         last_emit = std::time::Instant::now();
                     }
                 }
             }
+
+            if paused.load(Ordering::Relaxed) {
+                // Stopped on purpose: its silence is not idleness.
+                limit_watch.last_output = std::time::Instant::now();
+            }
+            if let (Some(_), Some(pid)) = (limit_watch.limits.max_memory_mb, pid) {
+                // A walk over all of /proc: once a second, not every tick.
+                if limit_reason.is_none() && last_memory_sample.elapsed() >= MEMORY_SAMPLE_INTERVAL
+                {
+                    last_memory_sample = std::time::Instant::now();
+                    if let Ok(Some(bytes)) =
+                        tokio::task::spawn_blocking(move || process_group_rss_bytes(pid)).await
+                    {
+                        limit_watch.record_memory(bytes);
+                    }
+                }
+            }
+            if limit_reason.is_none() {
+                if let Some(reason) = limit_watch.exceeded(std::time::Instant::now()) {
+                    // The same kill `kill_agent_impl` sends, but the process
+                    // stays registered: the reap below still owns it, and
+                    // reports the reason instead of an exit code. The group
+                    // goes too — a tool the CLI started may be what grew.
+                    if let Some(process) = state_clone.lock().await.agents.get_mut(&id_clone) {
+                        if let Some(pid) = pid {
+                            let _ = signal_process_group(pid, AgentSignal::Kill);
+                        }
+                        let _ = process.child.kill();
+                    }
+                    limit_reason = Some(reason);
+                }
+            }
         }
 
         // Final flush of remaining accumulated data
-        accum.push_str(&decoder.finish());
+        let text = decoder.finish();
+        if let Some(stream) = event_stream.as_mut() {
+            let mut events = stream.push(&text);
+            events.extend(stream.finish());
+            cost.add(&events);
+            publish_agent_events(
+                &app_clone,
+                &state_clone,
+                &id_clone,
+                &rp_clone,
+                events,
+                &mut event_seq,
+                persist_events,
+            )
+            .await;
+        }
+        accum.push_str(&text);
         if !accum.is_empty() {
-            emit_agent_output(&app_clone, &id_clone, &rp_clone, accum).await;
+            emit_agent_output(
+                &app_clone,
+                &state_clone,
+                &id_clone,
+                &rp_clone,
+                "stdout",
+                accum,
+            )
+            .await;
         }
 
-        if !has_produced_output {
+        if !has_produced_output && limit_reason.is_none() {
             let error_msg = format!("\r\n\x1b[31mError: Agent process terminated without output. Check if '{}' CLI is installed.\x1b[0m\r\n", cli_name);
-            let timestamp = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_millis() as u64;
-
-            let _ = app_clone.emit(
-                "agent-output",
-                AgentOutputEvent {
-                    agent_id: id_clone.clone(),
-                    stream: "stderr".to_string(),
-                    line: error_msg,
-                    timestamp,
-                    repo_path: rp_clone.clone(),
-                },
-            );
+            emit_agent_output(
+                &app_clone,
+                &state_clone,
+                &id_clone,
+                &rp_clone,
+                "stderr",
+                error_msg,
+            )
+            .await;
         }
 
         // Clean up AgentProcess on natural termination (release PTY resources)
         let proc_opt = {
             let mut mgr = state_clone.lock().await;
+            mgr.queue_wakeup.notify_one();
             mgr.agents.remove(&id_clone)
         };
+        let killed = proc_opt.is_none();
 
         // Reap the child to get the REAL exit code — a crashed or failed agent
         // must surface as Error, not Idle, so the conductor requeues instead of
         // marking the ticket done.
-        let exit_code: i32 = match proc_opt {
-            Some(mut process) => tokio::task::spawn_blocking(move || {
-                process
-                    .child
-                    .wait()
-                    .map(|status| if status.success() { 0 } else { 1 })
-                    .unwrap_or(-1)
+        let (exit_code, cpu_limited) = match proc_opt {
+            Some(mut process) => tokio::task::spawn_blocking(move || match process.child.wait() {
+                Ok(status) => (if status.success() { 0 } else { 1 }, cpu_limit_hit(&status)),
+                Err(_) => (-1, false),
             })
             .await
-            .unwrap_or(-1),
+            .unwrap_or((-1, false)),
             // Already removed by the explicit kill path, which emits its own event
-            None => 0,
+            None => (0, false),
         };
-
-        let status = if exit_code == 0 {
+        remove_task_files(&written_files);
+
+        let limit_reason = limit_reason.or_else(|| {
+            limit_watch
+                .limits
+                .max_cpu_secs
+                .filter(|_| cpu_limited && cfg!(target_os = "linux"))
+                .map(|secs| format!("CPU limit reached: used {}s of CPU time", secs))
+        });
+        let status = if limit_reason.is_some() {
+            AgentStatus::Limited
+        } else if exit_code == 0 {
             AgentStatus::Idle
         } else {
             AgentStatus::Error
         };
 
+        if let Some(reason) = &limit_reason {
+            emit_agent_output(
+                &app_clone,
+                &state_clone,
+                &id_clone,
+                &rp_clone,
+                "stderr",
+                format!("\r\n\x1b[33mStopped: {}\x1b[0m\r\n", reason),
+            )
+            .await;
+        }
+
+        let transcript_status = if killed {
+            "killed".to_string()
+        } else {
+            serde_json::to_value(&status)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default()
+        };
+        record_agent_cost(&transcript_info, project.as_deref(), &cost).await;
+        save_agent_transcript(
+            &state_clone,
+            transcript_info,
+            project,
+            transcript_status,
+            (!killed).then_some(exit_code),
+        )
+        .await;
+
         let _ = app_clone.emit(
             "agent-status",
             AgentStatusEvent {
@@ -625,6 +1240,7 @@ pub async fn spawn_agent_impl(
                 status,
                 exit_code: Some(exit_code),
                 repo_path: rp_clone,
+                reason: limit_reason,
             },
         );
 
@@ -640,12 +1256,331 @@ pub async fn spawn_agent_impl(
     Ok((info, writer, pair.master))
 }
 
-async fn emit_agent_output(app: &AppHandle, id: &str, repo_path: &Option<String>, data: String) {
+// ── Command line ────────────────────────────────────────────────────
+
+/// The program and arguments that start `spawn_cmd`, with the `ulimit`
+/// `prelude` ahead of it and, for `TaskVia::Stdin`, `task_file` on its
+/// standard input.
+///
+/// Argv runs directly when there is nothing to set up. Otherwise it goes
+/// through a fixed `sh` script that receives the command as its positional
+/// parameters, so the shell still never parses a word of it. A shell line
+/// goes to the platform shell as it is: non-login, because the PATH and env a
+/// login shell would source are applied from the cached login-shell
+/// environment instead of re-parsing rc files on every spawn.
+fn command_argv(
+    spawn_cmd: &SpawnCommand,
+    prelude: &str,
+    task_file: Option<&str>,
+) -> Result<Vec<String>, String> {
+    let stdin = task_file.filter(|_| spawn_cmd.task_via == TaskVia::Stdin);
+    match &spawn_cmd.command {
+        CommandLine::Shell(line) => {
+            let (shell, flag) = if cfg!(target_os = "windows") {
+                ("cmd", "/C")
+            } else if cfg!(target_os = "macos") {
+                ("/bin/zsh", "-c")
+            } else {
+                ("sh", "-c")
+            };
+            let mut line = format!("{}{}", prelude, line);
+            if let Some(file) = stdin {
+                line.push_str(&format!(
+                    " < \"{}\"",
+                    crate::providers::shell_escape_double_quoted(file)
+                ));
+            }
+            Ok(vec![shell.to_string(), flag.to_string(), line])
+        }
+        CommandLine::Argv(argv) if argv.is_empty() => Err(format!(
+            "Provider {} has no command to run",
+            spawn_cmd.executable
+        )),
+        CommandLine::Argv(argv) if prelude.is_empty() && stdin.is_none() => Ok(argv.clone()),
+        CommandLine::Argv(_) if cfg!(target_os = "windows") => {
+            Err("Passing the task on stdin needs a POSIX shell".to_string())
+        }
+        CommandLine::Argv(argv) => {
+            let mut wrapped = vec!["/bin/sh".to_string(), "-c".to_string()];
+            match stdin {
+                Some(file) => {
+                    wrapped.push(format!("{}f=$1; shift; exec \"$@\" < \"$f\"", prelude));
+                    wrapped.push("sh".to_string());
+                    wrapped.push(file.to_string());
+                }
+                None => {
+                    wrapped.push(format!("{}exec \"$@\"", prelude));
+                    wrapped.push("sh".to_string());
+                }
+            }
+            wrapped.extend(argv.iter().cloned());
+            Ok(wrapped)
+        }
+    }
+}
+
+/// Where an agent's task is written when it does not go on the command line.
+fn task_file_path(app: &AppHandle, agent_id: &str) -> std::path::PathBuf {
+    app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir())
+        .join("agent-tasks")
+        .join(format!("{}.md", agent_id))
+}
+
+/// Writes the task readable by the user only — it is the prompt, and may
+/// quote anything the user pasted into it.
+fn write_task_file(path: &std::path::Path, task: &str) -> Result<(), String> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to write the agent's task file: {}", e))?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(task.as_bytes()))
+        .map_err(|e| format!("Failed to write the agent's task file: {}", e))
+}
+
+fn remove_task_files(paths: &[std::path::PathBuf]) {
+    for path in paths {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// The credentials a provider config asked for, by variable name: the
+/// project's override when its database is open, the app-wide value
+/// otherwise. One configured nowhere is left out.
+fn credential_env_vars(
+    app: &AppHandle,
+    project: Option<&std::path::Path>,
+    wanted: &[(String, CredentialRef)],
+) -> Vec<(String, String)> {
+    let Some(credentials) = app.try_state::<crate::app_config::AppCredentialsState>() else {
+        return Vec::new();
+    };
+    let database = app.try_state::<crate::database::DatabaseState>();
+
+    wanted
+        .iter()
+        .filter_map(|(name, credential)| {
+            let global =
+                crate::app_config::global_namespace(credentials.path(), &credential.namespace)
+                    .remove(&credential.key);
+            let project_value = database.as_ref().zip(project).and_then(|(db, project)| {
+                let connections = db.connections.lock().ok()?;
+                let conn = connections.get(&*project.to_string_lossy())?;
+                crate::database::kv_get(conn, &credential.namespace, &credential.key)
+                    .ok()
+                    .flatten()
+            });
+            crate::app_config::resolve_credential(global, project_value)
+                .map(|value| (name.clone(), value))
+        })
+        .collect()
+}
+
+// ── Resource limits ─────────────────────────────────────────────────
+
+/// The limits the output task enforces, checked against times passed in so
+/// the arithmetic can be tested without waiting for it.
+struct LimitWatch {
+    limits: AgentLimits,
+    started: std::time::Instant,
+    last_output: std::time::Instant,
+    output_bytes: u64,
+    /// The latest `process_group_rss_bytes` sample.
+    memory_bytes: u64,
+}
+
+impl LimitWatch {
+    fn new(limits: AgentLimits, now: std::time::Instant) -> Self {
+        Self {
+            limits,
+            started: now,
+            last_output: now,
+            output_bytes: 0,
+            memory_bytes: 0,
+        }
+    }
+
+    fn record_memory(&mut self, bytes: u64) {
+        self.memory_bytes = bytes;
+    }
+
+    fn record_output(&mut self, bytes: usize, now: std::time::Instant) {
+        self.output_bytes += bytes as u64;
+        self.last_output = now;
+    }
+
+    /// The first limit the agent is over, worded as the reason its status
+    /// event will carry.
+    fn exceeded(&self, now: std::time::Instant) -> Option<String> {
+        let secs = std::time::Duration::from_secs;
+        if let Some(max) = self.limits.max_output_bytes {
+            if self.output_bytes > max {
+                return Some(format!(
+                    "Output limit reached: more than {} bytes of output",
+                    max
+                ));
+            }
+        }
+        if let Some(max) = self.limits.max_memory_mb {
+            if self.memory_bytes > max.saturating_mul(1024 * 1024) {
+                return Some(format!("Memory limit reached: used more than {} MB", max));
+            }
+        }
+        if let Some(max) = self.limits.max_runtime_secs {
+            if now.duration_since(self.started) >= secs(max) {
+                return Some(format!("Runtime limit reached: ran for {}s", max));
+            }
+        }
+        if let Some(max) = self.limits.idle_timeout_secs {
+            if now.duration_since(self.last_output) >= secs(max) {
+                return Some(format!("Idle timeout: no output for {}s", max));
+            }
+        }
+        None
+    }
+}
+
+/// How often the output task samples the agent's memory.
+const MEMORY_SAMPLE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Resident memory of every process in the group `pgid`, summed from
+/// `/proc/<pid>/status`. `None` where there is no `/proc` to read.
+#[cfg(target_os = "linux")]
+fn process_group_rss_bytes(pgid: u32) -> Option<u64> {
+    let mut total = 0;
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        if entry
+            .file_name()
+            .to_str()
+            .and_then(|n| n.parse::<u32>().ok())
+            .is_none()
+        {
+            continue;
+        }
+        // Processes come and go during the walk; a vanished one used nothing.
+        let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        if stat_process_group(&stat) != Some(pgid) {
+            continue;
+        }
+        if let Ok(status) = std::fs::read_to_string(entry.path().join("status")) {
+            total += status_rss_bytes(&status).unwrap_or(0);
+        }
+    }
+    Some(total)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_group_rss_bytes(_pgid: u32) -> Option<u64> {
+    None
+}
+
+/// The process group field of `/proc/<pid>/stat`: the third after the
+/// command name, which is in parentheses and may hold spaces of its own.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn stat_process_group(stat: &str) -> Option<u32> {
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(2)?.parse().ok()
+}
+
+/// `VmRSS` from `/proc/<pid>/status`, in bytes. Kernel threads have none.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn status_rss_bytes(status: &str) -> Option<u64> {
+    let kb = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+/// `ulimit` lines run ahead of the agent command, so the CPU ceiling sits on
+/// the shell and everything it starts. A ceiling the shell cannot set — a
+/// hard limit already below it — ends the run there instead of letting the
+/// agent start unbounded.
+fn rlimit_prelude(limits: &AgentLimits) -> String {
+    let mut prelude = String::new();
+    if let Some(secs) = limits.max_cpu_secs {
+        // Soft and hard apart: at the soft limit the kernel sends SIGXCPU,
+        // which is how the reap tells this limit from any other death. Equal
+        // limits go straight to SIGKILL. The hard one still catches an agent
+        // that ignores the signal. Soft first — a hard limit below the
+        // current soft one is refused.
+        prelude.push_str(&format!(
+            "ulimit -S -t {} || exit 126; ulimit -H -t {} || exit 126; ",
+            secs,
+            secs.saturating_add(CPU_LIMIT_GRACE_SECS)
+        ));
+    }
+    prelude
+}
+
+/// CPU seconds between the soft and the hard `RLIMIT_CPU`.
+const CPU_LIMIT_GRACE_SECS: u64 = 5;
+
+/// Whether the run ended on SIGXCPU, the signal `RLIMIT_CPU` sends.
+/// portable-pty keeps only `strsignal`'s description of the signal, so this
+/// compares against the same call rather than one locale's wording. The argv
+/// wrapper execs into the agent, so the signal is always ours to see; a
+/// shell line only passes it on when the shell execs its last command, and
+/// an exit code of 152 is not taken for it — the agent may return that itself.
+#[cfg(unix)]
+fn cpu_limit_hit(status: &portable_pty::ExitStatus) -> bool {
+    let Some(signal) = status.signal() else {
+        return false;
+    };
+    let name = unsafe { libc::strsignal(libc::SIGXCPU) };
+    if name.is_null() {
+        return signal == format!("Signal {}", libc::SIGXCPU);
+    }
+    signal == unsafe { std::ffi::CStr::from_ptr(name) }.to_string_lossy()
+}
+
+#[cfg(not(unix))]
+fn cpu_limit_hit(_status: &portable_pty::ExitStatus) -> bool {
+    false
+}
+
+async fn emit_agent_output(
+    app: &AppHandle,
+    state: &AgentManagerState,
+    id: &str,
+    repo_path: &Option<String>,
+    stream: &str,
+    data: String,
+) {
     let timestamp = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
         .as_millis() as u64;
 
+    // Into the scrollback first, so a view that replays the moment this event
+    // arrives already finds it there.
+    let (seq, spill) = {
+        let mut manager = state.lock().await;
+        let AgentManager { agents, output, .. } = &mut *manager;
+        let seq = output.record(id, stream, timestamp, data.as_bytes(), |agent| {
+            agents.contains_key(agent)
+        });
+        (seq, output.take_spill(id))
+    };
+
     // Single event channel: the frontend store is the sole consumer and all
     // terminal surfaces replay from it. A parallel terminal-out emit doubled
     // IPC traffic and let terminals drift out of sync with the store.
@@ -653,12 +1588,218 @@ async fn emit_agent_output(app: &AppHandle, id: &str, repo_path: &Option<String>
         "agent-output",
         AgentOutputEvent {
             agent_id: id.to_string(),
-            stream: "stdout".to_string(),
+            stream: stream.to_string(),
             line: data,
             timestamp,
             repo_path: repo_path.clone(),
+            seq,
         },
     );
+
+    if let Some(write) = spill {
+        spill_agent_output(state, id, write).await;
+    }
+}
+
+/// Writes what fell out of an agent's in-memory scrollback to disk with the
+/// manager lock released, then books it under the lock again.
+async fn spill_agent_output(state: &AgentManagerState, id: &str, write: SpillWrite) {
+    let write = Arc::new(write);
+    let task_write = write.clone();
+    let written = tokio::task::spawn_blocking(move || task_write.write())
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+    let stale = state.lock().await.output.finish_spill(id, &write, written);
+    if !stale.is_empty() {
+        let _ = tokio::task::spawn_blocking(move || {
+            for path in stale {
+                let _ = std::fs::remove_file(path);
+            }
+        })
+        .await;
+    }
+}
+
+/// Whether the user keeps agent history. Read once per spawn rather than per
+/// output batch, so toggling it applies from the next agent on.
+fn agent_log_enabled(app: &AppHandle) -> bool {
+    app.try_state::<crate::webview_prefs::WebviewPrefsState>()
+        .and_then(|prefs| prefs.get(crate::agent_log::PERSIST_PREF_KEY))
+        .is_some_and(|value| value == "true")
+}
+
+/// Emits parsed events and, when `persist` is set, writes them to the
+/// activity log — the same opt-in and the same row shape as the feed events
+/// the frontend stores. The write runs on the blocking pool; a failed one is
+/// dropped: history is never worth stalling an agent's output over.
+async fn publish_agent_events(
+    app: &AppHandle,
+    state: &AgentManagerState,
+    id: &str,
+    repo_path: &Option<String>,
+    events: Vec<AgentStreamEvent>,
+    seq: &mut u64,
+    persist: bool,
+) {
+    if events.is_empty() {
+        return;
+    }
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    let agent_name = if persist {
+        let manager = state.lock().await;
+        manager.agents.get(id).map(|p| p.info.name.clone())
+    } else {
+        None
+    };
+
+    let mut rows = Vec::new();
+    for event in events {
+        if let AgentStreamEvent::Session { id: session_id } = &event {
+            persistence_record_session(app, id, session_id);
+        }
+        if persist {
+            if let Some((kind, label, path)) = crate::agent_events::log_row(&event) {
+                rows.push(crate::agent_log::AgentLogEvent {
+                    agent_id: id.to_string(),
+                    agent_name: agent_name.clone().unwrap_or_else(|| id.to_string()),
+                    repo_path: repo_path.clone(),
+                    kind: kind.to_string(),
+                    label,
+                    path,
+                    at: timestamp as i64,
+                    seq: *seq as i64,
+                });
+            }
+        }
+        let _ = app.emit(
+            "agent-event",
+            AgentStreamEventPayload {
+                agent_id: id.to_string(),
+                repo_path: repo_path.clone(),
+                timestamp,
+                seq: *seq,
+                event,
+            },
+        );
+        *seq += 1;
+    }
+
+    if rows.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    let written = tokio::task::spawn_blocking(move || {
+        match app.try_state::<crate::agent_log::AgentLogState>() {
+            Some(log) => log.with_connection(|conn| crate::agent_log::append_impl(conn, &rows)),
+            None => Ok(()),
+        }
+    })
+    .await
+    .unwrap_or_else(|e| Err(e.to_string()));
+    if let Err(error) = written {
+        eprintln!("Agent log: could not record parsed events: {error}");
+    }
+}
+
+// ── transcripts ─────────────────────────────────────────────────────
+
+/// Writes a finished run's whole output to its project's transcript store.
+/// Only projects the IDE has opened — those with a `.auric` — get one; an
+/// agent pointed at any other directory must not leave a database behind.
+/// Like the activity log, a failed write is reported and dropped.
+async fn save_agent_transcript(
+    state: &AgentManagerState,
+    info: AgentInfo,
+    project: Option<std::path::PathBuf>,
+    status: String,
+    exit_code: Option<i32>,
+) {
+    let Some(project) = project.filter(|p| p.join(".auric").is_dir()) else {
+        return;
+    };
+    let Ok(page) = state.lock().await.output.since(&info.id, 0) else {
+        return;
+    };
+
+    let ended_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+    let text: String = page.chunks.iter().map(|c| c.data.as_str()).collect();
+    let meta = crate::agent_transcripts::AgentTranscriptMeta {
+        id: format!("{}-{}", info.id, info.started_at),
+        agent_id: info.id,
+        agent_name: info.name,
+        provider: info.provider,
+        model: info.model,
+        cwd: info.repo_path,
+        ticket_id: info.spawned_by_ticket_id,
+        goal_id: info.spawned_by_goal_id,
+        status,
+        exit_code,
+        started_at: info.started_at as i64,
+        ended_at,
+        size_bytes: text.len() as i64,
+        truncated: page.truncated,
+    };
+
+    let saved = tokio::task::spawn_blocking(move || {
+        let db_path = crate::agent_transcripts::db_path_for_project(&project);
+        let mut conn = crate::agent_transcripts::init_db(&db_path)?;
+        crate::agent_transcripts::save_impl(&mut conn, &meta, &text, ended_at)
+    })
+    .await
+    .unwrap_or_else(|e| Err(e.to_string()));
+    if let Err(error) = saved {
+        eprintln!("Agent transcripts: could not save transcript: {error}");
+    }
+}
+
+// ── cost accounting ─────────────────────────────────────────────────
+
+/// Writes what a finished run cost to its project's database, against its
+/// ticket and goal. Only runs whose CLI reported spend have a cost — a parsed
+/// headless run; interactive runs print nothing to read it from. Same rule as
+/// transcripts: only projects with a `.auric`, and a failed write is dropped.
+async fn record_agent_cost(info: &AgentInfo, project: Option<&std::path::Path>, cost: &CostTally) {
+    if cost.reports == 0 {
+        return;
+    }
+    let Some(project) = project.filter(|p| p.join(".auric").is_dir()) else {
+        return;
+    };
+    let project = project.to_string_lossy().to_string();
+    let ended_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+    let row = crate::database::AgentRunCost {
+        id: format!("{}-{}", info.id, info.started_at),
+        agent_id: info.id.clone(),
+        ticket_id: info.spawned_by_ticket_id.clone(),
+        goal_id: info.spawned_by_goal_id.clone(),
+        provider: info.provider.clone(),
+        model: info.model.clone(),
+        cost_usd: cost.usd,
+        input_tokens: cost.input_tokens as i64,
+        output_tokens: cost.output_tokens as i64,
+        started_at: info.started_at as i64,
+        ended_at,
+    };
+
+    let recorded = tokio::task::spawn_blocking(move || {
+        let conn = crate::database::init_db(&project)?;
+        crate::database::agent_run_cost_record_impl(&conn, &row)
+    })
+    .await
+    .unwrap_or_else(|e| Err(e.to_string()));
+    if let Err(error) = recorded {
+        eprintln!("Agent costs: could not record the run's cost: {error}");
+    }
 }
 
 // ── kill_agent ──────────────────────────────────────────────────────
@@ -669,15 +1810,39 @@ pub async fn kill_agent_impl(
     app: &AppHandle,
 ) -> Result<(), String> {
     let mut manager = state.lock().await;
+    // A queued agent has no process yet: taking it out of the queue is all
+    // there is to stopping it.
+    if let Some(queued) = manager.queue.remove(agent_id) {
+        drop(manager);
+        let _ = app.emit(
+            "agent-status",
+            AgentStatusEvent {
+                agent_id: agent_id.to_string(),
+                status: AgentStatus::Idle,
+                exit_code: None,
+                repo_path: queued.payload.info.repo_path,
+                reason: None,
+            },
+        );
+        return Ok(());
+    }
     let mut process = manager
         .agents
         .remove(agent_id)
         .ok_or_else(|| format!("Agent not found: {}", agent_id))?;
+    manager.queue_wakeup.notify_one();
     drop(manager);
 
     // Removing the AgentProcess only drops our handle to the child — the
     // PTY child process itself keeps running unless explicitly killed.
     let _ = process.child.kill();
+    // The shell dies stopped or not; the CLI under it would stay stopped
+    // until the hangup reached it.
+    if process.info.paused {
+        if let Some(pid) = process.child.process_id() {
+            let _ = signal_process_group(pid, AgentSignal::Continue);
+        }
+    }
 
     // Explicitly killed agents must not reappear after a restart.
     persistence_record_exit(app, agent_id);
@@ -689,12 +1854,117 @@ pub async fn kill_agent_impl(
             status: AgentStatus::Idle,
             exit_code: None,
             repo_path: process.info.repo_path,
+            reason: None,
         },
     );
 
     Ok(())
 }
 
+// ── input and signals ───────────────────────────────────────────────
+
+/// What `send_agent_input` writes to the agent's PTY. Text with a line break
+/// goes in as a bracketed paste, the way a terminal sends a paste — typed
+/// raw, its first newline would submit half a prompt. `submit` adds the
+/// Enter that sends it.
+pub fn agent_input_bytes(text: &str, submit: bool) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len() + 13);
+    if text.contains('\n') {
+        bytes.extend_from_slice(b"\x1b[200~");
+        bytes.extend_from_slice(text.as_bytes());
+        bytes.extend_from_slice(b"\x1b[201~");
+    } else {
+        bytes.extend_from_slice(text.as_bytes());
+    }
+    if submit {
+        bytes.push(b'\r');
+    }
+    bytes
+}
+
+/// Ctrl-C as the keyboard sends it: the terminal turns it into SIGINT, and a
+/// CLI in raw mode reads it as its own interrupt key. Either way the agent
+/// stops what it is doing, not itself.
+pub const INTERRUPT_BYTES: &[u8] = b"\x03";
+
+/// Fails unless the agent is running, with a message that says why not.
+pub async fn ensure_agent_running(agent_id: &str, state: &AgentManagerState) -> Result<(), String> {
+    let manager = state.lock().await;
+    if manager.agents.contains_key(agent_id) {
+        Ok(())
+    } else if manager.queue.iter().any(|q| q.id == agent_id) {
+        Err(format!(
+            "Agent {} is queued and has not started yet",
+            agent_id
+        ))
+    } else {
+        Err(format!("Agent not found: {}", agent_id))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AgentSignal {
+    Stop,
+    Continue,
+    Kill,
+}
+
+/// Signals the agent's whole process group. The child is the shell (or
+/// bwrap) the CLI runs under; stopping only that would leave the CLI running.
+#[cfg(unix)]
+fn signal_process_group(pid: u32, signal: AgentSignal) -> Result<(), String> {
+    let signal = match signal {
+        AgentSignal::Stop => libc::SIGSTOP,
+        AgentSignal::Continue => libc::SIGCONT,
+        AgentSignal::Kill => libc::SIGKILL,
+    };
+    // The PTY child is a session leader, so its pid is its group's id.
+    if unsafe { libc::kill(-(pid as libc::pid_t), signal) } == 0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "Failed to signal agent process: {}",
+            std::io::Error::last_os_error()
+        ))
+    }
+}
+
+#[cfg(not(unix))]
+fn signal_process_group(_pid: u32, _signal: AgentSignal) -> Result<(), String> {
+    Err("Pausing agents is not supported on this platform".to_string())
+}
+
+/// Stops (`paused`) or continues the agent's processes. Pausing a paused
+/// agent, or resuming a running one, changes nothing and is not an error.
+pub async fn set_agent_paused_impl(
+    agent_id: &str,
+    paused: bool,
+    state: &AgentManagerState,
+) -> Result<AgentInfo, String> {
+    let mut manager = state.lock().await;
+    let process = manager
+        .agents
+        .get_mut(agent_id)
+        .ok_or_else(|| format!("Agent not found: {}", agent_id))?;
+    if process.info.paused != paused {
+        let pid = process
+            .child
+            .process_id()
+            .ok_or_else(|| format!("Agent {} has no process to signal", agent_id))?;
+        signal_process_group(
+            pid,
+            if paused {
+                AgentSignal::Stop
+            } else {
+                AgentSignal::Continue
+            },
+        )?;
+        process.info.paused = paused;
+        process.paused.store(paused, Ordering::Relaxed);
+    }
+    Ok(process.info.clone())
+}
+
 // ── rename_agent ────────────────────────────────────────────────────
 
 /// Gives a running agent a human-chosen name. Every agent spawned into the
@@ -742,8 +2012,10 @@ pub async fn kill_agents_for_repo_impl(
         manager
             .agents
             .values()
-            .filter(|p| p.info.repo_path.as_deref() == Some(repo_path))
-            .map(|p| p.info.id.clone())
+            .map(|p| &p.info)
+            .chain(manager.queue.iter().map(|q| &q.payload.info))
+            .filter(|info| info.repo_path.as_deref() == Some(repo_path))
+            .map(|info| info.id.clone())
             .collect()
     };
 
@@ -754,12 +2026,31 @@ pub async fn kill_agents_for_repo_impl(
     Ok(count)
 }
 
+// ── get_agent_output ────────────────────────────────────────────────
+
+/// Everything the agent printed from `since_seq` on, for a view that was not
+/// listening when it happened. Works after the agent has exited, for as long
+/// as its scrollback is retained.
+pub async fn get_agent_output_impl(
+    agent_id: &str,
+    since_seq: u64,
+    state: &AgentManagerState,
+) -> Result<AgentOutputPage, String> {
+    let replay = state.lock().await.output.since(agent_id, since_seq)?;
+    tokio::task::spawn_blocking(move || replay.read())
+        .await
+        .map_err(|e| format!("Failed to read agent output: {}", e))?
+}
+
 // ── cleanup ─────────────────────────────────────────────────────────
 
 pub async fn cleanup_all_agents(app: AppHandle) {
     let state = app.state::<AgentManagerState>();
     let mut manager = state.lock().await;
     manager.agents.clear();
+    manager.queue.clear();
+    manager.starting.clear();
+    manager.output.clear();
 }
 
 // ── Tests ───────────────────────────────────────────────────────────
@@ -916,6 +2207,18 @@ mod tests {
         assert!(config.auto_accept_edits.is_none());
         assert!(config.provider.is_none());
         assert!(config.headless.is_none());
+        assert!(config.role.is_none());
+    }
+
+    #[test]
+    fn test_agent_config_can_leave_the_model_to_its_role() {
+        let json = r#"{ "name": "Conductor", "task": "ship it", "role": "implementer" }"#;
+        let config: AgentConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.model, "");
+        assert_eq!(
+            config.role,
+            Some(crate::provider_policy::AgentRole::Implementer)
+        );
     }
 
     #[test]
@@ -932,8 +2235,20 @@ mod tests {
             headless: Some(false),
             spawned_by_ticket_id: Some("ticket-7".to_string()),
             spawned_by_goal_id: None,
+            limits: Some(AgentLimits {
+                max_runtime_secs: Some(3600),
+                ..AgentLimits::default()
+            }),
+            priority: None,
+            rows: None,
+            cols: None,
+            resume_session_id: None,
+            images: Some(vec!["/tmp/screen.png".to_string()]),
+            system_prompt: Some("Be terse.".to_string()),
+            role: None,
+            fallback_model: None,
         };
-        let persisted = persisted_from_config(&config, "agent-4", "claude", 123);
+        let persisted = persisted_from_config(&config, "agent-4", "claude", 123, Some("s-1"));
         assert_eq!(persisted.id, "agent-4");
         assert_eq!(persisted.name, "Agent (alpha)");
         assert_eq!(persisted.model, "opus");
@@ -947,6 +2262,13 @@ mod tests {
         assert_eq!(persisted.started_at, 123);
         assert_eq!(persisted.spawned_by_ticket_id.as_deref(), Some("ticket-7"));
         assert!(persisted.spawned_by_goal_id.is_none());
+        assert_eq!(
+            persisted.limits.and_then(|l| l.max_runtime_secs),
+            Some(3600)
+        );
+        assert_eq!(persisted.session_id.as_deref(), Some("s-1"));
+        assert_eq!(persisted.images, Some(vec!["/tmp/screen.png".to_string()]));
+        assert_eq!(persisted.system_prompt.as_deref(), Some("Be terse."));
     }
 
     #[test]
@@ -1108,4 +2430,196 @@ mod tests {
             "process should have exited after calling kill() on the child handle"
         );
     }
+
+    #[test]
+    fn limit_watch_reports_the_first_limit_crossed() {
+        let start = std::time::Instant::now();
+        let secs = std::time::Duration::from_secs;
+        let mut watch = LimitWatch::new(
+            AgentLimits {
+                max_runtime_secs: Some(600),
+                idle_timeout_secs: Some(60),
+                max_output_bytes: Some(1000),
+                ..AgentLimits::default()
+            },
+            start,
+        );
+
+        assert_eq!(watch.exceeded(start + secs(59)), None);
+        assert_eq!(
+            watch.exceeded(start + secs(60)).as_deref(),
+            Some("Idle timeout: no output for 60s")
+        );
+
+        // Output resets the idle clock but not the runtime one.
+        watch.record_output(400, start + secs(590));
+        assert_eq!(watch.exceeded(start + secs(599)), None);
+        assert_eq!(
+            watch.exceeded(start + secs(600)).as_deref(),
+            Some("Runtime limit reached: ran for 600s")
+        );
+
+        watch.record_output(601, start + secs(10));
+        assert_eq!(
+            watch.exceeded(start + secs(10)).as_deref(),
+            Some("Output limit reached: more than 1000 bytes of output")
+        );
+
+        let unbounded = LimitWatch::new(AgentLimits::default(), start);
+        assert_eq!(unbounded.exceeded(start + secs(86_400)), None);
+    }
+
+    #[test]
+    fn follow_up_input_pastes_multiple_lines_and_submits_once() {
+        assert_eq!(agent_input_bytes("continue", true), b"continue\r");
+        assert_eq!(agent_input_bytes("y", false), b"y");
+        assert_eq!(
+            agent_input_bytes("fix this\nthen that", true),
+            b"\x1b[200~fix this\nthen that\x1b[201~\r"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn argv_runs_directly_unless_a_limit_or_stdin_needs_the_wrapper() {
+        let spawn = |argv: &[&str], task_via| SpawnCommand {
+            command: CommandLine::Argv(argv.iter().map(|arg| arg.to_string()).collect()),
+            env_vars: vec![],
+            executable: "claude".to_string(),
+            task_via,
+            task: "t".to_string(),
+            credential_env: vec![],
+            system_prompt_file: None,
+        };
+        let task = "it's \"$HOME\"\nand `more`";
+
+        assert_eq!(
+            command_argv(&spawn(&["claude", "-p", task], TaskVia::Argv), "", None).unwrap(),
+            ["claude", "-p", task]
+        );
+        // The wrapper's script is fixed; the command follows as `$@`.
+        assert_eq!(
+            command_argv(
+                &spawn(&["claude", "-p", task], TaskVia::Argv),
+                "ulimit -t 60 || exit 126; ",
+                None
+            )
+            .unwrap(),
+            [
+                "/bin/sh",
+                "-c",
+                "ulimit -t 60 || exit 126; exec \"$@\"",
+                "sh",
+                "claude",
+                "-p",
+                task
+            ]
+        );
+        assert_eq!(
+            command_argv(
+                &spawn(&["claude", "-p"], TaskVia::Stdin),
+                "",
+                Some("/data/agent-tasks/agent-1.md")
+            )
+            .unwrap(),
+            [
+                "/bin/sh",
+                "-c",
+                "f=$1; shift; exec \"$@\" < \"$f\"",
+                "sh",
+                "/data/agent-tasks/agent-1.md",
+                "claude",
+                "-p"
+            ]
+        );
+        assert!(command_argv(&spawn(&[], TaskVia::Argv), "", None).is_err());
+    }
+
+    #[test]
+    fn limits_are_validated_and_become_ulimits() {
+        let json = r#"{"name":"a","model":"auto","task":"t",
+            "limits":{"maxRuntimeSecs":3600,"maxCpuSecs":900}}"#;
+        let config: AgentConfig = serde_json::from_str(json).unwrap();
+        let limits = config.limits.expect("limits parsed");
+        assert!(limits.validate().is_ok());
+        assert_eq!(
+            rlimit_prelude(&limits),
+            "ulimit -S -t 900 || exit 126; ulimit -H -t 905 || exit 126; "
+        );
+        assert_eq!(rlimit_prelude(&AgentLimits::default()), "");
+
+        let zero = AgentLimits {
+            idle_timeout_secs: Some(0),
+            ..AgentLimits::default()
+        };
+        assert_eq!(
+            zero.validate().unwrap_err(),
+            "Agent limit idleTimeoutSecs must be greater than 0"
+        );
+    }
+
+    #[test]
+    fn a_memory_limit_is_watched_not_a_ulimit() {
+        let json = r#"{"name":"a","model":"auto","task":"t",
+            "limits":{"maxRuntimeSecs":3600,"maxMemoryMb":2048}}"#;
+        let config: AgentConfig = serde_json::from_str(json).unwrap();
+        let limits = config.limits.expect("limits parsed");
+        assert!(limits.validate().is_ok());
+        assert_eq!(rlimit_prelude(&limits), "");
+
+        let start = std::time::Instant::now();
+        let mut watch = LimitWatch::new(limits, start);
+        watch.record_memory(2048 * 1024 * 1024);
+        assert_eq!(watch.exceeded(start), None);
+        watch.record_memory(2048 * 1024 * 1024 + 1);
+        assert_eq!(
+            watch.exceeded(start).as_deref(),
+            Some("Memory limit reached: used more than 2048 MB")
+        );
+    }
+
+    #[test]
+    fn proc_files_give_the_group_and_resident_memory() {
+        let stat = "4242 (node (worker) 1) S 4200 4100 4100 34816 4100 4194304";
+        assert_eq!(stat_process_group(stat), Some(4100));
+        assert_eq!(stat_process_group("garbage"), None);
+
+        let status = "Name:\tnode\nVmPeak:\t  900000 kB\nVmRSS:\t  123456 kB\nThreads:\t11\n";
+        assert_eq!(status_rss_bytes(status), Some(123456 * 1024));
+        assert_eq!(status_rss_bytes("Name:\tkthreadd\n"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn the_group_memory_sum_covers_this_process() {
+        let pgid = unsafe { libc::getpgrp() } as u32;
+        let bytes = process_group_rss_bytes(pgid).expect("/proc is readable");
+        assert!(bytes > 0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn a_cpu_limit_is_recognised_from_the_exit_status() {
+        let pair = native_pty_system()
+            .openpty(PtySize {
+                rows: 24,
+                cols: 80,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .expect("failed to open pty");
+        let limits = AgentLimits {
+            max_cpu_secs: Some(1),
+            ..AgentLimits::default()
+        };
+        let mut cmd = CommandBuilder::new("sh");
+        cmd.arg("-c");
+        cmd.arg(format!("{}while :; do :; done", rlimit_prelude(&limits)));
+
+        let mut child = pair.slave.spawn_command(cmd).expect("failed to spawn sh");
+        drop(pair.slave);
+        let status = child.wait().expect("wait failed");
+
+        assert!(cpu_limit_hit(&status), "{:?}", status);
+    }
 }
@@ -105,6 +105,63 @@ pub struct PmSavePayload {
     pub dependencies: Vec<PmDependency>,
 }
 
+/// `updated_at` per row id, one map per versioned PM table. Dependencies are
+/// create/delete only and carry no version.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PmRowVersions {
+    #[serde(default)]
+    pub epics: HashMap<String, String>,
+    #[serde(default)]
+    pub tickets: HashMap<String, String>,
+    #[serde(default)]
+    pub test_cases: HashMap<String, String>,
+}
+
+/// Row-level PM sync: upserts, explicit deletions and the versions the caller
+/// last loaded. A row listed in `base_versions` is only written (or deleted)
+/// while the stored `updated_at` still matches; a row without a base entry is
+/// a create or a blind write, same as `GoalsSyncPayload`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PmSyncPayload {
+    #[serde(default)]
+    pub epics: Vec<PmEpic>,
+    #[serde(default)]
+    pub tickets: Vec<PmTicket>,
+    #[serde(default)]
+    pub test_cases: Vec<PmTestCase>,
+    #[serde(default)]
+    pub dependencies: Vec<PmDependency>,
+    #[serde(default)]
+    pub deleted_epic_ids: Vec<String>,
+    #[serde(default)]
+    pub deleted_ticket_ids: Vec<String>,
+    #[serde(default)]
+    pub deleted_test_case_ids: Vec<String>,
+    #[serde(default)]
+    pub deleted_dependency_ids: Vec<String>,
+    #[serde(default)]
+    pub base_versions: PmRowVersions,
+}
+
+/// The `updated_at` each written row now carries, so the caller can adopt it
+/// as the base of its next sync.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PmSyncResult {
+    pub versions: PmRowVersions,
+}
+
+/// Leading text of every optimistic-concurrency rejection from `pm_sync_impl`,
+/// so the frontend can tell "reload and retry" apart from a real failure.
+pub const PM_CONFLICT_PREFIX: &str = "PM conflict";
+
+/// SQL for a PM row's new `updated_at`: UTC to the millisecond. Every writer
+/// of a versioned PM row uses it (the MCP server has the same expression), so
+/// two writes in one second still read as two versions.
+pub const PM_VERSION_STAMP_SQL: &str = "strftime('%Y-%m-%dT%H:%M:%fZ', 'now')";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PmState {
@@ -205,6 +262,54 @@ pub struct PmGoalRun {
     pub summary: String,
     pub started_at: String,
     pub finished_at: Option<String>,
+    /// Filled in by the backend when the agent ends and its CLI reported
+    /// spend; a synced run never overwrites them.
+    #[serde(default)]
+    pub cost_usd: Option<f64>,
+    #[serde(default)]
+    pub input_tokens: Option<i64>,
+    #[serde(default)]
+    pub output_tokens: Option<i64>,
+}
+
+/// One finished agent run's spend, as its CLI reported it. Kept for every run,
+/// goal or not, so tickets can be totalled too.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentRunCost {
+    /// `<agent id>-<started at>`: agent ids start over with every app run.
+    pub id: String,
+    pub agent_id: String,
+    pub ticket_id: Option<String>,
+    pub goal_id: Option<String>,
+    pub provider: String,
+    pub model: String,
+    /// `None` when the CLI reports tokens but no price (Codex).
+    pub cost_usd: Option<f64>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub started_at: i64,
+    pub ended_at: i64,
+}
+
+/// Spend summed over every run of one goal or one ticket.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentCostTotal {
+    pub id: String,
+    pub cost_usd: f64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub runs: i64,
+    /// Runs that reported tokens but no price, so `cost_usd` leaves them out.
+    pub unpriced_runs: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentCostSummary {
+    pub goals: Vec<AgentCostTotal>,
+    pub tickets: Vec<AgentCostTotal>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -683,9 +788,110 @@ pub fn run_migrations(conn: &Connection) -> Result<(), String> {
         "ALTER TABLE pm_tickets ADD COLUMN skills TEXT NOT NULL DEFAULT '[]';",
     )?;
 
+    apply_migration(
+        conn,
+        20,
+        "create_agent_run_costs",
+        // Keep in sync with src/mcp/db.ts migration 20.
+        "CREATE TABLE agent_run_costs (
+            id            TEXT PRIMARY KEY,
+            agent_id      TEXT NOT NULL,
+            ticket_id     TEXT,
+            goal_id       TEXT,
+            provider      TEXT NOT NULL DEFAULT '',
+            model         TEXT NOT NULL DEFAULT '',
+            cost_usd      REAL,
+            input_tokens  INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0,
+            started_at    INTEGER NOT NULL,
+            ended_at      INTEGER NOT NULL
+        );
+        CREATE INDEX idx_agent_run_costs_ticket ON agent_run_costs(ticket_id);
+        CREATE INDEX idx_agent_run_costs_goal ON agent_run_costs(goal_id);
+
+        ALTER TABLE pm_goal_runs ADD COLUMN cost_usd REAL;
+        ALTER TABLE pm_goal_runs ADD COLUMN input_tokens INTEGER;
+        ALTER TABLE pm_goal_runs ADD COLUMN output_tokens INTEGER;",
+    )?;
+
+    Ok(())
+}
+
+/// Records what a finished agent run cost, and puts it on the goal run the
+/// agent was started for — the newest one for that agent, since agent ids
+/// repeat across app runs.
+pub fn agent_run_cost_record_impl(conn: &Connection, cost: &AgentRunCost) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO agent_run_costs (id, agent_id, ticket_id, goal_id, provider, \
+         model, cost_usd, input_tokens, output_tokens, started_at, ended_at) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            cost.id,
+            cost.agent_id,
+            cost.ticket_id,
+            cost.goal_id,
+            cost.provider,
+            cost.model,
+            cost.cost_usd,
+            cost.input_tokens,
+            cost.output_tokens,
+            cost.started_at,
+            cost.ended_at
+        ],
+    )
+    .map_err(|e| format!("Failed to record agent run cost: {}", e))?;
+
+    if let Some(goal_id) = &cost.goal_id {
+        conn.execute(
+            "UPDATE pm_goal_runs SET cost_usd = ?1, input_tokens = ?2, output_tokens = ?3 \
+             WHERE id = (SELECT id FROM pm_goal_runs WHERE agent_id = ?4 AND goal_id = ?5 \
+             ORDER BY started_at DESC, rowid DESC LIMIT 1)",
+            params![
+                cost.cost_usd,
+                cost.input_tokens,
+                cost.output_tokens,
+                cost.agent_id,
+                goal_id
+            ],
+        )
+        .map_err(|e| format!("Failed to record goal run cost: {}", e))?;
+    }
     Ok(())
 }
 
+/// Spend per goal and per ticket, over every recorded run.
+pub fn agent_cost_summary_impl(conn: &Connection) -> Result<AgentCostSummary, String> {
+    let totals = |column: &str| -> Result<Vec<AgentCostTotal>, String> {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {column}, COALESCE(SUM(cost_usd), 0), SUM(input_tokens), \
+                 SUM(output_tokens), COUNT(*), SUM(cost_usd IS NULL) \
+                 FROM agent_run_costs WHERE {column} IS NOT NULL \
+                 GROUP BY {column} ORDER BY {column}"
+            ))
+            .map_err(|e| format!("Failed to prepare agent cost query: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(AgentCostTotal {
+                    id: row.get(0)?,
+                    cost_usd: row.get(1)?,
+                    input_tokens: row.get(2)?,
+                    output_tokens: row.get(3)?,
+                    runs: row.get(4)?,
+                    unpriced_runs: row.get(5)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query agent costs: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(rows)
+    };
+    Ok(AgentCostSummary {
+        goals: totals("goal_id")?,
+        tickets: totals("ticket_id")?,
+    })
+}
+
 /// Records the start prompt of a freshly spawned agent. Re-running an identical
 /// prompt replaces the previous row (the history is a recency list, not an audit
 /// log), and the table is pruned to `AGENT_PROMPT_HISTORY_CAP` newest rows.
@@ -1096,6 +1302,302 @@ pub fn pm_save_impl(conn: &Connection, payload: &PmSavePayload) -> Result<(), St
     })
 }
 
+/// Stored `updated_at` of one row, `None` when the row does not exist.
+fn stored_updated_at(conn: &Connection, table: &str, id: &str) -> Result<Option<String>, String> {
+    let result = conn.query_row(
+        &format!("SELECT updated_at FROM {table} WHERE id = ?1"),
+        params![id],
+        |row| row.get::<_, String>(0),
+    );
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Failed to read {table} version: {}", e)),
+    }
+}
+
+/// Describes why `id` no longer matches the version the caller loaded, or
+/// `None` when it still does (or the caller sent no base for it). A deleted
+/// row only conflicts with an update: deleting it again is already done.
+fn version_conflict(
+    conn: &Connection,
+    table: &str,
+    label: &str,
+    id: &str,
+    base: &HashMap<String, String>,
+    deleting: bool,
+) -> Result<Option<String>, String> {
+    let Some(expected) = base.get(id) else {
+        return Ok(None);
+    };
+    match stored_updated_at(conn, table, id)? {
+        Some(found) if &found != expected => Ok(Some(format!(
+            "{label} '{id}' was changed since it was loaded (expected {expected}, found {found})"
+        ))),
+        None if !deleting => Ok(Some(format!(
+            "{label} '{id}' was deleted since it was loaded"
+        ))),
+        _ => Ok(None),
+    }
+}
+
+/// Row-level PM sync: checks every base version first, then applies deletions
+/// and upserts in one transaction. Any conflict rejects the whole payload with
+/// an error starting with `PM_CONFLICT_PREFIX` that names every stale row.
+/// Written rows are stamped with a fresh `updated_at`, returned in the result.
+pub fn pm_sync_impl(conn: &Connection, payload: &PmSyncPayload) -> Result<PmSyncResult, String> {
+    let base = &payload.base_versions;
+    let stamp: String = conn
+        .query_row(&format!("SELECT {PM_VERSION_STAMP_SQL}"), [], |row| {
+            row.get(0)
+        })
+        .map_err(|e| format!("Failed to read the clock: {}", e))?;
+    let mut result = PmSyncResult::default();
+
+    with_transaction(conn, || {
+        // Rows may arrive in any order (a ticket before its new epic); defer
+        // FK checks to commit, same as goals_sync_impl.
+        conn.execute_batch("PRAGMA defer_foreign_keys = ON;")
+            .map_err(|e| format!("Failed to defer foreign keys: {}", e))?;
+
+        let mut conflicts: Vec<String> = Vec::new();
+        let checks = [
+            ("pm_epics", "epic", &base.epics, &payload.deleted_epic_ids),
+            (
+                "pm_tickets",
+                "ticket",
+                &base.tickets,
+                &payload.deleted_ticket_ids,
+            ),
+            (
+                "pm_test_cases",
+                "test case",
+                &base.test_cases,
+                &payload.deleted_test_case_ids,
+            ),
+        ];
+        for (table, label, versions, deleted) in checks {
+            for id in deleted {
+                conflicts.extend(version_conflict(conn, table, label, id, versions, true)?);
+            }
+        }
+        for epic in &payload.epics {
+            conflicts.extend(version_conflict(
+                conn,
+                "pm_epics",
+                "epic",
+                &epic.id,
+                &base.epics,
+                false,
+            )?);
+        }
+        for ticket in &payload.tickets {
+            conflicts.extend(version_conflict(
+                conn,
+                "pm_tickets",
+                "ticket",
+                &ticket.id,
+                &base.tickets,
+                false,
+            )?);
+        }
+        for tc in &payload.test_cases {
+            conflicts.extend(version_conflict(
+                conn,
+                "pm_test_cases",
+                "test case",
+                &tc.id,
+                &base.test_cases,
+                false,
+            )?);
+        }
+        if !conflicts.is_empty() {
+            return Err(format!("{PM_CONFLICT_PREFIX}: {}", conflicts.join("; ")));
+        }
+
+        for id in &payload.deleted_dependency_ids {
+            conn.execute("DELETE FROM pm_dependencies WHERE id = ?1", params![id])
+                .map_err(|e| format!("Failed to delete dependency: {}", e))?;
+        }
+        for id in &payload.deleted_test_case_ids {
+            conn.execute("DELETE FROM pm_test_cases WHERE id = ?1", params![id])
+                .map_err(|e| format!("Failed to delete test case: {}", e))?;
+        }
+        for id in &payload.deleted_ticket_ids {
+            // Cascades to the ticket's test cases and reviews
+            conn.execute("DELETE FROM pm_tickets WHERE id = ?1", params![id])
+                .map_err(|e| format!("Failed to delete ticket: {}", e))?;
+        }
+        for id in &payload.deleted_epic_ids {
+            // Cascades to the epic's tickets
+            conn.execute("DELETE FROM pm_epics WHERE id = ?1", params![id])
+                .map_err(|e| format!("Failed to delete epic: {}", e))?;
+        }
+
+        for epic in &payload.epics {
+            conn.execute(
+                "INSERT INTO pm_epics (id, name, description, sort_order, created_at, updated_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6) \
+                 ON CONFLICT(id) DO UPDATE SET \
+                 name = excluded.name, description = excluded.description, \
+                 sort_order = excluded.sort_order, updated_at = excluded.updated_at",
+                params![
+                    epic.id,
+                    epic.name,
+                    epic.description,
+                    epic.sort_order,
+                    epic.created_at,
+                    stamp
+                ],
+            )
+            .map_err(|e| format!("Failed to upsert epic: {}", e))?;
+            result.versions.epics.insert(epic.id.clone(), stamp.clone());
+        }
+
+        for ticket in &payload.tickets {
+            let old_status: Option<String> = conn
+                .query_row(
+                    "SELECT status FROM pm_tickets WHERE id = ?1",
+                    params![ticket.id],
+                    |row| row.get(0),
+                )
+                .map(Some)
+                .or_else(|e| match e {
+                    rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                    other => Err(format!("Failed to read old status: {}", other)),
+                })?;
+
+            let context_json = serde_json::to_string(&ticket.context.as_ref().unwrap_or(&vec![]))
+                .map_err(|e| format!("Failed to serialize context: {}", e))?;
+            let skills_json = serde_json::to_string(&ticket.skills)
+                .map_err(|e| format!("Failed to serialize skills: {}", e))?;
+
+            conn.execute(
+                "INSERT INTO pm_tickets (id, epic_id, name, description, status, \
+                 status_updated_at, sort_order, working_directory, context, model_power, priority, \
+                 needs_human_supervision, goal_id, due_date, skills, created_at, updated_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17) \
+                 ON CONFLICT(id) DO UPDATE SET \
+                 epic_id = excluded.epic_id, name = excluded.name, \
+                 description = excluded.description, status = excluded.status, \
+                 status_updated_at = excluded.status_updated_at, \
+                 sort_order = excluded.sort_order, \
+                 working_directory = excluded.working_directory, context = excluded.context, \
+                 model_power = excluded.model_power, priority = excluded.priority, \
+                 needs_human_supervision = excluded.needs_human_supervision, \
+                 goal_id = excluded.goal_id, due_date = excluded.due_date, \
+                 skills = excluded.skills, updated_at = excluded.updated_at",
+                params![
+                    ticket.id,
+                    ticket.epic_id,
+                    ticket.name,
+                    ticket.description,
+                    ticket.status,
+                    ticket.status_updated_at,
+                    ticket.sort_order,
+                    ticket.working_directory,
+                    context_json,
+                    ticket.model_power,
+                    ticket.priority,
+                    ticket.needs_human_supervision,
+                    ticket.goal_id,
+                    ticket.due_date,
+                    skills_json,
+                    ticket.created_at,
+                    stamp
+                ],
+            )
+            .map_err(|e| format!("Failed to upsert ticket: {}", e))?;
+            result
+                .versions
+                .tickets
+                .insert(ticket.id.clone(), stamp.clone());
+
+            if old_status.as_deref() != Some(ticket.status.as_str()) {
+                conn.execute(
+                    "INSERT INTO pm_status_history \
+                     (id, ticket_id, from_status, to_status, changed_at, source) \
+                     VALUES (hex(randomblob(16)), ?1, ?2, ?3, datetime('now'), 'ui')",
+                    params![ticket.id, old_status, ticket.status],
+                )
+                .map_err(|e| format!("Failed to insert status history: {}", e))?;
+            }
+        }
+
+        for tc in &payload.test_cases {
+            conn.execute(
+                "INSERT INTO pm_test_cases (id, ticket_id, title, body, sort_order, created_at, \
+                 updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7) \
+                 ON CONFLICT(id) DO UPDATE SET \
+                 ticket_id = excluded.ticket_id, title = excluded.title, body = excluded.body, \
+                 sort_order = excluded.sort_order, updated_at = excluded.updated_at",
+                params![
+                    tc.id,
+                    tc.ticket_id,
+                    tc.title,
+                    tc.body,
+                    tc.sort_order,
+                    tc.created_at,
+                    stamp
+                ],
+            )
+            .map_err(|e| format!("Failed to upsert test case: {}", e))?;
+            result
+                .versions
+                .test_cases
+                .insert(tc.id.clone(), stamp.clone());
+        }
+
+        for dep in &payload.dependencies {
+            conn.execute(
+                "INSERT INTO pm_dependencies (id, source_type, source_id, target_type, target_id) \
+                 VALUES (?1, ?2, ?3, ?4, ?5) \
+                 ON CONFLICT(id) DO UPDATE SET \
+                 source_type = excluded.source_type, source_id = excluded.source_id, \
+                 target_type = excluded.target_type, target_id = excluded.target_id",
+                params![
+                    dep.id,
+                    dep.source_type,
+                    dep.source_id,
+                    dep.target_type,
+                    dep.target_id
+                ],
+            )
+            .map_err(|e| format!("Failed to upsert dependency: {}", e))?;
+        }
+
+        // Only the merged graph can tell whether the delta closes a cycle:
+        // the other half of it may be a row this payload never saw.
+        if !payload.dependencies.is_empty() {
+            validate_no_cycles(&pm_load_dependencies(conn)?)?;
+        }
+
+        Ok(())
+    })?;
+
+    Ok(result)
+}
+
+fn pm_load_dependencies(conn: &Connection) -> Result<Vec<PmDependency>, String> {
+    let mut dep_stmt = conn
+        .prepare("SELECT id, source_type, source_id, target_type, target_id FROM pm_dependencies")
+        .map_err(|e| format!("Failed to prepare dependencies query: {}", e))?;
+    let dependencies: Vec<PmDependency> = dep_stmt
+        .query_map([], |row| {
+            Ok(PmDependency {
+                id: row.get(0)?,
+                source_type: row.get(1)?,
+                source_id: row.get(2)?,
+                target_type: row.get(3)?,
+                target_id: row.get(4)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query dependencies: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(dependencies)
+}
+
 pub fn pm_load_impl(conn: &Connection) -> Result<PmState, String> {
     let mut epic_stmt = conn
         .prepare(
@@ -1184,22 +1686,7 @@ pub fn pm_load_impl(conn: &Connection) -> Result<PmState, String> {
         .filter_map(|r| r.ok())
         .collect();
 
-    let mut dep_stmt = conn
-        .prepare("SELECT id, source_type, source_id, target_type, target_id FROM pm_dependencies")
-        .map_err(|e| format!("Failed to prepare dependencies query: {}", e))?;
-    let dependencies: Vec<PmDependency> = dep_stmt
-        .query_map([], |row| {
-            Ok(PmDependency {
-                id: row.get(0)?,
-                source_type: row.get(1)?,
-                source_id: row.get(2)?,
-                target_type: row.get(3)?,
-                target_id: row.get(4)?,
-            })
-        })
-        .map_err(|e| format!("Failed to query dependencies: {}", e))?
-        .filter_map(|r| r.ok())
-        .collect();
+    let dependencies = pm_load_dependencies(conn)?;
 
     Ok(PmState {
         epics,
@@ -1598,7 +2085,7 @@ pub fn goals_load_impl(conn: &Connection) -> Result<GoalsState, String> {
     let mut run_stmt = conn
         .prepare(
             "SELECT id, goal_id, agent_id, ticket_id, prompt, model, provider, source, \
-             outcome, summary, started_at, finished_at \
+             outcome, summary, started_at, finished_at, cost_usd, input_tokens, output_tokens \
              FROM pm_goal_runs ORDER BY started_at",
         )
         .map_err(|e| format!("Failed to prepare goal runs query: {}", e))?;
@@ -1617,6 +2104,9 @@ pub fn goals_load_impl(conn: &Connection) -> Result<GoalsState, String> {
                 summary: row.get(9)?,
                 started_at: row.get(10)?,
                 finished_at: row.get(11)?,
+                cost_usd: row.get(12)?,
+                input_tokens: row.get(13)?,
+                output_tokens: row.get(14)?,
             })
         })
         .map_err(|e| format!("Failed to query goal runs: {}", e))?
@@ -1741,7 +2231,7 @@ mod tests {
         let count: i32 = conn
             .query_row("SELECT COUNT(*) FROM _migrations", [], |row| row.get(0))
             .unwrap();
-        assert_eq!(count, 19);
+        assert_eq!(count, 20);
 
         // kv_store table should exist
         let table_exists: bool = conn
@@ -1763,7 +2253,7 @@ mod tests {
         let count: i32 = conn
             .query_row("SELECT COUNT(*) FROM _migrations", [], |row| row.get(0))
             .unwrap();
-        assert_eq!(count, 19);
+        assert_eq!(count, 20);
     }
 
     #[test]
@@ -1781,7 +2271,7 @@ mod tests {
         let count: i32 = conn
             .query_row("SELECT COUNT(*) FROM _migrations", [], |row| row.get(0))
             .unwrap();
-        assert_eq!(count, 19);
+        assert_eq!(count, 20);
     }
 
     #[test]
@@ -1908,7 +2398,7 @@ mod tests {
         let migration_count: i32 = conn
             .query_row("SELECT COUNT(*) FROM _migrations", [], |row| row.get(0))
             .unwrap();
-        assert_eq!(migration_count, 19);
+        assert_eq!(migration_count, 20);
     }
 
     fn make_test_payload() -> PmSavePayload {
@@ -2272,6 +2762,233 @@ mod tests {
         );
     }
 
+    fn sync_from_save_payload(payload: PmSavePayload) -> PmSyncPayload {
+        PmSyncPayload {
+            epics: payload.epics,
+            tickets: payload.tickets,
+            test_cases: payload.test_cases,
+            dependencies: payload.dependencies,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pm_sync_upserts_and_deletes_only_listed_rows() {
+        let conn = setup_in_memory_db();
+        pm_save_impl(&conn, &make_test_payload()).unwrap();
+
+        // A ticket the MCP server created that this caller never saw.
+        let mut mcp_ticket = make_test_payload().tickets.remove(0);
+        mcp_ticket.id = "t-mcp".to_string();
+        pm_sync_impl(
+            &conn,
+            &PmSyncPayload {
+                tickets: vec![mcp_ticket],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let mut renamed = make_test_payload().tickets.remove(0);
+        renamed.name = "Renamed".to_string();
+        pm_sync_impl(
+            &conn,
+            &PmSyncPayload {
+                tickets: vec![renamed],
+                deleted_test_case_ids: vec!["tc1".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let state = pm_load_impl(&conn).unwrap();
+        assert_eq!(state.epics.len(), 1);
+        assert_eq!(state.tickets.len(), 2);
+        let t1 = state.tickets.iter().find(|t| t.id == "t1").unwrap();
+        assert_eq!(t1.name, "Renamed");
+        assert!(state.tickets.iter().any(|t| t.id == "t-mcp"));
+        assert!(state.test_cases.is_empty());
+        assert_eq!(state.dependencies.len(), 1);
+    }
+
+    #[test]
+    fn test_pm_sync_returns_versions_that_serve_as_the_next_base() {
+        let conn = setup_in_memory_db();
+        let result = pm_sync_impl(&conn, &sync_from_save_payload(make_test_payload())).unwrap();
+
+        let stored = pm_load_impl(&conn).unwrap();
+        assert_eq!(
+            result.versions.tickets.get("t1"),
+            Some(&stored.tickets[0].updated_at)
+        );
+        assert_eq!(
+            result.versions.epics.get("e1"),
+            Some(&stored.epics[0].updated_at)
+        );
+
+        let mut edited = stored.tickets[0].clone();
+        edited.description = "second pass".to_string();
+        pm_sync_impl(
+            &conn,
+            &PmSyncPayload {
+                tickets: vec![edited],
+                base_versions: result.versions,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            pm_load_impl(&conn).unwrap().tickets[0].description,
+            "second pass"
+        );
+    }
+
+    #[test]
+    fn test_pm_sync_rejects_a_stale_update_and_writes_nothing() {
+        let conn = setup_in_memory_db();
+        pm_save_impl(&conn, &make_test_payload()).unwrap();
+        // The MCP server touched the ticket after the UI loaded it.
+        conn.execute(
+            "UPDATE pm_tickets SET status = 'in_progress', updated_at = '2026-03-01 00:00:00' \
+             WHERE id = 't1'",
+            [],
+        )
+        .unwrap();
+
+        let mut epic = make_test_payload().epics.remove(0);
+        epic.name = "Epic Renamed".to_string();
+        let mut ticket = make_test_payload().tickets.remove(0);
+        ticket.status = "done".to_string();
+        let mut base = PmRowVersions::default();
+        base.tickets
+            .insert("t1".to_string(), "2026-01-01 00:00:00".to_string());
+
+        let err = pm_sync_impl(
+            &conn,
+            &PmSyncPayload {
+                epics: vec![epic],
+                tickets: vec![ticket],
+                base_versions: base,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(err.starts_with(PM_CONFLICT_PREFIX), "{err}");
+        assert!(err.contains("ticket 't1'"), "{err}");
+
+        let state = pm_load_impl(&conn).unwrap();
+        assert_eq!(state.epics[0].name, "Epic One");
+        assert_eq!(state.tickets[0].status, "in_progress");
+    }
+
+    #[test]
+    fn test_pm_sync_reports_a_row_deleted_under_an_update_but_not_under_a_delete() {
+        let conn = setup_in_memory_db();
+        let mut base = PmRowVersions::default();
+        base.test_cases
+            .insert("tc1".to_string(), "2026-01-01 00:00:00".to_string());
+
+        let err = pm_sync_impl(
+            &conn,
+            &PmSyncPayload {
+                test_cases: make_test_payload().test_cases,
+                base_versions: base.clone(),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(err.contains("was deleted"), "{err}");
+
+        pm_sync_impl(
+            &conn,
+            &PmSyncPayload {
+                deleted_test_case_ids: vec!["tc1".to_string()],
+                base_versions: base,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_pm_sync_records_status_changes_in_history() {
+        let conn = setup_in_memory_db();
+        pm_sync_impl(&conn, &sync_from_save_payload(make_test_payload())).unwrap();
+
+        let mut ticket = make_test_payload().tickets.remove(0);
+        ticket.status = "done".to_string();
+        pm_sync_impl(
+            &conn,
+            &PmSyncPayload {
+                tickets: vec![ticket.clone()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        // Same status again: not a transition.
+        pm_sync_impl(
+            &conn,
+            &PmSyncPayload {
+                tickets: vec![ticket],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let history = pm_load_history_impl(&conn).unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history
+            .iter()
+            .any(|h| h.from_status.is_none() && h.to_status == "open"));
+        assert!(history
+            .iter()
+            .any(|h| h.from_status.as_deref() == Some("open") && h.to_status == "done"));
+    }
+
+    #[test]
+    fn test_pm_sync_rejects_a_cycle_closed_against_stored_dependencies() {
+        let conn = setup_in_memory_db();
+        pm_save_impl(&conn, &make_test_payload()).unwrap();
+
+        let err = pm_sync_impl(
+            &conn,
+            &PmSyncPayload {
+                dependencies: vec![PmDependency {
+                    id: "d2".to_string(),
+                    source_type: "ticket".to_string(),
+                    source_id: "t2".to_string(),
+                    target_type: "ticket".to_string(),
+                    target_id: "t1".to_string(),
+                }],
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(err.contains("Cycle"), "{err}");
+        assert_eq!(pm_load_impl(&conn).unwrap().dependencies.len(), 1);
+    }
+
+    #[test]
+    fn test_pm_sync_deleting_an_epic_cascades_to_its_tickets() {
+        let conn = setup_in_memory_db();
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        pm_sync_impl(&conn, &sync_from_save_payload(make_test_payload())).unwrap();
+
+        pm_sync_impl(
+            &conn,
+            &PmSyncPayload {
+                deleted_epic_ids: vec!["e1".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let state = pm_load_impl(&conn).unwrap();
+        assert!(state.epics.is_empty());
+        assert!(state.tickets.is_empty());
+        assert!(state.test_cases.is_empty());
+    }
+
     #[test]
     fn test_validate_no_cycles_detects_cycle() {
         let deps = vec![
@@ -2746,6 +3463,9 @@ mod tests {
                 summary: "".to_string(),
                 started_at: "2026-01-01 00:00:00".to_string(),
                 finished_at: None,
+                cost_usd: None,
+                input_tokens: None,
+                output_tokens: None,
             }],
             vec![PmGoalRequirementLink {
                 id: "grl1".to_string(),
@@ -2767,6 +3487,80 @@ mod tests {
         assert_eq!(state.requirement_links[0].requirement_id, "r1");
     }
 
+    #[test]
+    fn test_agent_run_costs_sum_per_goal_and_ticket_and_land_on_the_goal_run() {
+        let conn = setup_in_memory_db();
+        let run = |id: &str, agent_id: &str, started_at: &str| PmGoalRun {
+            id: id.to_string(),
+            goal_id: "g1".to_string(),
+            agent_id: agent_id.to_string(),
+            ticket_id: Some("t1".to_string()),
+            prompt: "p".to_string(),
+            model: "sonnet".to_string(),
+            provider: "claude".to_string(),
+            source: "conductor".to_string(),
+            outcome: "running".to_string(),
+            summary: "".to_string(),
+            started_at: started_at.to_string(),
+            finished_at: None,
+            cost_usd: None,
+            input_tokens: None,
+            output_tokens: None,
+        };
+        // agent-1 ran for g1 before, in an earlier app run.
+        let payload = sync_payload(
+            vec![make_test_goal("g1", None)],
+            vec![
+                run("old", "agent-1", "2026-01-01 00:00:00"),
+                run("new", "agent-1", "2026-01-02 00:00:00"),
+            ],
+            vec![],
+        );
+        goals_sync_impl(&conn, &payload).unwrap();
+
+        let cost = |id: &str, ticket: Option<&str>, usd: Option<f64>| AgentRunCost {
+            id: id.to_string(),
+            agent_id: "agent-1".to_string(),
+            ticket_id: ticket.map(str::to_string),
+            goal_id: Some("g1".to_string()),
+            provider: "claude".to_string(),
+            model: "sonnet".to_string(),
+            cost_usd: usd,
+            input_tokens: 1000,
+            output_tokens: 200,
+            started_at: 1,
+            ended_at: 2,
+        };
+        agent_run_cost_record_impl(&conn, &cost("agent-1-1", Some("t1"), Some(1.25))).unwrap();
+        agent_run_cost_record_impl(&conn, &cost("agent-2-1", None, None)).unwrap();
+        agent_run_cost_record_impl(&conn, &cost("agent-3-1", Some("t1"), Some(0.5))).unwrap();
+
+        let summary = agent_cost_summary_impl(&conn).unwrap();
+        assert_eq!(
+            summary.goals,
+            vec![AgentCostTotal {
+                id: "g1".to_string(),
+                cost_usd: 1.75,
+                input_tokens: 3000,
+                output_tokens: 600,
+                runs: 3,
+                unpriced_runs: 1,
+            }]
+        );
+        assert_eq!(summary.tickets.len(), 1);
+        assert_eq!(summary.tickets[0].runs, 2);
+        assert_eq!(summary.tickets[0].cost_usd, 1.75);
+
+        // The newest run of that agent carries the cost, and a later sync of
+        // the frontend's draft does not wipe it.
+        goals_sync_impl(&conn, &payload).unwrap();
+        let state = goals_load_impl(&conn).unwrap();
+        let by_id = |id: &str| state.goal_runs.iter().find(|r| r.id == id).unwrap();
+        assert_eq!(by_id("old").cost_usd, None);
+        assert_eq!(by_id("new").cost_usd, Some(0.5));
+        assert_eq!(by_id("new").input_tokens, Some(1000));
+    }
+
     #[test]
     fn test_ticket_goal_id_roundtrip() {
         let conn = setup_in_memory_db();
@@ -2,11 +2,12 @@ use git2::{
     build::CheckoutBuilder, BranchType, Repository, StatusOptions, WorktreeAddOptions,
     WorktreePruneOptions,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use tauri::Emitter;
 use walkdir::WalkDir;
 
 #[derive(Debug, Serialize)]
@@ -20,8 +21,12 @@ pub struct GitFileStatus {
 #[derive(Debug, Serialize)]
 pub struct BranchInfo {
     name: String,
+    /// Commits on the branch its upstream does not have; 0 without an upstream.
     ahead: u32,
+    /// Commits on the upstream the branch does not have; 0 without an upstream.
     behind: u32,
+    /// Shorthand of the upstream (`origin/main`), `None` when none is set.
+    upstream: Option<String>,
 }
 
 /// One commit as the evidence engine reads history: what it touched is the
@@ -38,6 +43,51 @@ pub struct CommitInfo {
     pub touched: Vec<String>,
 }
 
+/// One page of history. Everything is optional: the default is the first
+/// page of HEAD's history.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GitLogQuery {
+    /// A revision (`main`), a range (`main..feature`) or a symmetric range
+    /// (`main...feature`). Defaults to HEAD.
+    pub range: Option<String>,
+    /// Only commits that changed this file, or anything under this directory.
+    pub path: Option<String>,
+    /// Case-insensitive substring of the author's name or email.
+    pub author: Option<String>,
+    /// Matching commits to skip before the page starts.
+    #[serde(default)]
+    pub offset: usize,
+    /// Page size; defaults to `GIT_LOG_PAGE_DEFAULT`, capped at `GIT_LOG_PAGE_MAX`.
+    pub limit: Option<usize>,
+}
+
+/// A commit as the history graph draws it.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitLogEntry {
+    pub oid: String,
+    /// First parent first, like git: the line the graph keeps straight.
+    pub parents: Vec<String>,
+    pub summary: String,
+    pub author: String,
+    pub author_email: String,
+    /// UTC, `YYYY-MM-DD HH:MM:SS`.
+    pub timestamp: String,
+    /// Shorthands of the branches, remote branches and tags pointing here.
+    pub refs: Vec<String>,
+    pub is_head: bool,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitLogPage {
+    pub commits: Vec<GitLogEntry>,
+    /// Another page may follow: a further match exists, or the scan stopped at
+    /// its cap before history ran out.
+    pub has_more: bool,
+}
+
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GitBranch {
@@ -109,11 +159,197 @@ pub struct WorktreeMergeResult {
     pub fast_forward: bool,
     pub cleaned_up: bool,
     pub oid: Option<String>,
+    /// Set when the merge stopped on conflicts: it is waiting in the worktree
+    /// for `git_worktree_resolve_conflict` and then finish or abort.
+    pub conflicts: Vec<GitMergeConflict>,
+}
+
+/// Where a worktree rebase onto the default branch stands.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeRebaseResult {
+    /// "up-to-date" | "fast-forward" | "rebased" | "conflict"
+    pub outcome: String,
+    pub default_branch: String,
+    /// The worktree branch tip afterwards; `None` while stopped on a conflict.
+    pub oid: Option<String>,
+    /// The commit that did not replay cleanly ("conflict" only).
+    pub conflict_commit: Option<GitRebaseCommit>,
+    pub conflicts: Vec<GitConflictFile>,
+    /// 1-based position of `conflict_commit` among the commits being
+    /// replayed, and how many there are. Both 0 when nothing was replayed.
+    pub step: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitRebaseCommit {
+    pub oid: String,
+    pub summary: String,
+}
+
+/// One conflicted path of a worktree merge and the three versions to choose
+/// from. "ours" is the default branch, "theirs" the worktree's branch. A side
+/// is `None` when it has no such file, and every side is `None` for a binary
+/// file.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitMergeConflict {
+    pub path: String,
+    /// Same vocabulary as `GitConflictFile::kind`.
+    pub kind: String,
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+    pub binary: bool,
+}
+
+/// How to settle one conflicted file. Taking a side that deleted the file
+/// deletes it.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum GitConflictResolution {
+    Ours,
+    Theirs,
+    Merged { content: String },
+}
+
+/// One path a pull, merge or rebase left conflicted in the index.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitConflictFile {
+    pub path: String,
+    /// "both-modified" | "both-added" | "deleted-by-us" | "deleted-by-them"
+    pub kind: String,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitFetchResult {
+    pub remote: String,
+    pub received_objects: usize,
+    pub received_bytes: usize,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitPullResult {
+    /// "up-to-date" | "fast-forward" | "rebased" | "diverged" | "conflict"
+    pub outcome: String,
+    /// HEAD after the pull; unchanged on "diverged" and "conflict".
+    pub oid: Option<String>,
+    /// Summary of the local commit that could not be replayed ("conflict" only).
+    pub conflict_commit: Option<String>,
+    pub conflicts: Vec<GitConflictFile>,
+}
+
+/// What to push and where. Every field is optional: the default is the
+/// current branch to its upstream remote (or `origin`), same name both ends.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GitPushOptions {
+    pub remote: Option<String>,
+    /// `src:dst`, `branch`, or `HEAD:dst`. A leading `+` forces blindly;
+    /// prefer `force_with_lease`.
+    pub refspec: Option<String>,
+    #[serde(default)]
+    pub force_with_lease: bool,
+    /// The remote OID the lease expects. Defaults to the local
+    /// remote-tracking ref, i.e. "what we last fetched", like git does.
+    pub expected_remote_oid: Option<String>,
+}
+
+/// One step of a running push, in the order libgit2 reports them.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum GitPushProgress {
+    #[serde(rename_all = "camelCase")]
+    Transfer {
+        current: usize,
+        total: usize,
+        bytes: usize,
+    },
+    /// The remote's verdict on one ref; `rejection` is its reason, `None`
+    /// when the ref was accepted.
+    #[serde(rename_all = "camelCase")]
+    RefUpdate {
+        refname: String,
+        rejection: Option<String>,
+    },
+}
+
+/// Payload of the `git-push-progress` event.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GitPushProgressEvent {
+    pub repo_path: String,
+    #[serde(flatten)]
+    pub progress: GitPushProgress,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitPushResult {
+    pub remote: String,
+    /// The refspec actually sent, fully qualified (`+` when leased).
+    pub refspec: String,
+    /// Refs the remote turned down. Non-empty means the push did not land,
+    /// even though the call itself succeeded.
+    pub rejected: Vec<GitPushRejection>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitPushRejection {
+    pub refname: String,
+    /// The remote's own words.
+    pub reason: String,
+}
+
+/// Part of one hunk of a file diff, addressed the way `git_diff_impl` prints
+/// it: `hunk` counts `@@` headers from zero, `lines` counts the body lines
+/// below that header from zero. No `lines` means the whole hunk. Context
+/// lines can be named but change nothing.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHunkSelection {
+    pub hunk: usize,
+    #[serde(default)]
+    pub lines: Option<Vec<usize>>,
+}
+
+/// One entry of `refs/stash` as a checkout sees it. `oid` is the handle the
+/// other stash commands take: stash indices shift whenever any worktree of
+/// the repo stashes or drops.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitStashEntry {
+    /// Position in `refs/stash` right now (`stash@{index}`).
+    pub index: usize,
+    pub oid: String,
+    pub message: String,
+    /// Branch the stash was made on; `None` for a detached HEAD.
+    pub branch: Option<String>,
+    /// UTC, `YYYY-MM-DD HH:MM:SS`.
+    pub timestamp: String,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitStashApplyResult {
+    /// Paths the stash conflicted on; they are left conflicted in the index.
+    pub conflicts: Vec<GitConflictFile>,
+    /// Whether the stash is gone: a pop that conflicts keeps it, like git.
+    pub dropped: bool,
 }
 
 const AURIC_WORKTREE_DIR_SUFFIX: &str = ".auric-wt";
 const AURIC_WORKTREE_BRANCH_PREFIX: &str = "auric/";
 const DEFAULT_BRANCH_CANDIDATES: [&str; 2] = ["main", "master"];
+/// Written into a worktree's git dir while a conflicted merge waits there:
+/// the default branch, then the two commits the merge commit will join.
+const AURIC_MERGE_STATE_FILE: &str = "AURIC_MERGE";
 
 /// How far below the project root discovery looks for a `.git`. Bounded so
 /// opening a huge unrelated folder as a project never turns into a full-disk
@@ -159,9 +395,85 @@ pub fn git_unstage(repo_path: &str, paths: Vec<String>) -> Result<(), String> {
     git_unstage_impl(repo_path, &paths)
 }
 
+/// Stages part of a file: `selections` address its `unstaged` diff.
+#[tauri::command]
+pub fn git_stage_hunks(
+    repo_path: &str,
+    file_path: &str,
+    selections: Vec<GitHunkSelection>,
+) -> Result<(), String> {
+    git_stage_hunks_impl(repo_path, file_path, &selections)
+}
+
+/// Unstages part of a file: `selections` address its `staged` diff.
+#[tauri::command]
+pub fn git_unstage_hunks(
+    repo_path: &str,
+    file_path: &str,
+    selections: Vec<GitHunkSelection>,
+) -> Result<(), String> {
+    git_unstage_hunks_impl(repo_path, file_path, &selections)
+}
+
+/// Throws away part of a file's workdir changes: `selections` address its
+/// `unstaged` diff.
+#[tauri::command]
+pub fn git_discard_hunks(
+    repo_path: &str,
+    file_path: &str,
+    selections: Vec<GitHunkSelection>,
+) -> Result<(), String> {
+    git_discard_hunks_impl(repo_path, file_path, &selections)
+}
+
+/// Stashes the workdir and index; untracked files too unless told otherwise.
+#[tauri::command]
+pub fn git_stash_save(
+    repo_path: &str,
+    message: Option<String>,
+    include_untracked: Option<bool>,
+) -> Result<String, String> {
+    git_stash_save_impl(
+        repo_path,
+        message.as_deref(),
+        include_untracked.unwrap_or(true),
+    )
+}
+
+#[tauri::command]
+pub fn git_stash_list(repo_path: &str) -> Result<Vec<GitStashEntry>, String> {
+    git_stash_list_impl(repo_path)
+}
+
+#[tauri::command]
+pub fn git_stash_show(repo_path: &str, stash_oid: &str) -> Result<String, String> {
+    git_stash_show_impl(repo_path, stash_oid)
+}
+
 #[tauri::command]
-pub fn git_commit(repo_path: &str, message: &str) -> Result<String, String> {
-    git_commit_impl(repo_path, message)
+pub fn git_stash_apply(repo_path: &str, stash_oid: &str) -> Result<GitStashApplyResult, String> {
+    git_stash_apply_impl(repo_path, stash_oid, false)
+}
+
+#[tauri::command]
+pub fn git_stash_pop(repo_path: &str, stash_oid: &str) -> Result<GitStashApplyResult, String> {
+    git_stash_apply_impl(repo_path, stash_oid, true)
+}
+
+#[tauri::command]
+pub fn git_stash_drop(repo_path: &str, stash_oid: &str) -> Result<(), String> {
+    git_stash_drop_impl(repo_path, stash_oid)
+}
+
+/// Async: with `commit.gpgsign` on, committing waits on the signing program,
+/// which may sit in a pinentry prompt for as long as the user takes.
+#[tauri::command(async)]
+pub fn git_commit(
+    repo_path: String,
+    message: String,
+    amend: Option<bool>,
+) -> Result<String, String> {
+    git_commit_with_impl(&repo_path, &message, amend.unwrap_or(false))
 }
 
 #[tauri::command]
@@ -169,9 +481,37 @@ pub fn git_discard(repo_path: &str, file_path: &str) -> Result<(), String> {
     git_discard_impl(repo_path, file_path)
 }
 
-#[tauri::command]
-pub fn git_push(repo_path: &str) -> Result<(), String> {
-    git_push_impl(repo_path)
+/// Pushes per `options` (default: the current branch to its upstream
+/// remote), streaming `git-push-progress` events while it runs.
+#[tauri::command(async)]
+pub fn git_push(
+    repo_path: String,
+    options: Option<GitPushOptions>,
+    app: tauri::AppHandle,
+) -> Result<GitPushResult, String> {
+    git_push_with_impl(&repo_path, &options.unwrap_or_default(), &mut |progress| {
+        let _ = app.emit(
+            "git-push-progress",
+            GitPushProgressEvent {
+                repo_path: repo_path.clone(),
+                progress,
+            },
+        );
+    })
+}
+
+/// Fetches `remote` (default: the current branch's upstream remote, else
+/// `origin`). Network-bound, so off the IPC thread.
+#[tauri::command(async)]
+pub fn git_fetch(repo_path: String, remote: Option<String>) -> Result<GitFetchResult, String> {
+    git_fetch_impl(&repo_path, remote.as_deref())
+}
+
+/// Fetches, then brings the current branch up to its upstream. `rebase`
+/// replays local commits on top; otherwise only a fast-forward is allowed.
+#[tauri::command(async)]
+pub fn git_pull(repo_path: String, rebase: Option<bool>) -> Result<GitPullResult, String> {
+    git_pull_impl(&repo_path, rebase.unwrap_or(false))
 }
 
 /// Commits actually walked before we give up, whether or not they matched.
@@ -200,11 +540,64 @@ pub fn git_log_since(
     )
 }
 
+const GIT_LOG_PAGE_DEFAULT: usize = 100;
+const GIT_LOG_PAGE_MAX: usize = 500;
+
+/// The per-page counterpart of `GIT_LOG_MAX_SCAN`. Larger, because a file
+/// history is expected to reach back past any evidence window, but still a
+/// bound: a path or author that matches nothing must not diff all of history
+/// for every page. Counted from where the page starts, past its `offset`.
+const GIT_LOG_PAGE_MAX_SCAN: usize = 20_000;
+
+// `async` for the same reason as `git_log_since`: a filtered page diffs
+// commits until it fills.
+#[tauri::command(async)]
+pub fn git_log(repo_path: String, query: Option<GitLogQuery>) -> Result<GitLogPage, String> {
+    git_log_impl(
+        &repo_path,
+        &query.unwrap_or_default(),
+        GIT_LOG_PAGE_MAX_SCAN,
+    )
+}
+
 #[tauri::command]
 pub fn git_list_branches(repo_path: &str) -> Result<Vec<GitBranch>, String> {
     git_list_branches_impl(repo_path)
 }
 
+#[tauri::command]
+pub fn git_branch_create(
+    repo_path: &str,
+    name: &str,
+    start_point: Option<String>,
+) -> Result<GitBranch, String> {
+    git_branch_create_impl(repo_path, name, start_point.as_deref())
+}
+
+#[tauri::command]
+pub fn git_branch_checkout(repo_path: &str, name: &str) -> Result<(), String> {
+    git_branch_checkout_impl(repo_path, name)
+}
+
+#[tauri::command]
+pub fn git_branch_rename(repo_path: &str, name: &str, new_name: &str) -> Result<(), String> {
+    git_branch_rename_impl(repo_path, name, new_name)
+}
+
+#[tauri::command]
+pub fn git_branch_delete(repo_path: &str, name: &str, force: Option<bool>) -> Result<(), String> {
+    git_branch_delete_impl(repo_path, name, force.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn git_branch_set_upstream(
+    repo_path: &str,
+    name: &str,
+    upstream: Option<String>,
+) -> Result<(), String> {
+    git_branch_set_upstream_impl(repo_path, name, upstream.as_deref())
+}
+
 #[tauri::command(async)]
 pub fn git_blame(repo_path: String, file_path: String) -> Result<Vec<BlameHunk>, String> {
     git_blame_impl(&repo_path, &file_path)
@@ -275,6 +668,74 @@ pub fn git_worktree_merge_into_default(
     git_worktree_merge_into_default_impl(&repo_path, &worktree_path, commit_message.as_deref())
 }
 
+/// The conflicts of the merge waiting in `worktree_path`, re-read from disk.
+#[tauri::command(async)]
+pub fn git_worktree_merge_conflicts(
+    worktree_path: String,
+) -> Result<Vec<GitMergeConflict>, String> {
+    git_worktree_merge_conflicts_impl(&worktree_path)
+}
+
+/// Settles one conflicted file; answers with the conflicts still open.
+#[tauri::command(async)]
+pub fn git_worktree_resolve_conflict(
+    worktree_path: String,
+    file_path: String,
+    resolution: GitConflictResolution,
+) -> Result<Vec<GitMergeConflict>, String> {
+    git_worktree_resolve_conflict_impl(&worktree_path, &file_path, &resolution)
+}
+
+/// Commits a fully resolved worktree merge onto main/master, then removes the
+/// worktree.
+#[tauri::command(async)]
+pub fn git_worktree_merge_finish(
+    repo_path: String,
+    worktree_path: String,
+) -> Result<WorktreeMergeResult, String> {
+    git_worktree_merge_finish_impl(&repo_path, &worktree_path)
+}
+
+/// Drops a waiting worktree merge; the worktree goes back to its branch tip.
+#[tauri::command(async)]
+pub fn git_worktree_merge_abort(worktree_path: String) -> Result<(), String> {
+    git_worktree_merge_abort_impl(&worktree_path)
+}
+
+/// Replays the worktree's `auric/…` branch onto the latest main/master. Stops
+/// on the first conflicting commit, leaving the rebase in the worktree for
+/// continue, skip or abort.
+#[tauri::command(async)]
+pub fn git_worktree_rebase_onto_default(
+    repo_path: String,
+    worktree_path: String,
+) -> Result<WorktreeRebaseResult, String> {
+    git_worktree_rebase_onto_default_impl(&repo_path, &worktree_path)
+}
+
+/// Commits the resolved commit of a stopped worktree rebase and replays the rest.
+#[tauri::command(async)]
+pub fn git_worktree_rebase_continue(
+    repo_path: String,
+    worktree_path: String,
+) -> Result<WorktreeRebaseResult, String> {
+    git_worktree_rebase_continue_impl(&repo_path, &worktree_path)
+}
+
+/// Drops the conflicting commit of a stopped worktree rebase and replays the rest.
+#[tauri::command(async)]
+pub fn git_worktree_rebase_skip(
+    repo_path: String,
+    worktree_path: String,
+) -> Result<WorktreeRebaseResult, String> {
+    git_worktree_rebase_skip_impl(&repo_path, &worktree_path)
+}
+
+#[tauri::command(async)]
+pub fn git_worktree_rebase_abort(worktree_path: String) -> Result<(), String> {
+    git_worktree_rebase_abort_impl(&worktree_path)
+}
+
 /// Walks history from HEAD, newest first, stopping below `since_iso`, at
 /// `limit` matches, or after `max_scan` commits visited. `path_prefix` keeps
 /// only commits touching that prefix. Not a repo is an empty answer, not an
@@ -457,6 +918,7 @@ pub fn git_branch_info_impl(repo_path: &str) -> Result<BranchInfo, String> {
                 name: "-".to_string(),
                 ahead: 0,
                 behind: 0,
+                upstream: None,
             })
         }
     };
@@ -468,15 +930,37 @@ pub fn git_branch_info_impl(repo_path: &str) -> Result<BranchInfo, String> {
                 name: "no head".to_string(),
                 ahead: 0,
                 behind: 0,
+                upstream: None,
             })
         }
     };
     let name = head.shorthand().unwrap_or("HEAD").to_string();
 
+    // Counted against whatever was last fetched — this never touches the
+    // network, so the status bar stays instant and `git_fetch` refreshes it.
+    let mut ahead = 0;
+    let mut behind = 0;
+    let mut upstream_name = None;
+    if head.is_branch() {
+        if let Ok(upstream) = repo
+            .find_branch(&name, BranchType::Local)
+            .and_then(|b| b.upstream())
+        {
+            upstream_name = upstream.name().ok().flatten().map(|n| n.to_string());
+            if let (Some(local), Some(remote)) = (head.target(), upstream.get().target()) {
+                if let Ok((a, b)) = repo.graph_ahead_behind(local, remote) {
+                    ahead = a as u32;
+                    behind = b as u32;
+                }
+            }
+        }
+    }
+
     Ok(BranchInfo {
         name,
-        ahead: 0,
-        behind: 0,
+        ahead,
+        behind,
+        upstream: upstream_name,
     })
 }
 
@@ -522,63 +1006,964 @@ pub fn git_unstage_impl(repo_path: &str, paths: &[String]) -> Result<(), String>
     Ok(())
 }
 
-pub fn git_commit_impl(repo_path: &str, message: &str) -> Result<String, String> {
-    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+/// One printed diff line: its origin (`+`, `-` or ` `) and where it sits in
+/// the old and new text (1-based, as libgit2 numbers them).
+struct HunkLine {
+    origin: char,
+    old_lineno: Option<u32>,
+    new_lineno: Option<u32>,
+    content: Vec<u8>,
+    /// Followed by a "no newline at end of file" marker.
+    no_newline: bool,
+}
 
-    let mut index = repo
-        .index()
-        .map_err(|e| format!("Failed to get index: {}", e))?;
+struct Hunk {
+    old_start: u32,
+    old_lines: u32,
+    lines: Vec<HunkLine>,
+}
 
-    let tree_oid = index
-        .write_tree()
-        .map_err(|e| format!("Failed to write tree: {}", e))?;
+/// The hunks between two versions of a file, with the same default options
+/// `git_diff_impl` uses, so hunk and line indices match what the user saw.
+/// A missing side is an empty file, which is what the synthetic added and
+/// deleted patches show as one all-`+` or all-`-` hunk.
+fn text_hunks(old: &[u8], new: &[u8], file_path: &str) -> Result<Vec<Hunk>, String> {
+    let patch = git2::Patch::from_buffers(old, None, new, None, None)
+        .map_err(|e| format!("Failed to diff {}: {}", file_path, e))?;
+    patch_hunks(&patch, file_path)
+}
 
-    if let Some(head_tree) = repo.head().ok().and_then(|h| h.peel_to_tree().ok()) {
-        if head_tree.id() == tree_oid {
-            return Err("Nothing to commit".to_string());
+fn patch_hunks(patch: &git2::Patch, file_path: &str) -> Result<Vec<Hunk>, String> {
+    if patch.delta().flags().is_binary() {
+        return Err(format!(
+            "{} is binary — stage it as a whole file",
+            file_path
+        ));
+    }
+    let mut hunks = Vec::new();
+    for h in 0..patch.num_hunks() {
+        let (hunk, _) = patch
+            .hunk(h)
+            .map_err(|e| format!("Failed to read hunk: {}", e))?;
+        let mut lines = Vec::new();
+        for l in 0..patch
+            .num_lines_in_hunk(h)
+            .map_err(|e| format!("Failed to read hunk: {}", e))?
+        {
+            let line = patch
+                .line_in_hunk(h, l)
+                .map_err(|e| format!("Failed to read hunk line: {}", e))?;
+            // `print_diff` drops the "no newline at end of file" markers, so
+            // they must not take up an index here either.
+            match line.origin() {
+                '+' | '-' | ' ' => lines.push(HunkLine {
+                    origin: line.origin(),
+                    old_lineno: line.old_lineno(),
+                    new_lineno: line.new_lineno(),
+                    content: line.content().to_vec(),
+                    no_newline: false,
+                }),
+                _ => {
+                    if let Some(last) = lines.last_mut() {
+                        last.no_newline = true;
+                    }
+                }
+            }
         }
+        hunks.push(Hunk {
+            old_start: hunk.old_start(),
+            old_lines: hunk.old_lines(),
+            lines,
+        });
     }
+    Ok(hunks)
+}
 
-    let tree = repo
-        .find_tree(tree_oid)
-        .map_err(|e| format!("Failed to find tree: {}", e))?;
-
-    let sig = repo.signature().map_err(|e| {
-        format!(
-            "Failed to get git signature: {}. Please configure git user.name and user.email.",
-            e
-        )
-    })?;
-
-    let parent_commit = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
-    let parents: Vec<&git2::Commit> = parent_commit.iter().collect();
-
-    let oid = repo
-        .commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
-        .map_err(|e| format!("Failed to commit: {}", e))?;
-
-    Ok(oid.to_string())
+/// `selected[h][l]` for every printed line, after checking the selections
+/// all point at something that exists.
+fn selection_mask(
+    hunks: &[Hunk],
+    selections: &[GitHunkSelection],
+) -> Result<Vec<Vec<bool>>, String> {
+    let mut mask: Vec<Vec<bool>> = hunks.iter().map(|h| vec![false; h.lines.len()]).collect();
+    for selection in selections {
+        let hunk = mask.get_mut(selection.hunk).ok_or_else(|| {
+            format!(
+                "Hunk {} is out of range — the diff has {} hunks; reload it and try again",
+                selection.hunk,
+                hunks.len()
+            )
+        })?;
+        match &selection.lines {
+            None => hunk.iter_mut().for_each(|line| *line = true),
+            Some(lines) => {
+                for &l in lines {
+                    let len = hunk.len();
+                    *hunk.get_mut(l).ok_or_else(|| {
+                        format!(
+                            "Line {} is out of range for hunk {} ({} lines)",
+                            l, selection.hunk, len
+                        )
+                    })? = true;
+                }
+            }
+        }
+    }
+    Ok(mask)
 }
 
-/// Pushes the current branch to `origin`, trying the SSH agent, the default
-/// key files and the configured credential helper in that order. Sets the
-/// upstream on first push so later pushes (and the branch display) know
-/// where home is.
-pub fn git_push_impl(repo_path: &str) -> Result<(), String> {
-    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
-    let head = repo
-        .head()
-        .map_err(|e| format!("Failed to read HEAD: {}", e))?;
-    let branch_name = head
-        .shorthand()
-        .filter(|_| head.is_branch())
-        .ok_or_else(|| "Detached HEAD — check out a branch before pushing".to_string())?
-        .to_string();
+/// `base` with the changes towards `target` for which `take` holds applied,
+/// and the rest left out. `None` is "no file": taking every change of a diff
+/// against a missing side ends up missing too, rather than empty.
+fn apply_hunk_lines(
+    base: Option<&[u8]>,
+    target: Option<&[u8]>,
+    hunks: &[Hunk],
+    take: &dyn Fn(usize, usize) -> bool,
+) -> Option<Vec<u8>> {
+    let mut changes = 0;
+    let mut taken = 0;
+    for (h, hunk) in hunks.iter().enumerate() {
+        for (l, line) in hunk.lines.iter().enumerate() {
+            if line.origin != ' ' {
+                changes += 1;
+                if take(h, l) {
+                    taken += 1;
+                }
+            }
+        }
+    }
+    if taken == 0 {
+        return base.map(|b| b.to_vec());
+    }
+    if taken == changes {
+        return target.map(|t| t.to_vec());
+    }
 
-    let mut remote = repo
-        .find_remote("origin")
-        .map_err(|_| "No 'origin' remote configured for this repository".to_string())?;
+    let old: Vec<&[u8]> = base
+        .unwrap_or_default()
+        .split_inclusive(|&b| b == b'\n')
+        .collect();
+    let new: Vec<&[u8]> = target
+        .unwrap_or_default()
+        .split_inclusive(|&b| b == b'\n')
+        .collect();
+    let mut out = Vec::new();
+    // A kept last line without a newline may no longer be last: give it one
+    // before anything lands behind it.
+    let push = |out: &mut Vec<u8>, line: &[u8]| {
+        if out.last().is_some_and(|&b| b != b'\n') {
+            out.push(b'\n');
+        }
+        out.extend_from_slice(line);
+    };
+    // Next old line (0-based) not yet copied.
+    let mut cursor = 0usize;
+    let copy_until = |out: &mut Vec<u8>, cursor: &mut usize, end: usize| {
+        while *cursor < end.min(old.len()) {
+            push(out, old[*cursor]);
+            *cursor += 1;
+        }
+    };
+
+    for (h, hunk) in hunks.iter().enumerate() {
+        // A hunk that removes nothing inserts after `old_start`; any other
+        // hunk starts at it.
+        let start = if hunk.old_lines == 0 {
+            hunk.old_start as usize
+        } else {
+            hunk.old_start.saturating_sub(1) as usize
+        };
+        copy_until(&mut out, &mut cursor, start);
+        for (l, line) in hunk.lines.iter().enumerate() {
+            match line.origin {
+                ' ' | '-' => {
+                    let Some(n) = line.old_lineno.map(|n| n as usize) else {
+                        continue;
+                    };
+                    copy_until(&mut out, &mut cursor, n - 1);
+                    cursor = cursor.max(n);
+                    let keep = line.origin == ' ' || !take(h, l);
+                    if let (true, Some(text)) = (keep, old.get(n - 1)) {
+                        push(&mut out, text);
+                    }
+                }
+                _ => {
+                    if take(h, l) {
+                        if let Some(text) = line.new_lineno.and_then(|n| new.get(n as usize - 1)) {
+                            push(&mut out, text);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    copy_until(&mut out, &mut cursor, old.len());
+    Some(out)
+}
+
+fn blob_bytes_in_index(repo: &Repository, index: &git2::Index, file_path: &str) -> Option<Vec<u8>> {
+    let entry = index.get_path(Path::new(file_path), 0)?;
+    repo.find_blob(entry.id).ok().map(|b| b.content().to_vec())
+}
+
+fn blob_bytes_at_head(repo: &Repository, file_path: &str) -> Option<Vec<u8>> {
+    let tree = repo.head().ok()?.peel_to_tree().ok()?;
+    let entry = tree.get_path(Path::new(file_path)).ok()?;
+    repo.find_blob(entry.id())
+        .ok()
+        .map(|b| b.content().to_vec())
+}
+
+/// The index, refusing files that are mid-merge: their stages are not one
+/// blob a hunk could be applied to.
+fn index_without_conflict(repo: &Repository, file_path: &str) -> Result<git2::Index, String> {
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    if (1..=3).any(|stage| index.get_path(Path::new(file_path), stage).is_some()) {
+        return Err(format!(
+            "{} has merge conflicts — resolve them first",
+            file_path
+        ));
+    }
+    Ok(index)
+}
+
+/// Points the index entry for `file_path` at `content`, or drops it for
+/// `None`. An entry that did not exist takes its mode from HEAD, then from
+/// the workdir file.
+fn write_index_content(
+    repo: &Repository,
+    index: &mut git2::Index,
+    file_path: &str,
+    content: Option<&[u8]>,
+) -> Result<(), String> {
+    let Some(content) = content else {
+        if index.get_path(Path::new(file_path), 0).is_some() {
+            index
+                .remove_path(Path::new(file_path))
+                .map_err(|e| format!("Failed to update index for {}: {}", file_path, e))?;
+        }
+        return index
+            .write()
+            .map_err(|e| format!("Failed to write index: {}", e));
+    };
+
+    let mode = match index.get_path(Path::new(file_path), 0) {
+        Some(entry) => entry.mode,
+        None => repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_tree().ok())
+            .and_then(|tree| tree.get_path(Path::new(file_path)).ok())
+            .map(|entry| entry.filemode() as u32)
+            .unwrap_or_else(|| workdir_file_mode(repo, file_path)),
+    };
+    // Zeroed stat data: the entry no longer matches the file on disk, and
+    // libgit2 must re-hash the workdir instead of trusting a stale stat.
+    let entry = git2::IndexEntry {
+        ctime: git2::IndexTime::new(0, 0),
+        mtime: git2::IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode,
+        uid: 0,
+        gid: 0,
+        file_size: content.len() as u32,
+        id: git2::Oid::zero(),
+        flags: 0,
+        flags_extended: 0,
+        path: file_path.as_bytes().to_vec(),
+    };
+    index
+        .add_frombuffer(&entry, content)
+        .map_err(|e| format!("Failed to update index for {}: {}", file_path, e))?;
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))
+}
+
+fn workdir_file_mode(repo: &Repository, file_path: &str) -> u32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let executable = repo
+            .workdir()
+            .and_then(|dir| fs::metadata(dir.join(file_path)).ok())
+            .is_some_and(|meta| meta.permissions().mode() & 0o111 != 0);
+        if executable {
+            return 0o100755;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (repo, file_path);
+    0o100644
+}
+
+fn workdir_bytes(repo: &Repository, file_path: &str) -> Result<Option<Vec<u8>>, String> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| "Cannot stage in a bare repository".to_string())?;
+    let full_path = workdir.join(file_path);
+    if !full_path.exists() {
+        return Ok(None);
+    }
+    fs::read(&full_path)
+        .map(Some)
+        .map_err(|e| format!("Failed to read {}: {}", file_path, e))
+}
+
+/// Stages the selected lines of the `unstaged` diff of `file_path` — index to
+/// workdir, or the synthetic added patch for an untracked file — and leaves
+/// the rest of the workdir change unstaged. The hunks come from the same
+/// `diff_index_to_workdir` patch `git_diff_impl` prints, after the clean
+/// filters, and go in like `git apply --cached`: a CRLF or LFS file stages
+/// what the diff showed, not its raw workdir bytes.
+pub fn git_stage_hunks_impl(
+    repo_path: &str,
+    file_path: &str,
+    selections: &[GitHunkSelection],
+) -> Result<(), String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    index_without_conflict(&repo, file_path)?;
+
+    let mut diff_opts = git2::DiffOptions::new();
+    diff_opts
+        .pathspec(file_path)
+        .disable_pathspec_match(true)
+        .include_untracked(true)
+        .show_untracked_content(true);
+    let diff = repo
+        .diff_index_to_workdir(None, Some(&mut diff_opts))
+        .map_err(|e| format!("Failed to generate diff: {}", e))?;
+    let Some(delta) = diff.get_delta(0) else {
+        return Ok(());
+    };
+    let patch = git2::Patch::from_diff(&diff, 0)
+        .map_err(|e| format!("Failed to diff {}: {}", file_path, e))?;
+    let hunks = match &patch {
+        Some(patch) => patch_hunks(patch, file_path)?,
+        None if delta.flags().is_binary() => {
+            return Err(format!(
+                "{} is binary — stage it as a whole file",
+                file_path
+            ))
+        }
+        None => Vec::new(),
+    };
+    // An empty new file, or a mode change: no line to pick, only the file.
+    if hunks.is_empty() {
+        return git_stage_impl(repo_path, &[file_path.to_string()]);
+    }
+    let mask = selection_mask(&hunks, selections)?;
+    if mask.iter().flatten().all(|&taken| taken) {
+        return git_stage_impl(repo_path, &[file_path.to_string()]);
+    }
+
+    let new_mode = match delta.status() {
+        git2::Delta::Added | git2::Delta::Untracked => Some(u32::from(delta.new_file().mode())),
+        _ => None,
+    };
+    let Some(patch) = selected_patch(file_path, new_mode, &hunks, &mask) else {
+        return Ok(());
+    };
+    let diff = git2::Diff::from_buffer(&patch)
+        .map_err(|e| format!("Failed to stage {}: {}", file_path, e))?;
+    repo.apply(&diff, git2::ApplyLocation::Index, None)
+        .map_err(|e| format!("Failed to stage {}: {}", file_path, e))
+}
+
+/// A patch of `file_path` holding only the selected lines of `hunks`, the way
+/// `git add -p` edits a hunk: an addition left out is dropped, a removal left
+/// out stays as context. `new_mode` makes it a patch that creates the file.
+/// `None` when no change is selected.
+fn selected_patch(
+    file_path: &str,
+    new_mode: Option<u32>,
+    hunks: &[Hunk],
+    mask: &[Vec<bool>],
+) -> Option<Vec<u8>> {
+    let mut body = Vec::new();
+    // How far the new side has drifted from the old one so far.
+    let mut shift = 0i64;
+    for (hunk, selected) in hunks.iter().zip(mask) {
+        let picked = |l: usize| selected[l] && hunk.lines[l].origin != ' ';
+        if !(0..hunk.lines.len()).any(picked) {
+            continue;
+        }
+        let mut lines = Vec::new();
+        let (mut old_count, mut new_count) = (0i64, 0i64);
+        for (l, line) in hunk.lines.iter().enumerate() {
+            let origin = match line.origin {
+                '+' if !picked(l) => continue,
+                '-' if !picked(l) => ' ',
+                origin => origin,
+            };
+            if origin != '+' {
+                old_count += 1;
+            }
+            if origin != '-' {
+                new_count += 1;
+            }
+            lines.push(origin as u8);
+            lines.extend_from_slice(&line.content);
+            if line.no_newline {
+                lines.extend_from_slice(b"\n\\ No newline at end of file\n");
+            }
+        }
+        // An empty side is numbered from the line before it, as git does.
+        let old_start = hunk.old_start as i64;
+        let new_start = old_start
+            + shift
+            + match (old_count, new_count) {
+                (0, _) => 1,
+                (_, 0) => -1,
+                _ => 0,
+            };
+        shift += new_count - old_count;
+        body.extend_from_slice(
+            format!("@@ -{old_start},{old_count} +{new_start},{new_count} @@\n").as_bytes(),
+        );
+        body.extend_from_slice(&lines);
+    }
+    if body.is_empty() {
+        return None;
+    }
+    let mut patch = format!("diff --git a/{file_path} b/{file_path}\n");
+    match new_mode {
+        Some(mode) => patch.push_str(&format!(
+            "new file mode {mode:o}\n--- /dev/null\n+++ b/{file_path}\n"
+        )),
+        None => patch.push_str(&format!("--- a/{file_path}\n+++ b/{file_path}\n")),
+    }
+    let mut patch = patch.into_bytes();
+    patch.extend_from_slice(&body);
+    Some(patch)
+}
+
+/// Unstages the selected lines of the `staged` diff of `file_path` (HEAD to
+/// index). The workdir is not touched, so they reappear as unstaged changes.
+pub fn git_unstage_hunks_impl(
+    repo_path: &str,
+    file_path: &str,
+    selections: &[GitHunkSelection],
+) -> Result<(), String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let mut index = index_without_conflict(&repo, file_path)?;
+    let base = blob_bytes_at_head(&repo, file_path);
+    let target = blob_bytes_in_index(&repo, &index, file_path);
+
+    let hunks = text_hunks(
+        base.as_deref().unwrap_or_default(),
+        target.as_deref().unwrap_or_default(),
+        file_path,
+    )?;
+    let mask = selection_mask(&hunks, selections)?;
+    let staged = apply_hunk_lines(base.as_deref(), target.as_deref(), &hunks, &|h, l| {
+        !mask[h][l]
+    });
+    write_index_content(&repo, &mut index, file_path, staged.as_deref())
+}
+
+/// Reverts the selected lines of the `unstaged` diff of `file_path` in the
+/// workdir, back to what the index holds. Reverting every line of an
+/// untracked file deletes it, like `git_discard_impl` does.
+pub fn git_discard_hunks_impl(
+    repo_path: &str,
+    file_path: &str,
+    selections: &[GitHunkSelection],
+) -> Result<(), String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let index = index_without_conflict(&repo, file_path)?;
+    let base = blob_bytes_in_index(&repo, &index, file_path);
+    let target = workdir_bytes(&repo, file_path)?;
+
+    let hunks = text_hunks(
+        base.as_deref().unwrap_or_default(),
+        target.as_deref().unwrap_or_default(),
+        file_path,
+    )?;
+    let mask = selection_mask(&hunks, selections)?;
+    let kept = apply_hunk_lines(base.as_deref(), target.as_deref(), &hunks, &|h, l| {
+        !mask[h][l]
+    });
+
+    let full_path = Path::new(repo_path).join(file_path);
+    match kept {
+        Some(content) => {
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to restore {}: {}", file_path, e))?;
+            }
+            fs::write(&full_path, content)
+                .map_err(|e| format!("Failed to write {}: {}", file_path, e))
+        }
+        None if full_path.exists() => fs::remove_file(&full_path)
+            .map_err(|e| format!("Failed to delete {}: {}", file_path, e)),
+        None => Ok(()),
+    }
+}
+
+/// The branch a stash was made on, read back from the message libgit2 writes:
+/// `WIP on <branch>: <sha> <summary>` or `On <branch>: <message>`.
+fn stash_branch(message: &str) -> Option<String> {
+    let rest = message
+        .strip_prefix("WIP on ")
+        .or_else(|| message.strip_prefix("On "))?;
+    let (branch, _) = rest.split_once(": ")?;
+    (branch != "(no branch)").then(|| branch.to_string())
+}
+
+/// `refs/stash` is shared by every worktree of a repository, so each checkout
+/// gets its own slice of it: an Auric worktree sees only the stashes made on
+/// its `auric/…` branch, any other checkout sees everything but those. An
+/// agent's parked changes never show up in the user's list, nor the other way
+/// round.
+fn stash_in_scope(current_branch: Option<&str>, stash_branch: Option<&str>) -> bool {
+    if branch_is_auric(current_branch) {
+        stash_branch == current_branch
+    } else {
+        !branch_is_auric(stash_branch)
+    }
+}
+
+fn current_branch(repo: &Repository) -> Option<String> {
+    let head = repo.head().ok()?;
+    head.is_branch()
+        .then(|| head.shorthand().map(|s| s.to_string()))
+        .flatten()
+}
+
+fn stash_entries(repo: &mut Repository) -> Result<Vec<GitStashEntry>, String> {
+    let mut raw = Vec::new();
+    repo.stash_foreach(|index, message, oid| {
+        raw.push((index, message.to_string(), *oid));
+        true
+    })
+    .map_err(|e| format!("Failed to read stashes: {}", e))?;
+
+    let current = current_branch(repo);
+    Ok(raw
+        .into_iter()
+        .filter_map(|(index, message, oid)| {
+            let branch = stash_branch(&message);
+            if !stash_in_scope(current.as_deref(), branch.as_deref()) {
+                return None;
+            }
+            let timestamp = repo
+                .find_commit(oid)
+                .ok()
+                .and_then(|c| chrono::DateTime::from_timestamp(c.time().seconds(), 0))
+                .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default();
+            Some(GitStashEntry {
+                index,
+                oid: oid.to_string(),
+                message: message.trim_end().to_string(),
+                branch,
+                timestamp,
+            })
+        })
+        .collect())
+}
+
+/// The current index of the stash `stash_oid`, if this checkout can see it.
+fn find_stash(repo: &mut Repository, stash_oid: &str) -> Result<GitStashEntry, String> {
+    stash_entries(repo)?
+        .into_iter()
+        .find(|entry| entry.oid == stash_oid)
+        .ok_or_else(|| format!("Stash {} not found — it may have been dropped", stash_oid))
+}
+
+/// Stashes local changes and returns the new stash's oid.
+pub fn git_stash_save_impl(
+    repo_path: &str,
+    message: Option<&str>,
+    include_untracked: bool,
+) -> Result<String, String> {
+    let mut repo =
+        Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let sig = git_signature(&repo)?;
+    let flags = include_untracked.then_some(git2::StashFlags::INCLUDE_UNTRACKED);
+    let message = message.map(str::trim).filter(|m| !m.is_empty());
+    match repo.stash_save2(&sig, message, flags) {
+        Ok(oid) => Ok(oid.to_string()),
+        Err(e) if e.code() == git2::ErrorCode::NotFound => Err("Nothing to stash".to_string()),
+        Err(e) => Err(format!("Failed to stash: {}", e)),
+    }
+}
+
+/// Stashes visible from this checkout, newest first (see `stash_in_scope`).
+pub fn git_stash_list_impl(repo_path: &str) -> Result<Vec<GitStashEntry>, String> {
+    let mut repo = match Repository::open(repo_path) {
+        Ok(r) => r,
+        Err(_) => return Ok(Vec::new()),
+    };
+    stash_entries(&mut repo)
+}
+
+/// The stash as a patch against the commit it was made on, followed by its
+/// untracked files as added.
+pub fn git_stash_show_impl(repo_path: &str, stash_oid: &str) -> Result<String, String> {
+    let mut repo =
+        Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let entry = find_stash(&mut repo, stash_oid)?;
+    let oid = git2::Oid::from_str(&entry.oid).map_err(|e| e.to_string())?;
+    let stash = repo
+        .find_commit(oid)
+        .map_err(|e| format!("Failed to read stash: {}", e))?;
+    let base_tree = stash
+        .parent(0)
+        .and_then(|p| p.tree())
+        .map_err(|e| format!("Failed to read stash base: {}", e))?;
+    let stash_tree = stash
+        .tree()
+        .map_err(|e| format!("Failed to read stash tree: {}", e))?;
+
+    let diff = repo
+        .diff_tree_to_tree(Some(&base_tree), Some(&stash_tree), None)
+        .map_err(|e| format!("Failed to generate diff: {}", e))?;
+    let mut text = print_diff(&diff)?;
+    // The third parent, when present, holds the untracked files.
+    if let Ok(untracked) = stash.parent(2).and_then(|p| p.tree()) {
+        let diff = repo
+            .diff_tree_to_tree(None, Some(&untracked), None)
+            .map_err(|e| format!("Failed to generate diff: {}", e))?;
+        text.push_str(&print_diff(&diff)?);
+    }
+    Ok(text)
+}
+
+/// Applies a stash onto the checkout, dropping it afterwards for a `pop`.
+/// libgit2 refuses up front when a file the stash touches has local changes;
+/// a stash that conflicts with the commit it lands on is applied with the
+/// conflicts left in the index, and a popped one is kept so nothing is lost.
+pub fn git_stash_apply_impl(
+    repo_path: &str,
+    stash_oid: &str,
+    pop: bool,
+) -> Result<GitStashApplyResult, String> {
+    let mut repo =
+        Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let entry = find_stash(&mut repo, stash_oid)?;
+
+    let mut options = git2::StashApplyOptions::new();
+    repo.stash_apply(entry.index, Some(&mut options))
+        .map_err(|e| match e.code() {
+            git2::ErrorCode::Conflict => {
+                "Applying the stash would overwrite local changes — commit or stash them first"
+                    .to_string()
+            }
+            _ => format!("Failed to apply stash: {}", e),
+        })?;
+
+    let conflicts = repo
+        .index()
+        .map(|index| conflict_files(&index))
+        .unwrap_or_default();
+    let dropped = pop && conflicts.is_empty();
+    if dropped {
+        repo.stash_drop(entry.index)
+            .map_err(|e| format!("Applied the stash but could not drop it: {}", e))?;
+    }
+    Ok(GitStashApplyResult { conflicts, dropped })
+}
+
+pub fn git_stash_drop_impl(repo_path: &str, stash_oid: &str) -> Result<(), String> {
+    let mut repo =
+        Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let entry = find_stash(&mut repo, stash_oid)?;
+    repo.stash_drop(entry.index)
+        .map_err(|e| format!("Failed to drop stash: {}", e))
+}
+
+pub fn git_commit_impl(repo_path: &str, message: &str) -> Result<String, String> {
+    git_commit_with_impl(repo_path, message, false)
+}
+
+/// Commits the index onto HEAD, or with `amend` rewrites HEAD in place: same
+/// parents and author, the current index, and `message` (the old one when
+/// blank). Signed whenever `commit.gpgsign` says so.
+pub fn git_commit_with_impl(repo_path: &str, message: &str, amend: bool) -> Result<String, String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+
+    let tree_oid = index
+        .write_tree()
+        .map_err(|e| format!("Failed to write tree: {}", e))?;
+
+    let head_commit = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    if amend && head_commit.is_none() {
+        return Err("Nothing to amend: there is no commit yet".to_string());
+    }
+    if !amend {
+        if let Some(head) = &head_commit {
+            if head.tree_id() == tree_oid {
+                return Err("Nothing to commit".to_string());
+            }
+        }
+    }
+
+    let tree = repo
+        .find_tree(tree_oid)
+        .map_err(|e| format!("Failed to find tree: {}", e))?;
+
+    let sig = git_signature(&repo)?;
+
+    let (author, message, parents): (_, _, Vec<git2::Commit>) = match (&head_commit, amend) {
+        (Some(head), true) => {
+            let message = if message.trim().is_empty() {
+                head.message().unwrap_or("").to_string()
+            } else {
+                message.to_string()
+            };
+            (head.author().to_owned(), message, head.parents().collect())
+        }
+        _ => (
+            sig.clone(),
+            message.to_string(),
+            head_commit.iter().cloned().collect(),
+        ),
+    };
+    let parents: Vec<&git2::Commit> = parents.iter().collect();
+
+    let oid = write_commit(&repo, &author, &sig, &message, &tree, &parents)?;
+
+    let summary = message.lines().next().unwrap_or("");
+    let reflog = match (amend, parents.is_empty()) {
+        (true, _) => format!("commit (amend): {summary}"),
+        (false, true) => format!("commit (initial): {summary}"),
+        (false, false) => format!("commit: {summary}"),
+    };
+    move_head_to(&repo, oid, &reflog)?;
+
+    Ok(oid.to_string())
+}
+
+/// Points HEAD — through its branch when it has one, including an unborn
+/// branch — at `oid`, the way `repo.commit(Some("HEAD"), …)` would.
+fn move_head_to(repo: &Repository, oid: git2::Oid, reflog: &str) -> Result<(), String> {
+    let head = repo
+        .find_reference("HEAD")
+        .map_err(|e| format!("Failed to read HEAD: {}", e))?;
+    match head.symbolic_target() {
+        Some(branch) => repo.reference(branch, oid, true, reflog).map(|_| ()),
+        None => repo.set_head_detached(oid),
+    }
+    .map_err(|e| format!("Failed to update HEAD: {}", e))
+}
+
+/// Writes a commit object without moving any ref, signed whenever
+/// `commit.gpgsign` says so. Every commit Auric creates goes through here.
+fn write_commit(
+    repo: &Repository,
+    author: &git2::Signature,
+    committer: &git2::Signature,
+    message: &str,
+    tree: &git2::Tree,
+    parents: &[&git2::Commit],
+) -> Result<git2::Oid, String> {
+    match commit_signing(repo)? {
+        Some(signing) => {
+            write_signed_commit(repo, &signing, author, committer, message, tree, parents)
+        }
+        None => repo
+            .commit(None, author, committer, message, tree, parents)
+            .map_err(|e| format!("Failed to commit: {}", e)),
+    }
+}
+
+fn write_signed_commit(
+    repo: &Repository,
+    signing: &CommitSigning,
+    author: &git2::Signature,
+    committer: &git2::Signature,
+    message: &str,
+    tree: &git2::Tree,
+    parents: &[&git2::Commit],
+) -> Result<git2::Oid, String> {
+    let buffer = repo
+        .commit_create_buffer(author, committer, message, tree, parents)
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    let buffer = std::str::from_utf8(&buffer)
+        .map_err(|_| "Failed to commit: commit buffer is not UTF-8".to_string())?;
+    let signature = sign_commit_buffer(signing, buffer)?;
+    repo.commit_signed(buffer, &signature, None)
+        .map_err(|e| format!("Failed to commit: {}", e))
+}
+
+/// Swaps the commit a rebase step just wrote for a signed copy, when
+/// `signing` is set. libgit2 signs rebase commits only through a callback
+/// git2 does not expose, so the step commits unsigned and this moves the
+/// detached HEAD of the rebase to the copy before the next step builds on it.
+fn sign_rebased_commit(
+    repo: &Repository,
+    signing: Option<&CommitSigning>,
+    oid: git2::Oid,
+) -> Result<(), String> {
+    let Some(signing) = signing else {
+        return Ok(());
+    };
+    let commit = repo
+        .find_commit(oid)
+        .map_err(|e| format!("could not read the replayed commit: {e}"))?;
+    let tree = commit
+        .tree()
+        .map_err(|e| format!("could not read the replayed commit: {e}"))?;
+    let parents: Vec<git2::Commit> = commit.parents().collect();
+    let parents: Vec<&git2::Commit> = parents.iter().collect();
+    let signed = write_signed_commit(
+        repo,
+        signing,
+        &commit.author(),
+        &commit.committer(),
+        &String::from_utf8_lossy(commit.message_bytes()),
+        &tree,
+        &parents,
+    )?;
+    repo.set_head_detached(signed)
+        .map_err(|e| format!("could not move the rebase onto the signed commit: {e}"))
+}
+
+/// How `commit.gpgsign` wants commits signed: which `gpg.format`, with
+/// which program and key.
+struct CommitSigning {
+    format: String,
+    program: String,
+    key: Option<String>,
+    committer: String,
+}
+
+/// `None` unless `commit.gpgsign` is on. Reads the same keys git does,
+/// falling back to git's defaults for the program and (for OpenPGP) to the
+/// committer identity as the key.
+fn commit_signing(repo: &Repository) -> Result<Option<CommitSigning>, String> {
+    let config = repo
+        .config()
+        .map_err(|e| format!("Failed to read git config: {}", e))?;
+    if !config.get_bool("commit.gpgsign").unwrap_or(false) {
+        return Ok(None);
+    }
+    let format = config
+        .get_string("gpg.format")
+        .unwrap_or_else(|_| "openpgp".to_string());
+    let program = match format.as_str() {
+        "openpgp" => config
+            .get_string("gpg.openpgp.program")
+            .or_else(|_| config.get_string("gpg.program"))
+            .unwrap_or_else(|_| "gpg".to_string()),
+        "x509" => config
+            .get_string("gpg.x509.program")
+            .unwrap_or_else(|_| "gpgsm".to_string()),
+        "ssh" => config
+            .get_string("gpg.ssh.program")
+            .unwrap_or_else(|_| "ssh-keygen".to_string()),
+        other => {
+            return Err(format!(
+                "Unsupported gpg.format '{other}' for commit signing"
+            ))
+        }
+    };
+    let key = config
+        .get_string("user.signingkey")
+        .ok()
+        .filter(|k| !k.trim().is_empty());
+    let sig = git_signature(repo)?;
+    let committer = format!(
+        "{} <{}>",
+        sig.name().unwrap_or(""),
+        sig.email().unwrap_or("")
+    );
+    Ok(Some(CommitSigning {
+        format,
+        program,
+        key,
+        committer,
+    }))
+}
+
+/// Runs the signing program over the commit buffer and returns the armored
+/// signature that goes in the `gpgsig` header.
+fn sign_commit_buffer(signing: &CommitSigning, buffer: &str) -> Result<String, String> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    // A literal public key (`ssh-ed25519 AAAA…`, optionally `key::`-prefixed)
+    // signs through the agent from a file holding that key; git does the same.
+    let mut literal_key = None;
+    let mut command = Command::new(&signing.program);
+    if signing.format == "ssh" {
+        let key = signing.key.as_deref().ok_or_else(|| {
+            "Commit signing with gpg.format=ssh needs user.signingkey".to_string()
+        })?;
+        let key = key.strip_prefix("key::").unwrap_or(key).trim();
+        let key_file = if key.starts_with("ssh-") || key.starts_with("ecdsa-") {
+            let file = std::env::temp_dir().join(format!(
+                "auric-signingkey-{}-{}.pub",
+                std::process::id(),
+                chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+            ));
+            fs::write(&file, format!("{key}\n"))
+                .map_err(|e| format!("Failed to write the signing key: {}", e))?;
+            literal_key = Some(file.clone());
+            command.arg("-U");
+            file
+        } else {
+            expand_home(key)
+        };
+        command
+            .args(["-Y", "sign", "-n", "git", "-f"])
+            .arg(key_file);
+    } else {
+        let key = signing.key.as_deref().unwrap_or(&signing.committer);
+        command.args(["--status-fd=2", "-bsau", key]);
+    }
+
+    let output = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(buffer.as_bytes())?;
+            }
+            child.wait_with_output()
+        });
+    if let Some(file) = literal_key {
+        let _ = fs::remove_file(file);
+    }
+    let output = output.map_err(|e| {
+        format!(
+            "Failed to run {} to sign the commit: {}",
+            signing.program, e
+        )
+    })?;
+    let signature = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() || signature.trim().is_empty() {
+        return Err(format!(
+            "Signing the commit with {} failed: {}",
+            signing.program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(signature)
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
 
+/// Credential callbacks shared by every network operation: the SSH agent,
+/// the default key files and the configured credential helper, in that order.
+fn remote_callbacks<'a>() -> git2::RemoteCallbacks<'a> {
     // git2 re-asks the callback after every failed credential, which loops
     // forever if we keep proposing the same one — bail after a few tries
     // with a message that names the fix instead of hanging the UI.
@@ -619,23 +2004,411 @@ pub fn git_push_impl(repo_path: &str) -> Result<(), String> {
         }
         git2::Cred::default()
     });
+    callbacks
+}
 
-    let mut options = git2::PushOptions::new();
-    options.remote_callbacks(callbacks);
-    let refspec = format!("refs/heads/{branch_name}:refs/heads/{branch_name}");
-    remote
-        .push(&[&refspec], Some(&mut options))
-        .map_err(|e| format!("Push failed: {}", e))?;
+/// Fully qualifies one side of a push refspec. `HEAD` resolves to the branch
+/// it points at, a bare name is a branch.
+fn qualify_push_ref(repo: &Repository, name: &str) -> Result<String, String> {
+    if name == "HEAD" {
+        let head = repo
+            .head()
+            .map_err(|e| format!("Failed to read HEAD: {}", e))?;
+        return head
+            .name()
+            .filter(|_| head.is_branch())
+            .map(|n| n.to_string())
+            .ok_or_else(|| "Detached HEAD — check out a branch before pushing".to_string());
+    }
+    if name.starts_with("refs/") {
+        Ok(name.to_string())
+    } else {
+        Ok(format!("refs/heads/{name}"))
+    }
+}
+
+/// `(force, src, dst)` of a user refspec, both sides fully qualified.
+fn parse_push_refspec(repo: &Repository, raw: &str) -> Result<(bool, String, String), String> {
+    let (force, spec) = match raw.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => (false, raw),
+    };
+    let (src, dst) = match spec.split_once(':') {
+        Some((src, dst)) => (src, dst),
+        None => (spec, spec),
+    };
+    if src.is_empty() || dst.is_empty() {
+        return Err(format!(
+            "Invalid refspec '{raw}': both sides must name a ref"
+        ));
+    }
+    let src = qualify_push_ref(repo, src)?;
+    // `HEAD:HEAD` would name the remote's HEAD; mirror the source instead.
+    let dst = if dst == "HEAD" {
+        src.clone()
+    } else {
+        qualify_push_ref(repo, dst)?
+    };
+    Ok((force, src, dst))
+}
+
+/// Where `dst` currently points on `remote`, read over a fresh connection.
+/// `None` when the remote does not have the ref at all.
+fn remote_ref_oid(remote: &mut git2::Remote, dst: &str) -> Result<Option<git2::Oid>, String> {
+    let connection = remote
+        .connect_auth(git2::Direction::Push, Some(remote_callbacks()), None)
+        .map_err(|e| format!("Could not reach the remote: {}", e))?;
+    let heads = connection
+        .list()
+        .map_err(|e| format!("Could not list remote refs: {}", e))?;
+    Ok(heads.iter().find(|h| h.name() == dst).map(|h| h.oid()))
+}
+
+/// Pushes `options.refspec` (default: the current branch, same name) to
+/// `options.remote` (default: the branch's upstream remote, else `origin`),
+/// using `remote_callbacks` and reporting progress through `on_progress`.
+///
+/// `force_with_lease` forces only while the remote ref is still where we
+/// expect. libgit2 has no atomic lease, so the check is a listing right
+/// before the push: it catches "someone pushed since I fetched", which is
+/// the case that loses work, not a push racing the same second.
+///
+/// Sets the upstream of a pushed local branch that has none, so later pushes
+/// (and the branch display) know where home is. Refs the remote rejects come
+/// back in `rejected`, each with its reason, rather than only as progress
+/// events a caller may not be listening to yet.
+pub fn git_push_with_impl(
+    repo_path: &str,
+    options: &GitPushOptions,
+    on_progress: &mut dyn FnMut(GitPushProgress),
+) -> Result<GitPushResult, String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let remote_name = options
+        .remote
+        .clone()
+        .unwrap_or_else(|| default_remote_name(&repo));
+    let (force, src, dst) =
+        parse_push_refspec(&repo, options.refspec.as_deref().unwrap_or("HEAD"))?;
+
+    let mut remote = repo
+        .find_remote(&remote_name)
+        .map_err(|_| format!("No '{remote_name}' remote configured for this repository"))?;
+
+    let dst_short = dst.strip_prefix("refs/heads/").unwrap_or(&dst).to_string();
+    if options.force_with_lease {
+        let expected = match options.expected_remote_oid.as_deref() {
+            Some(raw) => Some(
+                git2::Oid::from_str(raw)
+                    .map_err(|e| format!("Invalid expected remote OID '{raw}': {e}"))?,
+            ),
+            None => {
+                let tracking = remote_tracking_ref(&remote, &dst).ok_or_else(|| {
+                    format!(
+                        "Nothing fetched from '{remote_name}' tracks {dst} — pass the remote OID the lease should expect"
+                    )
+                })?;
+                repo.find_reference(&tracking).ok().and_then(|r| r.target())
+            }
+        };
+        let actual = remote_ref_oid(&mut remote, &dst)?;
+        if actual != expected {
+            let describe = |oid: Option<git2::Oid>| {
+                oid.map(|o| o.to_string())
+                    .unwrap_or_else(|| "absent".to_string())
+            };
+            return Err(format!(
+                "Stale lease: {dst} on '{remote_name}' is {}, expected {} — fetch and review before forcing",
+                describe(actual),
+                describe(expected)
+            ));
+        }
+    }
+    let forced = force || options.force_with_lease;
+    let refspec = format!("{}{src}:{dst}", if forced { "+" } else { "" });
+
+    let progress = std::cell::RefCell::new(on_progress);
+    let rejected: std::cell::RefCell<Vec<GitPushRejection>> = std::cell::RefCell::new(Vec::new());
+    {
+        let mut callbacks = remote_callbacks();
+        callbacks.push_transfer_progress(|current, total, bytes| {
+            (progress.borrow_mut())(GitPushProgress::Transfer {
+                current,
+                total,
+                bytes,
+            });
+        });
+        callbacks.push_update_reference(|refname, status| {
+            if let Some(reason) = status {
+                rejected.borrow_mut().push(GitPushRejection {
+                    refname: refname.to_string(),
+                    reason: reason.to_string(),
+                });
+            }
+            (progress.borrow_mut())(GitPushProgress::RefUpdate {
+                refname: refname.to_string(),
+                rejection: status.map(|s| s.to_string()),
+            });
+            Ok(())
+        });
+
+        let mut push_options = git2::PushOptions::new();
+        push_options.remote_callbacks(callbacks);
+        remote
+            .push(&[&refspec], Some(&mut push_options))
+            .map_err(|e| format!("Push failed: {}", e))?;
+    }
+
+    let rejected = rejected.into_inner();
 
     // Best-effort: the push itself succeeded, a missing upstream note is a
     // cosmetic follow-up, not a failure.
-    if let Ok(mut branch) = repo.find_branch(&branch_name, git2::BranchType::Local) {
+    if let (true, Some(local)) = (rejected.is_empty(), src.strip_prefix("refs/heads/")) {
+        if let Ok(mut branch) = repo.find_branch(local, git2::BranchType::Local) {
+            if branch.upstream().is_err() {
+                let _ = branch.set_upstream(Some(&format!("{remote_name}/{dst_short}")));
+            }
+        }
+    }
+
+    Ok(GitPushResult {
+        remote: remote_name,
+        refspec,
+        rejected,
+    })
+}
+
+/// The remote-tracking ref a fetch from `remote` files its `dst` under —
+/// what a bare `--force-with-lease` expects. `None` when no fetch refspec
+/// covers `dst`, as for tags.
+fn remote_tracking_ref(remote: &git2::Remote, dst: &str) -> Option<String> {
+    remote
+        .refspecs()
+        .filter(|spec| spec.direction() == git2::Direction::Fetch && spec.src_matches(dst))
+        .find_map(|spec| spec.transform(dst).ok())
+        .and_then(|name| name.as_str().map(str::to_string))
+}
+
+/// The remote the current branch tracks, or `origin` when it tracks none.
+fn default_remote_name(repo: &Repository) -> String {
+    repo.head()
+        .ok()
+        .filter(|h| h.is_branch())
+        .and_then(|h| h.name().map(|n| n.to_string()))
+        .and_then(|refname| repo.branch_upstream_remote(&refname).ok())
+        .and_then(|buf| buf.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| "origin".to_string())
+}
+
+pub fn git_fetch_impl(repo_path: &str, remote: Option<&str>) -> Result<GitFetchResult, String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let remote_name = remote
+        .map(|r| r.to_string())
+        .unwrap_or_else(|| default_remote_name(&repo));
+    let mut remote = repo
+        .find_remote(&remote_name)
+        .map_err(|_| format!("No '{remote_name}' remote configured for this repository"))?;
+
+    let mut options = git2::FetchOptions::new();
+    options.remote_callbacks(remote_callbacks());
+    // An empty refspec list means "the remote's configured fetch refspecs".
+    remote
+        .fetch(&[] as &[&str], Some(&mut options), None)
+        .map_err(|e| format!("Fetch failed: {}", e))?;
+
+    let stats = remote.stats();
+    Ok(GitFetchResult {
+        remote: remote_name,
+        received_objects: stats.received_objects(),
+        received_bytes: stats.received_bytes(),
+    })
+}
+
+/// Every conflicted path in `index`, classified by which sides still have it.
+fn conflict_kind(conflict: &git2::IndexConflict) -> &'static str {
+    match (
+        conflict.ancestor.is_some(),
+        conflict.our.is_some(),
+        conflict.their.is_some(),
+    ) {
+        (false, true, true) => "both-added",
+        (true, true, true) => "both-modified",
+        (_, false, true) => "deleted-by-us",
+        _ => "deleted-by-them",
+    }
+}
+
+fn conflict_files(index: &git2::Index) -> Vec<GitConflictFile> {
+    let Ok(conflicts) = index.conflicts() else {
+        return Vec::new();
+    };
+    let mut files: Vec<GitConflictFile> = conflicts
+        .flatten()
+        .filter_map(|c| {
+            let entry = c
+                .our
+                .as_ref()
+                .or(c.their.as_ref())
+                .or(c.ancestor.as_ref())?;
+            Some(GitConflictFile {
+                path: String::from_utf8_lossy(&entry.path).into_owned(),
+                kind: conflict_kind(&c).to_string(),
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+fn git_signature(repo: &Repository) -> Result<git2::Signature<'static>, String> {
+    repo.signature().map_err(|e| {
+        format!(
+            "Failed to get git signature: {}. Please configure git user.name and user.email.",
+            e
+        )
+    })
+}
+
+/// Fetches the current branch's upstream, then fast-forwards onto it or —
+/// with `rebase` — replays local commits on top. A conflicting replay is
+/// aborted so the checkout is exactly as before, and the conflicted paths
+/// come back in the result rather than as an error.
+pub fn git_pull_impl(repo_path: &str, rebase: bool) -> Result<GitPullResult, String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let head = repo
+        .head()
+        .map_err(|e| format!("Failed to read HEAD: {}", e))?;
+    let branch_name = head
+        .shorthand()
+        .filter(|_| head.is_branch())
+        .ok_or_else(|| "Detached HEAD — check out a branch before pulling".to_string())?
+        .to_string();
+    drop(head);
+
+    let upstream_remote = {
+        let branch = repo
+            .find_branch(&branch_name, BranchType::Local)
+            .map_err(|e| format!("branch {branch_name} not found: {e}"))?;
         if branch.upstream().is_err() {
-            let _ = branch.set_upstream(Some(&format!("origin/{branch_name}")));
+            return Err(format!(
+                "{branch_name} has no upstream — push it once or set one before pulling"
+            ));
         }
+        default_remote_name(&repo)
+    };
+    git_fetch_impl(repo_path, Some(&upstream_remote))?;
+
+    let local_id = branch_commit_id(&repo, &branch_name)?;
+    let upstream_id = repo
+        .find_branch(&branch_name, BranchType::Local)
+        .and_then(|b| b.upstream())
+        .map_err(|e| format!("could not read the upstream of {branch_name}: {e}"))?
+        .get()
+        .peel_to_commit()
+        .map(|c| c.id())
+        .map_err(|e| format!("upstream of {branch_name} is not a commit: {e}"))?;
+
+    let up_to_date = local_id == upstream_id
+        || repo
+            .graph_descendant_of(local_id, upstream_id)
+            .unwrap_or(false);
+    if up_to_date {
+        return Ok(GitPullResult {
+            outcome: "up-to-date".to_string(),
+            oid: Some(local_id.to_string()),
+            conflict_commit: None,
+            conflicts: Vec::new(),
+        });
     }
 
-    Ok(())
+    if repo_is_dirty(Path::new(repo_path)) {
+        return Err(format!(
+            "{branch_name} has uncommitted changes — commit or stash them before pulling"
+        ));
+    }
+
+    let can_ff = repo
+        .graph_descendant_of(upstream_id, local_id)
+        .unwrap_or(false);
+    if can_ff {
+        fast_forward_branch(&repo, &branch_name, upstream_id, true, "pull: fast-forward")?;
+        return Ok(GitPullResult {
+            outcome: "fast-forward".to_string(),
+            oid: Some(upstream_id.to_string()),
+            conflict_commit: None,
+            conflicts: Vec::new(),
+        });
+    }
+
+    if !rebase {
+        return Ok(GitPullResult {
+            outcome: "diverged".to_string(),
+            oid: Some(local_id.to_string()),
+            conflict_commit: None,
+            conflicts: Vec::new(),
+        });
+    }
+
+    let upstream = repo
+        .find_annotated_commit(upstream_id)
+        .map_err(|e| format!("could not read the upstream commit: {e}"))?;
+    let sig = git_signature(&repo)?;
+    let signing = commit_signing(&repo)?;
+    let mut rebase_op = repo
+        .rebase(None, Some(&upstream), None, None)
+        .map_err(|e| format!("could not start rebase: {e}"))?;
+    while let Some(op) = rebase_op.next() {
+        let op = match op {
+            Ok(op) => op,
+            Err(e) => {
+                let _ = rebase_op.abort();
+                return Err(format!("rebase failed: {e}"));
+            }
+        };
+        let index = repo
+            .index()
+            .map_err(|e| format!("Failed to get index: {}", e))?;
+        if index.has_conflicts() {
+            let conflicts = conflict_files(&index);
+            let conflict_commit = repo
+                .find_commit(op.id())
+                .ok()
+                .map(|c| c.summary().unwrap_or("").to_string());
+            rebase_op
+                .abort()
+                .map_err(|e| format!("could not abort the conflicting rebase: {e}"))?;
+            return Ok(GitPullResult {
+                outcome: "conflict".to_string(),
+                oid: Some(local_id.to_string()),
+                conflict_commit,
+                conflicts,
+            });
+        }
+        let replayed = match rebase_op.commit(None, &sig, None) {
+            Ok(oid) => sign_rebased_commit(&repo, signing.as_ref(), oid),
+            // The upstream already contains this change: nothing to replay.
+            Err(e) if e.code() == git2::ErrorCode::Applied => Ok(()),
+            Err(e) => Err(format!("could not replay commit: {e}")),
+        };
+        if let Err(e) = replayed {
+            let _ = rebase_op.abort();
+            return Err(e);
+        }
+    }
+    rebase_op
+        .finish(Some(&sig))
+        .map_err(|e| format!("could not finish rebase: {e}"))?;
+
+    let oid = repo
+        .head()
+        .ok()
+        .and_then(|h| h.target())
+        .map(|o| o.to_string());
+    Ok(GitPullResult {
+        outcome: "rebased".to_string(),
+        oid,
+        conflict_commit: None,
+        conflicts: Vec::new(),
+    })
 }
 
 pub fn git_discard_impl(repo_path: &str, file_path: &str) -> Result<(), String> {
@@ -831,6 +2604,190 @@ pub fn git_diff_impl(
     }
 }
 
+/// Commit id → shorthands of every ref that peels to it.
+fn refs_by_commit(repo: &Repository) -> HashMap<git2::Oid, Vec<String>> {
+    let mut map: HashMap<git2::Oid, Vec<String>> = HashMap::new();
+    let Ok(references) = repo.references() else {
+        return map;
+    };
+    for reference in references.flatten() {
+        let Some(name) = reference.shorthand().map(|s| s.to_string()) else {
+            continue;
+        };
+        // `origin/HEAD` only repeats what the remote branch already says, and
+        // the stash is not history.
+        if reference.kind() == Some(git2::ReferenceType::Symbolic)
+            || reference.name() == Some("refs/stash")
+        {
+            continue;
+        }
+        if let Ok(commit) = reference.peel_to_commit() {
+            map.entry(commit.id()).or_default().push(name);
+        }
+    }
+    for names in map.values_mut() {
+        names.sort();
+    }
+    map
+}
+
+/// Pushes `range` onto `walk`: one revision, `a..b` (b without a), or
+/// `a...b` (either side, without their merge base).
+fn push_log_range(repo: &Repository, walk: &mut git2::Revwalk, range: &str) -> Result<(), String> {
+    let spec = repo
+        .revparse(range)
+        .map_err(|e| format!("Invalid revision range '{}': {}", range, e))?;
+    let id = |object: Option<&git2::Object>| {
+        object
+            .and_then(|o| o.peel_to_commit().ok())
+            .map(|c| c.id())
+            .ok_or_else(|| format!("Invalid revision range '{}': not a commit", range))
+    };
+    let walk_err = |e: git2::Error| format!("Failed to walk history: {}", e);
+    let mode = spec.mode();
+    if mode.contains(git2::RevparseMode::SINGLE) {
+        return walk.push(id(spec.from())?).map_err(walk_err);
+    }
+    let from = id(spec.from())?;
+    let to = id(spec.to())?;
+    walk.push(to).map_err(walk_err)?;
+    if mode.contains(git2::RevparseMode::MERGE_BASE) {
+        walk.push(from).map_err(walk_err)?;
+        if let Ok(base) = repo.merge_base(from, to) {
+            walk.hide(base).map_err(walk_err)?;
+        }
+    } else {
+        walk.hide(from).map_err(walk_err)?;
+    }
+    Ok(())
+}
+
+/// A page of history for a graph or file-history view, newest first in
+/// topological order so a commit never shows below its parents. Filters
+/// apply before pagination: `offset` and `limit` count matching commits.
+/// `max_scan` bounds the commits walked per page, as in `git_log_since_impl`,
+/// but only once the offset is reached. Skipping it again walks no further
+/// than the earlier pages that found those matches; counting it would leave
+/// every page past the cap empty, with `has_more` still set.
+/// Not a repo, or a repo without commits, is an empty page.
+pub fn git_log_impl(
+    repo_path: &str,
+    query: &GitLogQuery,
+    max_scan: usize,
+) -> Result<GitLogPage, String> {
+    let empty = GitLogPage {
+        commits: Vec::new(),
+        has_more: false,
+    };
+    let repo = match Repository::open(repo_path) {
+        Ok(r) => r,
+        Err(_) => return Ok(empty),
+    };
+    let head_id = match repo.head().ok().and_then(|h| h.target()) {
+        Some(id) => id,
+        None if query.range.is_none() => return Ok(empty),
+        None => git2::Oid::zero(),
+    };
+
+    let mut walk = repo
+        .revwalk()
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)
+        .map_err(|e| format!("Failed to sort history: {}", e))?;
+    match query
+        .range
+        .as_deref()
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+    {
+        Some(range) => push_log_range(&repo, &mut walk, range)?,
+        None => walk
+            .push_head()
+            .map_err(|e| format!("Failed to start at HEAD: {}", e))?,
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(GIT_LOG_PAGE_DEFAULT)
+        .clamp(1, GIT_LOG_PAGE_MAX);
+    let path = query
+        .path
+        .as_deref()
+        .map(|p| p.trim_matches('/'))
+        .filter(|p| !p.is_empty());
+    let author = query
+        .author
+        .as_deref()
+        .map(|a| a.trim().to_lowercase())
+        .filter(|a| !a.is_empty());
+    let refs = refs_by_commit(&repo);
+
+    let mut commits = Vec::new();
+    let mut matched = 0usize;
+    let mut scanned = 0usize;
+    let mut has_more = false;
+    for oid in walk {
+        if matched >= query.offset {
+            if scanned >= max_scan {
+                has_more = true;
+                break;
+            }
+            scanned += 1;
+        }
+        let oid = oid.map_err(|e| format!("Failed to read commit id: {}", e))?;
+        let commit = repo
+            .find_commit(oid)
+            .map_err(|e| format!("Failed to read commit: {}", e))?;
+
+        if let Some(needle) = &author {
+            let signature = commit.author();
+            let name = signature.name().unwrap_or("").to_lowercase();
+            let email = signature.email().unwrap_or("").to_lowercase();
+            if !name.contains(needle.as_str()) && !email.contains(needle.as_str()) {
+                continue;
+            }
+        }
+        if let Some(path) = path {
+            let tree = commit
+                .tree()
+                .map_err(|e| format!("Failed to read commit tree: {}", e))?;
+            let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+            let mut diff_opts = git2::DiffOptions::new();
+            diff_opts.pathspec(path);
+            let diff = repo
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut diff_opts))
+                .map_err(|e| format!("Failed to diff commit: {}", e))?;
+            if diff.deltas().len() == 0 {
+                continue;
+            }
+        }
+
+        matched += 1;
+        if matched <= query.offset {
+            continue;
+        }
+        if commits.len() == limit {
+            has_more = true;
+            break;
+        }
+
+        let signature = commit.author();
+        commits.push(GitLogEntry {
+            oid: oid.to_string(),
+            parents: commit.parent_ids().map(|p| p.to_string()).collect(),
+            summary: commit.summary().unwrap_or("").to_string(),
+            author: signature.name().unwrap_or("").to_string(),
+            author_email: signature.email().unwrap_or("").to_string(),
+            timestamp: chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+                .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default(),
+            refs: refs.get(&oid).cloned().unwrap_or_default(),
+            is_head: oid == head_id,
+        });
+    }
+    Ok(GitLogPage { commits, has_more })
+}
+
 pub fn git_list_branches_impl(repo_path: &str) -> Result<Vec<GitBranch>, String> {
     let repo = match Repository::open(repo_path) {
         Ok(r) => r,
@@ -874,17 +2831,175 @@ pub fn git_list_branches_impl(repo_path: &str) -> Result<Vec<GitBranch>, String>
             is_current,
         });
     }
-
-    branches.sort_by(|a, b| match (a.is_current, b.is_current) {
-        (true, false) => Ordering::Less,
-        (false, true) => Ordering::Greater,
-        _ => match (a.kind.as_str(), b.kind.as_str()) {
-            ("local", "remote") => Ordering::Less,
-            ("remote", "local") => Ordering::Greater,
-            _ => a.name.cmp(&b.name),
-        },
-    });
-    Ok(branches)
+
+    branches.sort_by(|a, b| match (a.is_current, b.is_current) {
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        _ => match (a.kind.as_str(), b.kind.as_str()) {
+            ("local", "remote") => Ordering::Less,
+            ("remote", "local") => Ordering::Greater,
+            _ => a.name.cmp(&b.name),
+        },
+    });
+    Ok(branches)
+}
+
+fn local_branch<'r>(repo: &'r Repository, name: &str) -> Result<git2::Branch<'r>, String> {
+    repo.find_branch(name, BranchType::Local)
+        .map_err(|_| format!("No local branch named {name}"))
+}
+
+/// Creates `name` at `start_point` (any revision; default HEAD) without
+/// switching to it. Starting from a remote branch tracks it, like git does.
+pub fn git_branch_create_impl(
+    repo_path: &str,
+    name: &str,
+    start_point: Option<&str>,
+) -> Result<GitBranch, String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let name = name.trim();
+    if !git2::Branch::name_is_valid(name).unwrap_or(false) {
+        return Err(format!("'{name}' is not a valid branch name"));
+    }
+    if local_branch_exists(&repo, name) {
+        return Err(format!("Branch {name} already exists"));
+    }
+    let start = start_point.map(str::trim).filter(|s| !s.is_empty());
+    let commit = repo
+        .revparse_single(start.unwrap_or("HEAD"))
+        .and_then(|o| o.peel_to_commit())
+        .map_err(|e| format!("Cannot branch from '{}': {}", start.unwrap_or("HEAD"), e))?;
+    let mut branch = repo
+        .branch(name, &commit, false)
+        .map_err(|e| format!("could not create branch {name}: {e}"))?;
+    if let Some(remote_branch) = start.filter(|s| repo.find_branch(s, BranchType::Remote).is_ok()) {
+        branch
+            .set_upstream(Some(remote_branch))
+            .map_err(|e| format!("Created {name} but could not track {remote_branch}: {e}"))?;
+    }
+    Ok(GitBranch {
+        name: name.to_string(),
+        kind: "local".to_string(),
+        is_current: false,
+    })
+}
+
+/// Switches the checkout to local branch `name`. Refuses while anything is
+/// uncommitted — naming what — rather than carrying changes across or
+/// overwriting them.
+pub fn git_branch_checkout_impl(repo_path: &str, name: &str) -> Result<(), String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let branch = local_branch(&repo, name)?;
+    if head_is_branch(&repo, name) {
+        return Ok(());
+    }
+    if branch_checked_out_elsewhere(repo_path, name) {
+        return Err(format!("{name} is checked out in another worktree"));
+    }
+    let dirty = dirty_paths(Path::new(repo_path));
+    if !dirty.is_empty() {
+        return Err(format!(
+            "Cannot switch to {name}: uncommitted changes in {} — commit or stash them first",
+            describe_paths(&dirty)
+        ));
+    }
+    let commit = branch
+        .get()
+        .peel_to_commit()
+        .map_err(|e| format!("branch {name} is not a commit: {e}"))?;
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))
+        .map_err(|e| format!("could not check out {name}: {e}"))?;
+    repo.set_head(&format!("refs/heads/{name}"))
+        .map_err(|e| format!("could not switch HEAD to {name}: {e}"))
+}
+
+/// Renames a local branch; HEAD follows when it is the current one.
+/// `auric/…` branches are left alone: their worktree is found by that name.
+pub fn git_branch_rename_impl(repo_path: &str, name: &str, new_name: &str) -> Result<(), String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    if branch_is_auric(Some(name)) || branch_is_auric(Some(new_name)) {
+        return Err("auric/ branches belong to their worktree and cannot be renamed".to_string());
+    }
+    let new_name = new_name.trim();
+    if !git2::Branch::name_is_valid(new_name).unwrap_or(false) {
+        return Err(format!("'{new_name}' is not a valid branch name"));
+    }
+    if local_branch_exists(&repo, new_name) {
+        return Err(format!("Branch {new_name} already exists"));
+    }
+    let mut branch = local_branch(&repo, name)?;
+    branch
+        .rename(new_name, false)
+        .map(|_| ())
+        .map_err(|e| format!("could not rename {name}: {e}"))
+}
+
+/// Deletes a local branch. Unless `force`, only once its commits are safe:
+/// reachable from its upstream, or from HEAD when it has none — git's rule.
+pub fn git_branch_delete_impl(repo_path: &str, name: &str, force: bool) -> Result<(), String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let mut branch = local_branch(&repo, name)?;
+    if head_is_branch(&repo, name) {
+        return Err(format!("{name} is the current branch — switch away first"));
+    }
+    if branch_checked_out_elsewhere(repo_path, name) {
+        return Err(format!("{name} is checked out in another worktree"));
+    }
+    if !force {
+        let tip = branch_commit_id(&repo, name)?;
+        let (base, base_id) = match branch.upstream() {
+            Ok(upstream) => (
+                upstream
+                    .name()
+                    .ok()
+                    .flatten()
+                    .unwrap_or("its upstream")
+                    .to_string(),
+                upstream.get().target(),
+            ),
+            Err(_) => (
+                "HEAD".to_string(),
+                repo.head().ok().and_then(|h| h.target()),
+            ),
+        };
+        let unmerged = match base_id {
+            Some(base_id) => repo
+                .graph_ahead_behind(tip, base_id)
+                .map(|(ahead, _)| ahead)
+                .unwrap_or(0),
+            None => 1,
+        };
+        if unmerged > 0 {
+            return Err(format!(
+                "{name} is not fully merged into {base} — {unmerged} commit(s) would be lost; delete with force to drop them"
+            ));
+        }
+    }
+    branch
+        .delete()
+        .map_err(|e| format!("could not delete {name}: {e}"))
+}
+
+/// Points local branch `name` at remote branch `upstream` (`origin/main`),
+/// or clears its upstream for `None`.
+pub fn git_branch_set_upstream_impl(
+    repo_path: &str,
+    name: &str,
+    upstream: Option<&str>,
+) -> Result<(), String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let mut branch = local_branch(&repo, name)?;
+    let upstream = upstream.map(str::trim).filter(|u| !u.is_empty());
+    if let Some(upstream) = upstream {
+        if repo.find_branch(upstream, BranchType::Remote).is_err() {
+            return Err(format!(
+                "No remote branch named {upstream} — fetch or push it first"
+            ));
+        }
+    }
+    branch
+        .set_upstream(upstream)
+        .map_err(|e| format!("could not set the upstream of {name}: {e}"))
 }
 
 pub fn git_blame_impl(repo_path: &str, file_path: &str) -> Result<Vec<BlameHunk>, String> {
@@ -1473,6 +3588,29 @@ fn head_is_branch(repo: &Repository, name: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Whether another checkout of this repository — the main one or any linked
+/// worktree — has `name` as its HEAD. Git refuses to share a branch that way.
+fn branch_checked_out_elsewhere(repo_path: &str, name: &str) -> bool {
+    let Ok((main, source)) = open_main_repo(repo_path) else {
+        return false;
+    };
+    let here = Path::new(repo_path);
+    if !same_path(&source, here) && head_is_branch(&main, name) {
+        return true;
+    }
+    let Ok(names) = main.worktrees() else {
+        return false;
+    };
+    names.iter().flatten().any(|id| {
+        main.find_worktree(id)
+            .ok()
+            .filter(|wt| !same_path(wt.path(), here))
+            .and_then(|wt| Repository::open_from_worktree(&wt).ok())
+            .map(|other| head_is_branch(&other, name))
+            .unwrap_or(false)
+    })
+}
+
 fn branch_commit_id(repo: &Repository, name: &str) -> Result<git2::Oid, String> {
     let branch = repo
         .find_branch(name, BranchType::Local)
@@ -1489,12 +3627,13 @@ fn fast_forward_branch(
     branch_name: &str,
     oid: git2::Oid,
     checkout: bool,
+    log_message: &str,
 ) -> Result<(), String> {
     let mut reference = repo
         .find_reference(&format!("refs/heads/{branch_name}"))
         .map_err(|e| format!("could not open {branch_name}: {e}"))?;
     reference
-        .set_target(oid, "fast-forward merge of agent worktree")
+        .set_target(oid, log_message)
         .map_err(|e| format!("could not fast-forward {branch_name}: {e}"))?;
     if checkout {
         repo.set_head(&format!("refs/heads/{branch_name}"))
@@ -1505,6 +3644,12 @@ fn fast_forward_branch(
     Ok(())
 }
 
+enum BranchMerge {
+    Committed(git2::Oid),
+    /// Nothing was written; the index holds the conflicts.
+    Conflicted(git2::Index),
+}
+
 fn merge_commits_onto_branch(
     repo: &Repository,
     default_branch: &str,
@@ -1512,7 +3657,7 @@ fn merge_commits_onto_branch(
     ours_id: git2::Oid,
     theirs_id: git2::Oid,
     checkout: bool,
-) -> Result<git2::Oid, String> {
+) -> Result<BranchMerge, String> {
     let ours = repo
         .find_commit(ours_id)
         .map_err(|e| format!("could not read {default_branch}: {e}"))?;
@@ -1523,31 +3668,58 @@ fn merge_commits_onto_branch(
         .merge_commits(&ours, &theirs, None)
         .map_err(|e| format!("could not merge: {e}"))?;
     if index.has_conflicts() {
-        return Err(
-            "merge conflict — the worktree was left in place so you can resolve it".to_string(),
-        );
+        return Ok(BranchMerge::Conflicted(index));
     }
     let tree_oid = index
         .write_tree_to(repo)
         .map_err(|e| format!("could not write merge tree: {e}"))?;
+    commit_merge_tree(
+        repo,
+        default_branch,
+        theirs_branch,
+        ours_id,
+        theirs_id,
+        tree_oid,
+        checkout,
+    )
+    .map(BranchMerge::Committed)
+}
+
+fn commit_merge_tree(
+    repo: &Repository,
+    default_branch: &str,
+    theirs_branch: &str,
+    ours_id: git2::Oid,
+    theirs_id: git2::Oid,
+    tree_oid: git2::Oid,
+    checkout: bool,
+) -> Result<git2::Oid, String> {
+    let ours = repo
+        .find_commit(ours_id)
+        .map_err(|e| format!("could not read {default_branch}: {e}"))?;
+    let theirs = repo
+        .find_commit(theirs_id)
+        .map_err(|e| format!("could not read {theirs_branch}: {e}"))?;
     let tree = repo
         .find_tree(tree_oid)
         .map_err(|e| format!("could not read merge tree: {e}"))?;
-    let sig = repo.signature().map_err(|e| {
-        format!(
-            "Failed to get git signature: {}. Please configure git user.name and user.email.",
-            e
-        )
-    })?;
+    let sig = git_signature(repo)?;
     let msg = format!("Merge branch '{theirs_branch}' into {default_branch}");
-    let refname = if checkout {
-        "HEAD".to_string()
+    let oid = write_commit(repo, &sig, &sig, &msg, &tree, &[&ours, &theirs])?;
+    let reflog = format!("commit (merge): {msg}");
+    if checkout {
+        move_head_to(repo, oid, &reflog)?;
     } else {
-        format!("refs/heads/{default_branch}")
-    };
-    let oid = repo
-        .commit(Some(&refname), &sig, &sig, &msg, &tree, &[&ours, &theirs])
+        // Only from `ours`: a branch that moved meanwhile is not overwritten.
+        repo.reference_matching(
+            &format!("refs/heads/{default_branch}"),
+            oid,
+            true,
+            ours_id,
+            &reflog,
+        )
         .map_err(|e| format!("could not create merge commit: {e}"))?;
+    }
     if checkout {
         repo.checkout_head(Some(CheckoutBuilder::default().force()))
             .map_err(|e| format!("could not update working tree: {e}"))?;
@@ -1555,6 +3727,153 @@ fn merge_commits_onto_branch(
     Ok(oid)
 }
 
+/// A conflicted worktree merge waiting for resolution.
+struct PendingWorktreeMerge {
+    default_branch: String,
+    ours: git2::Oid,
+    theirs: git2::Oid,
+}
+
+fn read_pending_merge(worktree: &Repository) -> Option<PendingWorktreeMerge> {
+    let raw = fs::read_to_string(worktree.path().join(AURIC_MERGE_STATE_FILE)).ok()?;
+    let mut lines = raw.lines();
+    let default_branch = lines.next()?.to_string();
+    let ours = git2::Oid::from_str(lines.next()?).ok()?;
+    let theirs = git2::Oid::from_str(lines.next()?).ok()?;
+    Some(PendingWorktreeMerge {
+        default_branch,
+        ours,
+        theirs,
+    })
+}
+
+fn open_pending_merge(worktree_path: &str) -> Result<(Repository, PendingWorktreeMerge), String> {
+    if !path_is_auric_worktree(Path::new(worktree_path)) {
+        return Err("only Auric-managed worktrees can be merged here".to_string());
+    }
+    let worktree =
+        Repository::open(worktree_path).map_err(|e| format!("not a git repository: {e}"))?;
+    let pending = read_pending_merge(&worktree)
+        .ok_or_else(|| "no merge is in progress in this worktree".to_string())?;
+    Ok((worktree, pending))
+}
+
+/// Parks a conflicted merge in the worktree's checkout, where it can be
+/// resolved without touching the user's: the merged files (conflict markers
+/// included) in the workdir, all three stages in the index, and the commits
+/// to join in `AURIC_MERGE_STATE_FILE`.
+fn start_worktree_merge(
+    worktree_path: &str,
+    merged: &git2::Index,
+    pending: &PendingWorktreeMerge,
+    theirs_branch: &str,
+) -> Result<Vec<GitMergeConflict>, String> {
+    let worktree =
+        Repository::open(worktree_path).map_err(|e| format!("not a git repository: {e}"))?;
+    let mut index = worktree
+        .index()
+        .map_err(|e| format!("could not read worktree index: {e}"))?;
+    index
+        .clear()
+        .map_err(|e| format!("could not reset worktree index: {e}"))?;
+    for entry in merged.iter() {
+        index
+            .add(&entry)
+            .map_err(|e| format!("could not stage merge result: {e}"))?;
+    }
+    index
+        .write()
+        .map_err(|e| format!("could not write worktree index: {e}"))?;
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout
+        .force()
+        .allow_conflicts(true)
+        .conflict_style_merge(true)
+        .our_label(&pending.default_branch)
+        .their_label(theirs_branch);
+    worktree
+        .checkout_index(None, Some(&mut checkout))
+        .map_err(|e| format!("could not write merge result into the worktree: {e}"))?;
+
+    fs::write(
+        worktree.path().join(AURIC_MERGE_STATE_FILE),
+        format!(
+            "{}\n{}\n{}\n",
+            pending.default_branch, pending.ours, pending.theirs
+        ),
+    )
+    .map_err(|e| format!("could not record the merge: {e}"))?;
+
+    merge_conflicts_in(&worktree)
+}
+
+fn blob_side(repo: &Repository, entry: Option<&git2::IndexEntry>) -> (Option<Vec<u8>>, bool) {
+    let Some(entry) = entry else {
+        return (None, false);
+    };
+    match repo.find_blob(entry.id) {
+        Ok(blob) => (Some(blob.content().to_vec()), blob.is_binary()),
+        Err(_) => (None, false),
+    }
+}
+
+fn merge_conflicts_in(worktree: &Repository) -> Result<Vec<GitMergeConflict>, String> {
+    let index = worktree
+        .index()
+        .map_err(|e| format!("could not read worktree index: {e}"))?;
+    let conflicts = index
+        .conflicts()
+        .map_err(|e| format!("could not read conflicts: {e}"))?;
+    let mut out = Vec::new();
+    for conflict in conflicts.flatten() {
+        let Some(entry) = conflict
+            .our
+            .as_ref()
+            .or(conflict.their.as_ref())
+            .or(conflict.ancestor.as_ref())
+        else {
+            continue;
+        };
+        let path = String::from_utf8_lossy(&entry.path).into_owned();
+        let (base, base_binary) = blob_side(worktree, conflict.ancestor.as_ref());
+        let (ours, ours_binary) = blob_side(worktree, conflict.our.as_ref());
+        let (theirs, theirs_binary) = blob_side(worktree, conflict.their.as_ref());
+        let binary = base_binary || ours_binary || theirs_binary;
+        let text = |side: Option<Vec<u8>>| {
+            side.filter(|_| !binary)
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        };
+        out.push(GitMergeConflict {
+            path,
+            kind: conflict_kind(&conflict).to_string(),
+            base: text(base),
+            ours: text(ours),
+            theirs: text(theirs),
+            binary,
+        });
+    }
+    out.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(out)
+}
+
+fn ensure_default_branch_clean(
+    repo: &Repository,
+    default_branch: &str,
+    on_default: bool,
+) -> Result<(), String> {
+    if on_default {
+        if let Some(dir) = repo.workdir() {
+            if repo_is_dirty(dir) {
+                return Err(format!(
+                    "{default_branch} has uncommitted changes — commit or stash them before merging"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn git_worktree_merge_into_default_impl(
     repo_path: &str,
     worktree_path: &str,
@@ -1564,6 +3883,7 @@ pub fn git_worktree_merge_into_default_impl(
     if !path_is_auric_worktree(target) {
         return Err("only Auric-managed worktrees can be merged here".to_string());
     }
+    ensure_worktree_idle(worktree_path)?;
 
     let message = commit_message.unwrap_or("Auric worktree");
     commit_worktree_if_dirty(worktree_path, message)?;
@@ -1590,34 +3910,52 @@ pub fn git_worktree_merge_into_default_impl(
             fast_forward: false,
             cleaned_up: true,
             oid: Some(ours_id.to_string()),
+            conflicts: Vec::new(),
         });
     }
 
-    if on_default {
-        if let Some(dir) = repo.workdir() {
-            if repo_is_dirty(dir) {
-                return Err(format!(
-                    "{default_branch} has uncommitted changes — commit or stash them before merging"
-                ));
-            }
-        }
-    }
+    ensure_default_branch_clean(&repo, &default_branch, on_default)?;
 
     let can_ff = repo
         .graph_descendant_of(theirs_id, ours_id)
         .unwrap_or(false);
     let oid = if can_ff {
-        fast_forward_branch(&repo, &default_branch, theirs_id, on_default)?;
+        fast_forward_branch(
+            &repo,
+            &default_branch,
+            theirs_id,
+            on_default,
+            "fast-forward merge of agent worktree",
+        )?;
         theirs_id
     } else {
-        merge_commits_onto_branch(
+        match merge_commits_onto_branch(
             &repo,
             &default_branch,
             &theirs_branch,
             ours_id,
             theirs_id,
             on_default,
-        )?
+        )? {
+            BranchMerge::Committed(oid) => oid,
+            BranchMerge::Conflicted(index) => {
+                let pending = PendingWorktreeMerge {
+                    default_branch: default_branch.clone(),
+                    ours: ours_id,
+                    theirs: theirs_id,
+                };
+                let conflicts =
+                    start_worktree_merge(worktree_path, &index, &pending, &theirs_branch)?;
+                return Ok(WorktreeMergeResult {
+                    default_branch,
+                    merged: false,
+                    fast_forward: false,
+                    cleaned_up: false,
+                    oid: None,
+                    conflicts,
+                });
+            }
+        }
     };
 
     let cleaned_up = git_worktree_remove_impl(repo_path, worktree_path, true).is_ok();
@@ -1627,9 +3965,399 @@ pub fn git_worktree_merge_into_default_impl(
         fast_forward: can_ff,
         cleaned_up,
         oid: Some(oid.to_string()),
+        conflicts: Vec::new(),
+    })
+}
+
+pub fn git_worktree_merge_conflicts_impl(
+    worktree_path: &str,
+) -> Result<Vec<GitMergeConflict>, String> {
+    let (worktree, _) = open_pending_merge(worktree_path)?;
+    merge_conflicts_in(&worktree)
+}
+
+pub fn git_worktree_resolve_conflict_impl(
+    worktree_path: &str,
+    file_path: &str,
+    resolution: &GitConflictResolution,
+) -> Result<Vec<GitMergeConflict>, String> {
+    let (worktree, _) = open_pending_merge(worktree_path)?;
+    let mut index = worktree
+        .index()
+        .map_err(|e| format!("could not read worktree index: {e}"))?;
+    let path = Path::new(file_path);
+    let side = |stage: i32| index.get_path(path, stage);
+    if (1..=3).all(|stage| side(stage).is_none()) {
+        return Err(format!("{file_path} is not conflicted"));
+    }
+
+    let content = match resolution {
+        GitConflictResolution::Ours => blob_side(&worktree, side(2).as_ref()).0,
+        GitConflictResolution::Theirs => blob_side(&worktree, side(3).as_ref()).0,
+        GitConflictResolution::Merged { content } => Some(content.clone().into_bytes()),
+    };
+
+    let full_path = Path::new(worktree_path).join(file_path);
+    match content {
+        Some(bytes) => {
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("could not write {file_path}: {e}"))?;
+            }
+            fs::write(&full_path, bytes)
+                .map_err(|e| format!("could not write {file_path}: {e}"))?;
+            index
+                .add_path(path)
+                .map_err(|e| format!("could not stage {file_path}: {e}"))?;
+        }
+        None => {
+            if full_path.exists() {
+                fs::remove_file(&full_path)
+                    .map_err(|e| format!("could not delete {file_path}: {e}"))?;
+            }
+            index
+                .remove_path(path)
+                .map_err(|e| format!("could not stage {file_path}: {e}"))?;
+        }
+    }
+    index
+        .write()
+        .map_err(|e| format!("could not write worktree index: {e}"))?;
+
+    merge_conflicts_in(&worktree)
+}
+
+/// Commits the resolved worktree index as the merge of the worktree branch
+/// into the default branch, with the same parents and message a clean merge
+/// gets. Refuses while conflicts remain or when either branch moved since
+/// the merge started — the resolution would no longer be for those commits.
+pub fn git_worktree_merge_finish_impl(
+    repo_path: &str,
+    worktree_path: &str,
+) -> Result<WorktreeMergeResult, String> {
+    let (worktree, pending) = open_pending_merge(worktree_path)?;
+    let mut index = worktree
+        .index()
+        .map_err(|e| format!("could not read worktree index: {e}"))?;
+    let open: Vec<String> = conflict_files(&index).into_iter().map(|c| c.path).collect();
+    if !open.is_empty() {
+        return Err(format!(
+            "{} file(s) are still conflicted: {}",
+            open.len(),
+            open.join(", ")
+        ));
+    }
+    let theirs_branch = worktree_branch_name(Path::new(worktree_path))
+        .filter(|b| branch_is_auric(Some(b)))
+        .ok_or_else(|| "worktree is not on an auric/ branch".to_string())?;
+    let head_id = worktree
+        .head()
+        .ok()
+        .and_then(|h| h.target())
+        .ok_or_else(|| "worktree has no HEAD".to_string())?;
+    if head_id != pending.theirs {
+        return Err(format!(
+            "{theirs_branch} moved since the merge started — abort it and merge again"
+        ));
+    }
+    let tree_oid = index
+        .write_tree()
+        .map_err(|e| format!("could not write merge tree: {e}"))?;
+
+    let (repo, _) = open_main_repo(repo_path)?;
+    let default_branch = pending.default_branch;
+    if branch_commit_id(&repo, &default_branch)? != pending.ours {
+        return Err(format!(
+            "{default_branch} moved since the merge started — abort it and merge again"
+        ));
+    }
+    let on_default = head_is_branch(&repo, &default_branch);
+    ensure_default_branch_clean(&repo, &default_branch, on_default)?;
+
+    let oid = commit_merge_tree(
+        &repo,
+        &default_branch,
+        &theirs_branch,
+        pending.ours,
+        pending.theirs,
+        tree_oid,
+        on_default,
+    )?;
+    let _ = fs::remove_file(worktree.path().join(AURIC_MERGE_STATE_FILE));
+
+    let cleaned_up = git_worktree_remove_impl(repo_path, worktree_path, true).is_ok();
+    Ok(WorktreeMergeResult {
+        default_branch,
+        merged: true,
+        fast_forward: false,
+        cleaned_up,
+        oid: Some(oid.to_string()),
+        conflicts: Vec::new(),
+    })
+}
+
+/// Puts the worktree back on its branch tip, exactly as before the merge.
+/// The merge only ran on a committed, clean worktree, so untracked files now
+/// are the merge's and go too.
+pub fn git_worktree_merge_abort_impl(worktree_path: &str) -> Result<(), String> {
+    let (worktree, _) = open_pending_merge(worktree_path)?;
+    let head = worktree
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| format!("worktree has no HEAD: {e}"))?;
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force().remove_untracked(true);
+    worktree
+        .reset(head.as_object(), git2::ResetType::Hard, Some(&mut checkout))
+        .map_err(|e| format!("could not reset the worktree: {e}"))?;
+    fs::remove_file(worktree.path().join(AURIC_MERGE_STATE_FILE))
+        .map_err(|e| format!("could not clear the merge record: {e}"))
+}
+
+/// Refuses to start a merge or rebase on a worktree that is in the middle of
+/// one already.
+fn ensure_worktree_idle(worktree_path: &str) -> Result<(), String> {
+    let Ok(worktree) = Repository::open(worktree_path) else {
+        return Ok(());
+    };
+    if read_pending_merge(&worktree).is_some() {
+        return Err(
+            "a merge is already waiting in this worktree — finish or abort it first".to_string(),
+        );
+    }
+    if worktree.state() != git2::RepositoryState::Clean {
+        return Err(
+            "a rebase is in progress in this worktree — continue, skip or abort it first"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+fn open_worktree_rebase(worktree_path: &str) -> Result<Repository, String> {
+    if !path_is_auric_worktree(Path::new(worktree_path)) {
+        return Err("only Auric-managed worktrees can be rebased here".to_string());
+    }
+    let worktree =
+        Repository::open(worktree_path).map_err(|e| format!("not a git repository: {e}"))?;
+    // Checked for existence here; each caller opens its own handle, since a
+    // `Rebase` borrows the repository it came from.
+    worktree
+        .open_rebase(None)
+        .map_err(|_| "no rebase is in progress in this worktree".to_string())?;
+    Ok(worktree)
+}
+
+/// Replays the remaining commits of `rebase`. Stops, leaving the rebase on
+/// disk, at the first one that conflicts; a commit whose change the default
+/// branch already has is dropped, as `git rebase` does.
+fn drive_worktree_rebase(
+    worktree: &Repository,
+    rebase: &mut git2::Rebase,
+    default_branch: &str,
+) -> Result<WorktreeRebaseResult, String> {
+    let sig = git_signature(worktree)?;
+    let signing = commit_signing(worktree)?;
+    let total = rebase.len();
+    while let Some(op) = rebase.next() {
+        let op = match op {
+            Ok(op) => op,
+            Err(e) => return Err(format!("rebase failed: {e}")),
+        };
+        let op_id = op.id();
+        let index = worktree
+            .index()
+            .map_err(|e| format!("Failed to get index: {}", e))?;
+        if index.has_conflicts() {
+            let summary = worktree
+                .find_commit(op_id)
+                .ok()
+                .and_then(|c| c.summary().map(|s| s.to_string()))
+                .unwrap_or_default();
+            return Ok(WorktreeRebaseResult {
+                outcome: "conflict".to_string(),
+                default_branch: default_branch.to_string(),
+                oid: None,
+                conflict_commit: Some(GitRebaseCommit {
+                    oid: op_id.to_string(),
+                    summary,
+                }),
+                conflicts: conflict_files(&index),
+                step: rebase.operation_current().map_or(0, |i| i + 1),
+                total,
+            });
+        }
+        match rebase.commit(None, &sig, None) {
+            Ok(oid) => sign_rebased_commit(worktree, signing.as_ref(), oid)?,
+            Err(e) if e.code() == git2::ErrorCode::Applied => {}
+            Err(e) => return Err(format!("could not replay commit: {e}")),
+        }
+    }
+    rebase
+        .finish(Some(&sig))
+        .map_err(|e| format!("could not finish rebase: {e}"))?;
+    Ok(WorktreeRebaseResult {
+        outcome: "rebased".to_string(),
+        default_branch: default_branch.to_string(),
+        oid: worktree
+            .head()
+            .ok()
+            .and_then(|h| h.target())
+            .map(|o| o.to_string()),
+        conflict_commit: None,
+        conflicts: Vec::new(),
+        step: 0,
+        total,
     })
 }
 
+/// Brings an Auric worktree's branch up to date with the default branch so
+/// it can be judged against what main looks like now. A branch without own
+/// commits just fast-forwards; otherwise its commits are replayed on top.
+/// The worktree must be clean: its changes would not survive a replay.
+pub fn git_worktree_rebase_onto_default_impl(
+    repo_path: &str,
+    worktree_path: &str,
+) -> Result<WorktreeRebaseResult, String> {
+    let target = Path::new(worktree_path);
+    if !path_is_auric_worktree(target) {
+        return Err("only Auric-managed worktrees can be rebased here".to_string());
+    }
+    ensure_worktree_idle(worktree_path)?;
+    let branch = worktree_branch_name(target)
+        .filter(|b| branch_is_auric(Some(b)))
+        .ok_or_else(|| "worktree is not on an auric/ branch".to_string())?;
+    if repo_is_dirty(target) {
+        return Err(format!(
+            "{branch} has uncommitted changes — commit or stash them before rebasing"
+        ));
+    }
+
+    let (repo, _) = open_main_repo(repo_path)?;
+    let default_branch = resolve_default_branch(&repo)?;
+    let onto_id = branch_commit_id(&repo, &default_branch)?;
+    let worktree =
+        Repository::open(worktree_path).map_err(|e| format!("not a git repository: {e}"))?;
+    let branch_id = branch_commit_id(&worktree, &branch)?;
+
+    let settled = |outcome: &str, oid: git2::Oid| WorktreeRebaseResult {
+        outcome: outcome.to_string(),
+        default_branch: default_branch.clone(),
+        oid: Some(oid.to_string()),
+        conflict_commit: None,
+        conflicts: Vec::new(),
+        step: 0,
+        total: 0,
+    };
+    if branch_id == onto_id
+        || worktree
+            .graph_descendant_of(branch_id, onto_id)
+            .unwrap_or(false)
+    {
+        return Ok(settled("up-to-date", branch_id));
+    }
+    if worktree
+        .graph_descendant_of(onto_id, branch_id)
+        .unwrap_or(false)
+    {
+        fast_forward_branch(
+            &worktree,
+            &branch,
+            onto_id,
+            true,
+            &format!("rebase: fast-forward to {default_branch}"),
+        )?;
+        return Ok(settled("fast-forward", onto_id));
+    }
+
+    let onto = worktree
+        .find_annotated_commit(onto_id)
+        .map_err(|e| format!("could not read {default_branch}: {e}"))?;
+    let mut rebase = worktree
+        .rebase(None, Some(&onto), None, None)
+        .map_err(|e| format!("could not start rebase: {e}"))?;
+    drive_worktree_rebase(&worktree, &mut rebase, &default_branch)
+}
+
+/// Commits the stopped commit as resolved and staged in the worktree, then
+/// replays the rest. Resolving to "no change" drops the commit.
+pub fn git_worktree_rebase_continue_impl(
+    repo_path: &str,
+    worktree_path: &str,
+) -> Result<WorktreeRebaseResult, String> {
+    let worktree = open_worktree_rebase(worktree_path)?;
+    let (repo, _) = open_main_repo(repo_path)?;
+    let default_branch = resolve_default_branch(&repo)?;
+
+    let index = worktree
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let open: Vec<String> = conflict_files(&index).into_iter().map(|c| c.path).collect();
+    if !open.is_empty() {
+        return Err(format!(
+            "{} file(s) are still conflicted: {}",
+            open.len(),
+            open.join(", ")
+        ));
+    }
+
+    let sig = git_signature(&worktree)?;
+    let mut rebase = worktree
+        .open_rebase(None)
+        .map_err(|e| format!("could not open the rebase: {e}"))?;
+    match rebase.commit(None, &sig, None) {
+        Ok(oid) => sign_rebased_commit(&worktree, commit_signing(&worktree)?.as_ref(), oid)?,
+        Err(e) if e.code() == git2::ErrorCode::Applied => {}
+        Err(e) => return Err(format!("could not commit the resolved change: {e}")),
+    }
+    drive_worktree_rebase(&worktree, &mut rebase, &default_branch)
+}
+
+/// Leaves the stopped commit out of the rebased branch and replays the rest.
+pub fn git_worktree_rebase_skip_impl(
+    repo_path: &str,
+    worktree_path: &str,
+) -> Result<WorktreeRebaseResult, String> {
+    let worktree = open_worktree_rebase(worktree_path)?;
+    let (repo, _) = open_main_repo(repo_path)?;
+    let default_branch = resolve_default_branch(&repo)?;
+
+    // The next replay wants a clean checkout: throw away the half-applied
+    // commit, back to the last one that went through. Not a hard reset — that
+    // would also clear the rebase state. The worktree was clean when the
+    // rebase started, so anything untracked now came from this commit.
+    let head_tree = worktree
+        .head()
+        .and_then(|h| h.peel_to_tree())
+        .map_err(|e| format!("worktree has no HEAD: {e}"))?;
+    let mut index = worktree
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    index
+        .read_tree(&head_tree)
+        .and_then(|_| index.write())
+        .map_err(|e| format!("could not drop the conflicting commit: {e}"))?;
+    worktree
+        .checkout_head(Some(CheckoutBuilder::new().force().remove_untracked(true)))
+        .map_err(|e| format!("could not drop the conflicting commit: {e}"))?;
+
+    let mut rebase = worktree
+        .open_rebase(None)
+        .map_err(|e| format!("could not open the rebase: {e}"))?;
+    drive_worktree_rebase(&worktree, &mut rebase, &default_branch)
+}
+
+/// Puts the worktree branch back where it was before the rebase started.
+pub fn git_worktree_rebase_abort_impl(worktree_path: &str) -> Result<(), String> {
+    let worktree = open_worktree_rebase(worktree_path)?;
+    let mut rebase = worktree
+        .open_rebase(None)
+        .map_err(|e| format!("could not open the rebase: {e}"))?;
+    rebase
+        .abort()
+        .map_err(|e| format!("could not abort the rebase: {e}"))
+}
+
 fn same_path(a: &Path, b: &Path) -> bool {
     if a == b {
         return true;
@@ -1666,9 +4394,14 @@ fn project_is_dirty(root: &Path) -> bool {
 /// not count — a `node_modules` sitting on disk is not "you have a commit
 /// waiting".
 fn repo_is_dirty(repo_path: &Path) -> bool {
+    !dirty_paths(repo_path).is_empty()
+}
+
+/// The paths that make `repo_is_dirty` say yes, so a refusal can name them.
+fn dirty_paths(repo_path: &Path) -> Vec<String> {
     let repo = match Repository::open(repo_path) {
         Ok(repo) => repo,
-        Err(_) => return false,
+        Err(_) => return Vec::new(),
     };
     let mut opts = StatusOptions::new();
     opts.include_untracked(true)
@@ -1677,28 +4410,48 @@ fn repo_is_dirty(repo_path: &Path) -> bool {
 
     let statuses = match repo.statuses(Some(&mut opts)) {
         Ok(statuses) => statuses,
-        Err(_) => return false,
+        Err(_) => return Vec::new(),
     };
     let ignored = crate::ignored_repos::ignored_repos_for_project(repo_path);
-    statuses.iter().any(|entry| {
-        let path = entry.path().unwrap_or("");
-        if crate::ignored_repos::is_ignored_repo_path(path, &ignored) {
-            return false;
-        }
-        let status = entry.status();
-        !status.is_ignored()
-            && (status.is_index_new()
-                || status.is_index_modified()
-                || status.is_index_deleted()
-                || status.is_index_typechange()
-                || status.is_index_renamed()
-                || status.is_wt_new()
-                || status.is_wt_modified()
-                || status.is_wt_deleted()
-                || status.is_wt_typechange()
-                || status.is_wt_renamed()
-                || status.is_conflicted())
-    })
+    statuses
+        .iter()
+        .filter(|entry| {
+            let path = entry.path().unwrap_or("");
+            if crate::ignored_repos::is_ignored_repo_path(path, &ignored) {
+                return false;
+            }
+            let status = entry.status();
+            !status.is_ignored()
+                && (status.is_index_new()
+                    || status.is_index_modified()
+                    || status.is_index_deleted()
+                    || status.is_index_typechange()
+                    || status.is_index_renamed()
+                    || status.is_wt_new()
+                    || status.is_wt_modified()
+                    || status.is_wt_deleted()
+                    || status.is_wt_typechange()
+                    || status.is_wt_renamed()
+                    || status.is_conflicted())
+        })
+        .map(|entry| entry.path().unwrap_or("").to_string())
+        .collect()
+}
+
+/// "a.txt, b.txt and 3 more" — enough to recognise, short enough for a toast.
+fn describe_paths(paths: &[String]) -> String {
+    const SHOWN: usize = 3;
+    let head = paths
+        .iter()
+        .take(SHOWN)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if paths.len() > SHOWN {
+        format!("{head} and {} more", paths.len() - SHOWN)
+    } else {
+        head
+    }
 }
 
 #[cfg(test)]
@@ -1724,7 +4477,7 @@ mod tests {
     fn push_without_a_remote_names_the_problem() {
         let dir = TempDir::new().unwrap();
         let path = committed_repo(&dir);
-        let err = git_push_impl(&path).unwrap_err();
+        let err = git_push_with_impl(&path, &GitPushOptions::default(), &mut |_| {}).unwrap_err();
         assert!(err.contains("origin"), "unhelpful error: {err}");
     }
 
@@ -1742,7 +4495,7 @@ mod tests {
                 .unwrap();
         }
 
-        git_push_impl(&path).unwrap();
+        git_push_with_impl(&path, &GitPushOptions::default(), &mut |_| {}).unwrap();
 
         let remote = Repository::open_bare(bare.path()).unwrap();
         assert!(remote.head().unwrap().peel_to_commit().is_ok());
@@ -1760,7 +4513,7 @@ mod tests {
                 .unwrap();
         }
 
-        git_push_impl(&path).unwrap();
+        git_push_with_impl(&path, &GitPushOptions::default(), &mut |_| {}).unwrap();
 
         let repo = Repository::open(&path).unwrap();
         let head = repo.head().unwrap();
@@ -1770,6 +4523,351 @@ mod tests {
         assert!(branch.upstream().is_ok());
     }
 
+    /// A bare `origin` seeded from a fresh committed repo, plus a clone of it
+    /// with its own identity: `(upstream_work, bare, clone)`. The clone tracks
+    /// the pushed branch, so pull and ahead/behind have an upstream to read.
+    fn repo_with_clone() -> (TempDir, TempDir, TempDir) {
+        let work = TempDir::new().unwrap();
+        let bare = TempDir::new().unwrap();
+        let clone = TempDir::new().unwrap();
+        let path = committed_repo(&work);
+        Repository::init_bare(bare.path()).unwrap();
+        {
+            let repo = Repository::open(&path).unwrap();
+            repo.remote("origin", bare.path().to_str().unwrap())
+                .unwrap();
+        }
+        git_push_with_impl(&path, &GitPushOptions::default(), &mut |_| {}).unwrap();
+        let branch = current_branch_name(&path);
+        Repository::open_bare(bare.path())
+            .unwrap()
+            .set_head(&format!("refs/heads/{branch}"))
+            .unwrap();
+
+        let cloned = Repository::clone(bare.path().to_str().unwrap(), clone.path()).unwrap();
+        let mut config = cloned.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        (work, bare, clone)
+    }
+
+    fn push_commit(repo_path: &str, file: &str, contents: &str, message: &str) {
+        commit_in(repo_path, file, contents, message);
+        git_push_with_impl(repo_path, &GitPushOptions::default(), &mut |_| {}).unwrap();
+    }
+
+    fn push_options(refspec: &str) -> GitPushOptions {
+        GitPushOptions {
+            refspec: Some(refspec.to_string()),
+            ..GitPushOptions::default()
+        }
+    }
+
+    #[test]
+    fn push_sends_a_refspec_to_a_named_remote() {
+        let work = TempDir::new().unwrap();
+        let bare = TempDir::new().unwrap();
+        let path = committed_repo(&work);
+        Repository::init_bare(bare.path()).unwrap();
+        {
+            let repo = Repository::open(&path).unwrap();
+            repo.remote("mirror", bare.path().to_str().unwrap())
+                .unwrap();
+        }
+
+        let options = GitPushOptions {
+            remote: Some("mirror".to_string()),
+            ..push_options("HEAD:review/topic")
+        };
+        let result = git_push_with_impl(&path, &options, &mut |_| {}).unwrap();
+
+        assert_eq!(result.remote, "mirror");
+        let branch = current_branch_name(&path);
+        assert_eq!(
+            result.refspec,
+            format!("refs/heads/{branch}:refs/heads/review/topic")
+        );
+        let remote = Repository::open_bare(bare.path()).unwrap();
+        assert!(remote.find_reference("refs/heads/review/topic").is_ok());
+        assert!(remote
+            .find_reference(&format!("refs/heads/{branch}"))
+            .is_err());
+    }
+
+    #[test]
+    fn push_reports_ref_updates_as_progress() {
+        let (_work, _bare, clone) = repo_with_clone();
+        let clone_path = clone.path().to_str().unwrap();
+        commit_in(clone_path, "c.txt", "c", "clone change");
+
+        let mut events = Vec::new();
+        git_push_with_impl(clone_path, &GitPushOptions::default(), &mut |p| {
+            events.push(p)
+        })
+        .unwrap();
+
+        let branch = current_branch_name(clone_path);
+        assert!(events.contains(&GitPushProgress::RefUpdate {
+            refname: format!("refs/heads/{branch}"),
+            rejection: None,
+        }));
+    }
+
+    #[test]
+    fn push_refuses_a_non_fast_forward_and_leaves_the_remote_alone() {
+        let (work, bare, clone) = repo_with_clone();
+        let work_path = work.path().to_str().unwrap();
+        let clone_path = clone.path().to_str().unwrap();
+        push_commit(work_path, "b.txt", "b", "upstream change");
+        commit_in(clone_path, "c.txt", "c", "clone change");
+        let branch = current_branch_name(clone_path);
+        let remote_tip = || {
+            Repository::open_bare(bare.path())
+                .unwrap()
+                .refname_to_id(&format!("refs/heads/{branch}"))
+                .unwrap()
+        };
+        let before = remote_tip();
+
+        let err =
+            git_push_with_impl(clone_path, &GitPushOptions::default(), &mut |_| {}).unwrap_err();
+
+        assert!(err.starts_with("Push"), "unhelpful error: {err}");
+        assert_eq!(remote_tip(), before);
+    }
+
+    #[test]
+    fn force_with_lease_refuses_when_the_remote_moved_since_the_fetch() {
+        let (work, bare, clone) = repo_with_clone();
+        let work_path = work.path().to_str().unwrap();
+        let clone_path = clone.path().to_str().unwrap();
+        push_commit(work_path, "b.txt", "b", "upstream change");
+        commit_in(clone_path, "c.txt", "c", "clone change");
+        let branch = current_branch_name(clone_path);
+        let before = Repository::open_bare(bare.path())
+            .unwrap()
+            .refname_to_id(&format!("refs/heads/{branch}"))
+            .unwrap();
+
+        let options = GitPushOptions {
+            force_with_lease: true,
+            ..GitPushOptions::default()
+        };
+        let err = git_push_with_impl(clone_path, &options, &mut |_| {}).unwrap_err();
+
+        assert!(err.contains("Stale lease"), "unexpected error: {err}");
+        let after = Repository::open_bare(bare.path())
+            .unwrap()
+            .refname_to_id(&format!("refs/heads/{branch}"))
+            .unwrap();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn force_with_lease_overwrites_when_the_expectation_holds() {
+        let (work, bare, clone) = repo_with_clone();
+        let work_path = work.path().to_str().unwrap();
+        let clone_path = clone.path().to_str().unwrap();
+        push_commit(work_path, "b.txt", "b", "upstream change");
+        git_fetch_impl(clone_path, None).unwrap();
+        commit_in(clone_path, "c.txt", "c", "clone change");
+
+        let options = GitPushOptions {
+            force_with_lease: true,
+            ..GitPushOptions::default()
+        };
+        git_push_with_impl(clone_path, &options, &mut |_| {}).unwrap();
+
+        let branch = current_branch_name(clone_path);
+        let clone_head = Repository::open(clone_path)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap();
+        let remote_head = Repository::open_bare(bare.path())
+            .unwrap()
+            .refname_to_id(&format!("refs/heads/{branch}"))
+            .unwrap();
+        assert_eq!(clone_head, remote_head);
+    }
+
+    #[test]
+    fn force_with_lease_on_a_tag_needs_an_explicit_expectation() {
+        let (_work, bare, clone) = repo_with_clone();
+        let clone_path = clone.path().to_str().unwrap();
+        git_command(clone.path())
+            .args(["tag", "v1"])
+            .output()
+            .unwrap();
+        let mut options = GitPushOptions {
+            refspec: Some("refs/tags/v1".to_string()),
+            force_with_lease: true,
+            ..GitPushOptions::default()
+        };
+
+        // `refs/remotes/origin/refs/tags/v1` is no tracking ref: it would
+        // always read as absent.
+        let err = git_push_with_impl(clone_path, &options, &mut |_| {}).unwrap_err();
+        assert!(err.contains("Nothing fetched"), "unexpected error: {err}");
+
+        // A branch keeps its default through `refs/remotes/origin/*`.
+        options.refspec = None;
+        let result = git_push_with_impl(clone_path, &options, &mut |_| {}).unwrap();
+        assert!(result.rejected.is_empty());
+        assert!(Repository::open_bare(bare.path())
+            .unwrap()
+            .find_reference("refs/tags/v1")
+            .is_err());
+    }
+
+    #[test]
+    fn branch_info_counts_ahead_and_behind_its_upstream() {
+        let (work, _bare, clone) = repo_with_clone();
+        let work_path = work.path().to_str().unwrap();
+        let clone_path = clone.path().to_str().unwrap();
+
+        let info = git_branch_info_impl(clone_path).unwrap();
+        assert_eq!((info.ahead, info.behind), (0, 0));
+        assert!(info.upstream.as_deref().unwrap().starts_with("origin/"));
+
+        commit_in(clone_path, "mine.txt", "local", "local work");
+        push_commit(work_path, "theirs.txt", "remote", "remote work");
+        git_fetch_impl(clone_path, None).unwrap();
+
+        let info = git_branch_info_impl(clone_path).unwrap();
+        assert_eq!((info.ahead, info.behind), (1, 1));
+    }
+
+    #[test]
+    fn branch_info_without_upstream_has_no_counts() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        let info = git_branch_info_impl(&path).unwrap();
+        assert_eq!((info.ahead, info.behind), (0, 0));
+        assert!(info.upstream.is_none());
+    }
+
+    #[test]
+    fn fetch_without_a_remote_names_the_problem() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        let err = git_fetch_impl(&path, None).unwrap_err();
+        assert!(err.contains("origin"), "{err}");
+    }
+
+    #[test]
+    fn pull_fast_forwards_onto_the_upstream() {
+        let (work, _bare, clone) = repo_with_clone();
+        let clone_path = clone.path().to_str().unwrap();
+        push_commit(work.path().to_str().unwrap(), "b.txt", "new", "remote work");
+
+        let result = git_pull_impl(clone_path, false).unwrap();
+        assert_eq!(result.outcome, "fast-forward");
+        assert_eq!(
+            fs::read_to_string(clone.path().join("b.txt")).unwrap(),
+            "new"
+        );
+
+        let again = git_pull_impl(clone_path, false).unwrap();
+        assert_eq!(again.outcome, "up-to-date");
+    }
+
+    #[test]
+    fn pull_reports_divergence_without_rebase_and_leaves_head_alone() {
+        let (work, _bare, clone) = repo_with_clone();
+        let clone_path = clone.path().to_str().unwrap();
+        push_commit(
+            work.path().to_str().unwrap(),
+            "b.txt",
+            "remote",
+            "remote work",
+        );
+        commit_in(clone_path, "c.txt", "local", "local work");
+        let before = Repository::open(clone_path)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap();
+
+        let result = git_pull_impl(clone_path, false).unwrap();
+        assert_eq!(result.outcome, "diverged");
+        assert_eq!(result.oid, Some(before.to_string()));
+        assert!(!clone.path().join("b.txt").exists());
+    }
+
+    #[test]
+    fn pull_with_rebase_replays_local_commits_on_top() {
+        let (work, _bare, clone) = repo_with_clone();
+        let clone_path = clone.path().to_str().unwrap();
+        push_commit(
+            work.path().to_str().unwrap(),
+            "b.txt",
+            "remote",
+            "remote work",
+        );
+        commit_in(clone_path, "c.txt", "local", "local work");
+
+        let result = git_pull_impl(clone_path, true).unwrap();
+        assert_eq!(result.outcome, "rebased");
+        assert!(clone.path().join("b.txt").is_file());
+        assert!(clone.path().join("c.txt").is_file());
+
+        let repo = Repository::open(clone_path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.summary(), Some("local work"));
+        assert_eq!(head.parent(0).unwrap().summary(), Some("remote work"));
+        let info = git_branch_info_impl(clone_path).unwrap();
+        assert_eq!((info.ahead, info.behind), (1, 0));
+    }
+
+    #[test]
+    fn pull_with_rebase_reports_conflicted_files_and_restores_the_branch() {
+        let (work, _bare, clone) = repo_with_clone();
+        let clone_path = clone.path().to_str().unwrap();
+        push_commit(
+            work.path().to_str().unwrap(),
+            "a.txt",
+            "remote",
+            "remote edit",
+        );
+        commit_in(clone_path, "a.txt", "local", "local edit");
+        let before = Repository::open(clone_path)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap();
+
+        let result = git_pull_impl(clone_path, true).unwrap();
+        assert_eq!(result.outcome, "conflict");
+        assert_eq!(result.conflict_commit.as_deref(), Some("local edit"));
+        assert_eq!(
+            result.conflicts,
+            vec![GitConflictFile {
+                path: "a.txt".to_string(),
+                kind: "both-modified".to_string(),
+            }]
+        );
+
+        let repo = Repository::open(clone_path).unwrap();
+        assert_eq!(repo.head().unwrap().target(), Some(before));
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+        assert_eq!(
+            fs::read_to_string(clone.path().join("a.txt")).unwrap(),
+            "local"
+        );
+    }
+
+    #[test]
+    fn pull_without_an_upstream_names_the_problem() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        let err = git_pull_impl(&path, false).unwrap_err();
+        assert!(err.contains("upstream"), "{err}");
+    }
+
     /// `git`, detached from any git environment the caller happens to be in.
     ///
     /// A git hook exports `GIT_DIR`, `GIT_INDEX_FILE` and `GIT_WORK_TREE`, and
@@ -1898,50 +4996,240 @@ mod tests {
     }
 
     #[test]
-    fn test_git_log_since_caps_work_at_max_scan() {
+    fn test_git_log_since_caps_work_at_max_scan() {
+        let dir = init_test_repo();
+        // Five commits, none touching the requested prefix. Without a scan cap
+        // the walk would diff all five hunting a match; max_scan stops it early.
+        for i in 0..5 {
+            commit_file(&dir, "src/f.txt", &format!("v{}", i), &format!("c{}", i));
+        }
+        let log =
+            git_log_since_impl(dir.path().to_str().unwrap(), None, Some("docs/"), 200, 2).unwrap();
+        // Prefix matches nothing, and we gave up after 2 commits: no results,
+        // and — the point of the test — the loop terminated rather than
+        // scanning the whole history.
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn test_git_log_since_is_empty_for_non_repo_and_empty_repo() {
+        let plain = TempDir::new().unwrap();
+        assert!(
+            git_log_since_impl(plain.path().to_str().unwrap(), None, None, 200, usize::MAX)
+                .unwrap()
+                .is_empty()
+        );
+        let empty = init_test_repo();
+        assert!(
+            git_log_since_impl(empty.path().to_str().unwrap(), None, None, 200, usize::MAX)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_git_log_since_rejects_garbage_cutoff() {
+        let dir = init_test_repo();
+        commit_file(&dir, "f.txt", "x", "c");
+        let err = git_log_since_impl(
+            dir.path().to_str().unwrap(),
+            Some("next tuesday"),
+            None,
+            200,
+            usize::MAX,
+        )
+        .unwrap_err();
+        assert!(err.contains("Invalid since_iso"));
+    }
+
+    fn log_query(range: Option<&str>) -> GitLogQuery {
+        GitLogQuery {
+            range: range.map(|r| r.to_string()),
+            ..GitLogQuery::default()
+        }
+    }
+
+    #[test]
+    fn test_git_log_pages_through_history_with_parents_and_refs() {
+        let dir = init_test_repo();
+        let repo_path = dir.path().to_str().unwrap();
+        for i in 0..5 {
+            commit_file(&dir, "f.txt", &format!("v{}", i), &format!("c{}", i));
+        }
+        git_command(dir.path())
+            .args(["tag", "v1", "HEAD~1"])
+            .output()
+            .unwrap();
+
+        let first = git_log_impl(
+            repo_path,
+            &GitLogQuery {
+                limit: Some(2),
+                ..GitLogQuery::default()
+            },
+            usize::MAX,
+        )
+        .unwrap();
+        let summaries: Vec<&str> = first.commits.iter().map(|c| c.summary.as_str()).collect();
+        assert_eq!(summaries, vec!["c4", "c3"]);
+        assert!(first.has_more);
+        assert!(first.commits[0].is_head);
+        assert!(first.commits[0]
+            .refs
+            .contains(&current_branch_name(repo_path)));
+        assert_eq!(first.commits[1].refs, vec!["v1".to_string()]);
+        assert_eq!(first.commits[0].parents, vec![first.commits[1].oid.clone()]);
+
+        let last = git_log_impl(
+            repo_path,
+            &GitLogQuery {
+                offset: 4,
+                limit: Some(2),
+                ..GitLogQuery::default()
+            },
+            usize::MAX,
+        )
+        .unwrap();
+        assert_eq!(last.commits.len(), 1);
+        assert_eq!(last.commits[0].summary, "c0");
+        assert!(last.commits[0].parents.is_empty());
+        assert!(!last.has_more);
+    }
+
+    #[test]
+    fn test_git_log_filters_by_path_and_author() {
+        let dir = init_test_repo();
+        let repo_path = dir.path().to_str().unwrap();
+        commit_file(&dir, "src/a.rs", "a", "code");
+        commit_file(&dir, "docs/readme.md", "d", "docs");
+        fs::write(dir.path().join("src/a.rs"), "b").unwrap();
+        git_command(dir.path()).args(["add", "."]).output().unwrap();
+        git_command(dir.path())
+            .args([
+                "-c",
+                "user.name=Robot",
+                "-c",
+                "user.email=bot@agents.dev",
+                "commit",
+                "-m",
+                "agent code",
+            ])
+            .output()
+            .unwrap();
+
+        let by_path = git_log_impl(
+            repo_path,
+            &GitLogQuery {
+                path: Some("src".to_string()),
+                ..GitLogQuery::default()
+            },
+            usize::MAX,
+        )
+        .unwrap();
+        let summaries: Vec<&str> = by_path.commits.iter().map(|c| c.summary.as_str()).collect();
+        assert_eq!(summaries, vec!["agent code", "code"]);
+
+        let by_author = git_log_impl(
+            repo_path,
+            &GitLogQuery {
+                author: Some("AGENTS.dev".to_string()),
+                ..GitLogQuery::default()
+            },
+            usize::MAX,
+        )
+        .unwrap();
+        assert_eq!(by_author.commits.len(), 1);
+        assert_eq!(by_author.commits[0].author, "Robot");
+    }
+
+    #[test]
+    fn test_git_log_walks_a_revision_range() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        let base = current_branch_name(&path);
+        git_command(dir.path())
+            .args(["checkout", "-b", "feature"])
+            .output()
+            .unwrap();
+        commit_in(&path, "f.txt", "f", "feature work");
+        git_command(dir.path())
+            .args(["checkout", &base])
+            .output()
+            .unwrap();
+        commit_in(&path, "m.txt", "m", "main work");
+
+        let only_feature = git_log_impl(
+            &path,
+            &log_query(Some(&format!("{base}..feature"))),
+            usize::MAX,
+        )
+        .unwrap();
+        assert_eq!(only_feature.commits.len(), 1);
+        assert_eq!(only_feature.commits[0].summary, "feature work");
+        assert_eq!(only_feature.commits[0].refs, vec!["feature".to_string()]);
+
+        let both_sides = git_log_impl(
+            &path,
+            &log_query(Some(&format!("{base}...feature"))),
+            usize::MAX,
+        )
+        .unwrap();
+        assert_eq!(both_sides.commits.len(), 2);
+
+        let err = git_log_impl(&path, &log_query(Some("nope..feature")), usize::MAX).unwrap_err();
+        assert!(err.contains("Invalid revision range"), "{err}");
+    }
+
+    #[test]
+    fn test_git_log_caps_work_and_says_there_may_be_more() {
         let dir = init_test_repo();
-        // Five commits, none touching the requested prefix. Without a scan cap
-        // the walk would diff all five hunting a match; max_scan stops it early.
         for i in 0..5 {
             commit_file(&dir, "src/f.txt", &format!("v{}", i), &format!("c{}", i));
         }
-        let log =
-            git_log_since_impl(dir.path().to_str().unwrap(), None, Some("docs/"), 200, 2).unwrap();
-        // Prefix matches nothing, and we gave up after 2 commits: no results,
-        // and — the point of the test — the loop terminated rather than
-        // scanning the whole history.
-        assert!(log.is_empty());
-    }
+        let page = git_log_impl(
+            dir.path().to_str().unwrap(),
+            &GitLogQuery {
+                path: Some("docs".to_string()),
+                ..GitLogQuery::default()
+            },
+            2,
+        )
+        .unwrap();
+        assert!(page.commits.is_empty());
+        assert!(page.has_more);
 
-    #[test]
-    fn test_git_log_since_is_empty_for_non_repo_and_empty_repo() {
-        let plain = TempDir::new().unwrap();
-        assert!(
-            git_log_since_impl(plain.path().to_str().unwrap(), None, None, 200, usize::MAX)
-                .unwrap()
-                .is_empty()
-        );
         let empty = init_test_repo();
-        assert!(
-            git_log_since_impl(empty.path().to_str().unwrap(), None, None, 200, usize::MAX)
-                .unwrap()
-                .is_empty()
-        );
+        let page = git_log_impl(empty.path().to_str().unwrap(), &log_query(None), 10).unwrap();
+        assert!(page.commits.is_empty());
+        assert!(!page.has_more);
     }
 
     #[test]
-    fn test_git_log_since_rejects_garbage_cutoff() {
+    fn test_git_log_pages_past_the_scan_cap() {
         let dir = init_test_repo();
-        commit_file(&dir, "f.txt", "x", "c");
-        let err = git_log_since_impl(
-            dir.path().to_str().unwrap(),
-            Some("next tuesday"),
-            None,
-            200,
-            usize::MAX,
-        )
-        .unwrap_err();
-        assert!(err.contains("Invalid since_iso"));
+        let repo_path = dir.path().to_str().unwrap();
+        for i in 0..5 {
+            commit_file(&dir, "f.txt", &format!("v{}", i), &format!("c{}", i));
+        }
+        let page = |offset| {
+            let page = git_log_impl(
+                repo_path,
+                &GitLogQuery {
+                    offset,
+                    limit: Some(2),
+                    ..GitLogQuery::default()
+                },
+                2,
+            )
+            .unwrap();
+            let summaries: Vec<String> = page.commits.into_iter().map(|c| c.summary).collect();
+            (summaries, page.has_more)
+        };
+
+        assert_eq!(page(0), (vec!["c4".to_string(), "c3".to_string()], true));
+        // The skipped commits are not charged to this page's scan.
+        assert_eq!(page(2), (vec!["c2".to_string(), "c1".to_string()], true));
+        assert_eq!(page(4), (vec!["c0".to_string()], false));
     }
 
     #[test]
@@ -2209,85 +5497,487 @@ mod tests {
     }
 
     #[test]
-    fn git_stage_impl_stages_a_deleted_file() {
+    fn git_stage_impl_stages_a_deleted_file() {
+        let dir = init_test_repo();
+        let repo_path = dir.path().to_str().unwrap();
+        commit_file(&dir, "gone.txt", "bye\n", "init");
+        fs::remove_file(dir.path().join("gone.txt")).unwrap();
+
+        git_stage_impl(repo_path, &["gone.txt".to_string()]).unwrap();
+
+        let rows = git_status_impl(repo_path).unwrap();
+        let row = find_status(&rows, "gone.txt");
+        assert_eq!(row.staged.as_deref(), Some("deleted"));
+    }
+
+    #[test]
+    fn git_stage_impl_includes_deletion_in_a_multi_path_call() {
+        let dir = init_test_repo();
+        let repo_path = dir.path().to_str().unwrap();
+        commit_file(&dir, "keep.txt", "a\n", "one");
+        commit_file(&dir, "gone.txt", "b\n", "two");
+        fs::write(dir.path().join("keep.txt"), "aa\n").unwrap();
+        fs::remove_file(dir.path().join("gone.txt")).unwrap();
+
+        git_stage_impl(repo_path, &["keep.txt".to_string(), "gone.txt".to_string()]).unwrap();
+
+        let rows = git_status_impl(repo_path).unwrap();
+        assert_eq!(
+            find_status(&rows, "keep.txt").staged.as_deref(),
+            Some("modified")
+        );
+        assert_eq!(
+            find_status(&rows, "gone.txt").staged.as_deref(),
+            Some("deleted")
+        );
+    }
+
+    #[test]
+    fn git_commit_impl_refuses_a_clean_index() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        let err = git_commit_impl(&path, "empty").unwrap_err();
+        assert_eq!(err, "Nothing to commit");
+    }
+
+    #[test]
+    fn git_commit_amend_rewrites_head_with_the_index_and_message() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        commit_in(&path, "b.txt", "b\n", "second");
+        let repo = Repository::open(&path).unwrap();
+        let before = repo.head().unwrap().peel_to_commit().unwrap();
+
+        fs::write(dir.path().join("b.txt"), "b2\n").unwrap();
+        git_stage_impl(&path, &["b.txt".to_string()]).unwrap();
+        let oid = git_commit_with_impl(&path, "second, fixed", true).unwrap();
+
+        let amended = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(amended.id().to_string(), oid);
+        assert_eq!(amended.message(), Some("second, fixed"));
+        assert_eq!(
+            amended.parent_ids().collect::<Vec<_>>(),
+            before.parent_ids().collect::<Vec<_>>()
+        );
+        assert_eq!(amended.author().when(), before.author().when());
+        let blob = amended
+            .tree()
+            .unwrap()
+            .get_path(Path::new("b.txt"))
+            .unwrap();
+        assert_eq!(repo.find_blob(blob.id()).unwrap().content(), b"b2\n");
+
+        let reworded = git_commit_with_impl(&path, "  ", true).unwrap();
+        let reworded = repo
+            .find_commit(git2::Oid::from_str(&reworded).unwrap())
+            .unwrap();
+        assert_eq!(reworded.message(), Some("second, fixed"));
+        assert_eq!(reworded.tree_id(), amended.tree_id());
+    }
+
+    #[test]
+    fn git_commit_amend_needs_a_commit() {
+        let dir = init_test_repo();
+        let err = git_commit_with_impl(dir.path().to_str().unwrap(), "x", true).unwrap_err();
+        assert!(err.contains("Nothing to amend"), "{err}");
+    }
+
+    #[test]
+    fn git_commit_signs_with_ssh_when_gpgsign_is_on() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        let keys = TempDir::new().unwrap();
+        let key = keys.path().join("id_ed25519");
+        let made = StdCommand::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+            .arg(&key)
+            .output()
+            .unwrap();
+        assert!(made.status.success());
+        {
+            let mut config = Repository::open(&path).unwrap().config().unwrap();
+            config.set_bool("commit.gpgsign", true).unwrap();
+            config.set_str("gpg.format", "ssh").unwrap();
+            config
+                .set_str("user.signingkey", key.to_str().unwrap())
+                .unwrap();
+        }
+
+        commit_in(&path, "b.txt", "b\n", "signed");
+
+        let repo = Repository::open(&path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.message(), Some("signed"));
+        let (signature, _) = repo.extract_signature(&head.id(), None).unwrap();
+        let signature = std::str::from_utf8(&signature).unwrap();
+        assert!(
+            signature.starts_with("-----BEGIN SSH SIGNATURE-----"),
+            "{signature}"
+        );
+
+        Repository::open(&path)
+            .unwrap()
+            .config()
+            .unwrap()
+            .set_str("user.signingkey", "/nowhere/key")
+            .unwrap();
+        fs::write(dir.path().join("b.txt"), "b2\n").unwrap();
+        git_stage_impl(&path, &["b.txt".to_string()]).unwrap();
+        let err = git_commit_impl(&path, "unsigned").unwrap_err();
+        assert!(
+            err.starts_with("Signing the commit with ssh-keygen failed"),
+            "{err}"
+        );
+        assert_eq!(repo.head().unwrap().target(), Some(head.id()));
+    }
+
+    #[test]
+    fn git_diff_impl_side_splits_a_both_sides_file() {
+        let dir = init_test_repo();
+        let repo_path = dir.path().to_str().unwrap();
+        commit_file(&dir, "file.txt", "v1\n", "init");
+        fs::write(dir.path().join("file.txt"), "v2\n").unwrap();
+        git_stage_impl(repo_path, &["file.txt".to_string()]).unwrap();
+        fs::write(dir.path().join("file.txt"), "v3\n").unwrap();
+
+        let staged = git_diff_impl(repo_path, "file.txt", Some("staged")).unwrap();
+        let unstaged = git_diff_impl(repo_path, "file.txt", Some("unstaged")).unwrap();
+        let combined = git_diff_impl(repo_path, "file.txt", None).unwrap();
+
+        assert_ne!(staged, unstaged);
+        assert!(staged.contains("-v1"), "staged={staged}");
+        assert!(staged.contains("+v2"), "staged={staged}");
+        assert!(!staged.contains("v3"), "staged={staged}");
+        assert!(unstaged.contains("-v2"), "unstaged={unstaged}");
+        assert!(unstaged.contains("+v3"), "unstaged={unstaged}");
+        assert!(combined.contains("-v1"), "combined={combined}");
+        assert!(combined.contains("+v3"), "combined={combined}");
+    }
+
+    #[test]
+    fn git_diff_impl_untracked_staged_is_empty() {
+        let dir = init_test_repo();
+        let repo_path = dir.path().to_str().unwrap();
+        fs::write(dir.path().join("new.txt"), "hi\n").unwrap();
+
+        let staged = git_diff_impl(repo_path, "new.txt", Some("staged")).unwrap();
+        let unstaged = git_diff_impl(repo_path, "new.txt", Some("unstaged")).unwrap();
+        assert!(
+            staged.is_empty(),
+            "staged untracked should be empty, got {staged}"
+        );
+        assert!(unstaged.contains("+hi"), "unstaged={unstaged}");
+    }
+
+    /// Twenty numbered lines: far enough apart that edits at the top and the
+    /// bottom land in separate hunks.
+    fn numbered_lines() -> String {
+        (1..=20).map(|n| format!("line {n}\n")).collect()
+    }
+
+    fn whole_hunk(hunk: usize) -> GitHunkSelection {
+        GitHunkSelection { hunk, lines: None }
+    }
+
+    fn index_text(repo_path: &str, file: &str) -> Option<String> {
+        blob_text_in_index(&Repository::open(repo_path).unwrap(), file)
+    }
+
+    #[test]
+    fn stage_hunks_stages_one_hunk_and_leaves_the_other_unstaged() {
+        let dir = init_test_repo();
+        let repo_path = dir.path().to_str().unwrap();
+        let original = numbered_lines();
+        commit_file(&dir, "file.txt", &original, "init");
+        let edited = original
+            .replace("line 2\n", "line two\n")
+            .replace("line 19\n", "line nineteen\n");
+        fs::write(dir.path().join("file.txt"), &edited).unwrap();
+
+        git_stage_hunks_impl(repo_path, "file.txt", &[whole_hunk(1)]).unwrap();
+
+        let staged = git_diff_impl(repo_path, "file.txt", Some("staged")).unwrap();
+        let unstaged = git_diff_impl(repo_path, "file.txt", Some("unstaged")).unwrap();
+        assert!(staged.contains("+line nineteen"), "staged={staged}");
+        assert!(!staged.contains("line two"), "staged={staged}");
+        assert!(unstaged.contains("+line two"), "unstaged={unstaged}");
+        assert!(!unstaged.contains("nineteen"), "unstaged={unstaged}");
+    }
+
+    #[test]
+    fn stage_hunks_stages_single_lines_of_a_hunk() {
+        let dir = init_test_repo();
+        let repo_path = dir.path().to_str().unwrap();
+        commit_file(&dir, "file.txt", "a\nb\nc\n", "init");
+        fs::write(dir.path().join("file.txt"), "a\nB\nc\nd\n").unwrap();
+        // Printed hunk body: " a", "-b", "+B", " c", "+d".
+        let selection = GitHunkSelection {
+            hunk: 0,
+            lines: Some(vec![4]),
+        };
+
+        git_stage_hunks_impl(repo_path, "file.txt", &[selection]).unwrap();
+
+        assert_eq!(
+            index_text(repo_path, "file.txt").as_deref(),
+            Some("a\nb\nc\nd\n")
+        );
+        let unstaged = git_diff_impl(repo_path, "file.txt", Some("unstaged")).unwrap();
+        assert!(unstaged.contains("-b"), "unstaged={unstaged}");
+        assert!(unstaged.contains("+B"), "unstaged={unstaged}");
+    }
+
+    #[test]
+    fn hunk_staging_round_trips_an_untracked_file() {
+        let dir = init_test_repo();
+        let repo_path = dir.path().to_str().unwrap();
+        commit_file(&dir, "keep.txt", "k\n", "init");
+        fs::write(dir.path().join("new.txt"), "one\ntwo\nthree\n").unwrap();
+        let selection = GitHunkSelection {
+            hunk: 0,
+            lines: Some(vec![0, 2]),
+        };
+
+        git_stage_hunks_impl(repo_path, "new.txt", &[selection]).unwrap();
+        assert_eq!(
+            index_text(repo_path, "new.txt").as_deref(),
+            Some("one\nthree\n")
+        );
+        let unstaged = git_diff_impl(repo_path, "new.txt", Some("unstaged")).unwrap();
+        assert!(unstaged.contains("+two"), "unstaged={unstaged}");
+
+        git_unstage_hunks_impl(repo_path, "new.txt", &[whole_hunk(0)]).unwrap();
+        assert_eq!(index_text(repo_path, "new.txt"), None);
+        let rows = git_status_impl(repo_path).unwrap();
+        assert_eq!(find_status(&rows, "new.txt").staged, None);
+    }
+
+    #[test]
+    fn stage_hunks_shifts_later_hunks_past_the_lines_it_staged() {
+        let dir = init_test_repo();
+        let repo_path = dir.path().to_str().unwrap();
+        let original = numbered_lines();
+        commit_file(&dir, "file.txt", &original, "init");
+        let edited = original
+            .replace("line 2\n", "line 2\nnew a\nnew b\n")
+            .replace("line 19\n", "line nineteen\n")
+            .replace("line 20\n", "line 20");
+        fs::write(dir.path().join("file.txt"), &edited).unwrap();
+        // Hunk 0: " line 1", " line 2", "+new a", "+new b", " line 3", ...
+        let selections = [
+            GitHunkSelection {
+                hunk: 0,
+                lines: Some(vec![3]),
+            },
+            whole_hunk(1),
+        ];
+
+        git_stage_hunks_impl(repo_path, "file.txt", &selections).unwrap();
+
+        assert_eq!(
+            index_text(repo_path, "file.txt").unwrap(),
+            original
+                .replace("line 2\n", "line 2\nnew b\n")
+                .replace("line 19\n", "line nineteen\n")
+                .replace("line 20\n", "line 20")
+        );
+    }
+
+    #[test]
+    fn stage_hunks_stages_through_the_clean_filters() {
+        let dir = init_test_repo();
+        let repo_path = dir.path().to_str().unwrap();
+        let original = numbered_lines();
+        commit_file(&dir, "file.txt", &original, "init");
+        Repository::open(repo_path)
+            .unwrap()
+            .config()
+            .unwrap()
+            .set_bool("core.autocrlf", true)
+            .unwrap();
+        let edited = original
+            .replace("line 2\n", "line two\n")
+            .replace("line 19\n", "line nineteen\n")
+            .replace('\n', "\r\n");
+        fs::write(dir.path().join("file.txt"), &edited).unwrap();
+
+        git_stage_hunks_impl(repo_path, "file.txt", &[whole_hunk(1)]).unwrap();
+
+        assert_eq!(
+            index_text(repo_path, "file.txt").unwrap(),
+            original.replace("line 19\n", "line nineteen\n")
+        );
+    }
+
+    #[test]
+    fn stage_hunks_stages_an_empty_untracked_file_whole() {
+        let dir = init_test_repo();
+        let repo_path = dir.path().to_str().unwrap();
+        commit_file(&dir, "keep.txt", "k\n", "init");
+        fs::write(dir.path().join("empty.txt"), "").unwrap();
+
+        git_stage_hunks_impl(repo_path, "empty.txt", &[]).unwrap();
+
+        assert_eq!(index_text(repo_path, "empty.txt").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn unstage_hunks_keeps_the_workdir_change() {
+        let dir = init_test_repo();
+        let repo_path = dir.path().to_str().unwrap();
+        let original = numbered_lines();
+        commit_file(&dir, "file.txt", &original, "init");
+        let edited = original
+            .replace("line 2\n", "line two\n")
+            .replace("line 19\n", "line nineteen\n");
+        fs::write(dir.path().join("file.txt"), &edited).unwrap();
+        git_stage_impl(repo_path, &["file.txt".to_string()]).unwrap();
+
+        git_unstage_hunks_impl(repo_path, "file.txt", &[whole_hunk(0)]).unwrap();
+
+        assert_eq!(
+            index_text(repo_path, "file.txt").unwrap(),
+            original.replace("line 19\n", "line nineteen\n")
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("file.txt")).unwrap(),
+            edited
+        );
+    }
+
+    #[test]
+    fn discard_hunks_reverts_only_the_selected_change() {
         let dir = init_test_repo();
         let repo_path = dir.path().to_str().unwrap();
-        commit_file(&dir, "gone.txt", "bye\n", "init");
-        fs::remove_file(dir.path().join("gone.txt")).unwrap();
+        let original = numbered_lines();
+        commit_file(&dir, "file.txt", &original, "init");
+        let edited = original
+            .replace("line 2\n", "line two\n")
+            .replace("line 19\n", "line nineteen\n");
+        fs::write(dir.path().join("file.txt"), &edited).unwrap();
 
-        git_stage_impl(repo_path, &["gone.txt".to_string()]).unwrap();
+        git_discard_hunks_impl(repo_path, "file.txt", &[whole_hunk(0)]).unwrap();
 
-        let rows = git_status_impl(repo_path).unwrap();
-        let row = find_status(&rows, "gone.txt");
-        assert_eq!(row.staged.as_deref(), Some("deleted"));
+        assert_eq!(
+            fs::read_to_string(dir.path().join("file.txt")).unwrap(),
+            original.replace("line 19\n", "line nineteen\n")
+        );
     }
 
     #[test]
-    fn git_stage_impl_includes_deletion_in_a_multi_path_call() {
+    fn hunk_selection_out_of_range_is_an_error() {
         let dir = init_test_repo();
         let repo_path = dir.path().to_str().unwrap();
-        commit_file(&dir, "keep.txt", "a\n", "one");
-        commit_file(&dir, "gone.txt", "b\n", "two");
-        fs::write(dir.path().join("keep.txt"), "aa\n").unwrap();
-        fs::remove_file(dir.path().join("gone.txt")).unwrap();
+        commit_file(&dir, "file.txt", "a\n", "init");
+        fs::write(dir.path().join("file.txt"), "b\n").unwrap();
 
-        git_stage_impl(repo_path, &["keep.txt".to_string(), "gone.txt".to_string()]).unwrap();
+        let err = git_stage_hunks_impl(repo_path, "file.txt", &[whole_hunk(3)]).unwrap_err();
+        assert!(err.contains("out of range"), "err={err}");
+        assert_eq!(index_text(repo_path, "file.txt").as_deref(), Some("a\n"));
+    }
 
-        let rows = git_status_impl(repo_path).unwrap();
+    #[test]
+    fn stash_save_parks_tracked_and_untracked_changes_until_popped() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        fs::write(dir.path().join("a.txt"), "changed").unwrap();
+        fs::write(dir.path().join("new.txt"), "fresh\n").unwrap();
+
+        let oid = git_stash_save_impl(&path, Some("park it"), true).unwrap();
+
+        assert!(!repo_is_dirty(dir.path()));
+        let stashes = git_stash_list_impl(&path).unwrap();
+        assert_eq!(stashes.len(), 1);
+        assert_eq!(stashes[0].oid, oid);
+        assert_eq!(stashes[0].index, 0);
+        assert_eq!(stashes[0].branch, Some(current_branch_name(&path)));
+        assert!(stashes[0].message.ends_with("park it"));
+        let shown = git_stash_show_impl(&path, &oid).unwrap();
+        assert!(shown.contains("+changed"), "shown={shown}");
+        assert!(shown.contains("+fresh"), "shown={shown}");
+
+        let result = git_stash_apply_impl(&path, &oid, true).unwrap();
+        assert!(result.dropped);
+        assert!(result.conflicts.is_empty());
         assert_eq!(
-            find_status(&rows, "keep.txt").staged.as_deref(),
-            Some("modified")
+            fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "changed"
         );
+        assert!(dir.path().join("new.txt").is_file());
+        assert!(git_stash_list_impl(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn stash_save_with_nothing_to_stash_says_so() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
         assert_eq!(
-            find_status(&rows, "gone.txt").staged.as_deref(),
-            Some("deleted")
+            git_stash_save_impl(&path, None, true).unwrap_err(),
+            "Nothing to stash"
         );
     }
 
     #[test]
-    fn git_commit_impl_refuses_a_clean_index() {
+    fn stash_apply_keeps_the_stash_and_drop_removes_it() {
         let dir = TempDir::new().unwrap();
         let path = committed_repo(&dir);
-        let err = git_commit_impl(&path, "empty").unwrap_err();
-        assert_eq!(err, "Nothing to commit");
+        fs::write(dir.path().join("a.txt"), "changed").unwrap();
+        let oid = git_stash_save_impl(&path, None, true).unwrap();
+
+        let result = git_stash_apply_impl(&path, &oid, false).unwrap();
+        assert!(!result.dropped);
+        assert_eq!(git_stash_list_impl(&path).unwrap().len(), 1);
+
+        git_stash_drop_impl(&path, &oid).unwrap();
+        assert!(git_stash_list_impl(&path).unwrap().is_empty());
+        let err = git_stash_drop_impl(&path, &oid).unwrap_err();
+        assert!(err.contains("not found"), "err={err}");
     }
 
     #[test]
-    fn git_diff_impl_side_splits_a_both_sides_file() {
-        let dir = init_test_repo();
-        let repo_path = dir.path().to_str().unwrap();
-        commit_file(&dir, "file.txt", "v1\n", "init");
-        fs::write(dir.path().join("file.txt"), "v2\n").unwrap();
-        git_stage_impl(repo_path, &["file.txt".to_string()]).unwrap();
-        fs::write(dir.path().join("file.txt"), "v3\n").unwrap();
+    fn stash_pop_that_conflicts_keeps_the_stash() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        fs::write(dir.path().join("a.txt"), "stashed").unwrap();
+        let oid = git_stash_save_impl(&path, None, true).unwrap();
+        commit_in(&path, "a.txt", "committed", "diverge");
 
-        let staged = git_diff_impl(repo_path, "file.txt", Some("staged")).unwrap();
-        let unstaged = git_diff_impl(repo_path, "file.txt", Some("unstaged")).unwrap();
-        let combined = git_diff_impl(repo_path, "file.txt", None).unwrap();
+        let result = git_stash_apply_impl(&path, &oid, true).unwrap();
 
-        assert_ne!(staged, unstaged);
-        assert!(staged.contains("-v1"), "staged={staged}");
-        assert!(staged.contains("+v2"), "staged={staged}");
-        assert!(!staged.contains("v3"), "staged={staged}");
-        assert!(unstaged.contains("-v2"), "unstaged={unstaged}");
-        assert!(unstaged.contains("+v3"), "unstaged={unstaged}");
-        assert!(combined.contains("-v1"), "combined={combined}");
-        assert!(combined.contains("+v3"), "combined={combined}");
+        assert!(!result.dropped);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].path, "a.txt");
+        assert_eq!(git_stash_list_impl(&path).unwrap().len(), 1);
     }
 
     #[test]
-    fn git_diff_impl_untracked_staged_is_empty() {
-        let dir = init_test_repo();
-        let repo_path = dir.path().to_str().unwrap();
-        fs::write(dir.path().join("new.txt"), "hi\n").unwrap();
-
-        let staged = git_diff_impl(repo_path, "new.txt", Some("staged")).unwrap();
-        let unstaged = git_diff_impl(repo_path, "new.txt", Some("unstaged")).unwrap();
-        assert!(
-            staged.is_empty(),
-            "staged untracked should be empty, got {staged}"
+    fn stash_list_is_scoped_to_the_worktree_that_made_it() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        let wt = git_worktree_add_impl(&path, "stasher").unwrap();
+        fs::write(Path::new(&wt.path).join("a.txt"), "agent work").unwrap();
+        let agent_stash = git_stash_save_impl(&wt.path, Some("agent"), true).unwrap();
+        fs::write(dir.path().join("a.txt"), "user work").unwrap();
+        let user_stash = git_stash_save_impl(&path, Some("user"), true).unwrap();
+
+        let in_main = git_stash_list_impl(&path).unwrap();
+        let in_worktree = git_stash_list_impl(&wt.path).unwrap();
+
+        assert_eq!(in_main.len(), 1);
+        assert_eq!(in_main[0].oid, user_stash);
+        assert_eq!(in_worktree.len(), 1);
+        assert_eq!(in_worktree[0].oid, agent_stash);
+        // The worktree's stash now sits below the user's in the shared ref.
+        assert_eq!(in_worktree[0].index, 1);
+        assert!(git_stash_drop_impl(&path, &agent_stash).is_err());
+
+        git_stash_apply_impl(&wt.path, &agent_stash, true).unwrap();
+        assert_eq!(
+            fs::read_to_string(Path::new(&wt.path).join("a.txt")).unwrap(),
+            "agent work"
         );
-        assert!(unstaged.contains("+hi"), "unstaged={unstaged}");
+        assert_eq!(git_stash_list_impl(&path).unwrap()[0].oid, user_stash);
+        git_worktree_remove_impl(&path, &wt.path, true).unwrap();
     }
 
     fn current_branch_name(repo_path: &str) -> String {
@@ -2379,6 +6069,141 @@ mod tests {
         assert_eq!(remote_names, sorted_remotes);
     }
 
+    #[test]
+    fn branch_create_starts_from_any_revision_and_tracks_a_remote_start() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        let first = Repository::open(&path)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap();
+        commit_in(&path, "b.txt", "b\n", "second");
+
+        let created = git_branch_create_impl(&path, "old", Some(&first.to_string())).unwrap();
+        assert_eq!(created.name, "old");
+        assert!(!created.is_current);
+        let repo = Repository::open(&path).unwrap();
+        assert_eq!(branch_commit_id(&repo, "old").unwrap(), first);
+
+        let err = git_branch_create_impl(&path, "old", None).unwrap_err();
+        assert!(err.contains("already exists"), "{err}");
+        let err = git_branch_create_impl(&path, "bad..name", None).unwrap_err();
+        assert!(err.contains("not a valid branch name"), "{err}");
+
+        Repository::open(&path)
+            .unwrap()
+            .remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        add_remote_tracking(&path, "origin/feature");
+        git_branch_create_impl(&path, "feature", Some("origin/feature")).unwrap();
+        let feature = repo.find_branch("feature", BranchType::Local).unwrap();
+        assert_eq!(
+            feature.upstream().unwrap().name().unwrap(),
+            Some("origin/feature")
+        );
+    }
+
+    #[test]
+    fn branch_checkout_refuses_a_dirty_tree_and_names_the_files() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        let original = current_branch_name(&path);
+        git_branch_create_impl(&path, "topic", None).unwrap();
+        fs::write(dir.path().join("a.txt"), "changed").unwrap();
+
+        let err = git_branch_checkout_impl(&path, "topic").unwrap_err();
+        assert!(err.contains("uncommitted changes in a.txt"), "{err}");
+        assert_eq!(current_branch_name(&path), original);
+
+        fs::write(dir.path().join("a.txt"), "hi").unwrap();
+        git_branch_checkout_impl(&path, "topic").unwrap();
+        assert_eq!(current_branch_name(&path), "topic");
+        assert!(git_branch_checkout_impl(&path, "nope").is_err());
+    }
+
+    #[test]
+    fn branch_checkout_refuses_a_branch_held_by_another_worktree() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        let wt = git_worktree_add_impl(&path, "held").unwrap();
+        let branch = wt.branch.unwrap();
+
+        let err = git_branch_checkout_impl(&path, &branch).unwrap_err();
+        assert!(err.contains("another worktree"), "{err}");
+        let err = git_branch_delete_impl(&path, &branch, true).unwrap_err();
+        assert!(err.contains("another worktree"), "{err}");
+        let err = git_branch_rename_impl(&path, &branch, "mine").unwrap_err();
+        assert!(err.contains("cannot be renamed"), "{err}");
+    }
+
+    #[test]
+    fn branch_rename_moves_head_along_with_the_current_branch() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        let original = current_branch_name(&path);
+        git_branch_create_impl(&path, "other", None).unwrap();
+
+        let err = git_branch_rename_impl(&path, &original, "other").unwrap_err();
+        assert!(err.contains("already exists"), "{err}");
+
+        git_branch_rename_impl(&path, &original, "trunk").unwrap();
+        assert_eq!(current_branch_name(&path), "trunk");
+        let repo = Repository::open(&path).unwrap();
+        assert!(!local_branch_exists(&repo, &original));
+    }
+
+    #[test]
+    fn branch_delete_keeps_unmerged_work_unless_forced() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        let original = current_branch_name(&path);
+        git_branch_create_impl(&path, "merged", None).unwrap();
+        git_branch_create_impl(&path, "topic", None).unwrap();
+        git_branch_checkout_impl(&path, "topic").unwrap();
+        commit_in(&path, "b.txt", "b\n", "topic work");
+        git_branch_checkout_impl(&path, &original).unwrap();
+
+        let err = git_branch_delete_impl(&path, &original, false).unwrap_err();
+        assert!(err.contains("current branch"), "{err}");
+        let err = git_branch_delete_impl(&path, "topic", false).unwrap_err();
+        assert!(err.contains("1 commit(s) would be lost"), "{err}");
+
+        git_branch_delete_impl(&path, "merged", false).unwrap();
+        git_branch_delete_impl(&path, "topic", true).unwrap();
+        let repo = Repository::open(&path).unwrap();
+        assert!(!local_branch_exists(&repo, "merged"));
+        assert!(!local_branch_exists(&repo, "topic"));
+    }
+
+    #[test]
+    fn branch_set_upstream_sets_and_clears_tracking() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        let current = current_branch_name(&path);
+        Repository::open(&path)
+            .unwrap()
+            .remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        add_remote_tracking(&path, "origin/review");
+
+        let err = git_branch_set_upstream_impl(&path, &current, Some("origin/nope")).unwrap_err();
+        assert!(err.contains("No remote branch named origin/nope"), "{err}");
+
+        git_branch_set_upstream_impl(&path, &current, Some("origin/review")).unwrap();
+        let repo = Repository::open(&path).unwrap();
+        let branch = repo.find_branch(&current, BranchType::Local).unwrap();
+        assert_eq!(
+            branch.upstream().unwrap().name().unwrap(),
+            Some("origin/review")
+        );
+
+        git_branch_set_upstream_impl(&path, &current, None).unwrap();
+        let branch = repo.find_branch(&current, BranchType::Local).unwrap();
+        assert!(branch.upstream().is_err());
+    }
+
     #[test]
     fn git_list_branches_is_empty_for_non_repo() {
         let plain = TempDir::new().unwrap();
@@ -3395,18 +7220,313 @@ mod tests {
         );
     }
 
+    /// A worktree whose `a.txt` edit collides with one on main, merged:
+    /// `(repo_dir, repo_path, worktree, result)` with the merge waiting in the
+    /// worktree.
+    fn conflicted_worktree_merge() -> (TempDir, String, GitWorktree, WorktreeMergeResult) {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        force_default_branch(&path, "main");
+        let wt = git_worktree_add_impl(&path, "clash").unwrap();
+        commit_in(&path, "a.txt", "on main\n", "main edit");
+        commit_in(&wt.path, "a.txt", "on worktree\n", "wt edit");
+        let result = git_worktree_merge_into_default_impl(&path, &wt.path, None).unwrap();
+        (dir, path, wt, result)
+    }
+
+    #[test]
+    fn worktree_merge_reports_conflicts_and_keeps_the_worktree() {
+        let (_dir, path, wt, result) = conflicted_worktree_merge();
+
+        assert!(!result.merged);
+        assert!(!result.cleaned_up);
+        assert_eq!(
+            result.conflicts,
+            vec![GitMergeConflict {
+                path: "a.txt".to_string(),
+                kind: "both-modified".to_string(),
+                base: Some("hi".to_string()),
+                ours: Some("on main\n".to_string()),
+                theirs: Some("on worktree\n".to_string()),
+                binary: false,
+            }]
+        );
+        assert!(Path::new(&wt.path).exists());
+        let in_worktree = fs::read_to_string(Path::new(&wt.path).join("a.txt")).unwrap();
+        assert!(in_worktree.contains("<<<<<<< main"), "{in_worktree}");
+        assert_eq!(
+            git_worktree_merge_conflicts_impl(&wt.path).unwrap(),
+            result.conflicts
+        );
+        // The user's checkout is untouched until the merge is finished.
+        assert_eq!(
+            fs::read_to_string(Path::new(&path).join("a.txt")).unwrap(),
+            "on main\n"
+        );
+        let err = git_worktree_merge_into_default_impl(&path, &wt.path, None).unwrap_err();
+        assert!(err.contains("finish or abort"), "{err}");
+    }
+
+    #[test]
+    fn worktree_merge_finishes_after_every_conflict_is_resolved() {
+        let (_dir, path, wt, _) = conflicted_worktree_merge();
+        let err = git_worktree_merge_finish_impl(&path, &wt.path).unwrap_err();
+        assert!(err.contains("a.txt"), "{err}");
+
+        let remaining = git_worktree_resolve_conflict_impl(
+            &wt.path,
+            "a.txt",
+            &GitConflictResolution::Merged {
+                content: "both\n".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(remaining.is_empty());
+        let result = git_worktree_merge_finish_impl(&path, &wt.path).unwrap();
+
+        assert!(result.merged);
+        assert!(!result.fast_forward);
+        assert!(result.cleaned_up);
+        assert!(!Path::new(&wt.path).exists());
+        assert_eq!(
+            fs::read_to_string(Path::new(&path).join("a.txt")).unwrap(),
+            "both\n"
+        );
+        let repo = Repository::open(&path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 2);
+        let expected = format!("Merge branch '{}' into main", wt.branch.unwrap());
+        assert_eq!(head.summary(), Some(expected.as_str()));
+    }
+
+    #[test]
+    fn worktree_merge_resolve_takes_a_side() {
+        let (_dir, _path, wt, _) = conflicted_worktree_merge();
+
+        git_worktree_resolve_conflict_impl(&wt.path, "a.txt", &GitConflictResolution::Theirs)
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(Path::new(&wt.path).join("a.txt")).unwrap(),
+            "on worktree\n"
+        );
+        let err =
+            git_worktree_resolve_conflict_impl(&wt.path, "a.txt", &GitConflictResolution::Ours)
+                .unwrap_err();
+        assert!(err.contains("not conflicted"), "{err}");
+    }
+
+    #[test]
+    fn worktree_merge_abort_restores_the_worktree_branch() {
+        let (_dir, path, wt, _) = conflicted_worktree_merge();
+
+        git_worktree_merge_abort_impl(&wt.path).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(Path::new(&wt.path).join("a.txt")).unwrap(),
+            "on worktree\n"
+        );
+        assert!(!repo_is_dirty(Path::new(&wt.path)));
+        assert!(git_worktree_merge_conflicts_impl(&wt.path).is_err());
+        git_worktree_remove_impl(&path, &wt.path, true).unwrap();
+    }
+
+    fn worktree_head(worktree_path: &str) -> git2::Oid {
+        Repository::open(worktree_path)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap()
+    }
+
+    #[test]
+    fn worktree_rebase_is_a_no_op_when_main_has_not_moved() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        force_default_branch(&path, "main");
+        let wt = git_worktree_add_impl(&path, "fresh").unwrap();
+        commit_in(&wt.path, "b.txt", "b", "wt work");
+
+        let result = git_worktree_rebase_onto_default_impl(&path, &wt.path).unwrap();
+
+        assert_eq!(result.outcome, "up-to-date");
+        assert_eq!(result.default_branch, "main");
+        git_worktree_remove_impl(&path, &wt.path, true).unwrap();
+    }
+
+    #[test]
+    fn worktree_rebase_fast_forwards_a_branch_without_own_commits() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        force_default_branch(&path, "main");
+        let wt = git_worktree_add_impl(&path, "idle").unwrap();
+        commit_in(&path, "b.txt", "b", "main moves on");
+
+        let result = git_worktree_rebase_onto_default_impl(&path, &wt.path).unwrap();
+
+        assert_eq!(result.outcome, "fast-forward");
+        assert!(Path::new(&wt.path).join("b.txt").is_file());
+        git_worktree_remove_impl(&path, &wt.path, true).unwrap();
+    }
+
+    #[test]
+    fn worktree_rebase_replays_the_branch_onto_main() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        force_default_branch(&path, "main");
+        let wt = git_worktree_add_impl(&path, "drift").unwrap();
+        commit_in(&path, "b.txt", "b", "main moves on");
+        commit_in(&wt.path, "c.txt", "c", "wt work");
+
+        let result = git_worktree_rebase_onto_default_impl(&path, &wt.path).unwrap();
+
+        assert_eq!(result.outcome, "rebased");
+        assert_eq!(result.total, 1);
+        let worktree = Repository::open(&wt.path).unwrap();
+        let main_id = branch_commit_id(&worktree, "main").unwrap();
+        let tip = worktree_head(&wt.path);
+        assert_eq!(result.oid, Some(tip.to_string()));
+        assert!(worktree.graph_descendant_of(tip, main_id).unwrap());
+        assert!(worktree.head().unwrap().is_branch());
+        assert!(Path::new(&wt.path).join("b.txt").is_file());
+        assert!(Path::new(&wt.path).join("c.txt").is_file());
+        git_worktree_remove_impl(&path, &wt.path, true).unwrap();
+    }
+
+    /// Turns on ssh commit signing for `path` with a fresh key; the key lives
+    /// as long as the returned dir.
+    fn enable_ssh_signing(path: &str) -> TempDir {
+        let keys = TempDir::new().unwrap();
+        let key = keys.path().join("id_ed25519");
+        let made = StdCommand::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+            .arg(&key)
+            .output()
+            .unwrap();
+        assert!(made.status.success());
+        let mut config = Repository::open(path).unwrap().config().unwrap();
+        config.set_bool("commit.gpgsign", true).unwrap();
+        config.set_str("gpg.format", "ssh").unwrap();
+        config
+            .set_str("user.signingkey", key.to_str().unwrap())
+            .unwrap();
+        keys
+    }
+
+    fn is_signed(repo: &Repository, oid: git2::Oid) -> bool {
+        repo.extract_signature(&oid, None).is_ok()
+    }
+
     #[test]
-    fn worktree_merge_refuses_a_conflict_and_keeps_the_worktree() {
+    fn worktree_merge_and_rebase_sign_the_commits_they_write() {
+        let dir = TempDir::new().unwrap();
+        let path = committed_repo(&dir);
+        force_default_branch(&path, "main");
+        let merged = git_worktree_add_impl(&path, "merged").unwrap();
+        let rebased = git_worktree_add_impl(&path, "rebased").unwrap();
+        commit_in(&path, "b.txt", "b", "main moves on");
+        commit_in(&merged.path, "c.txt", "c", "merged work");
+        commit_in(&rebased.path, "d.txt", "d", "rebased work");
+        let _keys = enable_ssh_signing(&path);
+
+        let result = git_worktree_rebase_onto_default_impl(&path, &rebased.path).unwrap();
+        assert_eq!(result.outcome, "rebased");
+        let worktree = Repository::open(&rebased.path).unwrap();
+        let tip = worktree_head(&rebased.path);
+        assert!(is_signed(&worktree, tip));
+        assert!(worktree.head().unwrap().is_branch());
+
+        let result = git_worktree_merge_into_default_impl(&path, &merged.path, None).unwrap();
+        assert!(result.merged);
+        assert!(!result.fast_forward);
+        let repo = Repository::open(&path).unwrap();
+        let main_id = branch_commit_id(&repo, "main").unwrap();
+        assert_eq!(repo.find_commit(main_id).unwrap().parent_count(), 2);
+        assert!(is_signed(&repo, main_id));
+        git_worktree_remove_impl(&path, &rebased.path, true).unwrap();
+    }
+
+    /// A worktree whose one commit conflicts with main, rebase started.
+    fn conflicted_worktree_rebase() -> (TempDir, String, GitWorktree, WorktreeRebaseResult) {
         let dir = TempDir::new().unwrap();
         let path = committed_repo(&dir);
         force_default_branch(&path, "main");
         let wt = git_worktree_add_impl(&path, "clash").unwrap();
-        commit_in(&path, "a.txt", "on main", "main edit");
-        commit_in(&wt.path, "a.txt", "on worktree", "wt edit");
+        commit_in(&path, "a.txt", "on main\n", "main edit");
+        commit_in(&wt.path, "a.txt", "on worktree\n", "wt edit");
+        let result = git_worktree_rebase_onto_default_impl(&path, &wt.path).unwrap();
+        (dir, path, wt, result)
+    }
+
+    #[test]
+    fn worktree_rebase_stops_on_a_conflicting_commit_and_continues() {
+        let (_dir, path, wt, result) = conflicted_worktree_rebase();
+
+        assert_eq!(result.outcome, "conflict");
+        assert_eq!(
+            result.conflict_commit.as_ref().map(|c| c.summary.as_str()),
+            Some("wt edit")
+        );
+        assert_eq!((result.step, result.total), (1, 1));
+        assert_eq!(result.conflicts[0].path, "a.txt");
+        let err = git_worktree_rebase_continue_impl(&path, &wt.path).unwrap_err();
+        assert!(err.contains("a.txt"), "{err}");
+
+        fs::write(Path::new(&wt.path).join("a.txt"), "both\n").unwrap();
+        git_stage_impl(&wt.path, &["a.txt".to_string()]).unwrap();
+        let result = git_worktree_rebase_continue_impl(&path, &wt.path).unwrap();
+
+        assert_eq!(result.outcome, "rebased");
+        let worktree = Repository::open(&wt.path).unwrap();
+        let tip = worktree.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(tip.summary(), Some("wt edit"));
+        assert_eq!(
+            tip.parent_id(0).unwrap(),
+            branch_commit_id(&worktree, "main").unwrap()
+        );
+        assert_eq!(worktree.state(), git2::RepositoryState::Clean);
+        git_worktree_remove_impl(&path, &wt.path, true).unwrap();
+    }
+
+    #[test]
+    fn worktree_rebase_skip_drops_the_conflicting_commit() {
+        let (_dir, path, wt, _) = conflicted_worktree_rebase();
+
+        let result = git_worktree_rebase_skip_impl(&path, &wt.path).unwrap();
 
+        assert_eq!(result.outcome, "rebased");
+        let worktree = Repository::open(&wt.path).unwrap();
+        assert_eq!(
+            worktree_head(&wt.path),
+            branch_commit_id(&worktree, "main").unwrap()
+        );
+        assert_eq!(
+            fs::read_to_string(Path::new(&wt.path).join("a.txt")).unwrap(),
+            "on main\n"
+        );
+        git_worktree_remove_impl(&path, &wt.path, true).unwrap();
+    }
+
+    #[test]
+    fn worktree_rebase_abort_restores_the_branch() {
+        let (_dir, path, wt, _) = conflicted_worktree_rebase();
         let err = git_worktree_merge_into_default_impl(&path, &wt.path, None).unwrap_err();
-        assert!(err.contains("conflict"), "{err}");
-        assert!(Path::new(&wt.path).exists());
+        assert!(err.contains("rebase"), "{err}");
+
+        git_worktree_rebase_abort_impl(&wt.path).unwrap();
+
+        let worktree = Repository::open(&wt.path).unwrap();
+        assert_eq!(worktree.state(), git2::RepositoryState::Clean);
+        assert_eq!(
+            worktree.head().unwrap().peel_to_commit().unwrap().summary(),
+            Some("wt edit")
+        );
+        assert_eq!(
+            fs::read_to_string(Path::new(&wt.path).join("a.txt")).unwrap(),
+            "on worktree\n"
+        );
+        git_worktree_remove_impl(&path, &wt.path, true).unwrap();
     }
 
     #[test]
@@ -25,7 +25,7 @@
 //! and `.auric/.gitignore` is `*`, so nothing meant to be durable or tracked
 //! ever depends on their absence.
 
-use crate::database::apply_migration;
+use crate::database::{apply_migration, PM_VERSION_STAMP_SQL};
 use rusqlite::{params, Connection, OpenFlags};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -416,10 +416,12 @@ fn apply_patch_to_ticket(
     }
 
     conn.execute(
-        "UPDATE pm_tickets
-         SET name = ?1, description = ?2, priority = ?3, due_date = ?4,
-             updated_at = datetime('now')
-         WHERE id = ?5",
+        &format!(
+            "UPDATE pm_tickets
+             SET name = ?1, description = ?2, priority = ?3, due_date = ?4,
+                 updated_at = {PM_VERSION_STAMP_SQL}
+             WHERE id = ?5"
+        ),
         params![
             next_name,
             next_description,
@@ -747,7 +749,9 @@ fn write_ticket_attachments(
     let context_json = serde_json::to_string(&next)
         .map_err(|e| format!("Failed to serialize ticket context: {}", e))?;
     conn.execute(
-        "UPDATE pm_tickets SET context = ?1, updated_at = datetime('now') WHERE id = ?2",
+        &format!(
+            "UPDATE pm_tickets SET context = ?1, updated_at = {PM_VERSION_STAMP_SQL} WHERE id = ?2"
+        ),
         params![context_json, ticket_id],
     )
     .map_err(|e| format!("Failed to write ticket attachments: {}", e))?;
@@ -1,5 +1,13 @@
+#[cfg(unix)]
+mod agent_api;
+mod agent_events;
 mod agent_log;
+mod agent_output;
 mod agent_persistence;
+mod agent_queue;
+mod agent_sandbox;
+mod agent_sessions;
+mod agent_transcripts;
 mod agents;
 mod app_config;
 mod cc_usage;
@@ -30,14 +38,20 @@ mod webview_prefs;
 
 use agents::AgentManagerState;
 use database::{
-    BlueprintState, DatabaseState, GoalsState, GoalsSyncPayload, KvEntry, PmSavePayload, PmState,
-    RequirementsState,
+    AgentCostSummary, BlueprintState, DatabaseState, GoalsState, GoalsSyncPayload, KvEntry,
+    PmSavePayload, PmState, PmSyncPayload, PmSyncResult, RequirementsState,
 };
 use git::{
-    git_blame, git_branch_info, git_commit, git_default_branch, git_diff, git_diff_commit,
-    git_diff_file_ref, git_diff_ref_files, git_discard, git_discover_repos, git_list_branches,
-    git_log_since, git_projects_dirty, git_push, git_stage, git_status, git_unstage,
-    git_worktree_add, git_worktree_list, git_worktree_merge_into_default, git_worktree_remove,
+    git_blame, git_branch_checkout, git_branch_create, git_branch_delete, git_branch_info,
+    git_branch_rename, git_branch_set_upstream, git_commit, git_default_branch, git_diff,
+    git_diff_commit, git_diff_file_ref, git_diff_ref_files, git_discard, git_discard_hunks,
+    git_discover_repos, git_fetch, git_list_branches, git_log, git_log_since, git_projects_dirty,
+    git_pull, git_push, git_stage, git_stage_hunks, git_stash_apply, git_stash_drop,
+    git_stash_list, git_stash_pop, git_stash_save, git_stash_show, git_status, git_unstage,
+    git_unstage_hunks, git_worktree_add, git_worktree_list, git_worktree_merge_abort,
+    git_worktree_merge_conflicts, git_worktree_merge_finish, git_worktree_merge_into_default,
+    git_worktree_rebase_abort, git_worktree_rebase_continue, git_worktree_rebase_onto_default,
+    git_worktree_rebase_skip, git_worktree_remove, git_worktree_resolve_conflict,
 };
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
@@ -118,6 +132,68 @@ async fn check_cli_status(
     Ok(output.is_ok() && output.unwrap().status.success())
 }
 
+/// How long a provider's version command may take before the probe gives up.
+const PROVIDER_PROBE_TIMEOUT_SECS: u64 = 10;
+
+/// What is installed for a provider: its version against the config's
+/// minimum, where the executable resolves, and which optional capabilities
+/// that version supports.
+#[tauri::command]
+async fn probe_provider(
+    provider_id: String,
+    providers: tauri::State<'_, ProviderRegistryState>,
+) -> Result<providers::ProviderProbe, String> {
+    let provider = providers
+        .get(&provider_id)
+        .ok_or_else(|| format!("Unknown provider: {}", provider_id))?;
+    let vc = provider.version_check();
+
+    // Resolved by hand against the login-shell PATH, as agents are spawned
+    // with it — `Command` would search this process's PATH instead.
+    let env = agents::cached_login_shell_env().await;
+    let executable = if vc.command.contains('/') {
+        Some(std::path::PathBuf::from(&vc.command)).filter(|path| path.is_file())
+    } else {
+        env.iter()
+            .find(|(key, _)| key == "PATH")
+            .and_then(|(_, path)| mcp::find_in_path(&vc.command, path))
+    };
+
+    let output = match &executable {
+        None => Err("not found on the login-shell PATH".to_string()),
+        Some(executable) => {
+            let mut command = Command::new(executable);
+            command
+                .args(&vc.args)
+                .envs(env.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+                .kill_on_drop(true);
+            match tokio::time::timeout(
+                std::time::Duration::from_secs(PROVIDER_PROBE_TIMEOUT_SECS),
+                command.output(),
+            )
+            .await
+            {
+                Ok(Ok(output)) => Ok(providers::VersionOutput {
+                    success: output.status.success(),
+                    text: format!(
+                        "{}\n{}",
+                        String::from_utf8_lossy(&output.stdout),
+                        String::from_utf8_lossy(&output.stderr)
+                    ),
+                }),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err(format!("no answer within {}s", PROVIDER_PROBE_TIMEOUT_SECS)),
+            }
+        }
+    };
+
+    Ok(providers::probe_from(
+        provider.as_ref(),
+        executable.map(|path| path.to_string_lossy().into_owned()),
+        output,
+    ))
+}
+
 #[tauri::command]
 async fn watch_directory(
     path: String,
@@ -1105,19 +1181,55 @@ async fn spawn_agent(
     spawn_agent_with_session(config, &state, &terminal_state, &provider_state, app).await
 }
 
-/// Shared spawn path for `spawn_agent` and `resume_interrupted_agent`:
-/// spawns the PTY process and registers its terminal session.
+/// Shared spawn path for `spawn_agent` and `resume_interrupted_agent`: the
+/// spawn joins the queue, and starts right away if the concurrency caps allow.
+/// Otherwise it comes back `Queued` and `run_agent_queue` starts it later.
 async fn spawn_agent_with_session(
     config: agents::AgentConfig,
     state: &AgentManagerState,
     terminal_state: &TerminalState,
     provider_state: &ProviderRegistryState,
     app: tauri::AppHandle,
+) -> Result<agents::AgentInfo, String> {
+    let limits = agents::queue_limits(&app);
+    let (queued, ready) = agents::admit_agent(config, state, &limits).await?;
+
+    // This caller is waiting on its own start: a failure is its answer. The
+    // rest of the batch starts regardless — it has already left the queue.
+    let started = agent_queue::start_released(
+        ready,
+        &queued.id,
+        |spawn| {
+            start_agent_with_session(
+                spawn.id,
+                spawn.payload.config,
+                state,
+                terminal_state,
+                provider_state,
+                app.clone(),
+            )
+        },
+        |spawn| start_queued_agent(spawn, state, terminal_state, provider_state, &app),
+    )
+    .await;
+    started.unwrap_or(Ok(queued))
+}
+
+/// Starts an agent that has left the queue and registers its terminal
+/// session.
+async fn start_agent_with_session(
+    id: String,
+    config: agents::AgentConfig,
+    state: &AgentManagerState,
+    terminal_state: &TerminalState,
+    provider_state: &ProviderRegistryState,
+    app: tauri::AppHandle,
 ) -> Result<agents::AgentInfo, String> {
     // On natural termination the reaper drops the terminal session — without
     // this, every finished agent leaked its PTY master, writer, and FDs.
     let app_for_cleanup = app.clone();
-    let (info, writer, master) = agents::spawn_agent_impl(
+    let spawned = agents::spawn_agent_impl(
+        id.clone(),
         config,
         state,
         &app,
@@ -1128,7 +1240,14 @@ async fn spawn_agent_with_session(
             sessions.remove(&format!("agent-{}", agent_id));
         },
     )
-    .await?;
+    .await;
+    let (info, writer, master) = match spawned {
+        Ok(spawned) => spawned,
+        Err(error) => {
+            agents::abandon_start(state, &id).await;
+            return Err(error);
+        }
+    };
 
     // Register agent PTY writer and master in the global terminal state
     let session = Arc::new(AsyncMutex::new(TerminalSession {
@@ -1154,12 +1273,93 @@ async fn spawn_agent_with_session(
     Ok(info)
 }
 
+/// Starts a spawn that waited in the queue. Nobody is awaiting the result, so
+/// it is announced as an `agent-status` event either way.
+async fn start_queued_agent(
+    spawn: agent_queue::QueuedSpawn<agents::PendingSpawn>,
+    state: &AgentManagerState,
+    terminal_state: &TerminalState,
+    provider_state: &ProviderRegistryState,
+    app: &tauri::AppHandle,
+) {
+    let repo_path = spawn.payload.info.repo_path.clone();
+    let started = start_agent_with_session(
+        spawn.id.clone(),
+        spawn.payload.config,
+        state,
+        terminal_state,
+        provider_state,
+        app.clone(),
+    )
+    .await;
+    let (status, reason) = match started {
+        Ok(info) => {
+            // One that has already ended has announced that itself.
+            if !state.lock().await.agents.contains_key(&info.id) {
+                return;
+            }
+            (agents::AgentStatus::Running, None)
+        }
+        Err(error) => (agents::AgentStatus::Error, Some(error)),
+    };
+    let _ = app.emit(
+        "agent-status",
+        agents::AgentStatusEvent {
+            agent_id: spawn.id,
+            status,
+            exit_code: None,
+            repo_path,
+            reason,
+        },
+    );
+}
+
+/// How long the queue waits for a wakeup before looking again on its own —
+/// the caps are settings, and a raised one frees slots without any agent
+/// ending.
+const AGENT_QUEUE_RECHECK_SECS: u64 = 5;
+
+/// Promotes queued spawns for the lifetime of the app, whenever an agent ends
+/// or is killed.
+async fn run_agent_queue(app: tauri::AppHandle) {
+    let state = app.state::<AgentManagerState>().inner().clone();
+    let wakeup = state.lock().await.queue_wakeup.clone();
+    loop {
+        let _ = tokio::time::timeout(
+            std::time::Duration::from_secs(AGENT_QUEUE_RECHECK_SECS),
+            wakeup.notified(),
+        )
+        .await;
+        let limits = agents::queue_limits(&app);
+        for spawn in agents::take_ready_spawns(&state, &limits).await {
+            start_queued_agent(
+                spawn,
+                &state,
+                &app.state::<TerminalState>(),
+                &app.state::<ProviderRegistryState>(),
+                &app,
+            )
+            .await;
+        }
+    }
+}
+
 #[tauri::command]
 async fn kill_agent(
     agent_id: String,
     state: tauri::State<'_, AgentManagerState>,
     terminal_state: tauri::State<'_, TerminalState>,
     app: tauri::AppHandle,
+) -> Result<(), String> {
+    kill_agent_with_session(&agent_id, &state, &terminal_state, &app).await
+}
+
+/// Shared kill path for `kill_agent` and the agent API.
+async fn kill_agent_with_session(
+    agent_id: &str,
+    state: &AgentManagerState,
+    terminal_state: &TerminalState,
+    app: &tauri::AppHandle,
 ) -> Result<(), String> {
     // 1. Clean up terminal session first (prevents dangling handles)
     {
@@ -1168,7 +1368,145 @@ async fn kill_agent(
     }
 
     // 2. Kill the agent in the manager (handles removal and status emission)
-    agents::kill_agent_impl(&agent_id, &state, &app).await
+    agents::kill_agent_impl(agent_id, state, app).await
+}
+
+/// The agent's terminal session, for writing to its PTY or resizing it.
+async fn agent_session(
+    agent_id: &str,
+    state: &AgentManagerState,
+    terminal_state: &TerminalState,
+) -> Result<Arc<AsyncMutex<TerminalSession>>, String> {
+    agents::ensure_agent_running(agent_id, state).await?;
+    let sessions = terminal_state.sessions.lock().unwrap();
+    sessions
+        .get(&format!("agent-{}", agent_id))
+        .cloned()
+        .ok_or_else(|| format!("Agent {} has no terminal session", agent_id))
+}
+
+async fn write_to_agent(
+    agent_id: &str,
+    bytes: &[u8],
+    state: &AgentManagerState,
+    terminal_state: &TerminalState,
+) -> Result<(), String> {
+    let session = agent_session(agent_id, state, terminal_state).await?;
+    let mut session = session.lock().await;
+    let writer = session
+        .writer
+        .as_mut()
+        .ok_or_else(|| format!("Agent {} does not accept input", agent_id))?;
+    writer
+        .write_all(bytes)
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Failed to write to agent {}: {}", agent_id, e))
+}
+
+/// Sends a follow-up prompt, or any other text, to a running agent.
+/// `submit` (default on) presses Enter after it.
+#[tauri::command]
+async fn send_agent_input(
+    agent_id: String,
+    text: String,
+    submit: Option<bool>,
+    state: tauri::State<'_, AgentManagerState>,
+    terminal_state: tauri::State<'_, TerminalState>,
+) -> Result<(), String> {
+    let bytes = agents::agent_input_bytes(&text, submit.unwrap_or(true));
+    write_to_agent(&agent_id, &bytes, &state, &terminal_state).await
+}
+
+/// Ctrl-C: interrupts what the agent is doing without ending it.
+#[tauri::command]
+async fn interrupt_agent(
+    agent_id: String,
+    state: tauri::State<'_, AgentManagerState>,
+    terminal_state: tauri::State<'_, TerminalState>,
+) -> Result<(), String> {
+    write_to_agent(&agent_id, agents::INTERRUPT_BYTES, &state, &terminal_state).await
+}
+
+#[tauri::command]
+async fn pause_agent(
+    agent_id: String,
+    state: tauri::State<'_, AgentManagerState>,
+) -> Result<agents::AgentInfo, String> {
+    agents::set_agent_paused_impl(&agent_id, true, &state).await
+}
+
+#[tauri::command]
+async fn resume_agent(
+    agent_id: String,
+    state: tauri::State<'_, AgentManagerState>,
+) -> Result<agents::AgentInfo, String> {
+    agents::set_agent_paused_impl(&agent_id, false, &state).await
+}
+
+/// Fits the agent's PTY to the view showing it.
+#[tauri::command]
+async fn resize_agent(
+    agent_id: String,
+    rows: u16,
+    cols: u16,
+    state: tauri::State<'_, AgentManagerState>,
+    terminal_state: tauri::State<'_, TerminalState>,
+) -> Result<(), String> {
+    if rows == 0 || cols == 0 {
+        return Err("Agent terminal size must be at least 1x1".to_string());
+    }
+    let session = agent_session(&agent_id, &state, &terminal_state).await?;
+    let session = session.lock().await;
+    let master = session
+        .master
+        .as_ref()
+        .ok_or_else(|| format!("Agent {} has no terminal to resize", agent_id))?;
+    master
+        .resize(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("Failed to resize agent {}: {}", agent_id, e))
+}
+
+/// Replays an agent's output from `since_seq` (or from the start), for a view
+/// that opened after the agent began printing.
+#[tauri::command]
+async fn get_agent_output(
+    agent_id: String,
+    since_seq: Option<u64>,
+    state: tauri::State<'_, AgentManagerState>,
+) -> Result<agent_output::AgentOutputPage, String> {
+    agents::get_agent_output_impl(&agent_id, since_seq.unwrap_or(0), &state).await
+}
+
+/// Finished runs of the project's agents, newest first, narrowed by agent,
+/// ticket, goal and text.
+#[tauri::command]
+async fn search_agent_transcripts(
+    project_path: String,
+    query: agent_transcripts::TranscriptQuery,
+) -> Result<Vec<agent_transcripts::TranscriptHit>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        agent_transcripts::search_project(std::path::Path::new(&project_path), &query)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The full output of one finished run, as the terminal received it.
+#[tauri::command]
+async fn get_agent_transcript(
+    project_path: String,
+    transcript_id: String,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        agent_transcripts::load_project(std::path::Path::new(&project_path), &transcript_id)
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
@@ -1219,10 +1557,24 @@ async fn resume_interrupted_agent(
             .ok_or_else(|| format!("Interrupted agent not found: {}", agent_id))?
     };
 
+    // With the provider's own session to go back into, the agent keeps its
+    // whole conversation and only needs telling that it was cut off. Without
+    // one, it starts over and is told the task again.
+    let resume_session_id = persisted.session_id.filter(|_| {
+        provider_state
+            .get(&persisted.provider)
+            .is_some_and(|provider| provider.session_support().resumes)
+    });
+    let task = if resume_session_id.is_some() {
+        agents::RESUMED_SESSION_PROMPT.to_string()
+    } else {
+        agents::resume_task_prompt(&persisted.task)
+    };
+
     let config = agents::AgentConfig {
         name: persisted.name,
         model: persisted.model,
-        task: agents::resume_task_prompt(&persisted.task),
+        task,
         cwd: persisted.cwd,
         permission_mode: persisted.permission_mode,
         dangerously_ignore_permissions: Some(persisted.dangerously_ignore_permissions),
@@ -1231,6 +1583,16 @@ async fn resume_interrupted_agent(
         headless: Some(persisted.headless),
         spawned_by_ticket_id: persisted.spawned_by_ticket_id,
         spawned_by_goal_id: persisted.spawned_by_goal_id,
+        limits: persisted.limits,
+        priority: None,
+        rows: None,
+        cols: None,
+        resume_session_id,
+        images: persisted.images,
+        system_prompt: persisted.system_prompt,
+        // Provider and model were resolved when the agent first started.
+        role: None,
+        fallback_model: None,
     };
 
     spawn_agent_with_session(config, &state, &terminal_state, &provider_state, app).await
@@ -1279,6 +1641,15 @@ fn import_provider(
     state.import_provider(&json)
 }
 
+/// Every provider config file found, and whether it loaded — so a provider
+/// missing from the picker can say why.
+#[tauri::command]
+fn validate_provider_configs(
+    state: tauri::State<'_, ProviderRegistryState>,
+) -> Vec<providers::ProviderConfigCheck> {
+    state.validate_configs()
+}
+
 /// Discover user-supplied Theme JSON files (validation is frontend-side).
 #[tauri::command]
 fn list_themes(app: tauri::AppHandle) -> Vec<themes::ThemeFile> {
@@ -1464,6 +1835,21 @@ fn pm_save(
     database::pm_save_impl(conn, &payload)
 }
 
+/// Row-level PM save: applies only the rows in the payload and rejects stale
+/// ones (see `database::pm_sync_impl`).
+#[tauri::command]
+fn pm_sync(
+    project_path: String,
+    payload: PmSyncPayload,
+    state: tauri::State<'_, DatabaseState>,
+) -> Result<PmSyncResult, String> {
+    let connections = state.connections.lock().unwrap();
+    let conn = connections
+        .get(&project_path)
+        .ok_or("Database not initialized for this project")?;
+    database::pm_sync_impl(conn, &payload)
+}
+
 #[tauri::command]
 fn pm_load(
     project_path: String,
@@ -1921,6 +2307,19 @@ fn goals_load(
     database::goals_load_impl(conn)
 }
 
+/// Agent spend per goal and per ticket, summed over every recorded run.
+#[tauri::command]
+fn agent_cost_summary(
+    project_path: String,
+    state: tauri::State<'_, DatabaseState>,
+) -> Result<AgentCostSummary, String> {
+    let connections = state.connections.lock().unwrap();
+    let conn = connections
+        .get(&project_path)
+        .ok_or("Database not initialized for this project")?;
+    database::agent_cost_summary_impl(conn)
+}
+
 #[tauri::command]
 fn goals_clear(project_path: String, state: tauri::State<'_, DatabaseState>) -> Result<(), String> {
     let connections = state.connections.lock().unwrap();
@@ -2094,7 +2493,17 @@ pub fn run() {
             if let Ok(log_dir) = app.path().app_log_dir() {
                 crashlog::set_crash_log_dir(log_dir);
             }
-            app.manage(providers::new_provider_registry(Some(app.handle())));
+            let provider_registry = providers::new_provider_registry(Some(app.handle()));
+            // A config dropped into (or edited in) a search directory registers
+            // without a restart; the frontend re-reads the list on the event.
+            let handle = app.handle().clone();
+            if let Err(error) = provider_registry.watch(move || {
+                handle.state::<ProviderRegistryState>().reload();
+                let _ = handle.emit("providers-changed", ());
+            }) {
+                eprintln!("Provider config watcher unavailable: {error}");
+            }
+            app.manage(provider_registry);
 
             let recent_projects_path = app
                 .path()
@@ -2215,17 +2624,28 @@ pub fn run() {
                 .map(|p| p.max_agent_number())
                 .unwrap_or(0);
             app.manage(persistence);
-            if max_restored > 0 {
-                let manager_state = app.state::<AgentManagerState>().inner().clone();
-                tauri::async_runtime::block_on(async move {
-                    let mut manager = manager_state.lock().await;
-                    manager.counter = manager.counter.max(max_restored);
-                });
-            }
+            // Long agent sessions spill their scrollback here rather than
+            // keeping all of it in memory (see agent_output).
+            let output_spill_dir = app
+                .path()
+                .app_data_dir()
+                .ok()
+                .map(|d| d.join("agent-output"));
+            let manager_state = app.state::<AgentManagerState>().inner().clone();
+            tauri::async_runtime::block_on(async move {
+                let mut manager = manager_state.lock().await;
+                manager.counter = manager.counter.max(max_restored);
+                if let Some(dir) = output_spill_dir {
+                    manager.output.set_spill_dir(dir);
+                }
+            });
 
             // Pre-resolve the login-shell environment in the background so the
             // first agent spawn doesn't pay for it (see agents::warm_shell_env_cache).
             tauri::async_runtime::spawn(agents::warm_shell_env_cache());
+            tauri::async_runtime::spawn(run_agent_queue(app.handle().clone()));
+            #[cfg(unix)]
+            agent_api::spawn_agent_api_runner(app.handle().clone());
             Ok(())
         })
         .manage(DatabaseState {
@@ -2270,11 +2690,28 @@ pub fn run() {
             git_diff,
             git_stage,
             git_unstage,
+            git_stage_hunks,
+            git_unstage_hunks,
+            git_discard_hunks,
+            git_stash_save,
+            git_stash_list,
+            git_stash_show,
+            git_stash_apply,
+            git_stash_pop,
+            git_stash_drop,
             git_commit,
             git_push,
+            git_fetch,
+            git_pull,
             git_log_since,
+            git_log,
             git_discard,
             git_list_branches,
+            git_branch_create,
+            git_branch_checkout,
+            git_branch_rename,
+            git_branch_delete,
+            git_branch_set_upstream,
             git_blame,
             git_diff_commit,
             git_diff_ref_files,
@@ -2284,16 +2721,34 @@ pub fn run() {
             git_worktree_remove,
             git_default_branch,
             git_worktree_merge_into_default,
+            git_worktree_merge_conflicts,
+            git_worktree_resolve_conflict,
+            git_worktree_merge_finish,
+            git_worktree_merge_abort,
+            git_worktree_rebase_onto_default,
+            git_worktree_rebase_continue,
+            git_worktree_rebase_skip,
+            git_worktree_rebase_abort,
             list_agents,
             spawn_agent,
             kill_agent,
             kill_agents_for_repo,
             rename_agent,
+            get_agent_output,
+            send_agent_input,
+            interrupt_agent,
+            pause_agent,
+            resume_agent,
+            resize_agent,
+            search_agent_transcripts,
+            get_agent_transcript,
             list_interrupted_agents,
             resume_interrupted_agent,
             discard_interrupted_agent,
             list_providers,
             import_provider,
+            validate_provider_configs,
+            probe_provider,
             list_themes,
             import_theme,
             get_prompt_template,
@@ -2307,6 +2762,7 @@ pub fn run() {
             db_import,
             close_project_db,
             pm_save,
+            pm_sync,
             pm_load,
             pm_load_history,
             pm_clear,
@@ -2348,6 +2804,7 @@ pub fn run() {
             goals_save,
             goals_load,
             goals_clear,
+            agent_cost_summary,
             append_metrics_log,
             report_frontend_crash,
             list_crash_logs,
@@ -1,4 +1,5 @@
-//! Which agentic providers a project permits.
+//! Which agentic providers a project permits, and which one — with which
+//! model — each role starts with.
 //!
 //! The twin of `src/lib/config/providerPolicy.ts`. The TypeScript side decides
 //! what the dialogs offer; this side decides what actually spawns. Both are
@@ -23,6 +24,10 @@ pub struct ProviderPolicy {
     /// `None` means no allow list is in effect — the usual case.
     pub allow: Option<Vec<String>>,
     pub deny: Vec<String>,
+    /// Left out of the stored JSON when no role has a preset, so a policy
+    /// written before presets existed reads back unchanged.
+    #[serde(default, skip_serializing_if = "RoleDefaults::is_empty")]
+    pub defaults: RoleDefaults,
 }
 
 impl Default for ProviderPolicy {
@@ -33,6 +38,47 @@ impl Default for ProviderPolicy {
         Self {
             allow: None,
             deny: Vec::new(),
+            defaults: RoleDefaults::default(),
+        }
+    }
+}
+
+/// What an agent is spawned to do. A spawn names its role so the project's
+/// preset for it can fill in the provider and model it left open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentRole {
+    Implementer,
+    Judge,
+}
+
+/// The provider and model a role starts with. Either may be unset.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RolePreset {
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// One preset per role; a role without one uses the registry default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implementer: Option<RolePreset>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub judge: Option<RolePreset>,
+}
+
+impl RoleDefaults {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn get(&self, role: AgentRole) -> Option<&RolePreset> {
+        match role {
+            AgentRole::Implementer => self.implementer.as_ref(),
+            AgentRole::Judge => self.judge.as_ref(),
         }
     }
 }
@@ -58,6 +104,34 @@ fn normalize_list(value: Option<&serde_json::Value>) -> Vec<String> {
     out
 }
 
+/// A preset with nothing usable in it is no preset: the role then behaves as
+/// if the project had never mentioned it.
+fn normalize_preset(value: Option<&serde_json::Value>) -> Option<RolePreset> {
+    let serde_json::Value::Object(source) = value? else {
+        return None;
+    };
+    let text = |key: &str| source.get(key).and_then(serde_json::Value::as_str);
+    let preset = RolePreset {
+        provider: text("provider")
+            .map(normalize_id)
+            .filter(|id| !id.is_empty()),
+        model: text("model")
+            .map(|model| model.trim().to_string())
+            .filter(|model| !model.is_empty()),
+    };
+    (preset != RolePreset::default()).then_some(preset)
+}
+
+fn normalize_defaults(value: Option<&serde_json::Value>) -> RoleDefaults {
+    let Some(serde_json::Value::Object(source)) = value else {
+        return RoleDefaults::default();
+    };
+    RoleDefaults {
+        implementer: normalize_preset(source.get("implementer")),
+        judge: normalize_preset(source.get("judge")),
+    }
+}
+
 pub fn parse_provider_policy(raw: Option<&str>) -> ProviderPolicy {
     let Some(raw) = raw.filter(|r| !r.is_empty()) else {
         return ProviderPolicy::default();
@@ -77,6 +151,7 @@ pub fn parse_provider_policy(raw: Option<&str>) -> ProviderPolicy {
         // on the deny list.
         allow: if allow.is_empty() { None } else { Some(allow) },
         deny: normalize_list(source.get("deny")),
+        defaults: normalize_defaults(source.get("defaults")),
     }
 }
 
@@ -96,6 +171,40 @@ pub fn is_provider_allowed(provider_id: &str, policy: &ProviderPolicy) -> bool {
     }
 }
 
+/// The provider and model a spawn runs with once its role's preset has
+/// filled in what the spawn left open.
+///
+/// A provider the spawn named wins. The preset's model only comes along with
+/// the provider it was chosen for: when the preset supplied the provider, the
+/// spawn's model — picked without knowing which CLI would run it — gives way
+/// to the preset's. An empty model here means the provider's own default.
+pub fn apply_role_preset(
+    role: Option<AgentRole>,
+    provider: Option<&str>,
+    model: &str,
+    policy: &ProviderPolicy,
+) -> (Option<String>, String) {
+    let requested = provider.map(str::to_string);
+    let Some(preset) = role.and_then(|role| policy.defaults.get(role)) else {
+        return (requested, model.to_string());
+    };
+    let preset_model = || preset.model.clone().unwrap_or_default();
+
+    if let (None, Some(preset_provider)) = (&requested, &preset.provider) {
+        return (Some(preset_provider.clone()), preset_model());
+    }
+    let same_provider = match &preset.provider {
+        None => true,
+        Some(preset_provider) => provider.map(normalize_id).as_ref() == Some(preset_provider),
+    };
+    let model = if model.trim().is_empty() && same_provider {
+        preset_model()
+    } else {
+        model.to_string()
+    };
+    (requested, model)
+}
+
 /// Reads the policy for the project rooted at `cwd`.
 ///
 /// Opened read-only and without creating anything: this runs on the spawn path,
@@ -200,6 +309,62 @@ mod tests {
         assert!(fixtures.decide.len() >= 10);
     }
 
+    #[test]
+    fn a_role_preset_fills_only_what_the_spawn_left_open() {
+        let policy = parse_provider_policy(Some(
+            r#"{"defaults":{"implementer":{"provider":"codex","model":"gpt-5-codex"},
+                "judge":{"model":"opus"}}}"#,
+        ));
+        let implementer = Some(AgentRole::Implementer);
+        let apply = |role, provider, model| apply_role_preset(role, provider, model, &policy);
+
+        // Nothing named: the preset's provider and model, whatever model the
+        // spawn guessed for an unknown CLI.
+        assert_eq!(
+            apply(implementer, None, "sonnet"),
+            (Some("codex".to_string()), "gpt-5-codex".to_string())
+        );
+        // The preset's own provider named: only an empty model is filled in.
+        assert_eq!(
+            apply(implementer, Some("Codex"), ""),
+            (Some("Codex".to_string()), "gpt-5-codex".to_string())
+        );
+        assert_eq!(
+            apply(implementer, Some("codex"), "o3"),
+            (Some("codex".to_string()), "o3".to_string())
+        );
+        // Another provider named: the preset's model is not for it.
+        assert_eq!(
+            apply(implementer, Some("claude"), ""),
+            (Some("claude".to_string()), String::new())
+        );
+        // A model-only preset goes with whichever provider runs.
+        assert_eq!(
+            apply(Some(AgentRole::Judge), Some("claude"), ""),
+            (Some("claude".to_string()), "opus".to_string())
+        );
+        // No role, or a role without a preset: untouched.
+        assert_eq!(apply(None, None, ""), (None, String::new()));
+        assert_eq!(
+            apply_role_preset(implementer, None, "sonnet", &ProviderPolicy::default()),
+            (None, "sonnet".to_string())
+        );
+    }
+
+    #[test]
+    fn presets_survive_a_round_trip_and_stay_out_of_an_old_policy() {
+        let policy = parse_provider_policy(Some(r#"{"deny":["grok"]}"#));
+        assert_eq!(
+            serde_json::to_string(&policy).unwrap(),
+            r#"{"allow":null,"deny":["grok"]}"#
+        );
+
+        let raw =
+            r#"{"allow":null,"deny":[],"defaults":{"judge":{"provider":"claude","model":null}}}"#;
+        let policy = parse_provider_policy(Some(raw));
+        assert_eq!(serde_json::to_string(&policy).unwrap(), raw);
+    }
+
     #[test]
     fn an_unopened_project_is_open() {
         let dir = temp_dir("unopened");
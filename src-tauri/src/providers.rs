@@ -1,8 +1,10 @@
+use crate::agent_events::{AgentEventParser, EventStreamFormat};
+use crate::agent_sessions::is_valid_session_id;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
 use tauri::Manager;
 
 // ── Serializable types for the frontend ──────────────────────────────
@@ -35,9 +37,62 @@ pub struct ProviderInfo {
 
 // ── Return types for spawn logic ─────────────────────────────────────
 
+/// What to run: a program and its arguments, or a line for the shell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandLine {
+    /// Executed directly. No shell reads it, so an argument — the task above
+    /// all — can hold quotes, newlines or anything else as it is.
+    Argv(Vec<String>),
+    /// One line for `sh -c` (`cmd /C` on Windows), for a provider config that
+    /// opts into `"shell": true` because its arguments use shell features.
+    Shell(String),
+}
+
+/// How a provider's CLI receives the task (`taskVia` in its config).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskVia {
+    /// As an argument, where the `task` entry stands.
+    #[default]
+    Argv,
+    /// On standard input. The `task` entry adds nothing.
+    Stdin,
+    /// In a file, whose path stands where the `task` entry is.
+    File,
+}
+
+/// Where the task file's path goes until the spawn has written the file.
+pub const TASK_FILE_PLACEHOLDER: &str = "{taskFile}";
+/// Where the system prompt file's path goes, likewise.
+pub const SYSTEM_PROMPT_FILE_PLACEHOLDER: &str = "{systemPromptFile}";
+
+/// What a run brings beyond model, task and permissions, for the config
+/// arguments that ask for it. Anything unset leaves its argument out.
+#[derive(Debug, Clone, Default)]
+pub struct SpawnContext {
+    /// The agent's working directory — the worktree, for an agent in one.
+    pub cwd: Option<String>,
+    /// The main checkout, set only when it is not `cwd`.
+    pub project_dir: Option<String>,
+    pub images: Vec<String>,
+    pub system_prompt: Option<String>,
+    pub max_turns: Option<u64>,
+    /// The project's `.mcp.json`, when it has one.
+    pub mcp_config: Option<String>,
+}
+
+/// An environment variable whose value is an app credential, looked up by the
+/// spawn — a provider only names it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialRef {
+    pub namespace: String,
+    pub key: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct SpawnCommand {
-    pub command: String,
+    pub command: CommandLine,
     pub env_vars: Vec<(String, String)>,
     /// The leading token(s) of `command` that name the binary.
     ///
@@ -46,36 +101,285 @@ pub struct SpawnCommand {
     /// of the command" is neither the program nor a safe place to splice an
     /// extra flag in after.
     pub executable: String,
+    /// How the task reaches the CLI. For `stdin` and `file` the spawn writes
+    /// `task` to a private file first — a prompt of any size then stays off
+    /// the command line and clear of `ARG_MAX`.
+    pub task_via: TaskVia,
+    pub task: String,
+    /// Variables to set from app credentials, by name. One whose credential
+    /// is not configured is left unset, and the CLI's own login applies.
+    pub credential_env: Vec<(String, CredentialRef)>,
+    /// A system prompt for the spawn to write out, standing in the command
+    /// as `SYSTEM_PROMPT_FILE_PLACEHOLDER` until then.
+    pub system_prompt_file: Option<String>,
 }
 
 impl SpawnCommand {
     /// Inserts a flag directly behind the executable, leaving the provider's
     /// own argument order untouched.
     ///
-    /// Splices on the executable prefix rather than splitting the command into
-    /// words and rejoining it: the task is embedded in this string, and
-    /// re-joining would collapse any run of spaces inside the prompt the user
-    /// actually wrote.
+    /// A shell line is spliced on the executable prefix rather than split into
+    /// words and rejoined: the task is embedded in it, and re-joining would
+    /// collapse any run of spaces inside the prompt the user actually wrote.
     pub fn with_flag_after_executable(mut self, flag: &str, value: &str) -> Self {
-        let Some(rest) = self.command.strip_prefix(&self.executable) else {
+        match &mut self.command {
+            CommandLine::Argv(argv) => {
+                let at = self.executable.split_whitespace().count().min(argv.len());
+                argv.splice(at..at, [flag.to_string(), value.to_string()]);
+            }
+            CommandLine::Shell(line) => {
+                let Some(rest) = line.strip_prefix(&self.executable) else {
+                    return self;
+                };
+                // The value is quoted here, so it is escaped here too. A path
+                // under `Application Support` only needs the quotes; a home
+                // directory with a `$` or a `!` in it needs the escaping too.
+                *line = format!(
+                    "{executable} {flag} \"{value}\"{rest}",
+                    executable = self.executable,
+                    value = shell_escape_double_quoted(value),
+                );
+            }
+        }
+        self
+    }
+
+    /// Puts the path of the written task file where the `task` entry stood.
+    /// Only for `TaskVia::File`; otherwise the command is returned as it is.
+    pub fn with_task_file(mut self, path: &str) -> Self {
+        if self.task_via != TaskVia::File {
             return self;
-        };
-        // The value is quoted here, so it is escaped here too. A path under
-        // `Application Support` only needs the quotes; a home directory with a
-        // `$` or a `!` in it needs the escaping as well.
-        self.command = format!(
-            "{executable} {flag} \"{value}\"{rest}",
-            executable = self.executable,
-            value = shell_escape_double_quoted(value),
-        );
+        }
+        self.replace_placeholder(TASK_FILE_PLACEHOLDER, path);
         self
     }
+
+    /// Puts the path of the written system prompt where its argument stood.
+    pub fn with_system_prompt_file(mut self, path: &str) -> Self {
+        if self.system_prompt_file.is_some() {
+            self.replace_placeholder(SYSTEM_PROMPT_FILE_PLACEHOLDER, path);
+        }
+        self
+    }
+
+    fn replace_placeholder(&mut self, placeholder: &str, path: &str) {
+        match &mut self.command {
+            CommandLine::Argv(argv) => {
+                for arg in argv.iter_mut().filter(|arg| *arg == placeholder) {
+                    *arg = path.to_string();
+                }
+            }
+            CommandLine::Shell(line) => {
+                let quoted = format!("\"{}\"", shell_escape_double_quoted(path));
+                *line = line.replace(placeholder, &quoted);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct VersionCheck {
     pub command: String,
     pub args: Vec<String>,
+    /// Picks the version out of the command's output: the first capture
+    /// group, or the whole match without one. Unset finds the first dotted
+    /// number.
+    #[serde(default)]
+    pub version_pattern: Option<String>,
+    /// The oldest version this config works with.
+    #[serde(default)]
+    pub min_version: Option<String>,
+    /// The version each optional capability first shipped in. One left out is
+    /// taken to work in any version.
+    #[serde(default)]
+    pub capability_versions: CapabilityVersions,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityVersions {
+    pub resume: Option<String>,
+    pub stream_json: Option<String>,
+    pub headless: Option<String>,
+}
+
+/// The optional features a provider's config uses — or, in a probe, the ones
+/// the installed CLI also supports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCapabilities {
+    /// Runs can be resumed into their session (a `resume` argument).
+    pub resume: bool,
+    /// Headless runs print an event stream AuricIDE parses (`eventStream`).
+    pub stream_json: bool,
+    /// Runs can go unattended (a `headless` argument).
+    pub headless: bool,
+}
+
+/// The first dotted number — `2.0.14` in `2.0.14 (Claude Code)`.
+const DEFAULT_VERSION_PATTERN: &str = r"\d+(?:\.\d+)+";
+
+impl VersionCheck {
+    fn pattern(&self) -> Result<regex::Regex, regex::Error> {
+        regex::Regex::new(
+            self.version_pattern
+                .as_deref()
+                .unwrap_or(DEFAULT_VERSION_PATTERN),
+        )
+    }
+
+    /// Refuses a pattern that does not compile and a version that is not a
+    /// version, naming the field the way `parse_config` does.
+    fn validate(&self) -> Result<(), String> {
+        self.pattern()
+            .map_err(|e| format!("field 'versionCheck.versionPattern' — {}", e))?;
+        let versions = [
+            ("minVersion", &self.min_version),
+            (
+                "capabilityVersions.resume",
+                &self.capability_versions.resume,
+            ),
+            (
+                "capabilityVersions.streamJson",
+                &self.capability_versions.stream_json,
+            ),
+            (
+                "capabilityVersions.headless",
+                &self.capability_versions.headless,
+            ),
+        ];
+        for (field, version) in versions {
+            if let Some(version) = version {
+                if parse_version(version).is_none() {
+                    return Err(format!(
+                        "field 'versionCheck.{}' — \"{}\" is not a version",
+                        field, version
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The version in the command's output, if the pattern finds one.
+    pub fn extract_version(&self, output: &str) -> Option<String> {
+        let captures = self.pattern().ok()?.captures(output)?;
+        captures
+            .get(1)
+            .or_else(|| captures.get(0))
+            .map(|found| found.as_str().trim().to_string())
+    }
+}
+
+/// The numeric parts of a version: `v1.2.3-beta.1` is `[1, 2, 3]`.
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let core = version.trim().trim_start_matches('v');
+    let core = core.split(['-', '+', ' ']).next()?;
+    core.split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u64>>>()
+        .filter(|parts| !parts.is_empty())
+}
+
+/// Whether `version` is `min` or newer, missing parts counting as 0.
+/// `None` when either is not a version.
+pub fn version_at_least(version: &str, min: &str) -> Option<bool> {
+    let (mut version, mut min) = (parse_version(version)?, parse_version(min)?);
+    let len = version.len().max(min.len());
+    version.resize(len, 0);
+    min.resize(len, 0);
+    Some(version >= min)
+}
+
+/// What running the version command produced.
+pub struct VersionOutput {
+    pub success: bool,
+    /// stdout, then stderr — some CLIs print their version on the latter.
+    pub text: String,
+}
+
+/// What `probe_provider` found out about a provider's CLI.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderProbe {
+    pub provider_id: String,
+    /// Where the version command's program resolves on the login-shell PATH
+    /// agents are spawned with.
+    pub executable_path: Option<String>,
+    /// The version command ran and succeeded.
+    pub installed: bool,
+    pub version: Option<String>,
+    pub min_version: Option<String>,
+    /// Unset when the config names no minimum, or no version could be read.
+    pub meets_minimum: Option<bool>,
+    /// The capabilities the config uses that the installed version has.
+    pub capabilities: ProviderCapabilities,
+    pub error: Option<String>,
+}
+
+/// Puts a probe together from the version command's outcome.
+pub fn probe_from(
+    provider: &dyn AgentProvider,
+    executable_path: Option<String>,
+    output: Result<VersionOutput, String>,
+) -> ProviderProbe {
+    let check = provider.version_check();
+    let (installed, version, error) = match output {
+        Err(error) => (
+            false,
+            None,
+            Some(format!("Could not run `{}`: {}", check.command, error)),
+        ),
+        Ok(output) if !output.success => (
+            false,
+            None,
+            Some(format!("`{}` exited unsuccessfully", check.command)),
+        ),
+        Ok(output) => match check.extract_version(&output.text) {
+            Some(version) => (true, Some(version), None),
+            None => (
+                true,
+                None,
+                Some(format!("No version in the output of `{}`", check.command)),
+            ),
+        },
+    };
+
+    // A capability with a version of its own needs a version to compare; one
+    // without only needs the CLI to be there.
+    let supports = |used: bool, since: &Option<String>| {
+        installed
+            && used
+            && match since {
+                None => true,
+                Some(since) => version
+                    .as_deref()
+                    .and_then(|version| version_at_least(version, since))
+                    .unwrap_or(false),
+            }
+    };
+    let used = provider.capabilities();
+    let since = &check.capability_versions;
+
+    ProviderProbe {
+        provider_id: provider.info().id,
+        executable_path,
+        installed,
+        meets_minimum: check.min_version.as_deref().and_then(|min| {
+            version
+                .as_deref()
+                .and_then(|version| version_at_least(version, min))
+        }),
+        capabilities: ProviderCapabilities {
+            resume: supports(used.resume, &since.resume),
+            stream_json: supports(used.stream_json, &since.stream_json),
+            headless: supports(used.headless, &since.headless),
+        },
+        version,
+        min_version: check.min_version.clone(),
+        error,
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -83,6 +387,26 @@ pub struct PromptTemplate {
     pub template: String,
 }
 
+/// The provider-native conversation a run belongs to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionRequest {
+    /// A fresh session under an id AuricIDE chose.
+    New(String),
+    /// Back into a session an earlier run left behind.
+    Resume(String),
+}
+
+/// What a provider's CLI lets AuricIDE do with its sessions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionSupport {
+    /// The CLI takes the id of a new session from the command line.
+    pub names_new_sessions: bool,
+    /// The CLI can be pointed back into an earlier session.
+    pub resumes: bool,
+    /// Where the CLI writes one transcript file per session, named after it.
+    pub directory: Option<String>,
+}
+
 // ── AgentProvider trait ──────────────────────────────────────────────
 
 pub trait AgentProvider: Send + Sync {
@@ -101,6 +425,54 @@ pub trait AgentProvider: Send + Sync {
     fn version_check(&self) -> VersionCheck;
 
     fn prompt_template(&self) -> PromptTemplate;
+
+    /// A parser for the machine-readable events the CLI prints in headless
+    /// mode. `None` — the default — leaves the output to the frontend's line
+    /// matchers alone.
+    fn event_parser(&self) -> Option<Box<dyn AgentEventParser>> {
+        None
+    }
+
+    /// Whether runs can be named and resumed. The default is neither: every
+    /// run starts a conversation of its own, and a resume retells the task.
+    fn session_support(&self) -> SessionSupport {
+        SessionSupport::default()
+    }
+
+    /// The optional features this provider uses, whatever version of its CLI
+    /// is installed.
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            resume: self.session_support().resumes,
+            stream_json: self.event_parser().is_some(),
+            headless: false,
+        }
+    }
+
+    /// `build_spawn_command` for a run in a given session, with what else the
+    /// run brings. A provider without session support ignores `session`, and
+    /// one without arguments for it ignores `context`.
+    #[allow(clippy::too_many_arguments)]
+    fn build_session_spawn_command(
+        &self,
+        model: &str,
+        task: &str,
+        permission_mode: Option<&str>,
+        dangerously_ignore_permissions: bool,
+        auto_accept_edits: bool,
+        headless: bool,
+        _session: Option<&SessionRequest>,
+        _context: &SpawnContext,
+    ) -> SpawnCommand {
+        self.build_spawn_command(
+            model,
+            task,
+            permission_mode,
+            dangerously_ignore_permissions,
+            auto_accept_edits,
+            headless,
+        )
+    }
 }
 
 // ── DynamicProvider Configuration ────────────────────────────────────
@@ -130,6 +502,77 @@ enum ArgumentConfig {
         map: HashMap<String, String>,
         fallback: Option<String>,
     },
+    /// Names a new session: `flag` followed by the id AuricIDE chose.
+    #[serde(rename_all = "camelCase")]
+    SessionId {
+        flag: String,
+    },
+    /// Only on a resumed run: `args`, with `{sessionId}` replaced.
+    #[serde(rename_all = "camelCase")]
+    Resume {
+        args: Vec<String>,
+    },
+    /// Sets an environment variable rather than adding an argument: from an
+    /// app credential when one is configured, else `value`.
+    #[serde(rename_all = "camelCase")]
+    Env {
+        name: String,
+        #[serde(default)]
+        value: Option<String>,
+        #[serde(default)]
+        credential: Option<CredentialRef>,
+    },
+    /// `flag` and the agent's working directory.
+    #[serde(rename_all = "camelCase")]
+    Cwd {
+        flag: String,
+    },
+    /// `flag` and the main checkout, only for an agent in a worktree.
+    #[serde(rename_all = "camelCase")]
+    ProjectDir {
+        flag: String,
+    },
+    /// `flag` and the path, once per attached image.
+    #[serde(rename_all = "camelCase")]
+    Images {
+        flag: String,
+    },
+    /// `flag` and the run's system prompt — or the path of a file holding it,
+    /// with `file`.
+    #[serde(rename_all = "camelCase")]
+    SystemPrompt {
+        flag: String,
+        #[serde(default)]
+        file: bool,
+    },
+    /// `flag` and the run's turn budget.
+    #[serde(rename_all = "camelCase")]
+    MaxTurns {
+        flag: String,
+    },
+    /// `flag` and the path of the project's `.mcp.json`.
+    #[serde(rename_all = "camelCase")]
+    McpConfig {
+        flag: String,
+    },
+    /// `args`, only when the run matches: headless or not, and in one of the
+    /// `permission` modes. An unset condition always matches.
+    #[serde(rename_all = "camelCase")]
+    When {
+        #[serde(default)]
+        headless: Option<bool>,
+        #[serde(default)]
+        permission: Option<Vec<String>>,
+        args: Vec<ArgumentConfig>,
+    },
+}
+
+impl ArgumentConfig {
+    /// Whether `wanted` holds for this argument or any nested under it.
+    fn any(&self, wanted: fn(&ArgumentConfig) -> bool) -> bool {
+        wanted(self)
+            || matches!(self, ArgumentConfig::When { args, .. } if args.iter().any(|arg| arg.any(wanted)))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -151,6 +594,22 @@ pub(crate) struct ProviderConfig {
     info: ProviderConfigInfo,
     version_check: VersionCheck,
     prompt_template: String,
+    /// Names the JSON-lines format a headless run prints, when its arguments
+    /// ask for one (`--output-format stream-json`, `exec --json`).
+    #[serde(default)]
+    event_stream: Option<EventStreamFormat>,
+    /// Where the CLI keeps its session files (`~/.codex/sessions`), for a CLI
+    /// that neither takes a session id nor prints one.
+    #[serde(default)]
+    session_directory: Option<String>,
+    /// Run the command line through the shell rather than directly, for
+    /// arguments that rely on it (`$HOME`, `~`, a quoted phrase).
+    #[serde(default)]
+    shell: bool,
+    /// How the task reaches the CLI: as an argument unless the config says
+    /// otherwise.
+    #[serde(default)]
+    task_via: TaskVia,
 }
 
 // ── Shell escaping ──────────────────────────────────────────────────
@@ -158,7 +617,7 @@ pub(crate) struct ProviderConfig {
 /// Escape a string for use inside double quotes in zsh/bash.
 /// Handles all characters that are special inside double quotes:
 /// `\`, `"`, `` ` ``, `$`, and `!` (zsh history expansion).
-fn shell_escape_double_quoted(s: &str) -> String {
+pub(crate) fn shell_escape_double_quoted(s: &str) -> String {
     s.replace('\\', "\\\\")
         .replace('"', "\\\"")
         .replace('`', "\\`")
@@ -168,6 +627,20 @@ fn shell_escape_double_quoted(s: &str) -> String {
 
 // ── DynamicProvider ──────────────────────────────────────────────────
 
+/// One piece of a dynamic provider's command line, before it is rendered as
+/// argv or as a shell line.
+enum Piece {
+    /// Text from the config. Each of its words is an argument of its own.
+    Words(String),
+    /// A single argument, whatever it contains.
+    Value(String),
+    /// A single argument that may hold spaces or quotes — a path, a prompt.
+    /// Quoted and escaped on a shell line.
+    Quoted(String),
+    /// The task, quoted and escaped on a shell line when `quote` is set.
+    Task { quote: bool },
+}
+
 pub struct DynamicProvider {
     config: ProviderConfig,
 }
@@ -176,98 +649,271 @@ impl DynamicProvider {
     pub(crate) fn new(config: ProviderConfig) -> Self {
         Self { config }
     }
-}
-
-impl AgentProvider for DynamicProvider {
-    fn info(&self) -> ProviderInfo {
-        ProviderInfo {
-            id: self.config.id.clone(),
-            name: self.config.name.clone(),
-            models: self.config.info.models.clone(),
-            permission_modes: self.config.info.permission_modes.clone(),
-            default_model: self.config.info.default_model.clone(),
-            default_permission_mode: self.config.info.default_permission_mode.clone(),
-        }
-    }
 
-    fn build_spawn_command(
-        &self,
-        model: &str,
-        task: &str,
-        permission_mode: Option<&str>,
-        dangerously_ignore_permissions: bool,
-        auto_accept_edits: bool,
-        headless: bool,
-    ) -> SpawnCommand {
-        let mut cmd_parts = Vec::new();
-        cmd_parts.push(self.config.executable.clone());
+    /// Renders `args` for `run`, descending into the `when` blocks it matches.
+    fn render(&self, args: &[ArgumentConfig], run: &Run, out: &mut Rendered) {
+        // A flag, when the config gives one, then its value.
+        let flagged = |out: &mut Rendered, flag: &str, value: Piece| {
+            if !flag.is_empty() {
+                out.pieces.push(Piece::Words(flag.to_string()));
+            }
+            out.pieces.push(value);
+        };
 
-        for arg in &self.config.arguments {
+        for arg in args {
             match arg {
                 ArgumentConfig::Literal { value } => {
-                    cmd_parts.push(value.clone());
+                    out.pieces.push(Piece::Words(value.clone()));
                 }
                 ArgumentConfig::Model {
                     flag,
                     ignore_if_auto,
                 } => {
-                    if model == "auto" && *ignore_if_auto {
+                    if run.model == "auto" && *ignore_if_auto {
                         continue;
                     }
-                    if !flag.is_empty() {
-                        cmd_parts.push(flag.clone());
-                    }
-                    cmd_parts.push(model.to_string());
-                }
-                ArgumentConfig::Task { quote } => {
-                    let escaped_task = shell_escape_double_quoted(task);
-                    if *quote {
-                        cmd_parts.push(format!("\"{}\"", escaped_task));
-                    } else {
-                        cmd_parts.push(escaped_task);
-                    }
+                    flagged(out, flag, Piece::Value(run.model.to_string()));
                 }
+                ArgumentConfig::Task { quote } => match self.config.task_via {
+                    TaskVia::Argv => out.pieces.push(Piece::Task { quote: *quote }),
+                    TaskVia::File => out
+                        .pieces
+                        .push(Piece::Value(TASK_FILE_PLACEHOLDER.to_string())),
+                    TaskVia::Stdin => {}
+                },
                 ArgumentConfig::Headless {
                     flag,
                     interactive_flag,
                 } => {
-                    if headless {
-                        cmd_parts.push(flag.clone());
+                    if run.headless {
+                        out.pieces.push(Piece::Words(flag.clone()));
                     } else if let Some(interactive) = interactive_flag {
-                        cmd_parts.push(interactive.clone());
+                        out.pieces.push(Piece::Words(interactive.clone()));
                     }
                 }
                 ArgumentConfig::Permission { map, fallback } => {
-                    let mode_key = if let Some(m) = permission_mode {
-                        m.to_string()
-                    } else if dangerously_ignore_permissions {
-                        // Legacy mapping
-                        "bypassPermissions".to_string()
-                    } else if auto_accept_edits {
-                        "acceptEdits".to_string()
-                    } else {
-                        // No explicit mode requested: the provider's configured
-                        // defaultPermissionMode (dynamic-providers/*.json) decides.
-                        self.config.info.default_permission_mode.clone()
-                    };
-
                     let flag_val = map
-                        .get(&mode_key)
+                        .get(&run.permission)
                         .cloned()
                         .or(fallback.clone())
                         .unwrap_or_default();
 
                     if !flag_val.is_empty() {
-                        cmd_parts.push(flag_val);
+                        out.pieces.push(Piece::Words(flag_val));
+                    }
+                }
+                ArgumentConfig::SessionId { flag } => {
+                    if let Some(SessionRequest::New(id)) = run.session {
+                        out.pieces.push(Piece::Words(flag.clone()));
+                        out.pieces.push(Piece::Value(id.clone()));
+                    }
+                }
+                ArgumentConfig::Resume { args } => {
+                    if let Some(SessionRequest::Resume(id)) = run.session {
+                        out.pieces.extend(
+                            args.iter()
+                                .map(|arg| Piece::Value(arg.replace("{sessionId}", id))),
+                        );
+                    }
+                }
+                ArgumentConfig::Env {
+                    name,
+                    value,
+                    credential,
+                } => {
+                    // The value is the fallback; the spawn sets the credential
+                    // over it when there is one.
+                    if let Some(value) = value {
+                        out.env_vars.push((name.clone(), value.clone()));
+                    }
+                    if let Some(credential) = credential {
+                        out.credential_env.push((name.clone(), credential.clone()));
+                    }
+                }
+                ArgumentConfig::Cwd { flag } => {
+                    if let Some(cwd) = &run.context.cwd {
+                        flagged(out, flag, Piece::Quoted(cwd.clone()));
+                    }
+                }
+                ArgumentConfig::ProjectDir { flag } => {
+                    if let Some(dir) = &run.context.project_dir {
+                        flagged(out, flag, Piece::Quoted(dir.clone()));
+                    }
+                }
+                ArgumentConfig::Images { flag } => {
+                    for image in &run.context.images {
+                        flagged(out, flag, Piece::Quoted(image.clone()));
+                    }
+                }
+                ArgumentConfig::SystemPrompt { flag, file } => {
+                    let Some(prompt) = &run.context.system_prompt else {
+                        continue;
+                    };
+                    if *file {
+                        out.system_prompt_file = Some(prompt.clone());
+                        flagged(
+                            out,
+                            flag,
+                            Piece::Value(SYSTEM_PROMPT_FILE_PLACEHOLDER.to_string()),
+                        );
+                    } else {
+                        flagged(out, flag, Piece::Quoted(prompt.clone()));
+                    }
+                }
+                ArgumentConfig::MaxTurns { flag } => {
+                    if let Some(turns) = run.context.max_turns {
+                        flagged(out, flag, Piece::Value(turns.to_string()));
+                    }
+                }
+                ArgumentConfig::McpConfig { flag } => {
+                    if let Some(path) = &run.context.mcp_config {
+                        flagged(out, flag, Piece::Quoted(path.clone()));
+                    }
+                }
+                ArgumentConfig::When {
+                    headless,
+                    permission,
+                    args,
+                } => {
+                    let matches = !matches!(headless, Some(wanted) if *wanted != run.headless)
+                        && !matches!(permission, Some(modes) if !modes.contains(&run.permission));
+                    if matches {
+                        self.render(args, run, out);
                     }
                 }
             }
         }
+    }
+}
+
+/// The run an argument list is rendered for.
+struct Run<'a> {
+    model: &'a str,
+    /// The permission mode after the legacy flags and the config's default.
+    permission: String,
+    headless: bool,
+    session: Option<&'a SessionRequest>,
+    context: &'a SpawnContext,
+}
+
+/// What rendering the arguments produced.
+#[derive(Default)]
+struct Rendered {
+    pieces: Vec<Piece>,
+    env_vars: Vec<(String, String)>,
+    credential_env: Vec<(String, CredentialRef)>,
+    system_prompt_file: Option<String>,
+}
+
+impl AgentProvider for DynamicProvider {
+    fn info(&self) -> ProviderInfo {
+        ProviderInfo {
+            id: self.config.id.clone(),
+            name: self.config.name.clone(),
+            models: self.config.info.models.clone(),
+            permission_modes: self.config.info.permission_modes.clone(),
+            default_model: self.config.info.default_model.clone(),
+            default_permission_mode: self.config.info.default_permission_mode.clone(),
+        }
+    }
+
+    fn build_spawn_command(
+        &self,
+        model: &str,
+        task: &str,
+        permission_mode: Option<&str>,
+        dangerously_ignore_permissions: bool,
+        auto_accept_edits: bool,
+        headless: bool,
+    ) -> SpawnCommand {
+        self.build_session_spawn_command(
+            model,
+            task,
+            permission_mode,
+            dangerously_ignore_permissions,
+            auto_accept_edits,
+            headless,
+            None,
+            &SpawnContext::default(),
+        )
+    }
+
+    fn build_session_spawn_command(
+        &self,
+        model: &str,
+        task: &str,
+        permission_mode: Option<&str>,
+        dangerously_ignore_permissions: bool,
+        auto_accept_edits: bool,
+        headless: bool,
+        session: Option<&SessionRequest>,
+        context: &SpawnContext,
+    ) -> SpawnCommand {
+        // In shell mode the id lands on the command line unquoted; one that did
+        // not come from `new_session_id` is only used if it is plainly an id.
+        let session = session.filter(|session| match session {
+            SessionRequest::New(id) | SessionRequest::Resume(id) => is_valid_session_id(id),
+        });
+        let permission = if let Some(m) = permission_mode {
+            m.to_string()
+        } else if dangerously_ignore_permissions {
+            // Legacy mapping
+            "bypassPermissions".to_string()
+        } else if auto_accept_edits {
+            "acceptEdits".to_string()
+        } else {
+            // No explicit mode requested: the provider's configured
+            // defaultPermissionMode (dynamic-providers/*.json) decides.
+            self.config.info.default_permission_mode.clone()
+        };
+        let run = Run {
+            model,
+            permission,
+            headless,
+            session,
+            context,
+        };
+
+        let mut rendered = Rendered {
+            pieces: vec![Piece::Words(self.config.executable.clone())],
+            ..Rendered::default()
+        };
+        self.render(&self.config.arguments, &run, &mut rendered);
+
+        let command = if self.config.shell {
+            let line: Vec<String> = rendered
+                .pieces
+                .into_iter()
+                .map(|piece| match piece {
+                    Piece::Words(text) | Piece::Value(text) => text,
+                    Piece::Quoted(text) => format!("\"{}\"", shell_escape_double_quoted(&text)),
+                    Piece::Task { quote: true } => {
+                        format!("\"{}\"", shell_escape_double_quoted(task))
+                    }
+                    Piece::Task { quote: false } => shell_escape_double_quoted(task),
+                })
+                .collect();
+            CommandLine::Shell(line.join(" "))
+        } else {
+            let mut argv = Vec::new();
+            for piece in rendered.pieces {
+                match piece {
+                    Piece::Words(text) => argv.extend(text.split_whitespace().map(String::from)),
+                    Piece::Value(value) | Piece::Quoted(value) => argv.push(value),
+                    Piece::Task { .. } => argv.push(task.to_string()),
+                }
+            }
+            CommandLine::Argv(argv)
+        };
 
         SpawnCommand {
-            command: cmd_parts.join(" "),
-            env_vars: vec![],
+            command,
+            env_vars: rendered.env_vars,
             executable: self.config.executable.clone(),
+            task_via: self.config.task_via,
+            task: task.to_string(),
+            credential_env: rendered.credential_env,
+            system_prompt_file: rendered.system_prompt_file,
         }
     }
 
@@ -280,6 +926,33 @@ impl AgentProvider for DynamicProvider {
             template: self.config.prompt_template.clone(),
         }
     }
+
+    fn event_parser(&self) -> Option<Box<dyn AgentEventParser>> {
+        self.config.event_stream.map(EventStreamFormat::parser)
+    }
+
+    fn session_support(&self) -> SessionSupport {
+        let has = |wanted: fn(&ArgumentConfig) -> bool| {
+            self.config.arguments.iter().any(|arg| arg.any(wanted))
+        };
+        SessionSupport {
+            names_new_sessions: has(|arg| matches!(arg, ArgumentConfig::SessionId { .. })),
+            resumes: has(|arg| matches!(arg, ArgumentConfig::Resume { .. })),
+            directory: self.config.session_directory.clone(),
+        }
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            resume: self.session_support().resumes,
+            stream_json: self.config.event_stream.is_some(),
+            headless: self
+                .config
+                .arguments
+                .iter()
+                .any(|arg| arg.any(|arg| matches!(arg, ArgumentConfig::Headless { .. }))),
+        }
+    }
 }
 
 // ── CrushProvider ──────────────────────────────────────────────────
@@ -327,26 +1000,28 @@ impl AgentProvider for CrushProvider {
         _auto_accept_edits: bool,
         _headless: bool,
     ) -> SpawnCommand {
-        let escaped_task = shell_escape_double_quoted(task);
-
-        let mut cmd = "crush".to_string();
+        let mut argv = vec!["crush".to_string()];
 
         if model != "auto" {
-            cmd.push_str(&format!(" --model {}", model));
+            argv.extend(["--model".to_string(), model.to_string()]);
         }
 
         if let Some(mode) = permission_mode {
             if mode == "yolo" || mode == "bypassPermissions" {
-                cmd.push_str(" --yolo");
+                argv.push("--yolo".to_string());
             }
         }
 
-        cmd.push_str(&format!(" \"{}\"", escaped_task));
+        argv.push(task.to_string());
 
         SpawnCommand {
-            command: cmd,
+            command: CommandLine::Argv(argv),
             env_vars: vec![],
             executable: "crush".to_string(),
+            task_via: TaskVia::Argv,
+            task: task.to_string(),
+            credential_env: vec![],
+            system_prompt_file: None,
         }
     }
 
@@ -354,6 +1029,9 @@ impl AgentProvider for CrushProvider {
         VersionCheck {
             command: "crush".to_string(),
             args: vec!["--version".to_string()],
+            version_pattern: None,
+            min_version: None,
+            capability_versions: CapabilityVersions::default(),
         }
     }
 
@@ -378,15 +1056,44 @@ pub struct ProviderRegistry {
     default_id: RwLock<String>,
     /// Where imported configs are persisted (app_data_dir/dynamic-providers).
     import_dir: Option<PathBuf>,
+    /// The directories scanned for configs, lowest precedence first.
+    search_paths: Vec<PathBuf>,
+    /// Kept here only to stay alive — a dropped watcher stops watching.
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
 }
 
-impl ProviderRegistry {
-    pub fn new(app: Option<&tauri::AppHandle>) -> Self {
-        let mut providers: HashMap<String, Arc<dyn AgentProvider>> = HashMap::new();
+/// What became of one `*.json` file in a search directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProviderConfigStatus {
+    /// Registered under its id.
+    Loaded,
+    /// Valid, but a file later in the search order claims the same id.
+    Shadowed,
+    /// Valid, but claims `RESERVED_PROVIDER_ID`.
+    Reserved,
+    /// Unreadable, or not a provider config.
+    Invalid,
+}
 
-        // Add Crush as fallback/default if no others are present (or keep it always)
-        providers.insert(RESERVED_PROVIDER_ID.to_string(), Arc::new(CrushProvider));
+/// One scanned config file, for the settings screen to explain a provider
+/// that is missing from the picker.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderConfigCheck {
+    pub path: PathBuf,
+    /// The `id` the file claims, when it got far enough to say.
+    pub id: Option<String>,
+    pub status: ProviderConfigStatus,
+    pub error: Option<String>,
+}
+
+/// Debounce for the config watcher. Editors save through a truncate and a
+/// write, or a temp file and a rename; only the state after the burst counts.
+const WATCH_DEBOUNCE_MS: u64 = 400;
 
+impl ProviderRegistry {
+    pub fn new(app: Option<&tauri::AppHandle>) -> Self {
         // Load Dynamic Providers
         let mut search_paths = vec![
             PathBuf::from("dynamic-providers"),
@@ -408,7 +1115,34 @@ impl ProviderRegistry {
             }
         }
 
-        providers.extend(Self::load_configs_from(&search_paths));
+        let import_dir = app.and_then(|a| {
+            a.path()
+                .app_data_dir()
+                .ok()
+                .map(|d| d.join("dynamic-providers"))
+        });
+
+        let registry = Self {
+            providers: RwLock::new(HashMap::new()),
+            default_id: RwLock::new(String::new()),
+            import_dir,
+            search_paths: distinct_dirs(search_paths),
+            watcher: Mutex::new(None),
+        };
+        registry.reload();
+        registry
+    }
+
+    /// Rebuild the provider list from the search directories, as at startup.
+    ///
+    /// Agents already running keep the provider they were spawned with; the
+    /// next spawn sees the new one.
+    pub fn reload(&self) {
+        let mut providers: HashMap<String, Arc<dyn AgentProvider>> = HashMap::new();
+
+        // Add Crush as fallback/default if no others are present (or keep it always)
+        providers.insert(RESERVED_PROVIDER_ID.to_string(), Arc::new(CrushProvider));
+        providers.extend(Self::load_configs_from(&self.search_paths));
 
         // Determine default ID. First dynamic provider we find? Or claude?
         // Usually, pick the first dynamic one, or fallback to crush.
@@ -425,18 +1159,66 @@ impl ProviderRegistry {
             "crush".to_string()
         };
 
-        let import_dir = app.and_then(|a| {
-            a.path()
-                .app_data_dir()
-                .ok()
-                .map(|d| d.join("dynamic-providers"))
-        });
+        *self.providers.write().unwrap() = providers;
+        *self.default_id.write().unwrap() = default_id;
+    }
+
+    /// Every config file in the search directories and what became of it.
+    pub fn validate_configs(&self) -> Vec<ProviderConfigCheck> {
+        scan_configs(&self.search_paths).0
+    }
+
+    /// Watch the search directories and call `on_change` once a burst of
+    /// config edits has settled.
+    ///
+    /// A directory that does not exist yet cannot be watched, so the import
+    /// directory is created here; any other one created later is picked up on
+    /// the next start.
+    pub fn watch<F>(&self, on_change: F) -> Result<(), String>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        if let Some(dir) = &self.import_dir {
+            fs::create_dir_all(dir).map_err(|e| format!("Could not create provider dir: {}", e))?;
+        }
 
-        Self {
-            providers: RwLock::new(providers),
-            default_id: RwLock::new(default_id),
-            import_dir,
+        let on_change = Arc::new(on_change);
+        let generation = Arc::new(AtomicU64::new(0));
+
+        let mut watcher = RecommendedWatcher::new(
+            move |res: notify::Result<notify::Event>| {
+                let Ok(event) = res else { return };
+                if !event.paths.iter().any(|path| is_config_file(path)) {
+                    return;
+                }
+
+                // Trailing edge: only the last event of a burst reloads, so a
+                // half-written file is never what gets parsed.
+                let current = generation.fetch_add(1, Ordering::Relaxed) + 1;
+                let generation = Arc::clone(&generation);
+                let on_change = Arc::clone(&on_change);
+                std::thread::spawn(move || {
+                    std::thread::sleep(std::time::Duration::from_millis(WATCH_DEBOUNCE_MS));
+                    if generation.load(Ordering::Relaxed) == current {
+                        on_change();
+                    }
+                });
+            },
+            Config::default().with_poll_interval(std::time::Duration::from_millis(500)),
+        )
+        .map_err(|e| format!("Failed to create provider config watcher: {}", e))?;
+
+        for dir in self.search_paths.iter().filter(|dir| dir.is_dir()) {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|e| format!("Failed to watch {:?}: {}", dir, e))?;
         }
+
+        *self.watcher.lock().unwrap() = Some(watcher);
+        Ok(())
     }
 
     /// Scan `dirs` for `*.json` provider configs. Later directories win over
@@ -446,43 +1228,31 @@ impl ProviderRegistry {
     /// config never costs the user the rest of them. A config claiming the
     /// built-in id is skipped too — see `RESERVED_PROVIDER_ID`.
     fn load_configs_from(dirs: &[PathBuf]) -> HashMap<String, Arc<dyn AgentProvider>> {
-        let mut loaded: HashMap<String, Arc<dyn AgentProvider>> = HashMap::new();
-
-        for dir in dirs {
-            if !dir.is_dir() {
-                continue;
-            }
-            let Ok(entries) = fs::read_dir(dir) else {
-                continue;
-            };
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().and_then(|s| s.to_str()) != Some("json") {
-                    continue;
-                }
-                let Ok(content) = fs::read_to_string(&path) else {
-                    continue;
-                };
-                match serde_json::from_str::<ProviderConfig>(&content) {
-                    Ok(config) => {
-                        let id = config.id.clone();
-                        if id == RESERVED_PROVIDER_ID {
-                            eprintln!(
-                                "Ignoring {:?}: \"{}\" is a built-in provider id",
-                                path, RESERVED_PROVIDER_ID
-                            );
-                            continue;
-                        }
-                        loaded.insert(id, Arc::new(DynamicProvider::new(config)));
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to parse provider config {:?}: {}", path, e)
-                    }
-                }
+        let (checks, configs) = scan_configs(dirs);
+        for check in checks {
+            match check.status {
+                ProviderConfigStatus::Reserved => eprintln!(
+                    "Ignoring {:?}: \"{}\" is a built-in provider id",
+                    check.path, RESERVED_PROVIDER_ID
+                ),
+                ProviderConfigStatus::Invalid => eprintln!(
+                    "Failed to parse provider config {:?}: {}",
+                    check.path,
+                    check.error.unwrap_or_default()
+                ),
+                ProviderConfigStatus::Loaded | ProviderConfigStatus::Shadowed => {}
             }
         }
 
-        loaded
+        configs
+            .into_iter()
+            .map(|(id, config)| {
+                (
+                    id,
+                    Arc::new(DynamicProvider::new(config)) as Arc<dyn AgentProvider>,
+                )
+            })
+            .collect()
     }
 
     pub fn get(&self, id: &str) -> Option<Arc<dyn AgentProvider>> {
@@ -518,8 +1288,7 @@ impl ProviderRegistry {
     /// Import a dynamic provider config at runtime: validate JSON, persist it to
     /// the import dir, and register it live. Returns the imported provider's info.
     pub fn import_provider(&self, json: &str) -> Result<ProviderInfo, String> {
-        let config: ProviderConfig =
-            serde_json::from_str(json).map_err(|e| format!("Invalid provider config: {}", e))?;
+        let config = parse_config(json).map_err(|e| format!("Invalid provider config: {}", e))?;
         let id = config.id.trim().to_string();
         if id.is_empty() {
             return Err("Provider config is missing an \"id\"".to_string());
@@ -549,6 +1318,130 @@ impl ProviderRegistry {
     }
 }
 
+/// Parse one config file, naming the field that does not match.
+fn parse_config(json: &str) -> Result<ProviderConfig, String> {
+    let deserializer = &mut serde_json::Deserializer::from_str(json);
+    let config: ProviderConfig = serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        // A syntax error has no field to name, and neither does a field
+        // missing at the top level; serde_json already says where.
+        let path = e.path().to_string();
+        if e.inner().is_data() && path != "." {
+            format!("field '{}' — {}", path, e.inner())
+        } else {
+            e.inner().to_string()
+        }
+    })?;
+    config.version_check.validate()?;
+    Ok(config)
+}
+
+fn is_config_file(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("json")
+}
+
+/// `dirs` without repeats, keeping each directory at its last position so it
+/// still wins where it did. Running from the bundle, the working directory and
+/// the executable's are often the same folder.
+fn distinct_dirs(dirs: Vec<PathBuf>) -> Vec<PathBuf> {
+    let key = |dir: &PathBuf| fs::canonicalize(dir).unwrap_or_else(|_| dir.clone());
+    let keys: Vec<PathBuf> = dirs.iter().map(key).collect();
+    dirs.into_iter()
+        .enumerate()
+        .filter(|(i, _)| !keys[i + 1..].contains(&keys[*i]))
+        .map(|(_, dir)| dir)
+        .collect()
+}
+
+/// Check every `*.json` in `dirs`, returning the per-file verdicts (in search
+/// order, files sorted by name within a directory) and the configs that won
+/// their id.
+fn scan_configs(dirs: &[PathBuf]) -> (Vec<ProviderConfigCheck>, HashMap<String, ProviderConfig>) {
+    let mut checks: Vec<ProviderConfigCheck> = Vec::new();
+    let mut configs: HashMap<String, ProviderConfig> = HashMap::new();
+    // Which check currently holds each id, so the one it displaces can say why.
+    let mut holders: HashMap<String, usize> = HashMap::new();
+
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_config_file(path) && path.is_file())
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    checks.push(ProviderConfigCheck {
+                        path,
+                        id: None,
+                        status: ProviderConfigStatus::Invalid,
+                        error: Some(format!("Could not read: {}", e)),
+                    });
+                    continue;
+                }
+            };
+            let config = match parse_config(&content) {
+                Ok(config) => config,
+                Err(error) => {
+                    // Name the provider if the file is at least JSON with an id.
+                    let id = serde_json::from_str::<serde_json::Value>(&content)
+                        .ok()
+                        .and_then(|value| value.get("id")?.as_str().map(str::to_string));
+                    checks.push(ProviderConfigCheck {
+                        path,
+                        id,
+                        status: ProviderConfigStatus::Invalid,
+                        error: Some(error),
+                    });
+                    continue;
+                }
+            };
+
+            let id = config.id.trim().to_string();
+            if id.is_empty() {
+                checks.push(ProviderConfigCheck {
+                    path,
+                    id: None,
+                    status: ProviderConfigStatus::Invalid,
+                    error: Some("Provider config is missing an \"id\"".to_string()),
+                });
+                continue;
+            }
+            if id == RESERVED_PROVIDER_ID {
+                checks.push(ProviderConfigCheck {
+                    path,
+                    id: Some(id),
+                    status: ProviderConfigStatus::Reserved,
+                    error: Some(format!(
+                        "\"{}\" is a built-in provider id",
+                        RESERVED_PROVIDER_ID
+                    )),
+                });
+                continue;
+            }
+
+            if let Some(previous) = holders.insert(id.clone(), checks.len()) {
+                checks[previous].status = ProviderConfigStatus::Shadowed;
+                checks[previous].error = Some(format!("Overridden by {}", path.display()));
+            }
+            checks.push(ProviderConfigCheck {
+                path,
+                id: Some(id.clone()),
+                status: ProviderConfigStatus::Loaded,
+                error: None,
+            });
+            configs.insert(id, config);
+        }
+    }
+
+    (checks, configs)
+}
+
 pub type ProviderRegistryState = Arc<ProviderRegistry>;
 
 pub fn new_provider_registry(app: Option<&tauri::AppHandle>) -> ProviderRegistryState {
@@ -593,6 +1486,28 @@ mod tests {
         serde_json::from_str(json).unwrap()
     }
 
+    /// The command as one string to compare against: argv joined by spaces,
+    /// or the shell line as it is.
+    fn words(cmd: &SpawnCommand) -> String {
+        match &cmd.command {
+            CommandLine::Argv(argv) => argv.join(" "),
+            CommandLine::Shell(line) => line.clone(),
+        }
+    }
+
+    fn argv(cmd: &SpawnCommand) -> Vec<&str> {
+        match &cmd.command {
+            CommandLine::Argv(argv) => argv.iter().map(String::as_str).collect(),
+            CommandLine::Shell(line) => panic!("expected argv, got a shell line: {line}"),
+        }
+    }
+
+    fn get_shell_claude_config() -> ProviderConfig {
+        let mut config = get_claude_config();
+        config.shell = true;
+        config
+    }
+
     // ── Splicing a flag in behind the executable ──────────────────────
 
     #[test]
@@ -602,8 +1517,8 @@ mod tests {
             .build_spawn_command("sonnet", "task", Some("auto"), false, false, false)
             .with_flag_after_executable("--settings", "/tmp/s.json");
         assert_eq!(
-            cmd.command,
-            "claude --settings \"/tmp/s.json\" --model sonnet \"task\" --permission-mode auto"
+            words(&cmd),
+            "claude --settings /tmp/s.json --model sonnet task --permission-mode auto"
         );
     }
 
@@ -618,34 +1533,40 @@ mod tests {
             .build_spawn_command("auto", "task", Some("default"), false, false, false)
             .with_flag_after_executable("--settings", "/tmp/s.json");
         assert_eq!(
-            cmd.command,
-            "npx -y @anthropic-ai/claude-code --settings \"/tmp/s.json\" \"task\""
+            words(&cmd),
+            "npx -y @anthropic-ai/claude-code --settings /tmp/s.json task"
         );
     }
 
     #[test]
     fn a_spliced_value_is_escaped_for_the_double_quotes_it_lands_in() {
-        let provider = DynamicProvider::new(get_claude_config());
+        let path = "/Users/a$b!/Application Support/s.json";
+        let provider = DynamicProvider::new(get_shell_claude_config());
         let cmd = provider
             .build_spawn_command("auto", "task", Some("default"), false, false, false)
-            .with_flag_after_executable("--settings", "/Users/a$b!/Application Support/s.json");
+            .with_flag_after_executable("--settings", path);
         assert!(
-            cmd.command
-                .contains(r#""/Users/a\$b\!/Application Support/s.json""#),
+            words(&cmd).contains(r#""/Users/a\$b\!/Application Support/s.json""#),
             "{}",
-            cmd.command
+            words(&cmd)
         );
+
+        // As argv it is one argument, exactly as given.
+        let cmd = DynamicProvider::new(get_claude_config())
+            .build_spawn_command("auto", "task", Some("default"), false, false, false)
+            .with_flag_after_executable("--settings", path);
+        assert_eq!(argv(&cmd), ["claude", "--settings", path, "task"]);
     }
 
     #[test]
     fn splicing_does_not_reflow_whitespace_inside_the_task() {
         // The task is embedded in this string. Splitting the command into
         // words and rejoining it would quietly rewrite the user's prompt.
-        let provider = DynamicProvider::new(get_claude_config());
+        let provider = DynamicProvider::new(get_shell_claude_config());
         let cmd = provider
             .build_spawn_command("auto", "two  spaces", Some("default"), false, false, false)
             .with_flag_after_executable("--settings", "/tmp/s.json");
-        assert!(cmd.command.contains("\"two  spaces\""), "{}", cmd.command);
+        assert!(words(&cmd).contains("\"two  spaces\""), "{}", words(&cmd));
     }
 
     #[test]
@@ -654,7 +1575,7 @@ mod tests {
         // claude "task" (explicit interactive mode maps to no flag)
         let cmd =
             provider.build_spawn_command("auto", "task", Some("default"), false, false, false);
-        assert_eq!(cmd.command, "claude \"task\"");
+        assert_eq!(words(&cmd), "claude task");
     }
 
     #[test]
@@ -662,7 +1583,7 @@ mod tests {
         let provider = DynamicProvider::new(get_claude_config());
         // claude --model opus -p "task"
         let cmd = provider.build_spawn_command("opus", "task", Some("default"), false, false, true);
-        assert_eq!(cmd.command, "claude --model opus -p \"task\"");
+        assert_eq!(words(&cmd), "claude --model opus -p task");
     }
 
     #[test]
@@ -671,8 +1592,234 @@ mod tests {
         // claude --model sonnet "task" --permission-mode plan
         let cmd = provider.build_spawn_command("sonnet", "task", Some("plan"), false, false, false);
         assert_eq!(
-            cmd.command,
-            "claude --model sonnet \"task\" --permission-mode plan"
+            words(&cmd),
+            "claude --model sonnet task --permission-mode plan"
+        );
+    }
+
+    #[test]
+    fn an_event_stream_format_gives_the_provider_a_parser() {
+        assert!(DynamicProvider::new(get_claude_config())
+            .event_parser()
+            .is_none());
+
+        let mut config = get_claude_config();
+        config.event_stream = Some(EventStreamFormat::ClaudeStreamJson);
+        let mut parser = DynamicProvider::new(config)
+            .event_parser()
+            .expect("a configured format yields a parser");
+        assert_eq!(
+            parser
+                .parse_line(r#"{"type":"result","subtype":"success","is_error":false}"#)
+                .len(),
+            1
+        );
+        assert!(CrushProvider.event_parser().is_none());
+
+        let unknown = r#"{"id":"x","name":"x","executable":"x","arguments":[],
+            "info":{"models":[],"permissionModes":[],"defaultModel":"","defaultPermissionMode":""},
+            "versionCheck":{"command":"x","args":[]},"promptTemplate":"",
+            "eventStream":"yaml-please"}"#;
+        assert!(serde_json::from_str::<ProviderConfig>(unknown).is_err());
+    }
+
+    #[test]
+    fn session_arguments_name_a_new_session_or_resume_an_old_one() {
+        let mut config = get_claude_config();
+        config.arguments.insert(
+            1,
+            serde_json::from_str(r#"{ "type": "sessionId", "flag": "--session-id" }"#).unwrap(),
+        );
+        config.arguments.insert(
+            2,
+            serde_json::from_str(r#"{ "type": "resume", "args": ["--resume", "{sessionId}"] }"#)
+                .unwrap(),
+        );
+        let provider = DynamicProvider::new(config);
+        assert_eq!(
+            provider.session_support(),
+            SessionSupport {
+                names_new_sessions: true,
+                resumes: true,
+                directory: None,
+            }
+        );
+
+        let build = |session: Option<SessionRequest>| {
+            words(&provider.build_session_spawn_command(
+                "auto",
+                "task",
+                Some("default"),
+                false,
+                false,
+                true,
+                session.as_ref(),
+                &SpawnContext::default(),
+            ))
+        };
+        assert_eq!(build(None), "claude -p task");
+        assert_eq!(
+            build(Some(SessionRequest::New("abc-1".to_string()))),
+            "claude --session-id abc-1 -p task"
+        );
+        assert_eq!(
+            build(Some(SessionRequest::Resume("abc-1".to_string()))),
+            "claude --resume abc-1 -p task"
+        );
+        // Never anything but an id, read back from disk or not.
+        assert_eq!(
+            build(Some(SessionRequest::Resume("x; rm -rf ~".to_string()))),
+            "claude -p task"
+        );
+
+        assert_eq!(
+            DynamicProvider::new(get_claude_config()).session_support(),
+            SessionSupport::default()
+        );
+        assert_eq!(CrushProvider.session_support(), SessionSupport::default());
+    }
+
+    fn get_context_config() -> ProviderConfig {
+        let json = r#"{
+          "id": "claude",
+          "name": "Claude Code",
+          "executable": "claude",
+          "arguments": [
+            { "type": "env", "name": "OPENROUTER_API_KEY",
+              "credential": { "namespace": "llm_settings", "key": "api_key" } },
+            { "type": "env", "name": "DISABLE_AUTOUPDATER", "value": "1" },
+            { "type": "cwd", "flag": "--cwd" },
+            { "type": "projectDir", "flag": "--add-dir" },
+            { "type": "images", "flag": "--image" },
+            { "type": "systemPrompt", "flag": "--append-system-prompt-file", "file": true },
+            { "type": "maxTurns", "flag": "--max-turns" },
+            { "type": "mcpConfig", "flag": "--mcp-config" },
+            { "type": "when", "headless": true, "args": [
+                { "type": "literal", "value": "--output-format stream-json" },
+                { "type": "when", "permission": ["plan"], "args": [
+                    { "type": "literal", "value": "--verbose" }
+                ] }
+            ] },
+            { "type": "task", "quote": true }
+          ],
+          "info": {
+            "models": [],
+            "permissionModes": [],
+            "defaultModel": "sonnet",
+            "defaultPermissionMode": "default"
+          },
+          "versionCheck": { "command": "claude", "args": ["--version"] },
+          "promptTemplate": "claude \""
+        }"#;
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn full_context() -> SpawnContext {
+        SpawnContext {
+            cwd: Some("/work/repo-wt".to_string()),
+            project_dir: Some("/work/repo".to_string()),
+            images: vec!["/tmp/a b.png".to_string(), "/tmp/c.png".to_string()],
+            system_prompt: Some("Be terse.".to_string()),
+            max_turns: Some(12),
+            mcp_config: Some("/work/repo/.mcp.json".to_string()),
+        }
+    }
+
+    #[test]
+    fn context_arguments_appear_only_when_the_run_brings_them() {
+        let provider = DynamicProvider::new(get_context_config());
+        let build = |headless, permission, context: &SpawnContext| {
+            provider.build_session_spawn_command(
+                "auto", "task", permission, false, false, headless, None, context,
+            )
+        };
+
+        let bare = build(false, None, &SpawnContext::default());
+        assert_eq!(argv(&bare), ["claude", "task"]);
+        assert_eq!(
+            bare.env_vars,
+            [("DISABLE_AUTOUPDATER".to_string(), "1".to_string())]
+        );
+        assert_eq!(
+            bare.credential_env,
+            [(
+                "OPENROUTER_API_KEY".to_string(),
+                CredentialRef {
+                    namespace: "llm_settings".to_string(),
+                    key: "api_key".to_string(),
+                }
+            )]
+        );
+        assert_eq!(bare.system_prompt_file, None);
+
+        let full = build(false, None, &full_context());
+        assert_eq!(
+            argv(&full),
+            [
+                "claude",
+                "--cwd",
+                "/work/repo-wt",
+                "--add-dir",
+                "/work/repo",
+                "--image",
+                "/tmp/a b.png",
+                "--image",
+                "/tmp/c.png",
+                "--append-system-prompt-file",
+                SYSTEM_PROMPT_FILE_PLACEHOLDER,
+                "--max-turns",
+                "12",
+                "--mcp-config",
+                "/work/repo/.mcp.json",
+                "task",
+            ]
+        );
+        assert_eq!(full.system_prompt_file.as_deref(), Some("Be terse."));
+        let written = full.with_system_prompt_file("/data/agent-tasks/a.system.md");
+        assert!(argv(&written).contains(&"/data/agent-tasks/a.system.md"));
+    }
+
+    #[test]
+    fn when_blocks_follow_headless_and_permission_and_nest() {
+        let provider = DynamicProvider::new(get_context_config());
+        let build = |headless, permission| {
+            words(&provider.build_session_spawn_command(
+                "auto",
+                "task",
+                permission,
+                false,
+                false,
+                headless,
+                None,
+                &SpawnContext::default(),
+            ))
+        };
+
+        assert_eq!(build(false, Some("plan")), "claude task");
+        assert_eq!(build(true, None), "claude --output-format stream-json task");
+        assert_eq!(
+            build(true, Some("plan")),
+            "claude --output-format stream-json --verbose task"
+        );
+    }
+
+    #[test]
+    fn shell_mode_quotes_paths_and_prompts_from_the_run() {
+        let mut config = get_context_config();
+        config.shell = true;
+        let provider = DynamicProvider::new(config);
+        let context = SpawnContext {
+            images: vec!["/tmp/it's $HOME.png".to_string()],
+            system_prompt: Some("Say \"hi\"".to_string()),
+            ..SpawnContext::default()
+        };
+        let cmd = provider
+            .build_session_spawn_command("auto", "task", None, false, false, false, None, &context);
+        let cmd = cmd.with_system_prompt_file("/data/a b.md");
+
+        assert_eq!(
+            words(&cmd),
+            r#"claude --image "/tmp/it's \$HOME.png" --append-system-prompt-file "/data/a b.md" "task""#
         );
     }
 
@@ -683,6 +1830,8 @@ mod tests {
             providers: RwLock::new(providers),
             default_id: RwLock::new("crush".to_string()),
             import_dir: None, // no persistence in tests
+            search_paths: Vec::new(),
+            watcher: Mutex::new(None),
         }
     }
 
@@ -730,10 +1879,10 @@ mod tests {
         // Claude Code's explicit auto mode is distinct from bypassPermissions.
         let cmd = provider.build_spawn_command("sonnet", "task", Some("auto"), false, false, false);
         assert_eq!(
-            cmd.command,
-            "claude --model sonnet \"task\" --permission-mode auto"
+            words(&cmd),
+            "claude --model sonnet task --permission-mode auto"
         );
-        assert!(!cmd.command.contains("bypassPermissions"));
+        assert!(!words(&cmd).contains("bypassPermissions"));
     }
 
     #[test]
@@ -743,8 +1892,8 @@ mod tests {
         let provider = DynamicProvider::new(get_claude_config());
         let cmd = provider.build_spawn_command("sonnet", "task", None, false, false, false);
         assert_eq!(
-            cmd.command,
-            "claude --model sonnet \"task\" --permission-mode acceptEdits"
+            words(&cmd),
+            "claude --model sonnet task --permission-mode acceptEdits"
         );
     }
 
@@ -753,9 +1902,9 @@ mod tests {
         let provider = DynamicProvider::new(get_claude_config());
         let cmd = provider.build_spawn_command("sonnet", "task", None, true, false, false);
         assert!(
-            cmd.command.contains("--permission-mode bypassPermissions"),
+            words(&cmd).contains("--permission-mode bypassPermissions"),
             "legacy dangerously_ignore_permissions must still map to bypass: {}",
-            cmd.command
+            words(&cmd)
         );
     }
 
@@ -764,9 +1913,9 @@ mod tests {
         let provider = DynamicProvider::new(get_claude_config());
         let cmd = provider.build_spawn_command("sonnet", "task", Some("plan"), false, false, false);
         assert!(
-            cmd.command.contains("--permission-mode plan"),
+            words(&cmd).contains("--permission-mode plan"),
             "explicit mode must win: {}",
-            cmd.command
+            words(&cmd)
         );
     }
 
@@ -806,7 +1955,7 @@ mod tests {
         // gemini -i "task" --sandbox (default fallback)
         // Wait, permission_mode None -> fallback logic
         let cmd = provider.build_spawn_command("auto", "task", None, false, false, false);
-        assert_eq!(cmd.command, "gemini -i \"task\" --sandbox");
+        assert_eq!(words(&cmd), "gemini -i task --sandbox");
     }
 
     #[test]
@@ -815,8 +1964,8 @@ mod tests {
         // gemini -p "task" --model gemini-2.5-pro --sandbox
         let cmd = provider.build_spawn_command("gemini-2.5-pro", "task", None, false, false, true);
         assert_eq!(
-            cmd.command,
-            "gemini -p \"task\" --model gemini-2.5-pro --sandbox"
+            words(&cmd),
+            "gemini -p task --model gemini-2.5-pro --sandbox"
         );
     }
 
@@ -861,7 +2010,7 @@ mod tests {
         // grok "task" — the prompt is a positional argument in interactive mode.
         let cmd =
             provider.build_spawn_command("auto", "task", Some("default"), false, false, false);
-        assert_eq!(cmd.command, "grok \"task\"");
+        assert_eq!(words(&cmd), "grok task");
     }
 
     #[test]
@@ -871,8 +2020,8 @@ mod tests {
         let cmd =
             provider.build_spawn_command("grok-4.5", "task", Some("auto"), false, false, true);
         assert_eq!(
-            cmd.command,
-            "grok --model grok-4.5 -p \"task\" --permission-mode auto"
+            words(&cmd),
+            "grok --model grok-4.5 -p task --permission-mode auto"
         );
     }
 
@@ -883,7 +2032,7 @@ mod tests {
         // must not be the guardrail-free one.
         let provider = DynamicProvider::new(get_grok_config());
         let cmd = provider.build_spawn_command("auto", "task", None, false, false, true);
-        assert_eq!(cmd.command, "grok -p \"task\" --permission-mode auto");
+        assert_eq!(words(&cmd), "grok -p task --permission-mode auto");
     }
 
     #[test]
@@ -895,7 +2044,7 @@ mod tests {
             ("plan", "--permission-mode plan"),
         ] {
             let cmd = provider.build_spawn_command("auto", "task", Some(mode), false, false, true);
-            assert_eq!(cmd.command, format!("grok -p \"task\" {}", expected_flag));
+            assert_eq!(words(&cmd), format!("grok -p task {}", expected_flag));
         }
     }
 
@@ -949,8 +2098,8 @@ mod tests {
             true,
         );
         assert_eq!(
-            cmd.command,
-            "codex exec --model gpt-5.6-sol \"task\" --sandbox workspace-write"
+            words(&cmd),
+            "codex exec --model gpt-5.6-sol task --sandbox workspace-write"
         );
     }
 
@@ -958,7 +2107,7 @@ mod tests {
     fn test_dynamic_codex_interactive_drops_the_subcommand() {
         let provider = DynamicProvider::new(get_codex_config());
         let cmd = provider.build_spawn_command("auto", "task", Some("plan"), false, false, false);
-        assert_eq!(cmd.command, "codex \"task\" --sandbox read-only");
+        assert_eq!(words(&cmd), "codex task --sandbox read-only");
     }
 
     #[test]
@@ -967,7 +2116,7 @@ mod tests {
         // must confine writes to the workspace rather than lift the sandbox.
         let provider = DynamicProvider::new(get_codex_config());
         let cmd = provider.build_spawn_command("auto", "task", None, false, false, true);
-        assert_eq!(cmd.command, "codex exec \"task\" --sandbox workspace-write");
+        assert_eq!(words(&cmd), "codex exec task --sandbox workspace-write");
     }
 
     #[test]
@@ -982,10 +2131,10 @@ mod tests {
             true,
         );
         assert_eq!(
-            cmd.command,
-            "codex exec \"task\" --dangerously-bypass-approvals-and-sandbox"
+            words(&cmd),
+            "codex exec task --dangerously-bypass-approvals-and-sandbox"
         );
-        assert!(!cmd.command.contains("--sandbox"));
+        assert!(!words(&cmd).contains("--sandbox"));
     }
 
     #[test]
@@ -995,7 +2144,7 @@ mod tests {
         // become a sandbox choice — it leaves the decision to codex's config.
         let provider = DynamicProvider::new(get_codex_config());
         let cmd = provider.build_spawn_command("auto", "task", Some("auto"), false, false, true);
-        assert_eq!(cmd.command, "codex exec \"task\"");
+        assert_eq!(words(&cmd), "codex exec task");
     }
 
     // ── Dynamic Provider Tests (OpenCode Emulation) ────────────────────
@@ -1048,8 +2197,8 @@ mod tests {
             true,
         );
         assert_eq!(
-            cmd.command,
-            "opencode run \"task\" --model anthropic/claude-sonnet-4-6 --auto"
+            words(&cmd),
+            "opencode run task --model anthropic/claude-sonnet-4-6 --auto"
         );
     }
 
@@ -1059,7 +2208,7 @@ mod tests {
         // A bare positional would be the TUI project path. --prompt owns the task.
         let cmd =
             provider.build_spawn_command("auto", "task", Some("default"), false, false, false);
-        assert_eq!(cmd.command, "opencode --prompt \"task\"");
+        assert_eq!(words(&cmd), "opencode --prompt task");
     }
 
     #[test]
@@ -1068,18 +2217,18 @@ mod tests {
         // It must run without prompting; deny rules still apply.
         let provider = DynamicProvider::new(get_opencode_config());
         let cmd = provider.build_spawn_command("auto", "task", None, false, false, true);
-        assert_eq!(cmd.command, "opencode run \"task\" --auto");
+        assert_eq!(words(&cmd), "opencode run task --auto");
     }
 
     #[test]
     fn test_dynamic_opencode_plan_uses_the_plan_agent() {
         let provider = DynamicProvider::new(get_opencode_config());
         let cmd = provider.build_spawn_command("auto", "task", Some("plan"), false, false, true);
-        assert_eq!(cmd.command, "opencode run \"task\" --agent plan");
+        assert_eq!(words(&cmd), "opencode run task --agent plan");
         assert!(
-            !cmd.command.contains("--auto"),
+            !words(&cmd).contains("--auto"),
             "plan must not also auto-approve: {}",
-            cmd.command
+            words(&cmd)
         );
     }
 
@@ -1091,7 +2240,7 @@ mod tests {
         let provider = DynamicProvider::new(get_opencode_config());
         for mode in ["acceptEdits", "bypassPermissions"] {
             let cmd = provider.build_spawn_command("auto", "task", Some(mode), false, false, true);
-            assert_eq!(cmd.command, "opencode run \"task\" --auto");
+            assert_eq!(words(&cmd), "opencode run task --auto");
         }
     }
 
@@ -1114,7 +2263,7 @@ mod tests {
         );
         assert_eq!(provider.info().id, "opencode");
         let cmd = provider.build_spawn_command("auto", "task", Some("auto"), false, false, true);
-        assert_eq!(cmd.command, "opencode run \"task\" --auto");
+        assert_eq!(words(&cmd), "opencode run task --auto");
     }
 
     #[test]
@@ -1126,6 +2275,8 @@ mod tests {
             )])),
             default_id: RwLock::new(RESERVED_PROVIDER_ID.to_string()),
             import_dir: None,
+            search_paths: Vec::new(),
+            watcher: Mutex::new(None),
         };
         let hijack = format!(
             r#"{{"id": "{}", "name": "Not Crush", "executable": "nope",
@@ -1196,6 +2347,202 @@ mod tests {
         assert!(loaded.contains_key("opencode"));
     }
 
+    fn provider_json(id: &str) -> String {
+        format!(
+            r#"{{"id": "{}", "name": "{}", "executable": "{}",
+                 "arguments": [], "info": {{"models": [], "permissionModes": [],
+                 "defaultModel": "auto", "defaultPermissionMode": "default"}},
+                 "versionCheck": {{"command": "{}", "args": []}},
+                 "promptTemplate": "{}"}}"#,
+            id, id, id, id, id
+        )
+    }
+
+    #[test]
+    fn validation_says_why_each_file_is_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let (early, late) = (dir.path().join("early"), dir.path().join("late"));
+        fs::create_dir_all(&early).unwrap();
+        fs::create_dir_all(&late).unwrap();
+        fs::write(early.join("aider.json"), provider_json("aider")).unwrap();
+        fs::write(late.join("aider.json"), provider_json("aider")).unwrap();
+        fs::write(
+            late.join("broken.json"),
+            provider_json("gemini")
+                .replace(r#""arguments": []"#, r#""arguments": [{"type": "nope"}]"#),
+        )
+        .unwrap();
+        fs::write(late.join("crush.json"), provider_json(RESERVED_PROVIDER_ID)).unwrap();
+        fs::write(late.join("notes.txt"), "not a config").unwrap();
+
+        let checks = scan_configs(&[early.clone(), late.clone()]).0;
+        let verdict = |path: PathBuf| {
+            let check = checks.iter().find(|c| c.path == path).unwrap();
+            (check.id.clone(), check.status, check.error.clone())
+        };
+
+        assert_eq!(checks.len(), 4);
+        assert_eq!(
+            verdict(early.join("aider.json")),
+            (
+                Some("aider".to_string()),
+                ProviderConfigStatus::Shadowed,
+                Some(format!(
+                    "Overridden by {}",
+                    late.join("aider.json").display()
+                ))
+            )
+        );
+        assert_eq!(
+            verdict(late.join("aider.json")).1,
+            ProviderConfigStatus::Loaded
+        );
+        assert_eq!(
+            verdict(late.join("crush.json")).1,
+            ProviderConfigStatus::Reserved
+        );
+
+        let (id, status, error) = verdict(late.join("broken.json"));
+        assert_eq!(id.as_deref(), Some("gemini"));
+        assert_eq!(status, ProviderConfigStatus::Invalid);
+        let error = error.unwrap();
+        assert!(error.starts_with("field 'arguments[0].type' — "), "{error}");
+    }
+
+    #[test]
+    fn a_syntax_error_reports_where_it_is() {
+        let error = parse_config("{\n  \"id\": \"aider\",\n}").unwrap_err();
+        assert!(error.contains("line 3 column 1"), "{error}");
+        assert!(!error.starts_with("field"), "{error}");
+    }
+
+    #[test]
+    fn reload_picks_up_added_and_removed_configs() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = empty_registry();
+        registry.search_paths = vec![dir.path().to_path_buf()];
+
+        fs::write(dir.path().join("claude.json"), provider_json("claude")).unwrap();
+        registry.reload();
+        assert!(registry.get("claude").is_some());
+        assert_eq!(registry.default_provider().info().id, "claude");
+
+        fs::remove_file(dir.path().join("claude.json")).unwrap();
+        registry.reload();
+        assert!(registry.get("claude").is_none());
+        assert_eq!(registry.default_provider().info().id, RESERVED_PROVIDER_ID);
+    }
+
+    #[test]
+    fn a_directory_listed_twice_is_scanned_once_at_its_later_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        let twice = vec![a.clone(), b.clone(), a.join("..").join("a")];
+
+        assert_eq!(distinct_dirs(twice), vec![b, a.join("..").join("a")]);
+    }
+
+    // ── Version probing ───────────────────────────────────────────────
+
+    fn versioned_claude(version_check: &str) -> DynamicProvider {
+        let mut config = get_claude_config();
+        config.version_check = serde_json::from_str(version_check).unwrap();
+        config.event_stream = Some(EventStreamFormat::ClaudeStreamJson);
+        DynamicProvider::new(config)
+    }
+
+    fn ran(text: &str) -> Result<VersionOutput, String> {
+        Ok(VersionOutput {
+            success: true,
+            text: text.to_string(),
+        })
+    }
+
+    #[test]
+    fn versions_compare_by_their_numbers() {
+        assert_eq!(version_at_least("2.0.14", "2.0.9"), Some(true));
+        assert_eq!(version_at_least("1.10", "1.9.3"), Some(true));
+        assert_eq!(version_at_least("v1.2", "1.2.0"), Some(true));
+        assert_eq!(version_at_least("1.2.0-beta.1", "1.3"), Some(false));
+        assert_eq!(version_at_least("nightly", "1.0"), None);
+    }
+
+    #[test]
+    fn the_version_is_found_by_pattern_or_as_the_first_dotted_number() {
+        let check: VersionCheck = serde_json::from_str(
+            r#"{"command":"codex","args":["--version"],"versionPattern":"codex-cli (\\S+)"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            check
+                .extract_version("build 7.1\ncodex-cli 0.46.0\n")
+                .as_deref(),
+            Some("0.46.0")
+        );
+
+        let check = versioned_claude(r#"{"command":"claude","args":[]}"#).version_check();
+        assert_eq!(
+            check.extract_version("2.0.14 (Claude Code)").as_deref(),
+            Some("2.0.14")
+        );
+        assert_eq!(check.extract_version("no version here"), None);
+    }
+
+    #[test]
+    fn a_bad_pattern_or_version_is_refused_with_its_field() {
+        let config = |version_check: &str| {
+            provider_json("claude").replace(r#"{"command": "claude", "args": []}"#, version_check)
+        };
+        let error = parse_config(&config(
+            r#"{"command":"claude","args":[],"versionPattern":"("}"#,
+        ))
+        .unwrap_err();
+        assert!(
+            error.starts_with("field 'versionCheck.versionPattern' — "),
+            "{error}"
+        );
+        let error = parse_config(&config(
+            r#"{"command":"claude","args":[],"capabilityVersions":{"resume":"soon"}}"#,
+        ))
+        .unwrap_err();
+        assert!(
+            error.starts_with("field 'versionCheck.capabilityVersions.resume' — "),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn a_probe_reports_the_minimum_and_what_the_installed_version_supports() {
+        let provider = versioned_claude(
+            r#"{"command":"claude","args":["--version"],"minVersion":"1.0",
+                "capabilityVersions":{"streamJson":"2.1"}}"#,
+        );
+        let probe = probe_from(&provider, Some("/usr/bin/claude".into()), ran("2.0.14"));
+        assert!(probe.installed);
+        assert_eq!(probe.version.as_deref(), Some("2.0.14"));
+        assert_eq!(probe.meets_minimum, Some(true));
+        assert_eq!(
+            probe.capabilities,
+            ProviderCapabilities {
+                resume: false,
+                stream_json: false,
+                headless: true,
+            }
+        );
+        assert_eq!(probe.error, None);
+
+        let probe = probe_from(&provider, None, ran("0.9.1"));
+        assert_eq!(probe.meets_minimum, Some(false));
+
+        let probe = probe_from(&provider, None, Err("No such file".into()));
+        assert!(!probe.installed);
+        assert_eq!(probe.meets_minimum, None);
+        assert_eq!(probe.capabilities, ProviderCapabilities::default());
+        assert!(probe.error.unwrap().contains("No such file"));
+    }
+
     // ── CrushProvider Tests ────────────────────────────────────────────
 
     #[test]
@@ -1210,9 +2557,9 @@ mod tests {
             false,
         );
         assert!(
-            cmd.command.contains("--yolo"),
+            words(&cmd).contains("--yolo"),
             "Expected --yolo in command: {}",
-            cmd.command
+            words(&cmd)
         );
     }
 
@@ -1221,39 +2568,143 @@ mod tests {
         let provider = CrushProvider;
         let cmd = provider.build_spawn_command("auto", "do it", Some("yolo"), false, false, false);
         assert!(
-            cmd.command.contains("--yolo"),
+            words(&cmd).contains("--yolo"),
             "Expected --yolo in command: {}",
-            cmd.command
+            words(&cmd)
         );
     }
 
     #[test]
     fn test_shell_escape_backticks_and_parens() {
-        let provider = DynamicProvider::new(get_claude_config());
+        let provider = DynamicProvider::new(get_shell_claude_config());
         let task = "Call `list_epics()` then `create_epic({ name })` ok";
         let cmd = provider.build_spawn_command("sonnet", task, Some("default"), false, false, true);
         assert_eq!(
-            cmd.command,
+            words(&cmd),
             "claude --model sonnet -p \"Call \\`list_epics()\\` then \\`create_epic({ name })\\` ok\""
         );
     }
 
     #[test]
     fn test_shell_escape_backslash_and_dollar() {
-        let provider = DynamicProvider::new(get_claude_config());
+        let provider = DynamicProvider::new(get_shell_claude_config());
         let task = r#"path C:\Users and $HOME with "quotes""#;
         let cmd = provider.build_spawn_command("auto", task, Some("default"), false, false, true);
         assert_eq!(
-            cmd.command,
+            words(&cmd),
             r#"claude -p "path C:\\Users and \$HOME with \"quotes\"""#
         );
     }
 
     #[test]
     fn test_shell_escape_exclamation() {
-        let provider = CrushProvider;
-        let cmd = provider.build_spawn_command("auto", "fix this!", None, false, false, false);
-        assert_eq!(cmd.command, r#"crush "fix this\!""#);
+        let provider = DynamicProvider::new(get_shell_claude_config());
+        let cmd =
+            provider.build_spawn_command("auto", "fix this!", Some("default"), false, false, false);
+        assert_eq!(words(&cmd), r#"claude "fix this\!""#);
+    }
+
+    // ── Argv and task delivery ────────────────────────────────────────
+
+    #[test]
+    fn the_task_is_one_argument_however_it_is_written() {
+        let task = "Fix \"it\"\n\n- don't `rm` $HOME\\ \\n  (twice)!";
+        let cmd = DynamicProvider::new(get_claude_config()).build_spawn_command(
+            "sonnet",
+            task,
+            Some("plan"),
+            false,
+            false,
+            true,
+        );
+        // The config's multi-word permission flag still splits into words.
+        assert_eq!(
+            argv(&cmd),
+            [
+                "claude",
+                "--model",
+                "sonnet",
+                "-p",
+                task,
+                "--permission-mode",
+                "plan"
+            ]
+        );
+
+        let cmd =
+            CrushProvider.build_spawn_command("auto", task, Some("yolo"), false, false, false);
+        assert_eq!(argv(&cmd), ["crush", "--yolo", task]);
+    }
+
+    #[test]
+    fn shell_mode_is_opt_in() {
+        let mut json: serde_json::Value = serde_json::from_str(
+            r#"{"id":"x","name":"x","executable":"x","arguments":[{"type":"task","quote":true}],
+            "info":{"models":[],"permissionModes":[],"defaultModel":"","defaultPermissionMode":""},
+            "versionCheck":{"command":"x","args":[]},"promptTemplate":""}"#,
+        )
+        .unwrap();
+        let build = |json: &serde_json::Value| {
+            let config: ProviderConfig = serde_json::from_value(json.clone()).unwrap();
+            DynamicProvider::new(config)
+                .build_spawn_command("auto", "a b", None, false, false, true)
+        };
+        assert_eq!(
+            build(&json).command,
+            CommandLine::Argv(vec!["x".into(), "a b".into()])
+        );
+
+        json["shell"] = true.into();
+        assert_eq!(
+            build(&json).command,
+            CommandLine::Shell("x \"a b\"".to_string())
+        );
+    }
+
+    #[test]
+    fn task_via_stdin_or_file_keeps_the_prompt_off_the_command_line() {
+        let mut config = get_claude_config();
+        config.task_via = TaskVia::Stdin;
+        let cmd = DynamicProvider::new(config).build_spawn_command(
+            "auto",
+            "a huge prompt",
+            Some("default"),
+            false,
+            false,
+            true,
+        );
+        assert_eq!(argv(&cmd), ["claude", "-p"]);
+        assert_eq!(
+            (cmd.task_via, cmd.task.as_str()),
+            (TaskVia::Stdin, "a huge prompt")
+        );
+
+        let mut config = get_claude_config();
+        config.task_via = TaskVia::File;
+        let cmd = DynamicProvider::new(config)
+            .build_spawn_command("auto", "a huge prompt", Some("default"), false, false, true)
+            .with_task_file("/data/agent-tasks/agent-1.md");
+        assert_eq!(argv(&cmd), ["claude", "-p", "/data/agent-tasks/agent-1.md"]);
+
+        let mut config = get_shell_claude_config();
+        config.task_via = TaskVia::File;
+        let cmd = DynamicProvider::new(config)
+            .build_spawn_command("auto", "a huge prompt", Some("default"), false, false, true)
+            .with_task_file("/Application Support/agent-1.md");
+        assert_eq!(words(&cmd), "claude -p \"/Application Support/agent-1.md\"");
+
+        // A task that happens to read like the placeholder is left alone.
+        let cmd = DynamicProvider::new(get_claude_config())
+            .build_spawn_command(
+                "auto",
+                TASK_FILE_PLACEHOLDER,
+                Some("default"),
+                false,
+                false,
+                true,
+            )
+            .with_task_file("/elsewhere");
+        assert_eq!(argv(&cmd), ["claude", "-p", TASK_FILE_PLACEHOLDER]);
     }
 
     #[test]
@@ -1269,6 +2720,6 @@ mod tests {
             false,
             false,
         );
-        assert_eq!(cmd.command, "gemini -i \"task\" --model m");
+        assert_eq!(words(&cmd), "gemini -i task --model m");
     }
 }
@@ -64,6 +64,13 @@ impl WebviewPrefsState {
             lock: Mutex::new(()),
         }
     }
+
+    /// One preference as the file holds it, for backend code that has to
+    /// honour a setting the webview owns.
+    pub fn get(&self, key: &str) -> Option<String> {
+        let _guard = self.lock.lock().ok()?;
+        read_prefs(&self.path).remove(key)
+    }
 }
 
 /// True when the pair is small enough to be worth mirroring.
@@ -164,6 +164,12 @@ function AgentXterm({ agentId, onSelectionSpawn }: AgentXtermProps) {
         });
       };
 
+      // After a webview reload the store holds only what arrived since; the
+      // backend still has the rest, and it goes in front before the replay.
+      const backfilled = useStore.getState().backfillAgentLog(agentId);
+      followRestore(backfilled);
+      await backfilled;
+
       const stream = attachAgentStream(term, agentId);
       let detachStream = stream.detach;
       followRestore(stream.restored);
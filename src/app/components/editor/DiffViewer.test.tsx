@@ -346,6 +346,7 @@ describe('DiffViewer inline edit', () => {
           commitMessage: '',
           isCommitting: false,
           isPushing: false,
+          pushRejections: [],
         },
       },
     });
@@ -76,6 +76,21 @@ describe('SourceControlPanel – push', () => {
     expect(screen.getByRole('button', { name: 'Pushing...' })).toBeDisabled();
   });
 
+  it('lists the refs the remote rejected, with its reason', () => {
+    render(
+      <SourceControlPanel
+        {...defaultProps}
+        repos={[
+          singleRootRepo({
+            pushRejections: [{ refname: 'refs/heads/main', reason: 'stale info' }],
+          }),
+        ]}
+        onPush={vi.fn()}
+      />
+    );
+    expect(screen.getByTestId('push-rejections')).toHaveTextContent('main: stale info');
+  });
+
   it('offers no push when the caller cannot push', () => {
     render(<SourceControlPanel {...defaultProps} />);
     expect(screen.queryByRole('button', { name: /push/i })).not.toBeInTheDocument();
@@ -13,7 +13,7 @@ import type {
   GitRepoKind,
 } from '@/lib/tauri/git';
 import type { ProviderInfo } from '@/lib/tauri/providers';
-import type { ScmView } from '@/lib/store/gitSlice';
+import type { GitPushRejection, ScmView } from '@/lib/store/gitSlice';
 import { HistoryView } from './HistoryView';
 import { CompareView } from './CompareView';
 import { AgentWorktreesPanel } from './AgentWorktreesPanel';
@@ -30,6 +30,8 @@ export interface RepoView {
   commitMessage: string;
   isCommitting: boolean;
   isPushing: boolean;
+  /** Refs the remote turned down on the last push; shown under the push button. */
+  pushRejections?: GitPushRejection[];
 }
 
 export interface SourceControlProps {
@@ -459,6 +461,19 @@ function RepoBody({
             </div>
           )}
           {agenticCommit && pushButton}
+          {repo.pushRejections && repo.pushRejections.length > 0 && (
+            <ul
+              data-testid="push-rejections"
+              className="rounded border border-red-500/20 bg-red-500/10 px-2 py-1.5 text-[11px] text-red-300"
+            >
+              {repo.pushRejections.map((r) => (
+                <li key={r.refname}>
+                  <span className="font-mono">{r.refname.replace(/^refs\/heads\//, '')}</span>:{' '}
+                  {r.reason}
+                </li>
+              ))}
+            </ul>
+          )}
         </div>
       </div>
 
@@ -8,6 +8,7 @@ import {
   getGoalDescendants,
   getGoalSatisfaction,
   getGoalWorkflowStage,
+  getRunsCost,
   getRunsForGoal,
 } from '@/lib/store/goalsSlice';
 import { useStore } from '@/lib/store';
@@ -95,6 +96,11 @@ export function GoalDetailPanel({
   }, [goal, goals, tickets]);
 
   const goalRuns = useMemo(() => (goal ? getRunsForGoal(runs, goal.id) : []), [goal, runs]);
+  const goalCostLabel = useMemo(() => {
+    const { usd, priced } = getRunsCost(goalRuns);
+    if (priced === 0) return '';
+    return ` · $${usd.toFixed(2)} across ${priced} ${priced === 1 ? 'run' : 'runs'}`;
+  }, [goalRuns]);
 
   const linkedRequirements = useMemo(() => {
     if (!goal) return [];
@@ -544,7 +550,7 @@ export function GoalDetailPanel({
 
       {/* Runs */}
       <div>
-        <label className={labelCls}>Agent runs ({goalRuns.length})</label>
+        <label className={labelCls}>Agent runs ({goalRuns.length}){goalCostLabel}</label>
         {goalRuns.length === 0 ? (
           <p className="text-[10px] text-foreground-muted/70">
             No agents launched for this goal yet.
@@ -561,6 +567,7 @@ export function GoalDetailPanel({
                   </span>
                   <span className="text-foreground-muted">
                     {run.model || 'model?'} · {run.source}
+                    {run.costUsd != null && ` · $${run.costUsd.toFixed(2)}`}
                   </span>
                   <span className="ml-auto tabular-nums text-foreground-muted/70">
                     {run.startedAt}
@@ -305,6 +305,7 @@ describe('SettingsModal – Ticket Pattern', () => {
     commitMessage: '',
     isCommitting: false,
     isPushing: false,
+    pushRejections: [],
   });
 
   it('shows live preview when branch info is available', () => {
@@ -8,6 +8,32 @@ import { agentLogPurge } from '@/lib/tauri/agentLog';
 vi.mock('@/lib/tauri/agentLog', () => ({
   agentLogPurge: vi.fn(async () => {}),
 }));
+vi.mock('@/lib/tauri/providers', async (importOriginal) => {
+  const actual = await importOriginal<typeof import('@/lib/tauri/providers')>();
+  return {
+    ...actual,
+    validateProviderConfigs: vi.fn(async () => [
+      { path: '/p/claude.json', id: 'claude', status: 'loaded', error: null },
+      {
+        path: '/p/gemini.json',
+        id: 'gemini',
+        status: 'invalid',
+        error: "field 'arguments[0].type' — unknown variant `nope`",
+      },
+    ]),
+    onProvidersChanged: vi.fn(() => () => {}),
+    probeProvider: vi.fn(async (providerId: string) => ({
+      providerId,
+      executablePath: null,
+      installed: true,
+      version: '0.9.0',
+      minVersion: '1.0',
+      meetsMinimum: false,
+      capabilities: { resume: false, streamJson: false, headless: true },
+      error: null,
+    })),
+  };
+});
 
 beforeEach(() => {
   localStorage.clear();
@@ -23,6 +49,22 @@ describe('AgentContent — agent providers', () => {
     expect(screen.getByTestId('import-provider-button')).toBeInTheDocument();
   });
 
+  it('says why a config file is missing from the list', async () => {
+    render(<AgentContent />);
+
+    const issues = await screen.findByTestId('provider-config-issues');
+    expect(issues).toHaveTextContent('/p/gemini.json');
+    expect(issues).toHaveTextContent("field 'arguments[0].type'");
+    expect(issues).not.toHaveTextContent('/p/claude.json');
+  });
+
+  it('shows the installed version and flags one below the minimum', async () => {
+    render(<AgentContent />);
+
+    expect(await screen.findByText(/needs 1\.0\+/)).toBeInTheDocument();
+    expect(screen.getByTestId('provider-list')).toHaveTextContent('0.9.0');
+  });
+
   it('asks before skipping permission prompts and changes the setting only after confirmation', async () => {
     const user = userEvent.setup();
     render(<AgentContent />);
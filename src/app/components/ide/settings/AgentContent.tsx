@@ -1,8 +1,9 @@
 'use client';
 
-import { useState } from 'react';
+import { useEffect, useState } from 'react';
 
 import {
+  AGENT_CONCURRENCY_LIMITS,
   AGENT_LOG_RETENTION_DAYS,
   AGENT_TERMINAL_FONT_SIZES,
   loadAppConfig,
@@ -14,7 +15,15 @@ import { useStore } from '@/lib/store';
 import { SettingsSection } from '../../ui/settings/SettingsSection';
 import { SettingsToggle } from '../../ui/settings/SettingsToggle';
 import { GUIDANCE } from '@/lib/ui/descriptions';
-import { importProvider, listProviders } from '@/lib/tauri/providers';
+import {
+  importProvider,
+  listProviders,
+  onProvidersChanged,
+  probeProvider,
+  validateProviderConfigs,
+  type ProviderConfigCheck,
+  type ProviderProbe,
+} from '@/lib/tauri/providers';
 import { AuricIcon } from '@/app/components/ui/AuricIcon';
 import { SkillDiscoveryContent } from './SkillDiscoveryContent';
 
@@ -52,6 +61,44 @@ export function AgentContent() {
   const [agentLogRetentionDays, setAgentLogRetentionDays] = useState(
     () => loadAppConfig().agentLogRetentionDays
   );
+  const [agentMaxConcurrent, setAgentMaxConcurrent] = useState(
+    () => loadAppConfig().agentMaxConcurrent
+  );
+  const [agentMaxPerRepo, setAgentMaxPerRepo] = useState(() => loadAppConfig().agentMaxPerRepo);
+  const [agentApiEnabled, setAgentApiEnabled] = useState(() => loadAppConfig().agentApiEnabled);
+  // Config files that did not make it into the list above, with the reason.
+  const [providerIssues, setProviderIssues] = useState<ProviderConfigCheck[]>([]);
+  // The installed version of each provider's CLI, keyed by provider id.
+  const [providerProbes, setProviderProbes] = useState<Record<string, ProviderProbe>>({});
+
+  useEffect(() => {
+    let cancelled = false;
+    const check = async () => {
+      const checks = await validateProviderConfigs().catch(() => []);
+      if (!cancelled) setProviderIssues(checks.filter((c) => c.status !== 'loaded'));
+    };
+    void check();
+    const unsubscribe = onProvidersChanged(() => void check());
+    return () => {
+      cancelled = true;
+      unsubscribe();
+    };
+  }, []);
+
+  useEffect(() => {
+    let cancelled = false;
+    const probe = async () => {
+      const probes = await Promise.all(providers.map((p) => probeProvider(p.id).catch(() => null)));
+      if (cancelled) return;
+      const byId: Record<string, ProviderProbe> = {};
+      for (const found of probes) if (found) byId[found.providerId] = found;
+      setProviderProbes(byId);
+    };
+    void probe();
+    return () => {
+      cancelled = true;
+    };
+  }, [providers]);
 
   const handleUsageLimitsChange = (checked: boolean) => {
     setAppConfigValue('cliUsageLimits', checked);
@@ -61,6 +108,11 @@ export function AgentContent() {
     void (checked ? refreshUsageLimits() : loadUsageLimits());
   };
 
+  const handleAgentApiChange = (checked: boolean) => {
+    setAppConfigValue('agentApiEnabled', checked);
+    setAgentApiEnabled(checked);
+  };
+
   const handleAgentConsoleAutoOpenChange = (checked: boolean) => {
     setAppConfigValue('agentConsoleAutoOpen', checked);
     setAgentConsoleAutoOpen(checked);
@@ -99,6 +151,15 @@ export function AgentContent() {
     setAgentLogRetentionDays(days);
   };
 
+  const handleConcurrencyChange = (
+    key: 'agentMaxConcurrent' | 'agentMaxPerRepo',
+    value: string
+  ) => {
+    const limit = Number(value);
+    setAppConfigValue(key, limit);
+    (key === 'agentMaxConcurrent' ? setAgentMaxConcurrent : setAgentMaxPerRepo)(limit);
+  };
+
   const handleTerminalFontSizeChange = (value: string) => {
     const fontSize = Number(value);
     setAppConfigValue('agentTerminalFontSize', fontSize);
@@ -164,6 +225,39 @@ export function AgentContent() {
         />
       </SettingsSection>
 
+      <SettingsSection title="Agent Queue" icon="format_list_bulleted">
+        <p className="text-xs text-foreground-muted leading-relaxed">
+          Cap how many agents run at once. Agents started past a cap wait as queued and start when
+          another finishes — whether a person, the conductor or a schedule started them.
+        </p>
+        {(
+          [
+            ['agentMaxConcurrent', 'Running Agents', agentMaxConcurrent],
+            ['agentMaxPerRepo', 'Per Repository', agentMaxPerRepo],
+          ] as const
+        ).map(([key, label, value]) => (
+          <label
+            key={key}
+            className="flex items-center justify-between gap-4 text-xs text-foreground"
+          >
+            <span>{label}</span>
+            <select
+              aria-label={`Agent limit: ${label}`}
+              data-testid={`agent-queue-${key}`}
+              value={value}
+              onChange={(event) => handleConcurrencyChange(key, event.target.value)}
+              className="rounded-md border border-white/10 bg-black/30 px-2 py-1 text-xs text-foreground outline-none transition-colors focus:border-primary/60 focus-visible:ring-2 focus-visible:ring-primary/70"
+            >
+              {AGENT_CONCURRENCY_LIMITS.map((limit) => (
+                <option key={limit} value={limit}>
+                  {limit === 0 ? 'No limit' : limit}
+                </option>
+              ))}
+            </select>
+          </label>
+        ))}
+      </SettingsSection>
+
       <SettingsSection title="CLI Quota" icon="speed">
         <p className="text-xs text-foreground-muted leading-relaxed">
           Show remaining Claude Code and Codex usage in the status bar. Enabling this adds a
@@ -254,21 +348,52 @@ export function AgentContent() {
         </label>
       </SettingsSection>
 
+      <SettingsSection title="Agent API" icon="data_object">
+        <p className="text-xs text-foreground-muted leading-relaxed">
+          Let scripts on this machine drive agents over a local socket in the AuricIDE data folder.
+          Each connection must present the token stored next to it, which changes whenever the
+          socket opens.
+        </p>
+        <SettingsToggle
+          label="Enable the agent API"
+          description="JSON-RPC over agent-api.sock"
+          tooltip={GUIDANCE.settings.agentApiEnabled}
+          testId="agent-api-toggle"
+          checked={agentApiEnabled}
+          onChange={handleAgentApiChange}
+        />
+      </SettingsSection>
+
       <SettingsSection title="Agent Providers" icon="extension">
         <p className="text-xs text-foreground-muted leading-relaxed">
           Import provider configuration files to add agent CLIs. Project settings decide which
           providers each project can use.
         </p>
         <div className="flex flex-wrap gap-1.5" data-testid="provider-list">
-          {providers.map((p) => (
-            <span
-              key={p.id}
-              className="rounded-md border border-white/10 bg-white/5 px-2 py-1 text-[11px] text-foreground-muted"
-            >
-              {p.name}
-            </span>
-          ))}
+          {providers.map((p) => {
+            const probe = providerProbes[p.id];
+            return (
+              <span
+                key={p.id}
+                title={probe?.executablePath ?? probe?.error ?? undefined}
+                className="rounded-md border border-white/10 bg-white/5 px-2 py-1 text-[11px] text-foreground-muted"
+              >
+                {p.name}
+                {probe && <ProbeNote probe={probe} />}
+              </span>
+            );
+          })}
         </div>
+        {providerIssues.length > 0 && (
+          <ul className="space-y-1.5" data-testid="provider-config-issues">
+            {providerIssues.map((issue) => (
+              <li key={issue.path} className="text-[10px] leading-relaxed">
+                <span className="font-mono text-amber-400/90">{issue.path}</span>
+                <span className="block text-foreground-muted">{issue.error}</span>
+              </li>
+            ))}
+          </ul>
+        )}
         <button
           onClick={handleImportProvider}
           data-testid="import-provider-button"
@@ -284,3 +409,18 @@ export function AgentContent() {
     </div>
   );
 }
+
+/** The installed version after a provider's name, or why it cannot run. */
+function ProbeNote({ probe }: { probe: ProviderProbe }) {
+  if (!probe.installed) {
+    return <span className="ml-1.5 text-amber-400/90">not installed</span>;
+  }
+  if (probe.meetsMinimum === false) {
+    return (
+      <span className="ml-1.5 text-amber-400/90">
+        {probe.version} · needs {probe.minVersion}+
+      </span>
+    );
+  }
+  return probe.version ? <span className="ml-1.5 opacity-70">{probe.version}</span> : null;
+}
@@ -4,17 +4,32 @@ import { useCallback, useEffect, useState } from 'react';
 import { useStore } from '@/lib/store';
 import { SettingsSection } from '../../ui/settings/SettingsSection';
 import { listProviders, FALLBACK_CRUSH_PROVIDER, type ProviderInfo } from '@/lib/tauri/providers';
-import { loadProviderPolicy, saveProviderPolicy } from '@/lib/config/projectConfig';
 import {
+  loadAgentSandbox,
+  loadProviderPolicy,
+  saveAgentSandbox,
+  saveProviderPolicy,
+} from '@/lib/config/projectConfig';
+import { DEFAULT_AGENT_SANDBOX, type AgentSandbox } from '@/lib/config/agentSandbox';
+import {
+  AGENT_ROLES,
   DEFAULT_PROVIDER_POLICY,
   isProviderAllowed,
+  type AgentRole,
   type ProviderPolicy,
+  type RolePreset,
 } from '@/lib/config/providerPolicy';
 
 type Mode = 'all' | 'allow';
 
+const ROLE_LABELS: Record<AgentRole, string> = {
+  implementer: 'Implementer',
+  judge: 'Judge',
+};
+
 /**
- * Which agent providers this project permits.
+ * Which agent providers this project permits, which one each role starts
+ * with, and whether its agents run sandboxed.
  *
  * Deliberately shows every provider the machine has, including the ones this
  * project blocks — a screen that hid them would give no way to unblock one.
@@ -24,18 +39,23 @@ export function ProviderPolicyContent() {
   const showToast = useStore((s) => s.showToast);
   const [providers, setProviders] = useState<ProviderInfo[]>([FALLBACK_CRUSH_PROVIDER]);
   const [policy, setPolicy] = useState<ProviderPolicy>(DEFAULT_PROVIDER_POLICY);
+  const [sandbox, setSandbox] = useState<AgentSandbox>(DEFAULT_AGENT_SANDBOX);
+  const [writableDraft, setWritableDraft] = useState('');
   const [loading, setLoading] = useState(true);
 
   useEffect(() => {
     let cancelled = false;
     void (async () => {
-      const [fetched, stored] = await Promise.all([
+      const [fetched, stored, storedSandbox] = await Promise.all([
         listProviders().catch(() => [FALLBACK_CRUSH_PROVIDER]),
         loadProviderPolicy(rootPath ?? ''),
+        loadAgentSandbox(rootPath ?? ''),
       ]);
       if (cancelled) return;
       setProviders(fetched.length > 0 ? fetched : [FALLBACK_CRUSH_PROVIDER]);
       setPolicy(stored);
+      setSandbox(storedSandbox);
+      setWritableDraft(storedSandbox.writablePaths.join('\n'));
       setLoading(false);
     })();
     return () => {
@@ -56,6 +76,32 @@ export function ProviderPolicyContent() {
     [rootPath, showToast]
   );
 
+  const persistSandbox = useCallback(
+    async (next: AgentSandbox) => {
+      setSandbox(next);
+      if (!rootPath) return;
+      try {
+        await saveAgentSandbox(rootPath, next);
+      } catch {
+        showToast('Could not save the sandbox settings', 'error');
+      }
+    },
+    [rootPath, showToast]
+  );
+
+  const commitWritablePaths = () => {
+    const writablePaths = [
+      ...new Set(
+        writableDraft
+          .split('\n')
+          .map((line) => line.trim())
+          .filter(Boolean)
+      ),
+    ];
+    if (writablePaths.join('\n') === sandbox.writablePaths.join('\n')) return;
+    void persistSandbox({ ...sandbox, writablePaths });
+  };
+
   const mode: Mode = policy.allow !== null && policy.allow.length > 0 ? 'allow' : 'all';
 
   const permitted = (id: string) => isProviderAllowed(id, policy);
@@ -78,7 +124,12 @@ export function ProviderPolicyContent() {
         : allowed
           ? [...new Set([...policy.allow, id])]
           : policy.allow.filter((a) => a !== id);
-    void persist({ allow, deny });
+    void persist({ ...policy, allow, deny });
+  };
+
+  const setPreset = (role: AgentRole, preset: RolePreset) => {
+    // Empty presets are dropped when the policy is serialized.
+    void persist({ ...policy, defaults: { ...policy.defaults, [role]: preset } });
   };
 
   const allowedNames = providers.filter((p) => permitted(p.id)).map((p) => p.name);
@@ -161,6 +212,113 @@ export function ProviderPolicyContent() {
             : `Permitted in this project: ${allowedNames.join(', ')}.`}
         </p>
       </SettingsSection>
+
+      <SettingsSection title="Role Defaults" icon="alt_route">
+        <p className="text-xs text-foreground-muted leading-relaxed">
+          Pick the agent CLI and model each role starts with in this project. The conductor starts
+          implementers, and judges when review runs as an agent. A provider or model chosen for a
+          single run still wins.
+        </p>
+
+        <div className="flex flex-col gap-1.5" data-testid="provider-role-defaults">
+          {AGENT_ROLES.map((role) => {
+            const preset = policy.defaults?.[role] ?? { provider: null, model: null };
+            // The model list of the provider the role will actually get:
+            // without a preset, the registry default, which is listed first.
+            const models =
+              (providers.find((p) => p.id === preset.provider) ?? providers[0])?.models ?? [];
+            return (
+              <div key={role} className="flex items-center gap-2 text-xs text-foreground">
+                <span className="w-24 shrink-0">{ROLE_LABELS[role]}</span>
+                <select
+                  aria-label={`${ROLE_LABELS[role]} provider`}
+                  data-testid={`role-default-provider-${role}`}
+                  value={preset.provider ?? ''}
+                  onChange={(e) =>
+                    setPreset(role, { provider: e.target.value || null, model: null })
+                  }
+                  className="min-w-0 flex-1 rounded-md border border-white/10 bg-black/30 px-2 py-1 text-xs text-foreground outline-none transition-colors focus:border-primary/60 focus-visible:ring-2 focus-visible:ring-primary/70"
+                >
+                  <option value="">Default provider</option>
+                  {providers
+                    .filter((p) => permitted(p.id) || p.id === preset.provider)
+                    .map((p) => (
+                      <option key={p.id} value={p.id}>
+                        {p.name}
+                      </option>
+                    ))}
+                </select>
+                <select
+                  aria-label={`${ROLE_LABELS[role]} model`}
+                  data-testid={`role-default-model-${role}`}
+                  value={preset.model ?? ''}
+                  onChange={(e) => setPreset(role, { ...preset, model: e.target.value || null })}
+                  className="min-w-0 flex-1 rounded-md border border-white/10 bg-black/30 px-2 py-1 text-xs text-foreground outline-none transition-colors focus:border-primary/60 focus-visible:ring-2 focus-visible:ring-primary/70"
+                >
+                  <option value="">Default model</option>
+                  {preset.model && !models.some((m) => m.value === preset.model) && (
+                    <option value={preset.model}>{preset.model}</option>
+                  )}
+                  {models.map((m) => (
+                    <option key={m.value} value={m.value}>
+                      {m.label}
+                    </option>
+                  ))}
+                </select>
+              </div>
+            );
+          })}
+        </div>
+      </SettingsSection>
+
+      <SettingsSection title="Sandbox" icon="lock">
+        <p className="text-xs text-foreground-muted leading-relaxed">
+          Run this project&apos;s agents in a bubblewrap sandbox (Linux only). They can read
+          everything but write only to their working directory, the project&apos;s .auric folder
+          and the paths below. If the sandbox cannot be built, agents do not start and the error
+          says why.
+        </p>
+
+        <label className="flex items-center gap-2.5 text-xs text-foreground">
+          <input
+            type="checkbox"
+            data-testid="agent-sandbox-enabled"
+            checked={sandbox.enabled}
+            onChange={(e) => void persistSandbox({ ...sandbox, enabled: e.target.checked })}
+            className="accent-[var(--primary)]"
+          />
+          <span>Run agents sandboxed</span>
+        </label>
+
+        <label
+          className={`flex items-center gap-2.5 text-xs ${
+            sandbox.enabled ? 'text-foreground' : 'text-foreground-muted/60'
+          }`}
+        >
+          <input
+            type="checkbox"
+            data-testid="agent-sandbox-block-network"
+            checked={sandbox.blockNetwork}
+            disabled={!sandbox.enabled}
+            onChange={(e) => void persistSandbox({ ...sandbox, blockNetwork: e.target.checked })}
+            className="accent-[var(--primary)]"
+          />
+          <span>Block network access</span>
+        </label>
+
+        <label className="flex flex-col gap-1.5 text-xs text-foreground-muted">
+          <span>Also writable, one path per line — e.g. ~/.claude for the CLI&apos;s state</span>
+          <textarea
+            data-testid="agent-sandbox-writable-paths"
+            value={writableDraft}
+            disabled={!sandbox.enabled}
+            onChange={(e) => setWritableDraft(e.target.value)}
+            onBlur={commitWritablePaths}
+            rows={3}
+            className="rounded-lg border border-white/10 bg-white/5 px-2 py-1.5 font-mono text-[11px] text-foreground disabled:opacity-50"
+          />
+        </label>
+      </SettingsSection>
     </div>
   );
 }
@@ -13,6 +13,7 @@ import {
 } from '@/lib/tauri/terminal';
 import { getPromptTemplate, FALLBACK_PROMPT_TEMPLATE } from '@/lib/tauri/providers';
 import { xtermMounted, xtermUnmounted } from '@/lib/metrics';
+import { useStore } from '@/lib/store';
 import { attachAgentStream } from '@/lib/terminal/agentStream';
 import { onAgentPtyResize } from '@/lib/terminal/agentMirror';
 import { attachImagePaste, attachFileDrop } from '@/lib/terminal/imageInsert';
@@ -195,6 +196,9 @@ export function XtermTerminal({ id, cwd, initialCommand, agentId, onInput }: Xte
         // replayed screen is laid out for the width it is displayed at. The
         // TUI agent repaints itself for the new size via SIGWINCH.
         resizeShell(id, term.rows, term.cols).catch(() => {});
+        // Output printed before a webview reload is only in the backend.
+        await useStore.getState().backfillAgentLog(agentId);
+        if (!isMounted) return;
         let { detach } = attachAgentStream({ write: writeTerm }, agentId);
         // Another view (the fullscreen modal) may take the PTY geometry over.
         // Adopt it and redraw from a fresh mirror snapshot — keeping a screen
@@ -288,7 +288,7 @@ vi.mock('@/lib/tauri/database', () => ({
 }));
 
 vi.mock('@/lib/tauri/pm', () => ({
-  pmSave: vi.fn().mockResolvedValue(undefined),
+  pmSync: vi.fn().mockResolvedValue({ versions: { epics: {}, tickets: {}, testCases: {} } }),
   pmLoad: vi.fn().mockResolvedValue({ epics: [], tickets: [], testCases: [] }),
 }));
 
@@ -160,6 +160,7 @@ export default function Home() {
           commitMessage: repoState?.commitMessage ?? '',
           isCommitting: repoState?.isCommitting ?? false,
           isPushing: repoState?.isPushing ?? false,
+          pushRejections: repoState?.pushRejections ?? [],
         };
       }),
     [state.repos, state.repoStates, state.agentSettings.branchTicketPattern]
@@ -0,0 +1,41 @@
+import { describe, expect, it } from 'vitest';
+import fixtures from './agentSandbox.fixtures.json';
+import {
+  DEFAULT_AGENT_SANDBOX,
+  parseAgentSandbox,
+  serializeAgentSandbox,
+  type AgentSandbox,
+} from './agentSandbox';
+
+describe('agent sandbox — shared contract', () => {
+  // src-tauri/src/agent_sandbox.rs runs the same cases over the same file; a
+  // case that passes here and fails there means the settings screen shows a
+  // sandbox the agents do not run in.
+  describe('parseAgentSandbox', () => {
+    for (const testCase of fixtures.parse) {
+      it(testCase.name, () => {
+        expect(parseAgentSandbox(testCase.raw)).toEqual(testCase.expected);
+      });
+    }
+  });
+});
+
+describe('agent sandbox — TypeScript surface', () => {
+  it('defaults to no sandbox', () => {
+    expect(DEFAULT_AGENT_SANDBOX).toEqual({
+      enabled: false,
+      blockNetwork: false,
+      writablePaths: [],
+    });
+  });
+
+  it('round-trips through storage', () => {
+    const sandbox: AgentSandbox = {
+      enabled: true,
+      blockNetwork: false,
+      writablePaths: ['~/.claude'],
+    };
+
+    expect(parseAgentSandbox(serializeAgentSandbox(sandbox))).toEqual(sandbox);
+  });
+});
@@ -0,0 +1,71 @@
+/**
+ * Whether a project's agents run sandboxed.
+ *
+ * When `enabled`, every agent in the project starts inside bubblewrap with a
+ * read-only filesystem: only its working directory, the project's `.auric`
+ * and `writablePaths` stay writable. `blockNetwork` additionally cuts it off
+ * from the network. Linux only — elsewhere an enabled sandbox refuses to
+ * start agents instead of running them unsandboxed.
+ *
+ * Stored as JSON in the project database under the `agent_sandbox` namespace,
+ * written by the settings screen and enforced by the Rust spawn path through
+ * this file's twin in `src-tauri/src/agent_sandbox.rs`. Both parse against
+ * `agentSandbox.fixtures.json`.
+ */
+export interface AgentSandbox {
+  enabled: boolean;
+  blockNetwork: boolean;
+  /** Extra writable paths, typically the CLI's own state dir (`~/.claude`). */
+  writablePaths: string[];
+}
+
+export const DEFAULT_AGENT_SANDBOX: AgentSandbox = {
+  enabled: false,
+  blockNetwork: false,
+  writablePaths: [],
+};
+
+/**
+ * Reads stored settings. Nothing stored means no sandbox, but a value that is
+ * there and unreadable fails closed — sandboxed, network blocked — unlike the
+ * provider policy: a corrupted blob must not quietly take the sandbox away
+ * from a project that asked for one.
+ */
+export function parseAgentSandbox(raw: string | null | undefined): AgentSandbox {
+  if (!raw) return { ...DEFAULT_AGENT_SANDBOX, writablePaths: [] };
+
+  const unreadable: AgentSandbox = { enabled: true, blockNetwork: true, writablePaths: [] };
+  let parsed: unknown;
+  try {
+    parsed = JSON.parse(raw);
+  } catch {
+    return unreadable;
+  }
+  if (typeof parsed !== 'object' || parsed === null || Array.isArray(parsed)) {
+    return unreadable;
+  }
+
+  const source = parsed as { enabled?: unknown; blockNetwork?: unknown; writablePaths?: unknown };
+  const writablePaths: string[] = [];
+  if (Array.isArray(source.writablePaths)) {
+    for (const entry of source.writablePaths) {
+      if (typeof entry !== 'string') continue;
+      const path = entry.trim();
+      if (path && !writablePaths.includes(path)) writablePaths.push(path);
+    }
+  }
+
+  return {
+    enabled: source.enabled === true,
+    blockNetwork: source.blockNetwork === true,
+    writablePaths,
+  };
+}
+
+export function serializeAgentSandbox(sandbox: AgentSandbox): string {
+  return JSON.stringify({
+    enabled: sandbox.enabled,
+    blockNetwork: sandbox.blockNetwork,
+    writablePaths: sandbox.writablePaths,
+  });
+}
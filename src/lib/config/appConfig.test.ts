@@ -30,6 +30,9 @@ describe('app config', () => {
     setAppConfigValue('agentConsoleAutoOpen', true);
     setAppConfigValue('agentLogPersist', true);
     setAppConfigValue('agentLogRetentionDays', 7);
+    setAppConfigValue('agentMaxConcurrent', 4);
+    setAppConfigValue('agentMaxPerRepo', 2);
+    setAppConfigValue('agentApiEnabled', true);
 
     expect(loadAppConfig()).toEqual({
       enableDeepNlp: true,
@@ -40,6 +43,9 @@ describe('app config', () => {
       agentConsoleAutoOpen: true,
       agentLogPersist: true,
       agentLogRetentionDays: 7,
+      agentMaxConcurrent: 4,
+      agentMaxPerRepo: 2,
+      agentApiEnabled: true,
     });
   });
 
@@ -60,6 +66,14 @@ describe('app config', () => {
     expect(APP_CONFIG_KEYS.cliUsageLimits).toBe('auric.cli-usage-limits');
   });
 
+  it('keeps the agent API closed until it is asked for', () => {
+    // The socket lets any script running as the user drive agents, so it only
+    // opens on request. Rust reads the same key out of the mirror.
+    expect(APP_CONFIG_DEFAULTS.agentApiEnabled).toBe(false);
+    expect(loadAppConfig().agentApiEnabled).toBe(false);
+    expect(APP_CONFIG_KEYS.agentApiEnabled).toBe('auric.agent-api.enabled');
+  });
+
   it('uses a readable default for agent terminals and persists a chosen size', () => {
     expect(APP_CONFIG_DEFAULTS.agentTerminalFontSize).toBe(14);
     expect(APP_CONFIG_KEYS.agentTerminalFontSize).toBe('auric.agent-terminal-font-size');
@@ -103,6 +117,23 @@ describe('app config', () => {
   });
 });
 
+describe('agent concurrency caps', () => {
+  it('caps nothing until the user sets a limit', () => {
+    // Rust reads the same keys and treats absent or 0 as no cap, so spawning
+    // behaves as it did before the queue existed.
+    expect(APP_CONFIG_DEFAULTS.agentMaxConcurrent).toBe(0);
+    expect(APP_CONFIG_DEFAULTS.agentMaxPerRepo).toBe(0);
+    expect(APP_CONFIG_KEYS.agentMaxConcurrent).toBe('auric.agent-queue.max-concurrent');
+    expect(APP_CONFIG_KEYS.agentMaxPerRepo).toBe('auric.agent-queue.max-per-repo');
+  });
+
+  it('falls back to no cap for a value it does not offer', () => {
+    localStorage.setItem(APP_CONFIG_KEYS.agentMaxConcurrent, '5');
+
+    expect(loadAppConfig().agentMaxConcurrent).toBe(0);
+  });
+});
+
 describe('agent log persistence', () => {
   it('writes nothing to disk until the user asks for it', () => {
     // Opt-in on purpose: a history of agent activity is a file on the user's
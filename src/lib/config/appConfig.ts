@@ -47,6 +47,9 @@ export const APP_CONFIG_KEYS = {
   agentConsoleProjectsCollapsed: 'auric.agent-console.projects-collapsed',
   agentLogPersist: 'auric.agent-log.persist',
   agentLogRetentionDays: 'auric.agent-log.retention-days',
+  agentMaxConcurrent: 'auric.agent-queue.max-concurrent',
+  agentMaxPerRepo: 'auric.agent-queue.max-per-repo',
+  agentApiEnabled: 'auric.agent-api.enabled',
 } as const;
 
 /** Lets mounted UI react to a preference written in this same webview. */
@@ -64,6 +67,14 @@ export const AGENT_TERMINAL_FONT_SIZES = [
  */
 export const AGENT_LOG_RETENTION_DAYS = [2, 7, 30, 0] as const;
 
+/**
+ * Every offered cap on agents running at once, overall and per repository.
+ * `0` is no cap. Past the cap a spawn waits as `queued` — the queue itself is
+ * in Rust (`src-tauri/src/agent_queue.rs`), which reads these two keys out of
+ * the mirror.
+ */
+export const AGENT_CONCURRENCY_LIMITS = [0, 1, 2, 3, 4, 6, 8, 12, 16] as const;
+
 /**
  * The second bound on the stored history. Age alone cannot cap it: "no age
  * limit" is a span the user may pick, and a busy fleet writes events faster
@@ -113,6 +124,17 @@ export interface AppConfig {
   agentLogPersist: boolean;
   /** How long stored activity is kept, in days. `0` is no age limit. */
   agentLogRetentionDays: number;
+  /** Agents running at once across all projects. `0` is no cap. */
+  agentMaxConcurrent: number;
+  /** Agents running at once in one repository, worktrees included. `0` is no cap. */
+  agentMaxPerRepo: number;
+  /**
+   * Lets scripts on this machine list, start, read and stop agents over a
+   * local socket. Off by default: it is a way in that does not go through the
+   * window. Read from Rust out of the `webview-prefs.json` mirror — see
+   * `src-tauri/src/agent_api.rs`.
+   */
+  agentApiEnabled: boolean;
 }
 
 export const APP_CONFIG_DEFAULTS: AppConfig = {
@@ -124,6 +146,9 @@ export const APP_CONFIG_DEFAULTS: AppConfig = {
   agentConsoleAutoOpen: false,
   agentLogPersist: false,
   agentLogRetentionDays: 2,
+  agentMaxConcurrent: 0,
+  agentMaxPerRepo: 0,
+  agentApiEnabled: false,
 };
 
 /** The single read. Absent storage — SSR, tests, a blocked webview — is empty. */
@@ -210,6 +235,20 @@ export function loadAppConfig(): AppConfig {
       AGENT_LOG_RETENTION_DAYS,
       APP_CONFIG_DEFAULTS.agentLogRetentionDays
     ),
+    agentMaxConcurrent: decodeChoice(
+      readAppPref(APP_CONFIG_KEYS.agentMaxConcurrent),
+      AGENT_CONCURRENCY_LIMITS,
+      APP_CONFIG_DEFAULTS.agentMaxConcurrent
+    ),
+    agentMaxPerRepo: decodeChoice(
+      readAppPref(APP_CONFIG_KEYS.agentMaxPerRepo),
+      AGENT_CONCURRENCY_LIMITS,
+      APP_CONFIG_DEFAULTS.agentMaxPerRepo
+    ),
+    agentApiEnabled: decodeBoolean(
+      readAppPref(APP_CONFIG_KEYS.agentApiEnabled),
+      APP_CONFIG_DEFAULTS.agentApiEnabled
+    ),
   };
 }
 
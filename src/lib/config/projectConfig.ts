@@ -1,5 +1,11 @@
 import { dbGet, dbList, dbSet } from '@/lib/tauri/db';
 import { parseIgnoredRepos, serializeIgnoredRepos } from './ignoredRepos';
+import {
+  DEFAULT_AGENT_SANDBOX,
+  parseAgentSandbox,
+  serializeAgentSandbox,
+  type AgentSandbox,
+} from './agentSandbox';
 import {
   DEFAULT_PROVIDER_POLICY,
   parseProviderPolicy,
@@ -133,6 +139,32 @@ export async function saveProviderPolicy(rootPath: string, policy: ProviderPolic
   );
 }
 
+// ── Agent sandbox ──────────────────────────────────────────────────
+
+/** Its own namespace, next to the provider policy: Rust reads it on spawn too. */
+const AGENT_SANDBOX_NAMESPACE = 'agent_sandbox';
+const AGENT_SANDBOX_KEY = 'settings';
+
+/**
+ * Without a project or with an unreachable database, no sandbox — as on the
+ * spawn path, which finds no database there either. A stored value that is
+ * corrupt is `parseAgentSandbox`'s call, and it fails closed.
+ */
+export async function loadAgentSandbox(rootPath: string): Promise<AgentSandbox> {
+  if (!rootPath) return { ...DEFAULT_AGENT_SANDBOX };
+  try {
+    const raw = await dbGet(rootPath, AGENT_SANDBOX_NAMESPACE, AGENT_SANDBOX_KEY);
+    return parseAgentSandbox(raw);
+  } catch {
+    return { ...DEFAULT_AGENT_SANDBOX };
+  }
+}
+
+export async function saveAgentSandbox(rootPath: string, sandbox: AgentSandbox): Promise<void> {
+  if (!rootPath) return;
+  await dbSet(rootPath, AGENT_SANDBOX_NAMESPACE, AGENT_SANDBOX_KEY, serializeAgentSandbox(sandbox));
+}
+
 // ── Ignored nested git repos ───────────────────────────────────────
 
 /** Its own namespace: Rust reads this on discovery and the dirty probe. */
@@ -78,6 +78,23 @@ describe('provider policy — TypeScript surface', () => {
     expect(parseProviderPolicy(serializeProviderPolicy(policy))).toEqual(policy);
   });
 
+  it('round-trips role presets and drops the empty ones', () => {
+    const policy: ProviderPolicy = {
+      allow: null,
+      deny: [],
+      defaults: {
+        implementer: { provider: 'codex', model: 'gpt-5-codex' },
+        judge: { provider: null, model: null },
+      },
+    };
+
+    expect(parseProviderPolicy(serializeProviderPolicy(policy))).toEqual({
+      allow: null,
+      deny: [],
+      defaults: { implementer: { provider: 'codex', model: 'gpt-5-codex' } },
+    });
+  });
+
   it('serializes the open default to a value the parser reads back as open', () => {
     expect(parseProviderPolicy(serializeProviderPolicy(DEFAULT_PROVIDER_POLICY))).toEqual(
       DEFAULT_PROVIDER_POLICY
@@ -1,7 +1,8 @@
 import type { ProviderInfo } from '../tauri/providers';
 
 /**
- * Which agentic providers a project permits.
+ * Which agentic providers a project permits, and which one each role starts
+ * with.
  *
  * `allow === null` means no allow list is in effect — the usual case. A list
  * narrows the project to exactly its members. `deny` always wins, so "this
@@ -16,8 +17,27 @@ import type { ProviderInfo } from '../tauri/providers';
 export interface ProviderPolicy {
   allow: string[] | null;
   deny: string[];
+  /** Absent when no role has a preset — the shape of every policy before presets. */
+  defaults?: RoleDefaults;
 }
 
+/** What an agent is spawned to do; a spawn that names its role gets its preset. */
+export type AgentRole = 'implementer' | 'judge';
+
+export const AGENT_ROLES: AgentRole[] = ['implementer', 'judge'];
+
+/**
+ * The provider and model a role starts with, filled in by the Rust spawn path
+ * for whatever the spawn left open. A provider the spawn names wins, and the
+ * preset's model only comes along with the provider it was chosen for.
+ */
+export interface RolePreset {
+  provider: string | null;
+  model: string | null;
+}
+
+export type RoleDefaults = Partial<Record<AgentRole, RolePreset>>;
+
 export const DEFAULT_PROVIDER_POLICY: ProviderPolicy = { allow: null, deny: [] };
 
 /** Provider ids are lowercase in the registry; a hand-typed entry must still match. */
@@ -35,6 +55,26 @@ function normalizeList(value: unknown): string[] {
   return [...seen];
 }
 
+/** A preset with nothing usable in it is no preset. */
+function normalizePreset(value: unknown): RolePreset | null {
+  if (typeof value !== 'object' || value === null || Array.isArray(value)) return null;
+  const source = value as { provider?: unknown; model?: unknown };
+  const provider = normalizeId(source.provider) || null;
+  const model = (typeof source.model === 'string' ? source.model.trim() : '') || null;
+  return provider || model ? { provider, model } : null;
+}
+
+function normalizeDefaults(value: unknown): RoleDefaults {
+  if (typeof value !== 'object' || value === null || Array.isArray(value)) return {};
+  const source = value as Record<string, unknown>;
+  const defaults: RoleDefaults = {};
+  for (const role of AGENT_ROLES) {
+    const preset = normalizePreset(source[role]);
+    if (preset) defaults[role] = preset;
+  }
+  return defaults;
+}
+
 /**
  * Reads a stored policy. Anything unreadable — missing, empty, malformed,
  * wrong shape — becomes the open default rather than an error: a project that
@@ -54,8 +94,9 @@ export function parseProviderPolicy(raw: string | null | undefined): ProviderPol
     return { allow: null, deny: [] };
   }
 
-  const source = parsed as { allow?: unknown; deny?: unknown };
+  const source = parsed as { allow?: unknown; deny?: unknown; defaults?: unknown };
   const allow = Array.isArray(source.allow) ? normalizeList(source.allow) : [];
+  const defaults = normalizeDefaults(source.defaults);
 
   return {
     // An allow list that normalises to nothing is an absent one. Reaching that
@@ -64,11 +105,17 @@ export function parseProviderPolicy(raw: string | null | undefined): ProviderPol
     // everything stays possible, but only by saying so on the deny list.
     allow: allow.length > 0 ? allow : null,
     deny: normalizeList(source.deny),
+    ...(Object.keys(defaults).length > 0 ? { defaults } : {}),
   };
 }
 
 export function serializeProviderPolicy(policy: ProviderPolicy): string {
-  return JSON.stringify({ allow: policy.allow, deny: policy.deny });
+  const defaults = normalizeDefaults(policy.defaults);
+  return JSON.stringify({
+    allow: policy.allow,
+    deny: policy.deny,
+    ...(Object.keys(defaults).length > 0 ? { defaults } : {}),
+  });
 }
 
 export function isProviderAllowed(providerId: string, policy: ProviderPolicy): boolean {
@@ -109,6 +109,33 @@ describe('offerWorktreeMerge', () => {
     );
   });
 
+  it('reports conflicts instead of success when the merge stops on them', async () => {
+    const input = deps({
+      mergeAgentWorktree: vi.fn(async () => ({
+        defaultBranch: 'main',
+        merged: false,
+        fastForward: false,
+        cleanedUp: false,
+        oid: null,
+        conflicts: [
+          {
+            path: 'a.txt',
+            kind: 'both-modified' as const,
+            base: 'hi',
+            ours: 'on main',
+            theirs: 'on worktree',
+            binary: false,
+          },
+        ],
+      })),
+    });
+    await offerWorktreeMerge(input);
+    expect(input.showToast).toHaveBeenCalledWith(
+      'Merge into main has conflicts in a.txt. The worktree was kept so you can resolve them.',
+      'error'
+    );
+  });
+
   it('toasts when main or master cannot be found, and does not ask', async () => {
     const input = deps({
       defaultBranchFor: vi.fn(async () => {
@@ -51,6 +51,15 @@ export async function offerWorktreeMerge(input: OfferWorktreeMergeInput): Promis
 
   try {
     const result = await input.mergeAgentWorktree(path, `Agent work: ${agent.name}`);
+    const conflicts = result.conflicts ?? [];
+    if (conflicts.length > 0) {
+      const files = conflicts.map((c) => c.path).join(', ');
+      input.showToast(
+        `Merge into ${result.defaultBranch} has conflicts in ${files}. The worktree was kept so you can resolve them.`,
+        'error'
+      );
+      return;
+    }
     input.showToast(`Merged into ${result.defaultBranch} and removed the worktree.`, 'success');
   } catch (err) {
     const detail = err instanceof Error ? err.message : String(err);
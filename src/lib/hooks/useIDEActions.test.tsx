@@ -43,7 +43,10 @@ vi.mock('@/lib/hooks/useScheduledConductorRuns', () => ({
 vi.mock('@/lib/inbox/useInboxData', () => ({ useInboxData: () => undefined }));
 vi.mock('@/lib/hooks/useTitleBarGutter', () => ({ useTitleBarGutter: () => undefined }));
 
-vi.mock('@/lib/tauri/providers', () => ({ listProviders: vi.fn(async () => []) }));
+vi.mock('@/lib/tauri/providers', () => ({
+  listProviders: vi.fn(async () => []),
+  onProvidersChanged: vi.fn(() => () => {}),
+}));
 vi.mock('@/lib/tauri/fs', () => ({
   getProjectFilesInfo: vi.fn(async () => []),
   listAllFiles: vi.fn(async () => []),
@@ -3,7 +3,7 @@
 import { useEffect, useRef, useCallback } from 'react';
 import { useStore } from '@/lib/store';
 import { getProjectFilesInfo } from '@/lib/tauri/fs';
-import { listProviders } from '@/lib/tauri/providers';
+import { listProviders, onProvidersChanged } from '@/lib/tauri/providers';
 import { filterProviders } from '@/lib/config/providerPolicy';
 import { loadProviderPolicy } from '@/lib/config/projectConfig';
 import { createFsEventRouter, type FsEventRouter } from '@/lib/ide/fsEventRouter';
@@ -131,10 +131,11 @@ export function useIDEActions(
   // Provider info for default model resolution, narrowed by the open project's
   // policy so anything reading the store's provider list offers only what this
   // project permits. Re-runs on a project switch, because the next project's
-  // policy may permit a different set.
+  // policy may permit a different set — and whenever a provider config on
+  // disk changes, since Rust re-reads those live.
   useEffect(() => {
     let cancelled = false;
-    void (async () => {
+    const load = async () => {
       const fetched = await listProviders().catch(() => []);
       if (fetched.length === 0) return;
       const policy = await loadProviderPolicy(state.rootPath ?? '');
@@ -145,9 +146,12 @@ export function useIDEActions(
       if (cancelled || permitted.length === 0) return;
       state.setProviders(permitted);
       state.setDefaultProvider(permitted[0]);
-    })();
+    };
+    void load();
+    const unsubscribe = onProvidersChanged(() => void load());
     return () => {
       cancelled = true;
+      unsubscribe();
     };
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [state.rootPath]);
@@ -205,16 +209,19 @@ export function useIDEActions(
   useAgentEvents(
     useCallback(
       (event) => {
-        appendAgentLog(event.agentId, event.line);
+        appendAgentLog(event.agentId, event.line, event.seq);
       },
       [appendAgentLog]
     ),
     useCallback(
       (event) => {
-        updateAgentStatus(event.agentId, event.status);
+        // A run stopped on a limit did not finish its task — to the store it
+        // failed. The reason is already in its output.
+        const status = event.status === 'limited' ? 'error' : event.status;
+        updateAgentStatus(event.agentId, status);
         // An agent finishing is the moment its evidence lands: re-check the
         // front of the goal it worked, whether it succeeded or crashed.
-        if (event.status === 'idle' || event.status === 'error') {
+        if (status === 'idle' || status === 'error') {
           const agent = useStore.getState().agents.find((a) => a.id === event.agentId);
           const goalId =
             agent?.spawnedByGoalId ??
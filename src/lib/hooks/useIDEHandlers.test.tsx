@@ -139,6 +139,7 @@ vi.mock('@/lib/store', () => {
           commitMessage: '',
           isCommitting: false,
           isPushing: false,
+          pushRejections: [],
         },
       ])
     ),
@@ -239,7 +240,7 @@ describe('useIDEHandlers', () => {
     setSpawnDialogOpen: vi.fn(),
     setBottomCollapsed: vi.fn(),
     commit: vi.fn(async () => 'abc123'),
-    push: vi.fn(async () => undefined),
+    push: vi.fn(async () => ({ remote: 'origin', refspec: 'refs/heads/main' })),
     setCommitMessage: vi.fn(),
     repoStates: {} as Record<
       string,
@@ -268,6 +269,7 @@ describe('useIDEHandlers', () => {
     commitMessage: '',
     isCommitting: false,
     isPushing: false,
+    pushRejections: [],
   });
 
   beforeEach(() => {
@@ -1095,8 +1095,8 @@ export function useIDEHandlers(state: ReturnType<typeof useIDEState>) {
     async (repoPath: string) => {
       const { showToast } = useStore.getState();
       try {
-        await state.push(repoPath);
-        showToast('Pushed to origin', 'success');
+        const result = await state.push(repoPath);
+        showToast(`Pushed to ${result.remote}`, 'success');
       } catch (e) {
         // The error names the fix (no remote, no credentials) — surface it
         // instead of leaving a button that silently did nothing.
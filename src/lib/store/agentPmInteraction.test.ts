@@ -23,7 +23,7 @@ vi.mock('../tauri/agents', () => ({
 
 vi.mock('../tauri/pm', () => ({
   pmLoad: vi.fn(async () => ({ epics: [], tickets: [], testCases: [], dependencies: [] })),
-  pmSave: vi.fn(async () => undefined),
+  pmSync: vi.fn(async () => ({ versions: { epics: {}, tickets: {}, testCases: {} } })),
 }));
 
 describe('Agent and PM Interaction', () => {
@@ -95,6 +95,7 @@ vi.mock('../tauri/agents', () => ({
   })),
   discardInterruptedAgent: vi.fn(async () => undefined),
   sendToAgent: vi.fn(async () => undefined),
+  getAgentOutput: vi.fn(async () => ({ chunks: [], nextSeq: 0, truncated: false })),
   listAgentPromptHistory: vi.fn(async () => [
     { id: 'h1', prompt: 'newest', agentName: 'A', model: 'm', provider: 'claude', source: 'ui' },
     { id: 'h2', prompt: 'older', agentName: 'A', model: 'm', provider: 'claude', source: 'ui' },
@@ -1070,6 +1071,71 @@ describe('agentSlice – byte-bounded agent logs', () => {
   });
 });
 
+describe('agentSlice – backfilled agent logs', () => {
+  let store: StoreApi<AgentSlice>;
+
+  const page = (from: number, to: number) => ({
+    chunks: Array.from({ length: to - from }, (_, i) => ({
+      seq: from + i,
+      stream: 'stdout' as const,
+      timestamp: 0,
+      data: `c${from + i}`,
+    })),
+    nextSeq: to,
+    truncated: false,
+  });
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    store = createStore<AgentSlice>()(createAgentSlice);
+  });
+
+  it('fetches nothing when the store saw the agent from its first chunk', async () => {
+    const { getAgentOutput } = await import('../tauri/agents');
+    store.getState().appendAgentLog('agent-1', 'c0', 0);
+
+    await store.getState().backfillAgentLog('agent-1');
+
+    expect(getAgentOutput).not.toHaveBeenCalled();
+  });
+
+  it('puts the output printed before a reload in front of what arrived since', async () => {
+    const { getAgentOutput } = await import('../tauri/agents');
+    vi.mocked(getAgentOutput).mockResolvedValueOnce(page(0, 5));
+    store.getState().appendAgentLog('agent-1', 'c3', 3);
+    store.getState().appendAgentLog('agent-1', 'c4', 4);
+
+    await store.getState().backfillAgentLog('agent-1');
+
+    expect(store.getState().agentLogs['agent-1']).toEqual(['c0', 'c1', 'c2', 'c3', 'c4']);
+    // Views following the agent are already past these chunks.
+    expect(store.getState().agentLogMeta['agent-1']).toMatchObject({ seq: 2, bytes: 10 });
+    await store.getState().backfillAgentLog('agent-1');
+    expect(getAgentOutput).toHaveBeenCalledTimes(1);
+  });
+
+  it('drops the events a backfill already delivered', async () => {
+    const { getAgentOutput } = await import('../tauri/agents');
+    vi.mocked(getAgentOutput).mockResolvedValueOnce(page(0, 3));
+
+    await store.getState().backfillAgentLog('agent-1');
+    store.getState().appendAgentLog('agent-1', 'c2', 2);
+    store.getState().appendAgentLog('agent-1', 'c3', 3);
+
+    expect(store.getState().agentLogs['agent-1']).toEqual(['c0', 'c1', 'c2', 'c3']);
+    expect(store.getState().agentLogMeta['agent-1'].seq).toBe(4);
+  });
+
+  it('leaves the log alone when the backend cannot be asked', async () => {
+    const { getAgentOutput } = await import('../tauri/agents');
+    vi.mocked(getAgentOutput).mockRejectedValueOnce(new Error('no tauri'));
+
+    await store.getState().backfillAgentLog('agent-1');
+
+    expect(store.getState().agentLogMeta['agent-1']).toBeUndefined();
+  });
+});
+
 describe('agentSlice – killRunningAgent graceful handling', () => {
   let store: StoreApi<AgentSlice>;
 
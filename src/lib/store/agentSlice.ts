@@ -2,6 +2,7 @@ import type { StateCreator } from 'zustand';
 import type { AgentConfig, AgentInfo, InterruptedAgent } from '../tauri/agents';
 import {
   discardInterruptedAgent,
+  getAgentOutput,
   killAgent,
   killAgentsForRepo,
   listAgentPromptHistory,
@@ -74,6 +75,13 @@ export interface AgentLogMeta {
   seq: number;
   /** Bytes currently retained in agentLogs for this agent. */
   bytes: number;
+  /** Backend seq of the first chunk this store received. Above 0, the agent
+   * printed before anything here was listening — a webview reload — and
+   * `backfillAgentLog` has something to fetch. */
+  firstSeq?: number;
+  /** Backend seq of the newest chunk; an event at or below it is one a
+   * backfill already delivered. */
+  lastSeq?: number;
 }
 
 export interface AgentSlice {
@@ -162,7 +170,16 @@ export interface AgentSlice {
   sendAgentInput: (agentId: string, text: string) => Promise<void>;
   dismissFinishedAgent: (agentId: string) => void;
   updateAgentStatus: (agentId: string, status: AgentInfo['status']) => void;
-  appendAgentLog: (agentId: string, log: string) => void;
+  /** `seq` is the backend's number for the chunk, when it has one. */
+  appendAgentLog: (agentId: string, log: string, seq?: number) => void;
+  /**
+   * Fetches the output the agent printed before this store was listening and
+   * puts it in front of what arrived since. Only the terminal replays these
+   * chunks: they are not run through the event extractors, and `seq` does not
+   * move, so views already following the agent write nothing twice. A no-op
+   * when nothing was missed; never rejects.
+   */
+  backfillAgentLog: (agentId: string) => Promise<void>;
   /**
    * Trims the stored history to its configured bounds, then reads it back.
    * A no-op while persistence is off — nothing was written, so there is
@@ -209,6 +226,19 @@ function withoutAgentIds<T>(
 
 type LogRecords = Pick<AgentSlice, 'agentLogs' | 'agentLogMeta'>;
 
+/** Drops the oldest chunks past either cap, but always keeps the newest. */
+function trimAgentLog(chunks: string[], bytes: number): { chunks: string[]; bytes: number } {
+  let drop = 0;
+  while (
+    chunks.length - drop > 1 &&
+    (chunks.length - drop > MAX_AGENT_LOGS || bytes > MAX_AGENT_LOG_BYTES)
+  ) {
+    bytes -= chunks[drop].length;
+    drop++;
+  }
+  return { chunks: drop > 0 ? chunks.slice(drop) : chunks, bytes };
+}
+
 /**
  * The log records with one agent's entry removed — the shared shape behind
  * killing and dismissing a single agent, which otherwise differ only in what
@@ -419,7 +449,8 @@ export const createAgentSlice: StateCreator<AgentSlice> = (set, get) => ({
         id: crypto.randomUUID(),
         prompt: remembered,
         agentName: name,
-        model: config.model,
+        // Empty when the project's role preset chose it; Rust reports what ran.
+        model: config.model || agent.model,
         provider: config.provider ?? agent.provider,
         cwd: spawnConfig.cwd ?? agent.repoPath ?? null,
         source: config.runSource ?? 'ui',
@@ -438,7 +469,7 @@ export const createAgentSlice: StateCreator<AgentSlice> = (set, get) => ({
           agentId: agent.id,
           ticketId: agent.spawnedByTicketId ?? config.spawnedByTicketId ?? null,
           prompt: config.task,
-          model: config.model,
+          model: config.model || agent.model,
           provider: agent.provider,
           source: config.runSource ?? 'ui',
           outcome: 'running',
@@ -725,25 +756,13 @@ export const createAgentSlice: StateCreator<AgentSlice> = (set, get) => ({
     });
   },
 
-  appendAgentLog: (agentId, log) => {
+  appendAgentLog: (agentId, log, seq) => {
     const state = get();
     const existing = state.agentLogs[agentId] ?? [];
     const meta = state.agentLogMeta[agentId] ?? { seq: 0, bytes: 0 };
-    let updated = [...existing, log];
-    let bytes = meta.bytes + log.length;
-
-    // Trim oldest chunks past either cap, but always keep the newest chunk.
-    let drop = 0;
-    while (
-      updated.length - drop > 1 &&
-      (updated.length - drop > MAX_AGENT_LOGS || bytes > MAX_AGENT_LOG_BYTES)
-    ) {
-      bytes -= updated[drop].length;
-      drop++;
-    }
-    if (drop > 0) {
-      updated = updated.slice(drop);
-    }
+    // Delivered already, by a backfill that overtook its event.
+    if (seq !== undefined && meta.lastSeq !== undefined && seq <= meta.lastSeq) return;
+    const { chunks: updated, bytes } = trimAgentLog([...existing, log], meta.bytes + log.length);
 
     // Throttle lastActivityAt bumps: replacing the agents array on every
     // streamed chunk forces every agents-derived memo (orchestration graph,
@@ -800,7 +819,12 @@ export const createAgentSlice: StateCreator<AgentSlice> = (set, get) => ({
       },
       agentLogMeta: {
         ...state.agentLogMeta,
-        [agentId]: { seq: meta.seq + 1, bytes },
+        [agentId]: {
+          ...meta,
+          seq: meta.seq + 1,
+          bytes,
+          ...(seq !== undefined ? { firstSeq: meta.firstSeq ?? seq, lastSeq: seq } : {}),
+        },
       },
       // Only replace the record when a chunk actually produced an event — a
       // fresh object on every redraw-only chunk would cost every
@@ -871,6 +895,50 @@ export const createAgentSlice: StateCreator<AgentSlice> = (set, get) => ({
     if (shouldBumpActivity) void flushAgentLog();
   },
 
+  backfillAgentLog: async (agentId) => {
+    const before = get().agentLogMeta[agentId];
+    if (before !== undefined && (before.firstSeq ?? 0) === 0) return;
+
+    let page;
+    try {
+      page = await getAgentOutput(agentId);
+    } catch {
+      return;
+    }
+
+    // Read again: events kept arriving while the page was on its way.
+    const state = get();
+    const meta = state.agentLogMeta[agentId];
+    // Only what precedes the first event is missing. Anything newer than the
+    // events so far is still in flight as an event of its own — except when
+    // there were none yet, and then `lastSeq` below makes those a no-op.
+    const missedBefore = meta === undefined ? page.nextSeq : (meta.firstSeq ?? 0);
+    const missed = page.chunks.filter((chunk) => chunk.seq < missedBefore);
+    if (missed.length === 0) return;
+
+    const held = state.agentLogs[agentId] ?? [];
+    const { chunks, bytes } = trimAgentLog(
+      [...missed.map((chunk) => chunk.data), ...held],
+      missed.reduce((sum, chunk) => sum + chunk.data.length, meta?.bytes ?? 0)
+    );
+    set({
+      agentLogs: { ...state.agentLogs, [agentId]: chunks },
+      agentLogMeta: {
+        ...state.agentLogMeta,
+        [agentId]: {
+          // A fresh record counts what it was given, so a replay can tell
+          // that trimming already cut into it.
+          seq: meta?.seq ?? missed.length,
+          bytes,
+          // Nothing older is left to fetch, even when the backend had to
+          // drop the start of a long session itself.
+          firstSeq: 0,
+          lastSeq: meta?.lastSeq ?? missed[missed.length - 1].seq,
+        },
+      },
+    });
+  },
+
   loadAgentLogHistory: async () => {
     // Trim first, then read: otherwise the first load after a long absence
     // would pull rows that are about to be discarded anyway.
@@ -45,7 +45,7 @@ vi.mock('../tauri/goals', () => ({
 
 vi.mock('../tauri/pm', () => ({
   pmLoad: vi.fn(async () => ({ epics: [], tickets: [], testCases: [], dependencies: [] })),
-  pmSave: vi.fn(async () => undefined),
+  pmSync: vi.fn(async () => ({ versions: { epics: {}, tickets: {}, testCases: {} } })),
   pmClear: vi.fn(async () => undefined),
 }));
 
@@ -483,6 +483,26 @@ describe('conductorSlice', () => {
     expect(agent.provider).toBe('gemini');
   });
 
+  it('leaves provider and model to the project’s implementer preset', async () => {
+    store.setState({
+      pmDraftTickets: [makeTicket({ id: 't1' }), makeTicket({ id: 't2', modelPower: 'high' })],
+      conductorMaxConcurrent: 2,
+    });
+    store.getState().startConductor(null);
+    await store.getState().conductorTick();
+
+    const calls = vi.mocked(spawnAgent).mock.calls.map(([config]) => config);
+    const forTicket = (id: string) => calls.find((c) => c.spawnedByTicketId === id)!;
+    expect(forTicket('t1')).toMatchObject({
+      role: 'implementer',
+      model: '',
+      fallbackModel: 'sonnet',
+    });
+    expect(forTicket('t1').provider).toBeUndefined();
+    // A ticket that asks for more capability still says so.
+    expect(forTicket('t2')).toMatchObject({ role: 'implementer', model: 'opus' });
+  });
+
   it('omits permissionMode so the provider-configured default decides', async () => {
     store.setState({
       pmDraftTickets: [makeTicket({ id: 't1' })],
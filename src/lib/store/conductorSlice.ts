@@ -491,12 +491,16 @@ export const createConductorSlice: StateCreator<ConductorSlice> = (set, get) =>
     return {
       spawnReviewAgent: async (input) => {
         // Each half falls back on its own: a judge given a provider but no
-        // model must not silently lose the conductor's model as well.
+        // model must not silently lose the conductor's model as well. What is
+        // left open after that is the project's judge preset's to decide, and
+        // only without one does the default tier apply.
         const state = get();
         const agent = await full.spawnNewAgent?.({
           name: `review:${input.ticket.name.slice(0, 40)}`,
-          model: state.conductorJudgeModel || state.conductorModel || modelForPower(undefined),
+          model: state.conductorJudgeModel || state.conductorModel || '',
+          fallbackModel: modelForPower(undefined),
           provider: state.conductorJudgeProviderId ?? state.conductorProviderId ?? undefined,
+          role: 'judge',
           task: buildReviewAgentPrompt(input),
           cwd: full.rootPath ?? undefined,
           // Same reason as the implementer: the verdict is collected when this
@@ -1082,9 +1086,11 @@ export const createConductorSlice: StateCreator<ConductorSlice> = (set, get) =>
         const goal = goals.find((g) => g.id === effectiveGoalId);
         const testCases = (full.pmDraftTestCases ?? []).filter((tc) => tc.ticketId === ticket.id);
         const prompt = buildConductorPrompt(ticket, goal, testCases);
-        // A conductor-wide override wins; otherwise derive the model from the
-        // ticket's declared capability need.
-        const model = get().conductorModel || modelForPower(ticket.modelPower);
+        // A conductor-wide override wins; then the ticket's declared capability
+        // need. A ticket that declares none leaves the model to the project's
+        // implementer preset, and without one gets the default tier.
+        const model =
+          get().conductorModel || (ticket.modelPower ? modelForPower(ticket.modelPower) : '');
         const providerOverride = get().conductorProviderId ?? undefined;
 
         // Reserve the ticket synchronously BEFORE the async spawn so a
@@ -1098,7 +1104,9 @@ export const createConductorSlice: StateCreator<ConductorSlice> = (set, get) =>
           agent = await full.spawnNewAgent?.({
             name: `conductor:${ticket.name.slice(0, 40)}`,
             model,
+            fallbackModel: modelForPower(undefined),
             provider: providerOverride,
+            role: 'implementer',
             task: prompt,
             cwd: ticket.workingDirectory ?? full.rootPath ?? undefined,
             // No permissionMode: the provider's configured defaultPermissionMode
@@ -1152,7 +1160,10 @@ export const createConductorSlice: StateCreator<ConductorSlice> = (set, get) =>
         }));
         addDecision({
           action: 'spawn',
-          detail: `Launched ${model} agent for "${ticket.name}"`,
+          // A queued agent has no model yet: Rust settles it at start.
+          detail: agent.model
+            ? `Launched ${agent.model} agent for "${ticket.name}"`
+            : `Launched agent for "${ticket.name}"`,
           ticketId: ticket.id,
           agentId: agent.id,
         });
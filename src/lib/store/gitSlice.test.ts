@@ -57,7 +57,7 @@ vi.mock('../tauri/git', () => ({
   stageFiles: vi.fn(async () => undefined),
   unstageFiles: vi.fn(async () => undefined),
   commitChanges: vi.fn(async () => 'abc123'),
-  pushChanges: vi.fn(async () => undefined),
+  pushChanges: vi.fn(async () => ({ remote: 'origin', refspec: 'refs/heads/main', rejected: [] })),
   gitLogSince: vi.fn(async () => [sampleCommit]),
   listGitBranches: vi.fn(async () => [
     { name: 'main', kind: 'local', isCurrent: true },
@@ -667,10 +667,53 @@ describe('gitSlice', () => {
       const { pushChanges, getGitStatus } = await import('../tauri/git');
       vi.mocked(getGitStatus).mockClear();
 
-      await store.getState().push(repoRoot.path);
+      const result = await store.getState().push(repoRoot.path);
 
-      expect(pushChanges).toHaveBeenCalledWith(repoRoot.path);
+      expect(pushChanges).toHaveBeenCalledWith(repoRoot.path, undefined);
       expect(getGitStatus).toHaveBeenCalledWith(repoRoot.path);
+      expect(result).toEqual({ remote: 'origin', refspec: 'refs/heads/main', rejected: [] });
+    });
+
+    it('passes push options through to the backend', async () => {
+      const { pushChanges } = await import('../tauri/git');
+      const options = { remote: 'fork', refspec: 'main:review', forceWithLease: true };
+
+      await store.getState().push(repoRoot.path, options);
+
+      expect(pushChanges).toHaveBeenCalledWith(repoRoot.path, options);
+    });
+
+    it('records the refs the remote rejected and throws with each reason', async () => {
+      const { pushChanges } = await import('../tauri/git');
+      vi.mocked(pushChanges).mockResolvedValueOnce({
+        remote: 'origin',
+        refspec: 'refs/heads/main:refs/heads/main',
+        rejected: [{ refname: 'refs/heads/main', reason: 'stale info' }],
+      });
+
+      await expect(store.getState().push(repoRoot.path)).rejects.toThrow(
+        'Push rejected: refs/heads/main (stale info)'
+      );
+
+      expect(store.getState().repoStates[repoRoot.path]?.pushRejections).toEqual([
+        { refname: 'refs/heads/main', reason: 'stale info' },
+      ]);
+      expect(store.getState().repoStates[repoRoot.path]?.isPushing).toBe(false);
+    });
+
+    it('clears the last rejections when a new push starts', async () => {
+      const { pushChanges } = await import('../tauri/git');
+      vi.mocked(pushChanges).mockResolvedValueOnce({
+        remote: 'origin',
+        refspec: 'refs/heads/main',
+        rejected: [{ refname: 'a', reason: 'no' }],
+      });
+      await expect(store.getState().push(repoRoot.path)).rejects.toThrow();
+      expect(store.getState().repoStates[repoRoot.path]?.pushRejections).toHaveLength(1);
+
+      await store.getState().push(repoRoot.path);
+
+      expect(store.getState().repoStates[repoRoot.path]?.pushRejections).toEqual([]);
     });
 
     it('resets isPushing even when the push rejects, and rethrows', async () => {
@@ -977,6 +1020,7 @@ describe('selectRepoState', () => {
           commitMessage: '',
           isCommitting: false,
           isPushing: false,
+          pushRejections: [],
         },
       },
     };
@@ -1012,6 +1056,7 @@ describe('selectChangedFileCount', () => {
           commitMessage: '',
           isCommitting: false,
           isPushing: false,
+          pushRejections: [],
         },
         '/w/api': {
           ref: repoApi,
@@ -1020,6 +1065,7 @@ describe('selectChangedFileCount', () => {
           commitMessage: '',
           isCommitting: false,
           isPushing: false,
+          pushRejections: [],
         },
       },
     };
@@ -1039,6 +1085,7 @@ describe('selectBranchNameForPath', () => {
     commitMessage: '',
     isCommitting: false,
     isPushing: false,
+    pushRejections: [],
   });
 
   it("uses the path's own repo when one is found", () => {
@@ -30,8 +30,13 @@ import {
   removeGitWorktree,
   gitDefaultBranch,
   mergeGitWorktreeIntoDefault,
+  type GitPushOptions,
+  type GitPushRejection,
+  type GitPushResult,
 } from '../tauri/git';
 
+export type { GitPushRejection };
+
 export type ScmView = 'changes' | 'history' | 'compare';
 export type HunkNavDirection = 'next' | 'prev';
 
@@ -43,6 +48,8 @@ export interface GitRepoState {
   commitMessage: string;
   isCommitting: boolean;
   isPushing: boolean;
+  /** Refs the remote turned down on the last push, with its reason; empty otherwise. */
+  pushRejections: GitPushRejection[];
 }
 
 export interface GitSlice {
@@ -101,8 +108,12 @@ export interface GitSlice {
   unstageFile: (repoPath: string, path: string) => Promise<void>;
   unstageAll: (repoPath: string) => Promise<void>;
   commit: (repoPath: string) => Promise<string | null>;
-  /** Pushes the current branch to origin. Rethrows so the caller can report. */
-  push: (repoPath: string) => Promise<void>;
+  /**
+   * Pushes the current branch (or `options.refspec`). Throws so the caller can
+   * report, also when the remote rejects a ref; those land in the repo's
+   * `pushRejections` as well.
+   */
+  push: (repoPath: string, options?: GitPushOptions) => Promise<GitPushResult>;
   setCommitMessage: (repoPath: string, msg: string) => void;
   /** Auric-managed worktrees across every discovered repo. */
   agentWorktrees: GitWorktree[];
@@ -214,6 +225,7 @@ function applyRepoStatus(
       commitMessage: prev?.commitMessage ?? '',
       isCommitting: prev?.isCommitting ?? false,
       isPushing: prev?.isPushing ?? false,
+      pushRejections: prev?.pushRejections ?? [],
     },
   };
 }
@@ -304,6 +316,7 @@ function seedRepoStates(
       commitMessage: '',
       isCommitting: false,
       isPushing: false,
+      pushRejections: [],
     };
   }
   return seeded;
@@ -645,12 +658,22 @@ export const createGitSlice: StateCreator<GitSlice> = (set, get) => {
       }
     },
 
-    push: async (repoPath) => {
-      set((s) => ({ repoStates: setRepoField(s.repoStates, repoPath, { isPushing: true }) }));
+    push: async (repoPath, options) => {
+      set((s) => ({
+        repoStates: setRepoField(s.repoStates, repoPath, { isPushing: true, pushRejections: [] }),
+      }));
       try {
-        await pushChanges(repoPath);
+        const result = await pushChanges(repoPath, options);
+        if (result.rejected.length > 0) {
+          set((s) => ({
+            repoStates: setRepoField(s.repoStates, repoPath, { pushRejections: result.rejected }),
+          }));
+          const reasons = result.rejected.map((r) => `${r.refname} (${r.reason})`).join('; ');
+          throw new Error(`Push rejected: ${reasons}`);
+        }
         // The branch's ahead/behind display is what a push changes.
         await get().refreshRepoStatus(repoPath);
+        return result;
       } finally {
         set((s) => ({ repoStates: setRepoField(s.repoStates, repoPath, { isPushing: false }) }));
       }
@@ -8,6 +8,7 @@ import {
   getGoalDescendants,
   getGoalProgress,
   getGoalSatisfaction,
+  getRunsCost,
   getRunsForGoal,
   getGoalWorkflowStage,
   planGoalMove,
@@ -247,6 +248,16 @@ describe('goalsSlice progress + satisfaction', () => {
     ];
     expect(getRunsForGoal(runs, 'g1').map((r) => r.id)).toEqual(['c', 'a']);
   });
+
+  it('getRunsCost sums the priced runs only', () => {
+    const runs = [
+      makeRun({ id: 'a', costUsd: 1.5 }),
+      makeRun({ id: 'b', costUsd: null }),
+      makeRun({ id: 'c', costUsd: 0.25 }),
+    ];
+    expect(getRunsCost(runs)).toEqual({ usd: 1.75, priced: 2 });
+    expect(getRunsCost([])).toEqual({ usd: 0, priced: 0 });
+  });
 });
 
 describe('goalsSlice draft CRUD', () => {
@@ -290,6 +290,15 @@ export function getRunsForGoal(runs: PmGoalRun[], goalId: string): PmGoalRun[] {
     .sort((a, b) => b.startedAt.localeCompare(a.startedAt));
 }
 
+/** Spend over `runs`. `priced` counts the runs that reported a price; the rest add nothing. */
+export function getRunsCost(runs: PmGoalRun[]): { usd: number; priced: number } {
+  return runs.reduce(
+    (total, run) =>
+      run.costUsd == null ? total : { usd: total.usd + run.costUsd, priced: total.priced + 1 },
+    { usd: 0, priced: 0 }
+  );
+}
+
 function nowTimestamp(): string {
   return new Date().toISOString().replace('T', ' ').slice(0, 19);
 }
@@ -2,7 +2,15 @@ import { describe, expect, it, vi, beforeEach } from 'vitest';
 import { create } from 'zustand';
 import { createPmSlice, type PmSlice } from './pmSlice';
 import { createToastSlice, type ToastSlice } from './toastSlice';
-import type { PmEpic, PmTicket, PmTestCase, PmDependency, PmStatusHistoryEntry } from '../tauri/pm';
+import type {
+  PmEpic,
+  PmTicket,
+  PmTestCase,
+  PmDependency,
+  PmStatusHistoryEntry,
+  PmSyncPayload,
+  PmSyncResult,
+} from '../tauri/pm';
 
 const mockPmLoad = vi.fn<
   (...args: unknown[]) => Promise<{
@@ -12,7 +20,20 @@ const mockPmLoad = vi.fn<
     dependencies: PmDependency[];
   }>
 >(() => Promise.resolve({ epics: [], tickets: [], testCases: [], dependencies: [] }));
-const mockPmSave = vi.fn<(...args: unknown[]) => Promise<void>>(() => Promise.resolve());
+/** Stamps every upserted row with `v-next`, as the backend stamps a fresh `updatedAt`. */
+function stampAll(_projectPath: string, payload: PmSyncPayload): Promise<PmSyncResult> {
+  const stamp = (rows: { id: string }[]) => Object.fromEntries(rows.map((r) => [r.id, 'v-next']));
+  return Promise.resolve({
+    versions: {
+      epics: stamp(payload.epics),
+      tickets: stamp(payload.tickets),
+      testCases: stamp(payload.testCases),
+    },
+  });
+}
+const mockPmSync = vi.fn<(projectPath: string, payload: PmSyncPayload) => Promise<PmSyncResult>>(
+  stampAll
+);
 const mockPmClear = vi.fn<(...args: unknown[]) => Promise<void>>(() => Promise.resolve());
 const mockPmLoadHistory = vi.fn<(...args: unknown[]) => Promise<PmStatusHistoryEntry[]>>(() =>
   Promise.resolve([])
@@ -21,7 +42,8 @@ const mockInitProjectDb = vi.fn<(...args: unknown[]) => Promise<void>>(() => Pro
 
 vi.mock('../tauri/pm', () => ({
   pmLoad: (...args: unknown[]) => mockPmLoad(...args),
-  pmSave: (...args: unknown[]) => mockPmSave(...args),
+  pmSync: (projectPath: string, payload: PmSyncPayload) => mockPmSync(projectPath, payload),
+  PM_CONFLICT_PREFIX: 'PM conflict',
   pmClear: (...args: unknown[]) => mockPmClear(...args),
   pmLoadHistory: (...args: unknown[]) => mockPmLoadHistory(...args),
 }));
@@ -315,7 +337,7 @@ describe('pmSlice', () => {
     const store = createTestStore();
     await store.getState().loadPmData('/project');
 
-    const tickets = store.getState().pmTickets;
+    const tickets = store.getState().pmDraftTickets;
     expect(tickets.find((t) => t.id === 't-old')?.status).toBe('archived');
     expect(tickets.find((t) => t.id === 't-recent')?.status).toBe('done');
     expect(tickets.find((t) => t.id === 't-open')?.status).toBe('open');
@@ -333,14 +355,97 @@ describe('pmSlice', () => {
     await store.getState().savePmData('/project');
 
     expect(mockInitProjectDb).toHaveBeenCalledWith('/project');
-    expect(mockPmSave).toHaveBeenCalledWith('/project', {
-      epics: store.getState().pmEpics,
-      tickets: store.getState().pmTickets,
-      testCases: store.getState().pmTestCases,
-      dependencies: store.getState().pmDependencies,
+    expect(mockPmSync).toHaveBeenCalledWith('/project', {
+      epics: [makeEpic()],
+      tickets: [],
+      testCases: [],
+      dependencies: [],
+      deletedEpicIds: [],
+      deletedTicketIds: [],
+      deletedTestCaseIds: [],
+      deletedDependencyIds: [],
+      baseVersions: { epics: {}, tickets: {}, testCases: {} },
     });
     expect(store.getState().pmDirty).toBe(false);
     expect(store.getState().pmEpics).toEqual(store.getState().pmDraftEpics);
+    expect(store.getState().pmEpics[0]?.updatedAt).toBe('v-next');
+  });
+
+  it('savePmData sends only changed rows, each with the version it was loaded at', async () => {
+    mockPmLoad.mockResolvedValueOnce({
+      epics: [makeEpic({ updatedAt: 'v1' })],
+      tickets: [
+        makeTicket({ id: 't-kept', updatedAt: 'v1' }),
+        makeTicket({ id: 't-edited', updatedAt: 'v2' }),
+        makeTicket({ id: 't-gone', updatedAt: 'v3' }),
+      ],
+      testCases: [],
+      dependencies: [makeDep({ id: 'd-gone' })],
+    });
+    const store = createTestStore();
+    await store.getState().loadPmData('/project');
+
+    store.getState().updateTicket('t-edited', { name: 'Renamed' });
+    store.getState().deleteTicket('t-gone');
+    store.getState().removeDependency('d-gone');
+    await store.getState().savePmData('/project');
+
+    const payload = mockPmSync.mock.calls[0]?.[1];
+    expect(payload?.epics).toEqual([]);
+    expect(payload?.tickets.map((t) => t.id)).toEqual(['t-edited']);
+    expect(payload?.deletedTicketIds).toEqual(['t-gone']);
+    expect(payload?.deletedDependencyIds).toEqual(['d-gone']);
+    expect(payload?.baseVersions.tickets).toEqual({ 't-edited': 'v2', 't-gone': 'v3' });
+    expect(store.getState().pmDraftTickets.map((t) => [t.id, t.updatedAt])).toEqual([
+      ['t-kept', 'v1'],
+      ['t-edited', 'v-next'],
+    ]);
+  });
+
+  it('savePmData keeps the base an edit started from across a refresh', async () => {
+    mockPmLoad.mockResolvedValueOnce({
+      epics: [],
+      tickets: [makeTicket({ updatedAt: 'v1' })],
+      testCases: [],
+      dependencies: [],
+    });
+    const store = createTestStore();
+    await store.getState().loadPmData('/project');
+    store.getState().updateTicket('t1', { name: 'Mine' });
+
+    // An agent renames the same ticket; the poll brings its version in but
+    // keeps the local edit in the draft.
+    mockPmLoad.mockResolvedValueOnce({
+      epics: [],
+      tickets: [makeTicket({ name: 'Theirs', updatedAt: 'v2' })],
+      testCases: [],
+      dependencies: [],
+    });
+    await store.getState().refreshPmData('/project');
+    expect(store.getState().pmDraftTickets[0]?.name).toBe('Mine');
+
+    await store.getState().savePmData('/project');
+
+    const payload = mockPmSync.mock.calls[0]?.[1];
+    expect(payload?.baseVersions.tickets).toEqual({ t1: 'v1' });
+  });
+
+  it('savePmData sends tickets archived on load, since storage still has them done', async () => {
+    const twoDaysAgo = new Date(Date.now() - 2 * 24 * 60 * 60 * 1000).toISOString();
+    mockPmLoad.mockResolvedValueOnce({
+      epics: [],
+      tickets: [makeTicket({ status: 'done', statusUpdatedAt: twoDaysAgo, updatedAt: 'v1' })],
+      testCases: [],
+      dependencies: [],
+    });
+    const store = createTestStore();
+    await store.getState().loadPmData('/project');
+
+    await store.getState().savePmData('/project');
+
+    const payload = mockPmSync.mock.calls[0]?.[1];
+    expect(payload?.tickets.map((t) => t.status)).toEqual(['archived']);
+    expect(payload?.baseVersions.tickets).toEqual({ t1: 'v1' });
   });
 
   it('refreshes the inbox overview after a successful save so ticket status flows back', async () => {
@@ -840,13 +945,14 @@ describe('pmSlice', () => {
 
 describe('savePmData failure feedback', () => {
   beforeEach(() => {
-    mockPmSave.mockReset().mockResolvedValue(undefined);
+    mockPmSync.mockReset().mockImplementation(stampAll);
+    mockPmLoad.mockClear();
     mockInitProjectDb.mockReset().mockResolvedValue(undefined);
   });
 
   it('tells the user when the save fails', async () => {
     const store = createStoreWithToasts();
-    mockPmSave.mockRejectedValueOnce(new Error('database is locked'));
+    mockPmSync.mockRejectedValueOnce(new Error('database is locked'));
 
     await expect(store.getState().savePmData('/repo')).rejects.toThrow('database is locked');
 
@@ -860,7 +966,7 @@ describe('savePmData failure feedback', () => {
     store.getState().addEpic(makeEpic());
     expect(store.getState().pmDirty).toBe(true);
 
-    mockPmSave.mockRejectedValueOnce(new Error('nope'));
+    mockPmSync.mockRejectedValueOnce(new Error('nope'));
     await store
       .getState()
       .savePmData('/repo')
@@ -869,6 +975,23 @@ describe('savePmData failure feedback', () => {
     expect(store.getState().pmDirty).toBe(true);
   });
 
+  it('reports a conflict instead of overwriting, keeps the edits and reloads', async () => {
+    const store = createStoreWithToasts();
+    store.getState().addEpic(makeEpic());
+    mockPmSync.mockRejectedValueOnce(
+      "PM conflict: ticket 't1' was changed since it was loaded (expected v1, found v2)"
+    );
+
+    await expect(store.getState().savePmData('/repo')).rejects.toThrow('Nothing was overwritten');
+
+    const toast = store.getState().toasts.at(-1);
+    expect(toast?.variant).toBe('error');
+    expect(toast?.message).toContain("ticket 't1' was changed since it was loaded");
+    expect(store.getState().pmDirty).toBe(true);
+    expect(store.getState().pmDraftEpics).toHaveLength(1);
+    expect(mockPmLoad).toHaveBeenCalledWith('/repo');
+  });
+
   it('stays quiet on a successful save', async () => {
     const store = createStoreWithToasts();
     await store.getState().savePmData('/repo');
@@ -8,12 +8,14 @@ import type {
   PmDependency,
   PmStatusHistoryEntry,
   PmContextItem,
+  PmRowVersions,
 } from '../tauri/pm';
 import {
   pmLoad as ipcPmLoad,
-  pmSave as ipcPmSave,
+  pmSync as ipcPmSync,
   pmClear as ipcPmClear,
   pmLoadHistory as ipcPmLoadHistory,
+  PM_CONFLICT_PREFIX,
 } from '../tauri/pm';
 import { initProjectDb } from '../tauri/db';
 
@@ -33,6 +35,12 @@ export interface PmSlice {
   pmDraftTestCases: PmTestCase[];
   pmDraftDependencies: PmDependency[];
   pmDirty: boolean;
+  /**
+   * `updatedAt` each edited or deleted row had when the user first touched it.
+   * A refresh moves the persisted rows on; the save must still check against
+   * the version the edit was made from.
+   */
+  pmBaseVersions: PmRowVersions;
   // UI state
   pmModalOpen: boolean;
   pmSelectedEpicId: string | null;
@@ -158,6 +166,91 @@ function mergeDependencies(
   return result;
 }
 
+/** What a save changes in one versioned table, relative to what was loaded. */
+interface RowDelta<T> {
+  upserts: T[];
+  deletedIds: string[];
+  /** Loaded `updatedAt` of every changed or deleted row; new rows have none. */
+  baseVersions: Record<string, string>;
+}
+
+function rowDelta<T extends Identifiable>(
+  persisted: T[],
+  draft: T[],
+  recorded: Record<string, string>
+): RowDelta<T> {
+  const persistedMap = new Map(persisted.map((item) => [item.id, item]));
+  const draftIds = new Set(draft.map((item) => item.id));
+  const delta: RowDelta<T> = { upserts: [], deletedIds: [], baseVersions: {} };
+
+  for (const item of draft) {
+    const loaded = persistedMap.get(item.id);
+    if (!loaded) {
+      delta.upserts.push(item);
+    } else if (JSON.stringify(loaded) !== JSON.stringify(item)) {
+      delta.upserts.push(item);
+      delta.baseVersions[item.id] = recorded[item.id] ?? loaded.updatedAt;
+    }
+  }
+  for (const item of persisted) {
+    if (!draftIds.has(item.id)) {
+      delta.deletedIds.push(item.id);
+      delta.baseVersions[item.id] = recorded[item.id] ?? item.updatedAt;
+    }
+  }
+  return delta;
+}
+
+/** Dependencies carry no version; they are only ever added or removed. */
+function dependencyDelta(
+  persisted: PmDependency[],
+  draft: PmDependency[]
+): { upserts: PmDependency[]; deletedIds: string[] } {
+  const persistedMap = new Map(persisted.map((d) => [d.id, d]));
+  const draftIds = new Set(draft.map((d) => d.id));
+  return {
+    upserts: draft.filter((d) => {
+      const loaded = persistedMap.get(d.id);
+      return !loaded || JSON.stringify(loaded) !== JSON.stringify(d);
+    }),
+    deletedIds: persisted.filter((d) => !draftIds.has(d.id)).map((d) => d.id),
+  };
+}
+
+/** Takes over the `updatedAt` the backend stamped on every row it wrote. */
+function withVersions<T extends Identifiable>(items: T[], versions: Record<string, string>): T[] {
+  return items.map((item) =>
+    versions[item.id] !== undefined ? { ...item, updatedAt: versions[item.id] } : item
+  );
+}
+
+const NO_BASE_VERSIONS: PmRowVersions = { epics: {}, tickets: {}, testCases: {} };
+
+/**
+ * Remembers the persisted `updatedAt` of rows about to be edited or deleted,
+ * unless an earlier edit already did. New rows have nothing to remember.
+ */
+function recordBase(
+  bases: PmRowVersions,
+  table: keyof PmRowVersions,
+  persisted: Identifiable[],
+  ids: string[]
+): PmRowVersions {
+  const missing = ids.filter((id) => bases[table][id] === undefined);
+  if (missing.length === 0) return bases;
+  const recorded = { ...bases[table] };
+  for (const id of missing) {
+    const row = persisted.find((item) => item.id === id);
+    if (row) recorded[id] = row.updatedAt;
+  }
+  return { ...bases, [table]: recorded };
+}
+
+function isPmConflict(error: unknown): boolean {
+  const message = error instanceof Error ? error.message : String(error);
+  return message.startsWith(PM_CONFLICT_PREFIX);
+}
+
 export const createPmSlice: StateCreator<PmSlice> = (set, get) => ({
   // Persisted state
   pmEpics: [],
@@ -170,6 +263,7 @@ export const createPmSlice: StateCreator<PmSlice> = (set, get) => ({
   pmDraftTestCases: [],
   pmDraftDependencies: [],
   pmDirty: false,
+  pmBaseVersions: NO_BASE_VERSIONS,
   // Status history
   pmStatusHistory: [],
   pmHistoryLoading: false,
@@ -218,9 +312,11 @@ export const createPmSlice: StateCreator<PmSlice> = (set, get) => ({
           return t;
         });
 
+        // Persisted keeps what storage holds, so the archive is a change the
+        // next save sends rather than one it believes is already written.
         set({
           pmEpics: state.epics,
-          pmTickets: processedTickets,
+          pmTickets: state.tickets,
           pmTestCases: state.testCases,
           pmDependencies: state.dependencies,
           pmDraftEpics: state.epics,
@@ -228,6 +324,7 @@ export const createPmSlice: StateCreator<PmSlice> = (set, get) => ({
           pmDraftTestCases: state.testCases,
           pmDraftDependencies: state.dependencies,
           pmDirty: hasArchived,
+          pmBaseVersions: NO_BASE_VERSIONS,
         });
       }
     ),
@@ -280,20 +377,63 @@ export const createPmSlice: StateCreator<PmSlice> = (set, get) => ({
   savePmData: (projectPath) =>
     withPersistFeedback(get(), 'project data', async () => {
       await initProjectDb(projectPath);
-      const { pmDraftEpics, pmDraftTickets, pmDraftTestCases, pmDraftDependencies } = get();
-      await ipcPmSave(projectPath, {
-        epics: pmDraftEpics,
-        tickets: pmDraftTickets,
-        testCases: pmDraftTestCases,
-        dependencies: pmDraftDependencies,
+      const {
+        pmEpics,
+        pmTickets,
+        pmTestCases,
+        pmDependencies,
+        pmDraftEpics,
+        pmDraftTickets,
+        pmDraftTestCases,
+        pmDraftDependencies,
+        pmBaseVersions,
+      } = get();
+      // Only the rows this window changed go out, each with the version the
+      // edit started from, so a row an agent or another window wrote meanwhile
+      // is rejected instead of silently overwritten.
+      const epics = rowDelta(pmEpics, pmDraftEpics, pmBaseVersions.epics);
+      const tickets = rowDelta(pmTickets, pmDraftTickets, pmBaseVersions.tickets);
+      const testCases = rowDelta(pmTestCases, pmDraftTestCases, pmBaseVersions.testCases);
+      const dependencies = dependencyDelta(pmDependencies, pmDraftDependencies);
+
+      const { versions } = await ipcPmSync(projectPath, {
+        epics: epics.upserts,
+        tickets: tickets.upserts,
+        testCases: testCases.upserts,
+        dependencies: dependencies.upserts,
+        deletedEpicIds: epics.deletedIds,
+        deletedTicketIds: tickets.deletedIds,
+        deletedTestCaseIds: testCases.deletedIds,
+        deletedDependencyIds: dependencies.deletedIds,
+        baseVersions: {
+          epics: epics.baseVersions,
+          tickets: tickets.baseVersions,
+          testCases: testCases.baseVersions,
+        },
+      }).catch((error: unknown) => {
+        if (!isPmConflict(error)) throw error;
+        // The draft stays dirty. Forgetting the bases and refreshing moves the
+        // loaded versions up to the other writer's, so saving again is a
+        // deliberate overwrite.
+        set({ pmBaseVersions: NO_BASE_VERSIONS });
+        void get().refreshPmData(projectPath);
+        throw new Error(
+          `${error instanceof Error ? error.message : String(error)}. Nothing was ` +
+            'overwritten and your edits are kept; save again to apply them over it.'
+        );
       });
-      set({
-        pmEpics: pmDraftEpics,
-        pmTickets: pmDraftTickets,
-        pmTestCases: pmDraftTestCases,
+
+      set((s) => ({
+        pmEpics: withVersions(pmDraftEpics, versions.epics),
+        pmTickets: withVersions(pmDraftTickets, versions.tickets),
+        pmTestCases: withVersions(pmDraftTestCases, versions.testCases),
         pmDependencies: pmDraftDependencies,
+        pmDraftEpics: withVersions(s.pmDraftEpics, versions.epics),
+        pmDraftTickets: withVersions(s.pmDraftTickets, versions.tickets),
+        pmDraftTestCases: withVersions(s.pmDraftTestCases, versions.testCases),
         pmDirty: false,
-      });
+        pmBaseVersions: NO_BASE_VERSIONS,
+      }));
       refreshInboxAfterPmWrite(get, projectPath);
     }),
 
@@ -310,6 +450,7 @@ export const createPmSlice: StateCreator<PmSlice> = (set, get) => ({
       pmDraftTestCases: [],
       pmDraftDependencies: [],
       pmDirty: false,
+      pmBaseVersions: NO_BASE_VERSIONS,
     });
   },
 
@@ -324,6 +465,7 @@ export const createPmSlice: StateCreator<PmSlice> = (set, get) => ({
       pmDraftTestCases: [],
       pmDraftDependencies: [],
       pmDirty: false,
+      pmBaseVersions: NO_BASE_VERSIONS,
     }),
 
   discardPmChanges: () => {
@@ -334,6 +476,7 @@ export const createPmSlice: StateCreator<PmSlice> = (set, get) => ({
       pmDraftTestCases: pmTestCases,
       pmDraftDependencies: pmDependencies,
       pmDirty: false,
+      pmBaseVersions: NO_BASE_VERSIONS,
     });
   },
 
@@ -343,16 +486,24 @@ export const createPmSlice: StateCreator<PmSlice> = (set, get) => ({
     set((s) => ({
       pmDraftEpics: s.pmDraftEpics.map((e) => (e.id === id ? { ...e, ...updates } : e)),
       pmDirty: true,
+      pmBaseVersions: recordBase(s.pmBaseVersions, 'epics', s.pmEpics, [id]),
     })),
 
   deleteEpic: (id) =>
     set((s) => {
       const ticketIds = s.pmDraftTickets.filter((t) => t.epicId === id).map((t) => t.id);
+      const testCaseIds = s.pmDraftTestCases
+        .filter((tc) => ticketIds.includes(tc.ticketId))
+        .map((tc) => tc.id);
+      let bases = recordBase(s.pmBaseVersions, 'epics', s.pmEpics, [id]);
+      bases = recordBase(bases, 'tickets', s.pmTickets, ticketIds);
+      bases = recordBase(bases, 'testCases', s.pmTestCases, testCaseIds);
       return {
         pmDraftEpics: s.pmDraftEpics.filter((e) => e.id !== id),
         pmDraftTickets: s.pmDraftTickets.filter((t) => t.epicId !== id),
         pmDraftTestCases: s.pmDraftTestCases.filter((tc) => !ticketIds.includes(tc.ticketId)),
         pmDirty: true,
+        pmBaseVersions: bases,
       };
     }),
 
@@ -374,14 +525,20 @@ export const createPmSlice: StateCreator<PmSlice> = (set, get) => ({
         return t;
       }),
       pmDirty: true,
+      pmBaseVersions: recordBase(s.pmBaseVersions, 'tickets', s.pmTickets, [id]),
     })),
 
   deleteTicket: (id) =>
-    set((s) => ({
-      pmDraftTickets: s.pmDraftTickets.filter((t) => t.id !== id),
-      pmDraftTestCases: s.pmDraftTestCases.filter((tc) => tc.ticketId !== id),
-      pmDirty: true,
-    })),
+    set((s) => {
+      const testCaseIds = s.pmDraftTestCases.filter((tc) => tc.ticketId === id).map((tc) => tc.id);
+      const bases = recordBase(s.pmBaseVersions, 'tickets', s.pmTickets, [id]);
+      return {
+        pmDraftTickets: s.pmDraftTickets.filter((t) => t.id !== id),
+        pmDraftTestCases: s.pmDraftTestCases.filter((tc) => tc.ticketId !== id),
+        pmDirty: true,
+        pmBaseVersions: recordBase(bases, 'testCases', s.pmTestCases, testCaseIds),
+      };
+    }),
 
   moveTicket: (ticketId, newEpicId) =>
     set((s) => ({
@@ -389,6 +546,7 @@ export const createPmSlice: StateCreator<PmSlice> = (set, get) => ({
         t.id === ticketId ? { ...t, epicId: newEpicId } : t
       ),
       pmDirty: true,
+      pmBaseVersions: recordBase(s.pmBaseVersions, 'tickets', s.pmTickets, [ticketId]),
     })),
 
   addTestCase: (tc) =>
@@ -398,12 +556,14 @@ export const createPmSlice: StateCreator<PmSlice> = (set, get) => ({
     set((s) => ({
       pmDraftTestCases: s.pmDraftTestCases.map((tc) => (tc.id === id ? { ...tc, ...updates } : tc)),
       pmDirty: true,
+      pmBaseVersions: recordBase(s.pmBaseVersions, 'testCases', s.pmTestCases, [id]),
     })),
 
   deleteTestCase: (id) =>
     set((s) => ({
       pmDraftTestCases: s.pmDraftTestCases.filter((tc) => tc.id !== id),
       pmDirty: true,
+      pmBaseVersions: recordBase(s.pmBaseVersions, 'testCases', s.pmTestCases, [id]),
     })),
 
   addDependency: (dep) =>
@@ -422,14 +582,19 @@ export const createPmSlice: StateCreator<PmSlice> = (set, get) => ({
   archiveDoneTickets: () =>
     set((s) => {
       const now = new Date().toISOString();
-      let hasArchived = false;
+      const archivedIds: string[] = [];
       const pmDraftTickets = s.pmDraftTickets.map((t) => {
         if (t.status === 'done') {
-          hasArchived = true;
+          archivedIds.push(t.id);
           return { ...t, status: 'archived' as const, statusUpdatedAt: now };
         }
         return t;
       });
-      return hasArchived ? { pmDraftTickets, pmDirty: true } : s;
+      if (archivedIds.length === 0) return s;
+      return {
+        pmDraftTickets,
+        pmDirty: true,
+        pmBaseVersions: recordBase(s.pmBaseVersions, 'tickets', s.pmTickets, archivedIds),
+      };
     }),
 });
@@ -39,6 +39,7 @@ describe('agentEvents', () => {
         stream: 'stdout',
         line: 'hello world',
         timestamp: 1700000000000,
+        seq: 0,
       };
 
       mockListen.mockImplementation(
@@ -90,6 +91,7 @@ describe('agentEvents', () => {
         agentId: 'agent-1',
         status: 'idle',
         exitCode: 0,
+        reason: null,
       };
 
       mockListen.mockImplementation(
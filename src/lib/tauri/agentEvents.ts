@@ -6,13 +6,18 @@ export interface AgentOutputEvent {
   line: string;
   timestamp: number;
   repoPath?: string;
+  /** Per agent, from 0 — `get_agent_output` replays from this number. */
+  seq: number;
 }
 
 export interface AgentStatusEvent {
   agentId: string;
-  status: 'running' | 'idle' | 'queued' | 'error';
+  /** `limited`: stopped for going over one of the spawn's `limits`. */
+  status: 'running' | 'idle' | 'queued' | 'error' | 'limited';
   exitCode: number | null;
   repoPath?: string;
+  /** Which limit, for `limited`. */
+  reason: string | null;
 }
 
 export function onAgentOutput(callback: (event: AgentOutputEvent) => void): () => void {
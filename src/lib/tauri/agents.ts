@@ -28,6 +28,8 @@ export interface AgentInfo {
   repoPath?: string;
   spawnedByTicketId?: string;
   spawnedByGoalId?: string;
+  /** Stopped with `pause_agent`: still running, and still holding its slot. */
+  paused?: boolean;
   /** Set when this agent was spawned to REVIEW a ticket (conductor judge, agent
    * form). Frontend-only provenance, distinct from spawnedByTicketId so a
    * reviewer is never mistaken for the implementer of the ticket. */
@@ -37,10 +39,15 @@ export interface AgentInfo {
 export type PermissionMode =
   'bypassPermissions' | 'acceptEdits' | 'plan' | 'auto' | 'default' | 'yolo';
 
+import type { AgentRole } from '../config/providerPolicy';
 import { invoke } from './invoke';
 
 export interface AgentConfig {
   name: string;
+  /**
+   * Empty leaves it to the project's preset for `role`, then `fallbackModel`, then the
+   * provider's default.
+   */
   model: string;
   task: string;
   cwd?: string;
@@ -51,6 +58,42 @@ export interface AgentConfig {
   headless?: boolean;
   spawnedByTicketId?: string;
   spawnedByGoalId?: string;
+  /**
+   * Bounds on the run; unset means unbounded. Memory and CPU apply on Linux
+   * only. A run that goes over one stops with status `limited`.
+   */
+  limits?: {
+    maxRuntimeSecs?: number;
+    idleTimeoutSecs?: number;
+    maxOutputBytes?: number;
+    /** Resident memory of the agent and everything it started, sampled once a second. */
+    maxMemoryMb?: number;
+    maxCpuSecs?: number;
+    /** Passed to the CLI by a provider config with a `maxTurns` argument. */
+    maxTurns?: number;
+  };
+  /**
+   * Place in the spawn queue when the concurrency caps are full: higher starts
+   * sooner, equal in arrival order. Unset is 0.
+   */
+  priority?: number;
+  /** Initial PTY size; unset is 160x40. Resize to fit with `resize_agent`. */
+  rows?: number;
+  cols?: number;
+  /** Provider session to continue; used only if the provider declares a `resume` argument. */
+  resumeSessionId?: string | null;
+  /** Image files to attach; used only if the provider declares an `images` argument. */
+  images?: string[];
+  /** Extra system prompt; used only if the provider declares a `systemPrompt` argument. */
+  systemPrompt?: string;
+  /**
+   * What the agent is for. The project's provider policy may preset a provider
+   * and model per role; Rust fills in whichever of the two this config leaves
+   * open.
+   */
+  role?: AgentRole;
+  /** The model when neither `model` nor the role's preset names one. */
+  fallbackModel?: string;
   /** Frontend-only provenance hint for goal runs; ignored by the Rust backend. */
   runSource?: 'ui' | 'conductor';
   /**
@@ -129,6 +172,34 @@ export async function killAgentsForRepo(repoPath: string): Promise<number> {
   return await invoke<number>('kill_agents_for_repo', { repoPath });
 }
 
+export interface AgentOutputChunk {
+  /** Per agent, from 0 — the same number the `agent-output` event carries. */
+  seq: number;
+  stream: 'stdout' | 'stderr';
+  timestamp: number;
+  data: string;
+}
+
+export interface AgentOutputPage {
+  chunks: AgentOutputChunk[];
+  /** What to pass as `sinceSeq` next time to get only what is new. */
+  nextSeq: number;
+  /** The backend dropped output from `sinceSeq` on; `chunks` starts later. */
+  truncated: boolean;
+}
+
+/** Everything the agent printed from `sinceSeq` on (default: the start), for a
+ * view that was not listening when it happened. */
+export async function getAgentOutput(
+  agentId: string,
+  sinceSeq?: number
+): Promise<AgentOutputPage> {
+  return await invoke<AgentOutputPage>('get_agent_output', {
+    agentId,
+    sinceSeq: sinceSeq ?? null,
+  });
+}
+
 /** An agent that was running when the app last quit — its process died with
  * the app, but its spawn config survived and it can be resumed or discarded. */
 export interface InterruptedAgent {
@@ -145,13 +216,18 @@ export interface InterruptedAgent {
   startedAt: number;
   spawnedByTicketId?: string | null;
   spawnedByGoalId?: string | null;
+  /** The provider's own session id, when one was captured; resume continues it. */
+  sessionId?: string | null;
 }
 
 export async function listInterruptedAgents(): Promise<InterruptedAgent[]> {
   return await invoke<InterruptedAgent[]>('list_interrupted_agents');
 }
 
-/** Re-spawns an interrupted agent with a continuation task and returns the new agent. */
+/**
+ * Re-spawns an interrupted agent and returns the new agent — back into its provider
+ * session when one was captured, otherwise with a continuation task.
+ */
 export async function resumeInterruptedAgent(agentId: string): Promise<AgentInfo> {
   return await invoke<AgentInfo>('resume_interrupted_agent', { agentId });
 }
@@ -38,6 +38,17 @@ describe('git IPC wrappers', () => {
     expect(mockInvoke).toHaveBeenCalledWith('git_commit', { repoPath: '/repo', message: 'test' });
   });
 
+  it('commitChanges passes amend only when set', async () => {
+    mockInvoke.mockResolvedValueOnce('def456');
+    const { commitChanges } = await import('./git');
+    await commitChanges('/repo', 'reworded', true);
+    expect(mockInvoke).toHaveBeenCalledWith('git_commit', {
+      repoPath: '/repo',
+      message: 'reworded',
+      amend: true,
+    });
+  });
+
   it('stageFiles does not throw', async () => {
     mockInvoke.mockResolvedValueOnce(undefined);
     const { stageFiles } = await import('./git');
@@ -9,11 +9,16 @@ export interface GitFileStatus {
 }
 
 import { invoke } from './invoke';
+import { subscribeToTauriEvent } from './subscribe';
 
 export interface BranchInfo {
   name: string;
+  /** Commits the upstream lacks, as of the last fetch; 0 without an upstream. */
   ahead: number;
+  /** Upstream commits the branch lacks, as of the last fetch; 0 without an upstream. */
   behind: number;
+  /** Shorthand of the tracked branch (`origin/main`), null when none is set. */
+  upstream?: string | null;
 }
 
 export type GitRepoKind = 'root' | 'nested' | 'submodule';
@@ -71,13 +76,68 @@ export async function getGitDiff(
   );
 }
 
-export async function commitChanges(repoPath: string, message: string): Promise<string> {
-  return await invoke<string>('git_commit', { repoPath, message });
+/**
+ * Commits the index, signed when `commit.gpgsign` is set. With `amend` it
+ * rewrites HEAD instead; a blank message then keeps the old one.
+ */
+export async function commitChanges(
+  repoPath: string,
+  message: string,
+  amend?: boolean
+): Promise<string> {
+  return await invoke<string>(
+    'git_commit',
+    amend ? { repoPath, message, amend } : { repoPath, message }
+  );
 }
 
-/** Pushes the current branch to origin; the backend handles credentials. */
-export async function pushChanges(repoPath: string): Promise<void> {
-  await invoke('git_push', { repoPath });
+/** What to push and where; every field is optional and defaults as `git push` would. */
+export interface GitPushOptions {
+  /** Remote name; defaults to the branch's upstream remote, then `origin`. */
+  remote?: string | null;
+  /** Refspec to push; defaults to the current branch onto its upstream name. */
+  refspec?: string | null;
+  /** Forces, but only while the remote ref still sits at `expectedRemoteOid`. */
+  forceWithLease?: boolean;
+  /** The remote-tracking oid the lease checks against; the local tracking ref when unset. */
+  expectedRemoteOid?: string | null;
+}
+
+/** A ref the remote turned down, in the remote's own words. */
+export interface GitPushRejection {
+  refname: string;
+  reason: string;
+}
+
+export interface GitPushResult {
+  remote: string;
+  refspec: string;
+  /** Refs the remote turned down; any at all means the push did not land. */
+  rejected: GitPushRejection[];
+}
+
+/** A push progress step; `refUpdate` carries the remote's rejection reason, if any. */
+export type GitPushProgress =
+  | { type: 'transfer'; current: number; total: number; bytes: number }
+  | { type: 'refUpdate'; refname: string; rejection: string | null };
+
+export type GitPushProgressEvent = GitPushProgress & { repoPath: string };
+
+/** Pushes the current branch (or `options.refspec`); the backend handles credentials. */
+export async function pushChanges(
+  repoPath: string,
+  options?: GitPushOptions
+): Promise<GitPushResult> {
+  return await invoke<GitPushResult>('git_push', { repoPath, options: options ?? null });
+}
+
+/** Subscribes to `git-push-progress`; returns the unsubscribe function. */
+export function onGitPushProgress(callback: (event: GitPushProgressEvent) => void): () => void {
+  return subscribeToTauriEvent<GitPushProgressEvent>(
+    'git-push-progress',
+    callback,
+    '[Browser mode] Git push progress listener not available'
+  );
 }
 
 export async function discardChanges(repoPath: string, filePath: string): Promise<void> {
@@ -190,12 +250,28 @@ export async function gitDefaultBranch(repoPath: string): Promise<string> {
   return await invoke<string>('git_default_branch', { repoPath });
 }
 
+/**
+ * One conflicted file of a worktree merge. `ours` is main/master, `theirs`
+ * the worktree branch; a side is null when it has no such file, and all of
+ * them are null for a binary file.
+ */
+export interface GitMergeConflict {
+  path: string;
+  kind: 'both-modified' | 'both-added' | 'deleted-by-us' | 'deleted-by-them';
+  base: string | null;
+  ours: string | null;
+  theirs: string | null;
+  binary: boolean;
+}
+
 export interface WorktreeMergeResult {
   defaultBranch: string;
   merged: boolean;
   fastForward: boolean;
   cleanedUp: boolean;
   oid: string | null;
+  /** Non-empty when the merge stopped on conflicts and waits in the worktree. */
+  conflicts?: GitMergeConflict[];
 }
 
 /**
@@ -37,6 +37,10 @@ export interface PmGoalRun {
   summary: string;
   startedAt: string;
   finishedAt: string | null;
+  /** Spend the agent's CLI reported, filled in by the backend when the run ends. */
+  costUsd?: number | null;
+  inputTokens?: number | null;
+  outputTokens?: number | null;
 }
 
 export interface PmGoalRequirementLink {
@@ -1,5 +1,5 @@
 import { describe, expect, it, vi, beforeEach } from 'vitest';
-import { pmLoad, pmSave } from './pm';
+import { pmLoad, pmSync } from './pm';
 
 vi.mock('@tauri-apps/api/core', () => ({
   invoke: vi.fn((cmd: string) => {
@@ -20,8 +20,10 @@ vi.mock('@tauri-apps/api/core', () => ({
         dependencies: [],
       });
     }
-    if (cmd === 'pm_save') {
-      return Promise.resolve(null);
+    if (cmd === 'pm_sync') {
+      return Promise.resolve({
+        versions: { epics: { e1: '2026-05-01T10:00:00.000Z' }, tickets: {}, testCases: {} },
+      });
     }
     return Promise.reject(new Error(`Unknown command: ${cmd}`));
   }),
@@ -38,9 +40,18 @@ describe('IPC pm', () => {
     expect(state.epics[0].name).toBe('Epic 1');
   });
 
-  it('pmSave resolves without error', async () => {
-    await expect(
-      pmSave('/project', { epics: [], tickets: [], testCases: [], dependencies: [] })
-    ).resolves.toBeUndefined();
+  it('pmSync returns the stamped row versions', async () => {
+    const result = await pmSync('/project', {
+      epics: [],
+      tickets: [],
+      testCases: [],
+      dependencies: [],
+      deletedEpicIds: [],
+      deletedTicketIds: [],
+      deletedTestCaseIds: [],
+      deletedDependencyIds: [],
+      baseVersions: { epics: {}, tickets: {}, testCases: {} },
+    });
+    expect(result.versions.epics.e1).toBe('2026-05-01T10:00:00.000Z');
   });
 });
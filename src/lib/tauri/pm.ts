@@ -70,8 +70,37 @@ export async function pmLoad(projectPath: string): Promise<PmState> {
   return await invoke<PmState>('pm_load', { projectPath });
 }
 
-export async function pmSave(projectPath: string, payload: PmState): Promise<void> {
-  await invoke('pm_save', { projectPath, payload });
+/** Start of every `pmSync` error caused by a stale row; mirrors `database::PM_CONFLICT_PREFIX`. */
+export const PM_CONFLICT_PREFIX = 'PM conflict';
+
+/** `updatedAt` per row id, one map per versioned PM table. */
+export interface PmRowVersions {
+  epics: Record<string, string>;
+  tickets: Record<string, string>;
+  testCases: Record<string, string>;
+}
+
+/**
+ * Row-level sync: upserts plus explicit deletions. A row listed in
+ * `baseVersions` is only written while the stored `updatedAt` still matches;
+ * otherwise the whole sync is rejected with an error starting with
+ * `PM_CONFLICT_PREFIX` that names every stale row.
+ */
+export interface PmSyncPayload extends PmState {
+  deletedEpicIds: string[];
+  deletedTicketIds: string[];
+  deletedTestCaseIds: string[];
+  deletedDependencyIds: string[];
+  baseVersions: PmRowVersions;
+}
+
+export interface PmSyncResult {
+  /** The `updatedAt` every written row now carries. */
+  versions: PmRowVersions;
+}
+
+export async function pmSync(projectPath: string, payload: PmSyncPayload): Promise<PmSyncResult> {
+  return await invoke<PmSyncResult>('pm_sync', { projectPath, payload });
 }
 
 export async function pmClear(projectPath: string): Promise<void> {
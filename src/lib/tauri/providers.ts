@@ -10,6 +10,7 @@ export interface PermissionModeOption {
 }
 
 import { invoke } from './invoke';
+import { subscribeToTauriEvent } from './subscribe';
 
 export interface ProviderInfo {
   id: string;
@@ -29,6 +30,57 @@ export async function importProvider(json: string): Promise<ProviderInfo> {
   return await invoke<ProviderInfo>('import_provider', { json });
 }
 
+/** What became of one config file in the `dynamic-providers/` search directories. */
+export interface ProviderConfigCheck {
+  path: string;
+  /** The id the file claims, when it parsed far enough to say. */
+  id: string | null;
+  status: 'loaded' | 'shadowed' | 'reserved' | 'invalid';
+  error: string | null;
+}
+
+/** Every provider config file found, and why any of them is not in the picker. */
+export async function validateProviderConfigs(): Promise<ProviderConfigCheck[]> {
+  return await invoke<ProviderConfigCheck[]>('validate_provider_configs');
+}
+
+/** Optional features a provider's config uses that its installed CLI supports. */
+export interface ProviderCapabilities {
+  resume: boolean;
+  streamJson: boolean;
+  headless: boolean;
+}
+
+/** What is installed for a provider, from running its `versionCheck` command. */
+export interface ProviderProbe {
+  providerId: string;
+  /** Where the CLI resolves on the login-shell PATH agents are spawned with. */
+  executablePath: string | null;
+  installed: boolean;
+  version: string | null;
+  minVersion: string | null;
+  /** `null` when the config names no minimum or no version could be read. */
+  meetsMinimum: boolean | null;
+  capabilities: ProviderCapabilities;
+  error: string | null;
+}
+
+export async function probeProvider(providerId: string): Promise<ProviderProbe> {
+  return await invoke<ProviderProbe>('probe_provider', { providerId });
+}
+
+/**
+ * Fires after a config in a search directory was added, edited or removed and
+ * the registry re-read them. Re-fetch the list; the event carries nothing.
+ */
+export function onProvidersChanged(callback: () => void): () => void {
+  return subscribeToTauriEvent<unknown>(
+    'providers-changed',
+    () => callback(),
+    '[Browser mode] Provider listener not available'
+  );
+}
+
 export async function getPromptTemplate(providerId?: string): Promise<string> {
   const result = await invoke<{ template: string }>('get_prompt_template', {
     providerId: providerId ?? null,
@@ -42,6 +42,8 @@ export const GUIDANCE = {
     linting: 'Shows real-time warnings and errors for your Markdown files.',
     cliUsageLimits:
       'Shows remaining usage and reset times. Claude updates while its interactive agent runs; Codex updates every 15 minutes, or when you refresh.',
+    agentApiEnabled:
+      'Opens a local socket that scripts on this machine can use to list, start, read and stop agents, and to read tickets and goals. A script must present the token stored next to the socket.',
     agentConsoleAutoOpen:
       'When no project is open and agents are running, shows the Agent Console instead of the start screen.',
   },
@@ -136,6 +136,18 @@ describe('context tools', () => {
       expect(before).toBeDefined();
     });
 
+    it('stamps updated_at to the millisecond, in the format pm_sync writes', () => {
+      addContextItem(db, ticketId, 'snippet', 'something');
+
+      const stamped = (
+        db.prepare('SELECT updated_at FROM pm_tickets WHERE id = ?').get(ticketId) as {
+          updated_at: string;
+        }
+      ).updated_at;
+
+      expect(stamped).toMatch(/^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{3}Z$/);
+    });
+
     it('throws for a non-existent ticket', () => {
       expect(() => addContextItem(db, 'no-such-id', 'snippet', 'x')).toThrow(
         'Ticket not found: no-such-id'
@@ -86,6 +86,10 @@ describe('openDatabase', () => {
       cnt: number;
     };
     expect(skillsRow.cnt).toBe(1);
+    const costsRow = db.prepare('SELECT COUNT(*) AS cnt FROM _migrations WHERE id = 20').get() as {
+      cnt: number;
+    };
+    expect(costsRow.cnt).toBe(1);
     db.close();
   });
 
@@ -95,7 +99,7 @@ describe('openDatabase', () => {
     db1.close();
     const db2 = openDatabase(dbPath);
     const row = db2.prepare('SELECT COUNT(*) AS cnt FROM _migrations').get() as { cnt: number };
-    expect(row.cnt).toBe(18);
+    expect(row.cnt).toBe(19);
     db2.close();
   });
 
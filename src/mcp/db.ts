@@ -324,8 +324,41 @@ function runMigrations(db: Database.Database): void {
     db.exec("ALTER TABLE pm_tickets ADD COLUMN skills TEXT NOT NULL DEFAULT '[]'");
     record(19, 'add_ticket_skills');
   }
+
+  // Migration #20: per-run agent spend (keep in sync with src-tauri/src/database.rs).
+  if (!applied(20)) {
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS agent_run_costs (
+        id            TEXT PRIMARY KEY,
+        agent_id      TEXT NOT NULL,
+        ticket_id     TEXT,
+        goal_id       TEXT,
+        provider      TEXT NOT NULL DEFAULT '',
+        model         TEXT NOT NULL DEFAULT '',
+        cost_usd      REAL,
+        input_tokens  INTEGER NOT NULL DEFAULT 0,
+        output_tokens INTEGER NOT NULL DEFAULT 0,
+        started_at    INTEGER NOT NULL,
+        ended_at      INTEGER NOT NULL
+      );
+      CREATE INDEX IF NOT EXISTS idx_agent_run_costs_ticket ON agent_run_costs(ticket_id);
+      CREATE INDEX IF NOT EXISTS idx_agent_run_costs_goal ON agent_run_costs(goal_id);
+
+      ALTER TABLE pm_goal_runs ADD COLUMN cost_usd REAL;
+      ALTER TABLE pm_goal_runs ADD COLUMN input_tokens INTEGER;
+      ALTER TABLE pm_goal_runs ADD COLUMN output_tokens INTEGER;
+    `);
+    record(20, 'create_agent_run_costs');
+  }
 }
 
+/**
+ * SQL for a row's new `updated_at`: UTC to the millisecond, the exact format
+ * `pm_sync` stamps. The UI saves with the version it loaded, so two writes in
+ * the same second must not produce the same stamp.
+ */
+export const VERSION_STAMP_SQL = "strftime('%Y-%m-%dT%H:%M:%fZ', 'now')";
+
 export function openDatabase(path: string): Database.Database {
   const db = new Database(path);
   db.pragma('journal_mode = WAL');
@@ -2,6 +2,7 @@ import type Database from 'better-sqlite3';
 import type { FastMCP } from 'fastmcp';
 import { z } from 'zod';
 import { resolveTicketId } from './resolve';
+import { VERSION_STAMP_SQL } from '../db';
 
 // ---------------------------------------------------------------------------
 // Types
@@ -55,10 +56,8 @@ function readContext(db: Database.Database, ticketId: string): ContextItem[] {
 }
 
 function writeContext(db: Database.Database, ticketId: string, items: ContextItem[]): void {
-  db.prepare("UPDATE pm_tickets SET context = ?, updated_at = datetime('now') WHERE id = ?").run(
-    JSON.stringify(items),
-    ticketId
-  );
+  const sql = `UPDATE pm_tickets SET context = ?, updated_at = ${VERSION_STAMP_SQL} WHERE id = ?`;
+  db.prepare(sql).run(JSON.stringify(items), ticketId);
 }
 
 // ---------------------------------------------------------------------------
@@ -4,6 +4,7 @@ import { FastMCP } from 'fastmcp';
 import { z } from 'zod';
 import { isVerifiedEvidence } from '../../lib/pm/enums';
 import { resolveGoalId, resolveRequirementId, resolveTicketId } from './resolve';
+import { VERSION_STAMP_SQL } from '../db';
 
 export interface GoalRow {
   id: string;
@@ -34,6 +35,9 @@ export interface GoalRunRow {
   summary: string;
   started_at: string;
   finished_at: string | null;
+  cost_usd: number | null;
+  input_tokens: number | null;
+  output_tokens: number | null;
 }
 
 interface TicketSummaryRow {
@@ -159,7 +163,7 @@ export function updateGoal(
     return existing;
   }
 
-  setClauses.push("updated_at = datetime('now')");
+  setClauses.push(`updated_at = ${VERSION_STAMP_SQL}`);
   values.push(id);
   db.prepare(`UPDATE pm_goals SET ${setClauses.join(', ')} WHERE id = ?`).run(...values);
 
@@ -234,7 +238,7 @@ export function linkTicketToGoal(
   goalId: string | null
 ): { linked: boolean } {
   const changes = db
-    .prepare("UPDATE pm_tickets SET goal_id = ?, updated_at = datetime('now') WHERE id = ?")
+    .prepare(`UPDATE pm_tickets SET goal_id = ?, updated_at = ${VERSION_STAMP_SQL} WHERE id = ?`)
     .run(goalId, ticketId).changes;
   if (changes === 0) throw new Error(`Ticket '${ticketId}' not found`);
   return { linked: true };
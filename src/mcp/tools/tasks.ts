@@ -3,6 +3,7 @@ import type { FastMCP } from 'fastmcp';
 import { z } from 'zod';
 import { insertStatusHistory } from './history';
 import { resolveTicketId } from './resolve';
+import { VERSION_STAMP_SQL } from '../db';
 
 interface Ticket {
   id: string;
@@ -23,7 +24,7 @@ interface Ticket {
 
 const UPDATE_STATUS_SQL = `
   UPDATE pm_tickets
-  SET status = ?, status_updated_at = datetime('now'), updated_at = datetime('now')
+  SET status = ?, status_updated_at = datetime('now'), updated_at = ${VERSION_STAMP_SQL}
   WHERE id = ?
 `;
 
@@ -33,7 +34,7 @@ const SELECT_TICKET_SQL = `
 
 const UPDATE_DESCRIPTION_SQL = `
   UPDATE pm_tickets
-  SET description = ?, updated_at = datetime('now')
+  SET description = ?, updated_at = ${VERSION_STAMP_SQL}
   WHERE id = ?
 `;
 
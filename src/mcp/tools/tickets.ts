@@ -3,6 +3,7 @@ import type { FastMCP } from 'fastmcp';
 import { z } from 'zod';
 import { insertStatusHistory } from './history';
 import { resolveEpicId, resolveGoalId, resolveTicketId } from './resolve';
+import { VERSION_STAMP_SQL } from '../db';
 import { isValidDueDate } from '@/lib/inbox/dueDate';
 import { assertOneOf, PRIORITIES, TICKET_STATUSES } from '@/lib/pm/enums';
 import { normalizeTicketSkills } from '@/lib/pm/ticketSkills';
@@ -191,7 +192,7 @@ export function updateTicket(db: Database.Database, params: UpdateTicketParams):
   }
 
   if (setClauses.length > 0) {
-    setClauses.push("updated_at = datetime('now')");
+    setClauses.push(`updated_at = ${VERSION_STAMP_SQL}`);
     const sql = `UPDATE pm_tickets SET ${setClauses.join(', ')} WHERE id = ?`;
     values.push(params.id);
     db.prepare(sql).run(...values);